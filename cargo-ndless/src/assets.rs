@@ -0,0 +1,84 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::debug;
+use ndless_assets::Writer;
+
+/// Packs every file in `dir` into a bundle, with paths relative to `dir`.
+pub fn pack(dir: &Path) -> Result<Vec<u8>> {
+	let mut writer = Writer::new();
+	add_dir(&mut writer, dir, "")
+		.with_context(|| format!("Failed to read assets from {}", dir.display()))?;
+	debug!("Packed {} assets", writer.len());
+	Ok(writer.finish()?)
+}
+
+fn add_dir(writer: &mut Writer, dir: &Path, prefix: &str) -> Result<()> {
+	let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+	// The same files should always make the same bundle
+	entries.sort_by_key(|entry| entry.file_name());
+	for entry in entries {
+		let name = entry.file_name();
+		let name = name
+			.to_str()
+			.with_context(|| format!("{} isn't valid UTF-8", entry.path().display()))?;
+		let path = format!("{}{}", prefix, name);
+		if entry.file_type()?.is_dir() {
+			add_dir(writer, &entry.path(), &format!("{}/", path))?;
+		} else {
+			let data = fs::read(entry.path())?;
+			writer
+				.add(&path, data)
+				.with_context(|| format!("Failed to add {}", path))?;
+		}
+	}
+	Ok(())
+}
+
+/// Appends a bundle to the end of a `.tns` file.
+pub fn append(tns_file: &Path, bundle: &[u8]) -> Result<()> {
+	OpenOptions::new()
+		.append(true)
+		.open(tns_file)?
+		.write_all(bundle)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+
+	use ndless_assets::Bundle;
+
+	use super::*;
+
+	#[test]
+	fn pack_dir() {
+		let dir = env::temp_dir().join(format!("cargo-ndless-assets-{}", std::process::id()));
+		fs::create_dir_all(dir.join("sprites/enemies")).unwrap();
+		fs::write(dir.join("sprites/player.png"), b"player").unwrap();
+		fs::write(dir.join("sprites/enemies/bat.png"), b"bat").unwrap();
+		fs::write(dir.join("level1.txt"), b"#####").unwrap();
+		let tns_file = dir.join("game.tns");
+		fs::write(&tns_file, b"PRG\0").unwrap();
+
+		let bundle = pack(&dir.join("sprites")).unwrap();
+		assert_eq!(bundle, pack(&dir.join("sprites")).unwrap());
+		append(&tns_file, &bundle).unwrap();
+		let tns = fs::read(&tns_file).unwrap();
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert!(tns.starts_with(b"PRG\0"));
+		let bundle = Bundle::new(&tns).unwrap();
+		let paths: Vec<_> = bundle
+			.index()
+			.entries()
+			.iter()
+			.map(|entry| entry.path.as_str())
+			.collect();
+		assert_eq!(paths, ["enemies/bat.png", "player.png"]);
+		assert_eq!(bundle.get("enemies/bat.png"), Some(&b"bat"[..]));
+	}
+}
@@ -14,6 +14,7 @@ use structopt::StructOpt;
 use self::cli::Opt;
 use crate::firebird::send_file;
 
+mod assets;
 mod cli;
 mod files;
 mod firebird;
@@ -27,6 +28,9 @@ struct ZehnOptions {
 	flags: String,
 	name: Option<String>,
 	notice: Option<String>,
+	/// A directory of files to bundle into the `.tns`, relative to the
+	/// package, which can be read with `ndless::assets`
+	assets: Option<PathBuf>,
 }
 
 fn update_path() {
@@ -165,6 +169,11 @@ fn build(build_settings: cli::Build) -> Result<(bool, Vec<PathBuf>)> {
 				make_prg_status.context("Failed to run make-prg")?.success(),
 				"Failed to run make-prg"
 			);
+			if let Some(ref assets) = config.assets {
+				let dir = package.manifest_path.parent().unwrap().join(assets);
+				let bundle = assets::pack(&dir)?;
+				assets::append(&tns_file, &bundle).context("Failed to add assets")?;
+			}
 			Ok(Some(tns_file))
 		})
 		.filter_map(|res| res.transpose())
@@ -0,0 +1,430 @@
+//! The asset bundle format.
+//!
+//! A bundle is a set of files that `cargo ndless` appends to the end of a
+//! `.tns` file, after the program, where the loader ignores it. It is read from
+//! the end so that it can be found without knowing how long the program is.
+//! All numbers are little-endian:
+//!
+//! | Size | Contents                                                       |
+//! |------|----------------------------------------------------------------|
+//! | ...  | The contents of each file, one after another                    |
+//! | ...  | The index: for each file, sorted by path, a `u16` path length, the UTF-8 path, and the `u32` offset and length of its contents |
+//! | 4    | The number of files                                             |
+//! | 4    | The offset of the index                                         |
+//! | 4    | The length of the whole bundle, including this footer           |
+//! | 8    | [`MAGIC`]                                                        |
+//!
+//! Offsets are from the start of the bundle. Paths are relative, separated by
+//! `/`, and [normalized](normalize).
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt;
+
+/// Identifies a bundle, and the version of its format.
+pub const MAGIC: [u8; 8] = *b"NDASSET1";
+/// The length of the [`Footer`].
+pub const FOOTER_LEN: usize = 20;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Error {
+	/// There is no bundle, or it was made by a different version.
+	BadMagic,
+	/// The bundle is cut off.
+	Truncated,
+	/// The index doesn't make sense.
+	Malformed,
+	/// A path is empty or goes outside the bundle with `..`.
+	InvalidPath,
+	/// Two files have the same path.
+	Duplicate,
+	/// The bundle would be larger than 4 GiB.
+	TooLarge,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Error::BadMagic => "no asset bundle found",
+			Error::Truncated => "asset bundle is incomplete",
+			Error::Malformed => "asset bundle is malformed",
+			Error::InvalidPath => "invalid asset path",
+			Error::Duplicate => "duplicate asset path",
+			Error::TooLarge => "asset bundle is too large",
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Converts a path to the form used in bundles, so that `/sprites/player.png`,
+/// `sprites\player.png` and `./sprites//player.png` all refer to the same file.
+/// Paths may not be empty or contain `..`.
+pub fn normalize(path: &str) -> Result<String, Error> {
+	let mut normalized = String::with_capacity(path.len());
+	for component in path.split(&['/', '\\'][..]) {
+		match component {
+			"" | "." => {}
+			".." => return Err(Error::InvalidPath),
+			component => {
+				if !normalized.is_empty() {
+					normalized.push('/');
+				}
+				normalized.push_str(component);
+			}
+		}
+	}
+	if normalized.is_empty() || normalized.len() > u16::MAX as usize {
+		return Err(Error::InvalidPath);
+	}
+	Ok(normalized)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+	u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// The end of a bundle.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Footer {
+	/// The number of files.
+	pub count: u32,
+	/// The offset of the index from the start of the bundle.
+	pub index_offset: u32,
+	/// The length of the bundle, including the footer.
+	pub bundle_len: u32,
+}
+
+impl Footer {
+	/// Parses the last [`FOOTER_LEN`] bytes of a file.
+	pub fn parse(bytes: &[u8]) -> Result<Footer, Error> {
+		if bytes.len() != FOOTER_LEN || bytes[12..] != MAGIC {
+			return Err(Error::BadMagic);
+		}
+		let footer = Footer {
+			count: read_u32(&bytes[0..]),
+			index_offset: read_u32(&bytes[4..]),
+			bundle_len: read_u32(&bytes[8..]),
+		};
+		if (footer.bundle_len as usize) < FOOTER_LEN
+			|| footer.index_offset as usize > footer.bundle_len as usize - FOOTER_LEN
+		{
+			return Err(Error::Malformed);
+		}
+		Ok(footer)
+	}
+
+	/// The length of the index, which comes right before the footer.
+	pub fn index_len(&self) -> usize {
+		self.bundle_len as usize - FOOTER_LEN - self.index_offset as usize
+	}
+
+	pub fn to_bytes(&self) -> [u8; FOOTER_LEN] {
+		let mut bytes = [0; FOOTER_LEN];
+		bytes[0..4].copy_from_slice(&self.count.to_le_bytes());
+		bytes[4..8].copy_from_slice(&self.index_offset.to_le_bytes());
+		bytes[8..12].copy_from_slice(&self.bundle_len.to_le_bytes());
+		bytes[12..].copy_from_slice(&MAGIC);
+		bytes
+	}
+}
+
+/// A file in a bundle.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Entry {
+	pub path: String,
+	/// The offset of the contents from the start of the bundle.
+	pub offset: u32,
+	pub len: u32,
+}
+
+/// The list of files in a bundle.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Index {
+	entries: Vec<Entry>,
+}
+
+impl Index {
+	/// Parses the index, given the footer and the bytes between the two.
+	pub fn parse(footer: &Footer, mut bytes: &[u8]) -> Result<Index, Error> {
+		if bytes.len() != footer.index_len() {
+			return Err(Error::Truncated);
+		}
+		let mut entries: Vec<Entry> = Vec::with_capacity(footer.count as usize);
+		for _ in 0..footer.count {
+			if bytes.len() < 2 {
+				return Err(Error::Malformed);
+			}
+			let path_len = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+			if bytes.len() < 2 + path_len + 8 {
+				return Err(Error::Malformed);
+			}
+			let path =
+				core::str::from_utf8(&bytes[2..2 + path_len]).map_err(|_| Error::Malformed)?;
+			let offset = read_u32(&bytes[2 + path_len..]);
+			let len = read_u32(&bytes[2 + path_len + 4..]);
+			if offset as u64 + len as u64 > footer.index_offset as u64 {
+				return Err(Error::Malformed);
+			}
+			if let Some(last) = entries.last() {
+				// Sorted, so that lookups can use a binary search
+				if last.path.as_str() >= path {
+					return Err(Error::Malformed);
+				}
+			}
+			entries.push(Entry {
+				path: path.into(),
+				offset,
+				len,
+			});
+			bytes = &bytes[2 + path_len + 8..];
+		}
+		if !bytes.is_empty() {
+			return Err(Error::Malformed);
+		}
+		Ok(Index { entries })
+	}
+
+	/// Finds a file. The path is [normalized](normalize) first.
+	pub fn get(&self, path: &str) -> Option<&Entry> {
+		let path = normalize(path).ok()?;
+		self.entries
+			.binary_search_by(|entry| entry.path.as_str().cmp(&path))
+			.ok()
+			.map(|index| &self.entries[index])
+	}
+
+	/// Every file, sorted by path.
+	pub fn entries(&self) -> &[Entry] {
+		&self.entries
+	}
+}
+
+/// A bundle that is already in memory.
+#[derive(Debug, Clone)]
+pub struct Bundle<'a> {
+	data: &'a [u8],
+	index: Index,
+}
+
+impl<'a> Bundle<'a> {
+	/// Reads the bundle at the end of `data`. Anything before the bundle,
+	/// such as the program, is ignored.
+	pub fn new(data: &'a [u8]) -> Result<Bundle<'a>, Error> {
+		if data.len() < FOOTER_LEN {
+			return Err(Error::BadMagic);
+		}
+		let footer = Footer::parse(&data[data.len() - FOOTER_LEN..])?;
+		if footer.bundle_len as usize > data.len() {
+			return Err(Error::Truncated);
+		}
+		let data = &data[data.len() - footer.bundle_len as usize..];
+		let index_end = data.len() - FOOTER_LEN;
+		let index = Index::parse(&footer, &data[footer.index_offset as usize..index_end])?;
+		Ok(Bundle { data, index })
+	}
+
+	pub fn index(&self) -> &Index {
+		&self.index
+	}
+
+	/// Gets the contents of a file.
+	pub fn get(&self, path: &str) -> Option<&'a [u8]> {
+		let entry = self.index.get(path)?;
+		let start = entry.offset as usize;
+		Some(&self.data[start..start + entry.len as usize])
+	}
+}
+
+/// Builds a bundle.
+#[derive(Debug, Clone, Default)]
+pub struct Writer {
+	files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Writer {
+	pub fn new() -> Writer {
+		Default::default()
+	}
+
+	/// Adds a file. The path is [normalized](normalize) first.
+	pub fn add<D: Into<Vec<u8>>>(&mut self, path: &str, data: D) -> Result<&mut Self, Error> {
+		let path = normalize(path)?;
+		if self.files.contains_key(&path) {
+			return Err(Error::Duplicate);
+		}
+		self.files.insert(path, data.into());
+		Ok(self)
+	}
+
+	pub fn len(&self) -> usize {
+		self.files.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.files.is_empty()
+	}
+
+	/// Returns the finished bundle, ready to be appended to a `.tns` file.
+	pub fn finish(self) -> Result<Vec<u8>, Error> {
+		let data_len = self.files.values().map(Vec::len).sum::<usize>();
+		let index_len = self
+			.files
+			.keys()
+			.map(|path| 2 + path.len() + 8)
+			.sum::<usize>();
+		let bundle_len = data_len + index_len + FOOTER_LEN;
+		if bundle_len > u32::MAX as usize {
+			return Err(Error::TooLarge);
+		}
+		let mut bundle = Vec::with_capacity(bundle_len);
+		let mut index = Vec::with_capacity(index_len);
+		for (path, data) in &self.files {
+			index.extend_from_slice(&(path.len() as u16).to_le_bytes());
+			index.extend_from_slice(path.as_bytes());
+			index.extend_from_slice(&(bundle.len() as u32).to_le_bytes());
+			index.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			bundle.extend_from_slice(data);
+		}
+		let footer = Footer {
+			count: self.files.len() as u32,
+			index_offset: bundle.len() as u32,
+			bundle_len: bundle_len as u32,
+		};
+		bundle.extend_from_slice(&index);
+		bundle.extend_from_slice(&footer.to_bytes());
+		Ok(bundle)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+	fn sample() -> Vec<u8> {
+		let mut writer = Writer::new();
+		writer
+			.add("sprites/player.png", &b"\x89PNG player"[..])
+			.unwrap()
+			.add("/fonts\\small.fnt", vec![1, 2, 3])
+			.unwrap()
+			.add("./empty.txt", Vec::new())
+			.unwrap();
+		writer.finish().unwrap()
+	}
+
+	#[test]
+	fn normalizing() {
+		assert_eq!(normalize("a/b.png").unwrap(), "a/b.png");
+		assert_eq!(normalize("/a//./b.png").unwrap(), "a/b.png");
+		assert_eq!(normalize("a\\b.png").unwrap(), "a/b.png");
+		assert_eq!(normalize("../a.png"), Err(Error::InvalidPath));
+		assert_eq!(normalize("a/../b.png"), Err(Error::InvalidPath));
+		assert_eq!(normalize("/./"), Err(Error::InvalidPath));
+		assert_eq!(normalize(""), Err(Error::InvalidPath));
+	}
+
+	#[test]
+	fn round_trip() {
+		let data = sample();
+		let bundle = Bundle::new(&data).unwrap();
+		assert_eq!(
+			bundle.get("sprites/player.png"),
+			Some(&b"\x89PNG player"[..])
+		);
+		assert_eq!(
+			bundle.get("/sprites/player.png"),
+			Some(&b"\x89PNG player"[..])
+		);
+		assert_eq!(bundle.get("fonts/small.fnt"), Some(&[1, 2, 3][..]));
+		assert_eq!(bundle.get("empty.txt"), Some(&[][..]));
+		assert_eq!(bundle.get("sprites"), None);
+		assert_eq!(bundle.get("../empty.txt"), None);
+		let paths: Vec<_> = bundle
+			.index()
+			.entries()
+			.iter()
+			.map(|entry| entry.path.as_str())
+			.collect();
+		assert_eq!(
+			paths,
+			["empty.txt", "fonts/small.fnt", "sprites/player.png"]
+		);
+
+		let empty = Writer::new().finish().unwrap();
+		assert_eq!(empty.len(), FOOTER_LEN);
+		assert!(Bundle::new(&empty).unwrap().index().entries().is_empty());
+	}
+
+	#[test]
+	fn appended() {
+		// As found at the end of a .tns file
+		let mut tns = b"PRG\0 program code, which happens to contain NDASSET1".to_vec();
+		tns.extend_from_slice(&sample());
+		let bundle = Bundle::new(&tns).unwrap();
+		assert_eq!(bundle.get("fonts/small.fnt"), Some(&[1, 2, 3][..]));
+	}
+
+	#[test]
+	fn footer() {
+		let data = sample();
+		let footer = Footer::parse(&data[data.len() - FOOTER_LEN..]).unwrap();
+		assert_eq!(footer.count, 3);
+		assert_eq!(footer.bundle_len as usize, data.len());
+		assert_eq!(footer.to_bytes(), &data[data.len() - FOOTER_LEN..]);
+		assert_eq!(
+			footer.index_len(),
+			data.len() - FOOTER_LEN - footer.index_offset as usize
+		);
+	}
+
+	#[test]
+	fn errors() {
+		let mut writer = Writer::new();
+		writer.add("a.png", Vec::new()).unwrap();
+		assert_eq!(
+			writer.add("/a.png", Vec::new()).unwrap_err(),
+			Error::Duplicate
+		);
+		assert_eq!(
+			writer.add("..", Vec::new()).unwrap_err(),
+			Error::InvalidPath
+		);
+
+		assert_eq!(Bundle::new(b"PRG\0").unwrap_err(), Error::BadMagic);
+		assert_eq!(
+			Bundle::new(b"PRG\0 no bundle here, just a program").unwrap_err(),
+			Error::BadMagic
+		);
+		// Anything cut off from the start is missing
+		let data = sample();
+		assert_eq!(Bundle::new(&data[1..]).unwrap_err(), Error::Truncated);
+		// Cut off at the end, the footer is gone
+		assert_eq!(
+			Bundle::new(&data[..data.len() - 1]).unwrap_err(),
+			Error::BadMagic
+		);
+
+		// An entry that runs into the index
+		let mut damaged = data.clone();
+		let footer = Footer::parse(&data[data.len() - FOOTER_LEN..]).unwrap();
+		let len_at = data.len() - FOOTER_LEN - 4;
+		damaged[len_at..len_at + 4].copy_from_slice(&footer.index_offset.to_le_bytes());
+		assert_eq!(Bundle::new(&damaged).unwrap_err(), Error::Malformed);
+		// An index offset past the end
+		let mut damaged = data;
+		let offset_at = damaged.len() - FOOTER_LEN + 4;
+		damaged[offset_at..offset_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+		assert_eq!(Bundle::new(&damaged).unwrap_err(), Error::Malformed);
+	}
+}
@@ -100,6 +100,9 @@ pub use yield_now::Yield;
 
 pub mod keypad;
 pub mod mpsc;
+pub mod power;
+pub mod screen;
+pub mod screenshot;
 pub mod task;
 pub mod timer;
 mod yield_now;
@@ -0,0 +1,118 @@
+//! Watches the battery.
+//!
+//! The battery is measured with [`ndless::hw::power`] every few seconds, which
+//! is often enough for a battery that lasts hours. Use
+//! [`BatteryStream`] to show the level in a status bar, or
+//! [`wait_for_low_battery`] to save the user's work before the calculator
+//! turns itself off.
+//!
+//! ```no_run
+//! use ndless_async::task::{block_on, AsyncListeners};
+//! use ndless_async::power::wait_for_low_battery;
+//! use ndless_async::first;
+//! use ndless::hw::power::LOW_BATTERY_PERCENT;
+//! # async fn run_game(_: &AsyncListeners) {}
+//! # fn save_game() {}
+//!
+//! let listeners = AsyncListeners::new();
+//! block_on(&listeners, async {
+//!     first!(run_game(&listeners), async {
+//!         wait_for_low_battery(&listeners.timer(), LOW_BATTERY_PERCENT).await;
+//!         save_game();
+//!     });
+//! });
+//! ```
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+use futures_util::stream::Stream;
+use futures_util::StreamExt;
+use ndless::hw::power::{battery_status, BatteryStatus};
+
+use crate::timer::{Interval, TimerListener};
+
+/// How often the battery is measured by default.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A [`Stream`] of [`BatteryStatus`]es, which produces the current status
+/// straight away, then a new one each time the percentage or charging state
+/// changes. The voltage on its own changes too often to be worth reporting.
+///
+/// ```no_run
+/// use ndless_async::StreamExt;
+/// use ndless_async::power::BatteryStream;
+/// # async fn show(listeners: &ndless_async::task::AsyncListeners) {
+///
+/// let mut battery = BatteryStream::new(&listeners.timer());
+/// while let Some(status) = battery.next().await {
+///     println!("Battery: {}", status);
+/// }
+/// # }
+/// ```
+pub struct BatteryStream {
+	interval: Interval,
+	last: Option<BatteryStatus>,
+}
+
+impl BatteryStream {
+	/// Measures the battery every [`DEFAULT_INTERVAL`].
+	pub fn new(timer_listener: &TimerListener) -> Self {
+		Self::new_with_rate(timer_listener, DEFAULT_INTERVAL)
+	}
+	/// Measures the battery every `ms` milliseconds.
+	pub fn new_with_ms(timer_listener: &TimerListener, ms: u32) -> Self {
+		Self::new_with_rate(timer_listener, Duration::from_millis(ms as u64))
+	}
+	/// Measures the battery with the specified interval.
+	pub fn new_with_rate(timer_listener: &TimerListener, dur: Duration) -> Self {
+		Self {
+			interval: timer_listener.every(dur),
+			last: None,
+		}
+	}
+	/// The last status that this stream produced.
+	pub fn last(&self) -> Option<BatteryStatus> {
+		self.last
+	}
+}
+
+fn changed(last: Option<BatteryStatus>, status: BatteryStatus) -> bool {
+	match last {
+		Some(last) => last.percent != status.percent || last.charge != status.charge,
+		None => true,
+	}
+}
+
+impl Stream for BatteryStream {
+	type Item = BatteryStatus;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		if self.last.is_none() {
+			let status = battery_status();
+			self.last = Some(status);
+			return Poll::Ready(Some(status));
+		}
+		while let Poll::Ready(Some(_)) = Pin::new(&mut self.interval).poll_next(cx) {
+			let status = battery_status();
+			if changed(self.last, status) {
+				self.last = Some(status);
+				return Poll::Ready(Some(status));
+			}
+		}
+		Poll::Pending
+	}
+}
+
+/// Completes once the battery is at or below `threshold` percent and not
+/// charging, checking every [`DEFAULT_INTERVAL`]. If the battery is already
+/// low, this completes straight away.
+pub async fn wait_for_low_battery(timer_listener: &TimerListener, threshold: u8) -> BatteryStatus {
+	let mut stream = BatteryStream::new(timer_listener);
+	while let Some(status) = stream.next().await {
+		if status.is_low(threshold) {
+			return status;
+		}
+	}
+	unreachable!("battery streams never end")
+}
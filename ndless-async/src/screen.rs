@@ -0,0 +1,67 @@
+//! Dims the screen while the keypad isn't being used.
+//!
+//! [`dim_when_idle`] lowers the [brightness](ndless::hw::screen::brightness)
+//! after a while without any key presses, and brings it back as soon as a key
+//! is pressed. Run it alongside the rest of the program:
+//!
+//! ```no_run
+//! use ndless_async::task::{block_on, AsyncListeners};
+//! use ndless_async::keypad::KeypadListener;
+//! use ndless_async::screen::dim_when_idle;
+//! use ndless_async::first;
+//! use ndless::hw::screen::DimPolicy;
+//! # async fn read_book(_: &KeypadListener<'_>) {}
+//!
+//! let listeners = AsyncListeners::new();
+//! let keypad = KeypadListener::new(&listeners.timer());
+//! block_on(&listeners, async {
+//!     first!(
+//!         read_book(&keypad),
+//!         dim_when_idle(&listeners.timer(), &keypad, DimPolicy::default())
+//!     );
+//! });
+//! ```
+use futures_util::StreamExt;
+use ndless::hw::screen::{brightness, set_brightness, DimPolicy, Dimmer};
+
+use crate::keypad::KeypadListener;
+use crate::timer::TimerListener;
+
+/// Dims the screen according to `policy` whenever no key has been pressed for
+/// a while. This never completes, so it should be run with
+/// [`first!`][crate::first] or a similar combinator, which will stop it when
+/// the rest of the program is done. The screen is brightened again if it's
+/// dimmed when this is stopped.
+pub async fn dim_when_idle(
+	timer_listener: &TimerListener,
+	keypad: &KeypadListener<'_>,
+	policy: DimPolicy,
+) {
+	let mut keys = keypad.stream();
+	let mut dimmer = Restore(Dimmer::new(policy));
+	loop {
+		let pressed = match dimmer.0.timeout() {
+			Some(timeout) => timer_listener.timeout(timeout, keys.next()).await.is_ok(),
+			None => keys.next().await.is_some(),
+		};
+		let level = if pressed {
+			dimmer.0.activity()
+		} else {
+			dimmer.0.idle(brightness())
+		};
+		if let Some(level) = level {
+			set_brightness(level);
+		}
+	}
+}
+
+/// Brings back the brightness when the future is dropped.
+struct Restore(Dimmer);
+
+impl Drop for Restore {
+	fn drop(&mut self) {
+		if let Some(level) = self.0.activity() {
+			set_brightness(level);
+		}
+	}
+}
@@ -0,0 +1,98 @@
+//! Takes screenshots when a combination of keys is pressed.
+//!
+//! Run [`screenshot_on_hotkey`] alongside your program, and pressing
+//! [`DEFAULT_HOTKEY`] saves what's on the screen to
+//! `/documents/screenshots` with [`ndless::lcd::screenshot_numbered`].
+//!
+//! ```no_run
+//! use ndless_async::task::{block_on, AsyncListeners};
+//! use ndless_async::keypad::KeypadListener;
+//! use ndless_async::screenshot::{screenshot_on_hotkey, DEFAULT_HOTKEY};
+//! use ndless_async::first;
+//! use ndless::lcd::ImageFormat;
+//! # async fn run_game(_: &KeypadListener<'_>) {}
+//!
+//! let listeners = AsyncListeners::new();
+//! let keypad = KeypadListener::new(&listeners.timer());
+//! block_on(&listeners, async {
+//!     first!(
+//!         run_game(&keypad),
+//!         screenshot_on_hotkey(&keypad, &DEFAULT_HOTKEY, ImageFormat::Png)
+//!     );
+//! });
+//! ```
+use futures_util::future;
+use futures_util::stream::Stream;
+use futures_util::StreamExt;
+use ndless::alloc::vec::Vec;
+use ndless::input::Key;
+use ndless::io;
+use ndless::lcd::{screenshot_numbered, ImageFormat};
+use ndless::path::PathBuf;
+
+use crate::keypad::{KeyState, KeypadListener};
+
+/// Ctrl and Scratchpad, which programs rarely use together.
+pub const DEFAULT_HOTKEY: [Key; 2] = [Key::Ctrl, Key::Scratchpad];
+
+/// A [`Stream`] that takes a screenshot each time the keys in `hotkey` are
+/// all held down, producing where it was saved. The screenshot is taken when
+/// the last key of the combination is pressed, and not again until one of
+/// them is released.
+///
+/// ```no_run
+/// use ndless_async::StreamExt;
+/// use ndless_async::screenshot::{screenshots, DEFAULT_HOTKEY};
+/// use ndless::lcd::ImageFormat;
+/// # async fn show(keypad: &ndless_async::keypad::KeypadListener<'_>) {
+///
+/// let mut screenshots = screenshots(&keypad, &DEFAULT_HOTKEY, ImageFormat::Bmp);
+/// while let Some(path) = screenshots.next().await {
+///     match path {
+///         Ok(path) => println!("Saved {}", path.display()),
+///         Err(err) => println!("Couldn't save a screenshot: {}", err),
+///     }
+/// }
+/// # }
+/// ```
+pub fn screenshots(
+	keypad: &KeypadListener,
+	hotkey: &[Key],
+	format: ImageFormat,
+) -> impl Stream<Item = io::Result<PathBuf>> {
+	let hotkey = hotkey.to_vec();
+	// Kept up to date from the events rather than
+	// `KeypadListener::list_keys`, which may be ahead of the stream
+	let mut held = Vec::with_capacity(hotkey.len());
+	keypad.stream().filter_map(move |event| {
+		let mut complete = false;
+		if hotkey.contains(&event.key) {
+			match event.state {
+				KeyState::Pressed => {
+					if !held.contains(&event.key) {
+						held.push(event.key);
+						complete = held.len() == hotkey.len();
+					}
+				}
+				KeyState::Released => held.retain(|&key| key != event.key),
+			}
+		}
+		future::ready(if complete {
+			Some(screenshot_numbered(format))
+		} else {
+			None
+		})
+	})
+}
+
+/// Takes a screenshot each time `hotkey` is pressed. This never completes,
+/// so run it alongside your program with [`first!`](crate::first). Errors
+/// are ignored: use [`screenshots`] to report them.
+pub async fn screenshot_on_hotkey(
+	keypad: &KeypadListener<'_>,
+	hotkey: &[Key],
+	format: ImageFormat,
+) {
+	let mut screenshots = screenshots(keypad, hotkey, format);
+	while screenshots.next().await.is_some() {}
+}
@@ -0,0 +1,239 @@
+//! The [Glyph Bitmap Distribution Format], a text format for bitmap fonts.
+//!
+//! [Glyph Bitmap Distribution Format]: https://www.adobe.com/content/dam/acom/en/devnet/font/pdfs/5005.BDF_Spec.pdf
+
+use super::{charset, Error, Font, Glyph};
+
+/// A bounding box: width, height, and the offset of the bottom left corner.
+type BoundingBox = (u32, u32, i32, i32);
+
+struct Parser<'a> {
+	lines: std::iter::Enumerate<std::str::Lines<'a>>,
+	line: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn error<T>(&self, message: impl Into<String>) -> Result<T, Error> {
+		Err(Error::Bdf {
+			line: self.line,
+			message: message.into(),
+		})
+	}
+
+	/// The next line that isn't empty or a comment, split into its keyword
+	/// and the rest.
+	fn next(&mut self) -> Result<(&'a str, &'a str), Error> {
+		for (number, line) in &mut self.lines {
+			self.line = number + 1;
+			let line = line.trim();
+			let (keyword, rest) = match line.find(char::is_whitespace) {
+				Some(index) => (&line[..index], line[index..].trim_start()),
+				None => (line, ""),
+			};
+			if !keyword.is_empty() && keyword != "COMMENT" {
+				return Ok((keyword, rest));
+			}
+		}
+		self.error("unexpected end of file")
+	}
+
+	fn numbers<const N: usize>(&self, text: &str) -> Result<[i32; N], Error> {
+		let mut numbers = [0; N];
+		let mut words = text.split_whitespace();
+		for number in &mut numbers {
+			*number = match words.next().map(str::parse) {
+				Some(Ok(value)) => value,
+				_ => return self.error(format!("expected {} numbers", N)),
+			};
+		}
+		Ok(numbers)
+	}
+
+	fn bounding_box(&self, text: &str) -> Result<BoundingBox, Error> {
+		let [width, height, x, y] = self.numbers(text)?;
+		if width < 0 || height < 0 {
+			return self.error("negative size");
+		}
+		Ok((width as u32, height as u32, x, y))
+	}
+}
+
+/// Parses the text of a BDF font.
+pub fn parse_bdf(text: &str) -> Result<Font, Error> {
+	let mut parser = Parser {
+		lines: text.lines().enumerate(),
+		line: 0,
+	};
+	if parser.next()?.0 != "STARTFONT" {
+		return parser.error("not a BDF font");
+	}
+	let mut name = String::new();
+	let mut font_box = None;
+	let mut ascent = None;
+	let mut descent = None;
+	let mut default_code = None;
+	let mut registry = String::new();
+	let mut encoding = String::new();
+	let mut glyphs = Vec::new();
+	// The encoding is only known once the properties have been read
+	let mut codes = Vec::new();
+	loop {
+		let (keyword, rest) = parser.next()?;
+		match keyword {
+			"FONT" => name = rest.to_string(),
+			"FONTBOUNDINGBOX" => font_box = Some(parser.bounding_box(rest)?),
+			"STARTPROPERTIES" => loop {
+				let (keyword, rest) = parser.next()?;
+				let value = rest.trim_matches('"');
+				match keyword {
+					"ENDPROPERTIES" => break,
+					"FONT_ASCENT" => ascent = Some(parser.numbers::<1>(rest)?[0]),
+					"FONT_DESCENT" => descent = Some(parser.numbers::<1>(rest)?[0]),
+					"DEFAULT_CHAR" => default_code = Some(parser.numbers::<1>(rest)?[0]),
+					"CHARSET_REGISTRY" => registry = value.to_string(),
+					"CHARSET_ENCODING" => encoding = value.to_string(),
+					_ => {}
+				}
+			},
+			"STARTCHAR" => {
+				let (code, glyph) = parse_glyph(&mut parser, font_box)?;
+				// Glyphs without an encoding can't be looked up
+				if code >= 0 {
+					codes.push(code as u32);
+					glyphs.push(glyph);
+				}
+			}
+			"ENDFONT" => break,
+			_ => {}
+		}
+	}
+	// Fall back to the font's bounding box, like X11 does
+	let (_, box_height, _, box_y) = font_box.unwrap_or_default();
+	let to_char = charset(&registry, &encoding)?;
+	let mut font = Font {
+		name,
+		ascent: ascent.unwrap_or(box_height as i32 + box_y),
+		descent: descent.unwrap_or(-box_y),
+		default_char: default_code.and_then(|code| to_char(code as u32)),
+		glyphs: Vec::new(),
+	};
+	for (code, mut glyph) in codes.into_iter().zip(glyphs) {
+		if let Some(c) = to_char(code) {
+			glyph.ch = c;
+			font.glyphs.push(glyph);
+		}
+	}
+	font.glyphs.sort_by_key(|glyph| glyph.ch);
+	font.glyphs.dedup_by_key(|glyph| glyph.ch);
+	Ok(font)
+}
+
+/// Parses a glyph after its `STARTCHAR`, returning its encoding.
+fn parse_glyph(parser: &mut Parser, font_box: Option<BoundingBox>) -> Result<(i32, Glyph), Error> {
+	let mut code = None;
+	let mut advance = None;
+	let mut glyph_box = font_box;
+	loop {
+		let (keyword, rest) = parser.next()?;
+		match keyword {
+			// A second number is an index for fonts without an encoding
+			"ENCODING" => code = Some(parser.numbers::<1>(rest)?[0]),
+			"DWIDTH" => advance = Some(parser.numbers::<1>(rest)?[0]),
+			"BBX" => glyph_box = Some(parser.bounding_box(rest)?),
+			"BITMAP" => break,
+			"ENDCHAR" => return parser.error("glyph has no bitmap"),
+			_ => {}
+		}
+	}
+	let code = match code {
+		Some(code) => code,
+		None => return parser.error("glyph has no encoding"),
+	};
+	let (width, height, x, y) = match glyph_box {
+		Some(glyph_box) => glyph_box,
+		None => return parser.error("glyph has no size"),
+	};
+	let mut glyph = Glyph {
+		ch: '\0',
+		advance: advance.unwrap_or(width as i32),
+		width,
+		height,
+		x,
+		y,
+		bitmap: Vec::new(),
+	};
+	let stride = glyph.stride();
+	for _ in 0..height {
+		let (row, _) = parser.next()?;
+		// Rows may be padded to more bytes than the glyph needs
+		if row.len() < stride * 2 || !row.is_ascii() {
+			return parser.error("bitmap row is too short");
+		}
+		for i in 0..stride {
+			match u8::from_str_radix(&row[i * 2..i * 2 + 2], 16) {
+				Ok(byte) => glyph.bitmap.push(byte),
+				Err(_) => return parser.error("invalid bitmap"),
+			}
+		}
+	}
+	// Clear any pixels past the edge of the glyph
+	if width % 8 != 0 {
+		let mask = 0xFF << (8 - width % 8);
+		for row in glyph.bitmap.chunks_mut(stride) {
+			row[stride - 1] &= mask;
+		}
+	}
+	if parser.next()?.0 != "ENDCHAR" {
+		return parser.error("expected ENDCHAR");
+	}
+	Ok((code, glyph))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parsing() {
+		let font = parse_bdf(include_str!("../testdata/sample.bdf")).unwrap();
+		assert_eq!(
+			font.name,
+			"-test-sample-medium-r-normal--7-70-75-75-p-50-iso10646-1"
+		);
+		assert_eq!((font.ascent, font.descent), (5, 2));
+		assert_eq!(font.default_char, Some('¤'));
+		let chars: String = font.glyphs.iter().map(|glyph| glyph.ch).collect();
+		assert_eq!(chars, "ABgj¤éĀ");
+		let j = font.get('j').unwrap();
+		assert_eq!((j.advance, j.width, j.height, j.x, j.y), (3, 3, 7, -1, -2));
+		assert!(j.pixel(2, 0) && !j.pixel(1, 0) && j.pixel(0, 5));
+		let wide = font.get('Ā').unwrap();
+		assert_eq!(wide.stride(), 2);
+		assert!(wide.pixel(8, 0) && wide.pixel(9, 6) && !wide.pixel(0, 0));
+
+		let minimal = "STARTFONT 2.1\nFONTBOUNDINGBOX 3 4 0 -1\nSTARTCHAR a\nENCODING 97\nBITMAP\nFF\nE0\nE0\nE0\nENDCHAR\nENDFONT\n";
+		let font = parse_bdf(minimal).unwrap();
+		assert_eq!((font.ascent, font.descent, font.default_char), (3, 1, None));
+		// The extra bits are cleared, and the size comes from the font
+		let a = &font.glyphs[0];
+		assert_eq!((a.advance, a.width, a.y), (3, 3, -1));
+		assert_eq!(a.bitmap, [0xE0; 4]);
+	}
+
+	#[test]
+	fn errors() {
+		let error = |text: &str| match parse_bdf(text) {
+			Err(Error::Bdf { line, message }) => (line, message),
+			other => panic!("{:?}", other),
+		};
+		assert_eq!(error("").1, "unexpected end of file");
+		assert_eq!(error("hello").1, "not a BDF font");
+		let font =
+			"STARTFONT 2.1\nSTARTCHAR a\nENCODING 97\nBBX 8 2 0 0\nBITMAP\nFF\nZZ\nENDCHAR\n";
+		assert_eq!(error(font), (7, "invalid bitmap".to_string()));
+		let font = "STARTFONT 2.1\nSTARTCHAR a\nENCODING 97\nBBX 8 x 0 0\n";
+		assert_eq!(error(font), (4, "expected 4 numbers".to_string()));
+		let koi8 = "STARTFONT 2.1\nSTARTPROPERTIES 2\nCHARSET_REGISTRY \"KOI8\"\nCHARSET_ENCODING \"R\"\nENDPROPERTIES\nENDFONT\n";
+		assert!(matches!(parse_bdf(koi8), Err(Error::Charset(charset)) if charset == "KOI8-R"));
+	}
+}
@@ -0,0 +1,361 @@
+//! Converts bitmap fonts into Rust source for `ndless::font`.
+//!
+//! Fonts are read from BDF files, or from PCF files like the ones X11 comes
+//! with (uncompressed, so `.pcf.gz` files need to be unzipped first). The
+//! result is a `static` [`ndless::font::Font`] that takes no time to load and
+//! lives in the program's binary.
+//!
+//! This is meant to be used from a build script, which writes the font to
+//! `OUT_DIR` for the program to [`include!`]:
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() {
+//! 	ndless_fonts::convert("fonts/terminus-12.bdf", "TERMINUS").unwrap();
+//! }
+//! ```
+//!
+//! ```ignore
+//! // main.rs
+//! include!(concat!(env!("OUT_DIR"), "/terminus.rs"));
+//! ```
+//!
+//! Large fonts can be cut down to the characters that are used first:
+//!
+//! ```no_run
+//! let mut font = ndless_fonts::load("fonts/unifont.bdf").unwrap();
+//! font.retain(|c| (c as u32) < 0x250);
+//! ndless_fonts::write(&font, "UNIFONT").unwrap();
+//! ```
+//!
+//! Only fonts in Unicode or Latin-1 are supported, as characters in other
+//! encodings can't be looked up by their `char`.
+//!
+//! [`ndless::font::Font`]: https://docs.rs/ndless/latest/ndless/font/struct.Font.html
+#![allow(clippy::tabs_in_doc_comments, clippy::needless_doctest_main)]
+
+use std::fmt::{self, Write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+mod bdf;
+mod pcf;
+
+pub use self::bdf::parse_bdf;
+pub use self::pcf::parse_pcf;
+
+/// Why a font couldn't be read or converted.
+#[derive(Debug)]
+pub enum Error {
+	Io(io::Error),
+	/// A BDF file is invalid, at a line (counting from 1).
+	Bdf {
+		line: usize,
+		message: String,
+	},
+	/// A PCF file is invalid.
+	Pcf(&'static str),
+	/// The file is compressed, and needs to be decompressed first.
+	Compressed,
+	/// The font's characters aren't in Unicode or Latin-1.
+	Charset(String),
+	/// The font has no glyphs that can be used.
+	Empty,
+	/// A glyph is too large for `ndless::font`, which limits sizes to 255
+	/// pixels.
+	TooLarge(char),
+	/// [`convert`] or [`write`] was called outside of a build script.
+	NoOutDir,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::Io(err) => write!(f, "couldn't read font: {}", err),
+			Error::Bdf { line, message } => {
+				write!(f, "invalid BDF font on line {}: {}", line, message)
+			}
+			Error::Pcf(message) => write!(f, "invalid PCF font: {}", message),
+			Error::Compressed => f.write_str("font is compressed"),
+			Error::Charset(charset) => write!(f, "unsupported font encoding {}", charset),
+			Error::Empty => f.write_str("font has no glyphs"),
+			Error::TooLarge(c) => write!(f, "glyph {:?} is too large", c),
+			Error::NoOutDir => f.write_str("OUT_DIR isn't set, so this isn't a build script"),
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Io(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Error::Io(err)
+	}
+}
+
+/// A font as it was read from a file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Font {
+	/// The font's name, which is usually an X11 font name.
+	pub name: String,
+	/// How far the font reaches above the baseline, in pixels.
+	pub ascent: i32,
+	/// How far the font reaches below the baseline, in pixels.
+	pub descent: i32,
+	/// The character drawn in place of those that the font doesn't have.
+	pub default_char: Option<char>,
+	/// Every glyph, sorted by character.
+	pub glyphs: Vec<Glyph>,
+}
+
+/// One character of a [`Font`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Glyph {
+	pub ch: char,
+	/// How far to move right after drawing the glyph.
+	pub advance: i32,
+	pub width: u32,
+	pub height: u32,
+	/// From where the glyph is drawn to the left edge of its bitmap.
+	pub x: i32,
+	/// From the baseline up to the bottom edge of the bitmap.
+	pub y: i32,
+	/// Rows from top to bottom, each [`stride`](Glyph::stride) bytes long,
+	/// with the leftmost pixel in the most significant bit.
+	pub bitmap: Vec<u8>,
+}
+
+impl Glyph {
+	/// The number of bytes in each row of the bitmap.
+	pub fn stride(&self) -> usize {
+		(self.width as usize).div_ceil(8)
+	}
+
+	pub fn pixel(&self, x: u32, y: u32) -> bool {
+		let byte = self.bitmap[y as usize * self.stride() + x as usize / 8];
+		byte & (0x80 >> (x % 8)) != 0
+	}
+}
+
+/// Finds the character that an encoding number stands for, in a font using
+/// `registry` and `encoding`, which are empty if the font doesn't say.
+fn charset(registry: &str, encoding: &str) -> Result<fn(u32) -> Option<char>, Error> {
+	match (registry.to_ascii_uppercase().as_str(), encoding) {
+		("", _) | ("ISO10646", _) => Ok(core::char::from_u32),
+		// The first 256 Unicode code points are the same as Latin-1
+		("ISO8859", "1") => Ok(|code| core::char::from_u32(code).filter(|&c| c <= '\u{FF}')),
+		_ => Err(Error::Charset(format!("{}-{}", registry, encoding))),
+	}
+}
+
+impl Font {
+	/// Reads a BDF or PCF font, depending on what's in `data`.
+	pub fn parse(data: &[u8]) -> Result<Font, Error> {
+		if data.starts_with(pcf::MAGIC) {
+			parse_pcf(data)
+		} else if data.starts_with(b"\x1F\x8B") {
+			Err(Error::Compressed)
+		} else {
+			let text = String::from_utf8_lossy(data);
+			parse_bdf(&text)
+		}
+	}
+
+	pub fn get(&self, c: char) -> Option<&Glyph> {
+		self.glyphs
+			.binary_search_by_key(&c, |glyph| glyph.ch)
+			.ok()
+			.map(|index| &self.glyphs[index])
+	}
+
+	/// Keeps only the glyphs for characters where `f` returns `true`, and
+	/// the [default character](Font::default_char).
+	pub fn retain(&mut self, mut f: impl FnMut(char) -> bool) {
+		let default_char = self.default_char;
+		self.glyphs
+			.retain(|glyph| Some(glyph.ch) == default_char || f(glyph.ch));
+	}
+
+	/// The glyph that's drawn for missing characters: the default character,
+	/// or else `?`, or else the first glyph.
+	fn fallback(&self) -> usize {
+		let find = |c: char| self.glyphs.iter().position(|glyph| glyph.ch == c);
+		self.default_char
+			.and_then(find)
+			.or_else(|| find('?'))
+			.unwrap_or(0)
+	}
+
+	/// Generates a `static` called `name` holding the font. `krate` is the
+	/// path to the `ndless` crate, which is normally `ndless`.
+	pub fn to_rust(&self, name: &str, krate: &str) -> Result<String, Error> {
+		if self.glyphs.is_empty() {
+			return Err(Error::Empty);
+		}
+		let small = |value: i32| (0..=255).contains(&value);
+		let signed = |value: i32| (-128..=127).contains(&value);
+		if !small(self.ascent) || !small(self.descent) {
+			return Err(Error::TooLarge(self.glyphs[0].ch));
+		}
+		let mut glyphs = String::new();
+		let mut bitmap = Vec::new();
+		for glyph in &self.glyphs {
+			let top = glyph.y + glyph.height as i32;
+			if !small(glyph.advance)
+				|| !small(glyph.width as i32)
+				|| !small(glyph.height as i32)
+				|| !signed(glyph.x)
+				|| !signed(top)
+			{
+				return Err(Error::TooLarge(glyph.ch));
+			}
+			writeln!(
+				glyphs,
+				"\t\t{}::font::Glyph {{ ch: {:?}, advance: {}, width: {}, height: {}, left: {}, top: {}, offset: {} }},",
+				krate,
+				glyph.ch,
+				glyph.advance,
+				glyph.width,
+				glyph.height,
+				glyph.x,
+				top,
+				bitmap.len()
+			)
+			.unwrap();
+			bitmap.extend_from_slice(&glyph.bitmap);
+		}
+		let mut bytes = String::new();
+		for line in bitmap.chunks(16) {
+			bytes.push_str("\t\t");
+			let line: Vec<_> = line.iter().map(|byte| format!("0x{:02X},", byte)).collect();
+			bytes.push_str(&line.join(" "));
+			bytes.push('\n');
+		}
+		Ok(format!(
+			"// Generated by ndless-fonts from {:?}.\n\
+			 pub static {}: {}::font::Font = {}::font::Font::new(\n\
+			 \t{},\n\t{},\n\t{},\n\t&[\n{}\t],\n\t&[\n{}\t],\n);\n",
+			self.name,
+			name,
+			krate,
+			krate,
+			self.ascent,
+			self.descent,
+			self.fallback(),
+			glyphs,
+			bytes
+		))
+	}
+}
+
+/// Reads a BDF or PCF font from a file.
+pub fn load(path: impl AsRef<Path>) -> Result<Font, Error> {
+	Font::parse(&std::fs::read(path)?)
+}
+
+/// Writes `font` to `OUT_DIR` as a `static` called `name`, in a file named
+/// after it in lowercase: `TERMINUS` goes in `terminus.rs`. Returns where
+/// the file is.
+pub fn write(font: &Font, name: &str) -> Result<PathBuf, Error> {
+	let out_dir = std::env::var_os("OUT_DIR").ok_or(Error::NoOutDir)?;
+	let path = Path::new(&out_dir).join(format!("{}.rs", name.to_lowercase()));
+	std::fs::write(&path, font.to_rust(name, "ndless")?)?;
+	Ok(path)
+}
+
+/// [Loads](load) a font and [writes](write) it to `OUT_DIR`, and tells Cargo
+/// to run the build script again if the font changes.
+pub fn convert(path: impl AsRef<Path>, name: &str) -> Result<PathBuf, Error> {
+	let path = path.as_ref();
+	println!("cargo:rerun-if-changed={}", path.display());
+	write(&load(path)?, name)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn testdata(name: &str) -> PathBuf {
+		Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("testdata")
+			.join(name)
+	}
+
+	#[test]
+	fn formats() {
+		let bdf = load(testdata("sample.bdf")).unwrap();
+		assert_eq!(bdf.glyphs.len(), 7);
+		let mut checked = 0;
+		for entry in std::fs::read_dir(testdata("")).unwrap() {
+			let path = entry.unwrap().path();
+			if path.extension().is_some_and(|ext| ext == "pcf") {
+				let pcf = load(&path).unwrap_or_else(|err| panic!("{:?}: {}", path, err));
+				assert_eq!(pcf, bdf, "{:?}", path);
+				checked += 1;
+			}
+		}
+		assert!(checked >= 4);
+
+		assert!(matches!(
+			Font::parse(b"\x1F\x8B\x08"),
+			Err(Error::Compressed)
+		));
+		assert!(matches!(
+			Font::parse(b"\x01fcp\x01\x00\x00\x00"),
+			Err(Error::Pcf(_))
+		));
+	}
+
+	#[test]
+	fn generating() {
+		let mut font = load(testdata("sample.bdf")).unwrap();
+		font.retain(|c| c.is_ascii_uppercase());
+		let chars: String = font.glyphs.iter().map(|glyph| glyph.ch).collect();
+		// The default character is kept
+		assert_eq!(chars, "AB\u{A4}");
+		let source = font.to_rust("SAMPLE", "crate").unwrap();
+		assert!(source.starts_with("// Generated by ndless-fonts from \"-test-sample-"));
+		assert!(source.contains(
+			"pub static SAMPLE: crate::font::Font = crate::font::Font::new(\n\t5,\n\t2,\n\t2,\n"
+		));
+		assert!(source.contains("crate::font::Glyph { ch: 'B', advance: 5, width: 4, height: 5, left: 0, top: 5, offset: 5 },"));
+
+		font.glyphs[0].advance = 300;
+		assert!(matches!(
+			font.to_rust("SAMPLE", "crate"),
+			Err(Error::TooLarge('A'))
+		));
+		font.glyphs.clear();
+		assert!(matches!(font.to_rust("SAMPLE", "crate"), Err(Error::Empty)));
+	}
+
+	/// The fonts built into `ndless` are generated from BDF files next to
+	/// them. Set `NDLESS_UPDATE_GOLDEN` to generate them again after changing
+	/// the BDF files.
+	#[test]
+	fn builtin() {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../ndless/src/bindings/font");
+		for &(file, name) in &[
+			("mono_8x8", "MONO_8X8"),
+			("proportional_8", "PROPORTIONAL_8"),
+			("tiny_4x6", "TINY_4X6"),
+		] {
+			let font = load(dir.join(file).with_extension("bdf")).unwrap();
+			let source = font.to_rust(name, "crate").unwrap();
+			let path = dir.join(file).with_extension("rs");
+			if std::env::var_os("NDLESS_UPDATE_GOLDEN").is_some() {
+				std::fs::write(&path, source).unwrap();
+			} else {
+				let existing = std::fs::read_to_string(&path).unwrap();
+				assert!(existing == source, "{:?} is out of date", path);
+			}
+		}
+	}
+}
@@ -0,0 +1,305 @@
+//! The Portable Compiled Format that X11 uses for bitmap fonts, as described
+//! in <https://fontforge.org/docs/techref/pcf-format.html>.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+use super::{charset, Error, Font, Glyph};
+
+pub(super) const MAGIC: &[u8] = b"\x01fcp";
+
+const PROPERTIES: u32 = 1 << 0;
+const ACCELERATORS: u32 = 1 << 1;
+const METRICS: u32 = 1 << 2;
+const BITMAPS: u32 = 1 << 3;
+const BDF_ENCODINGS: u32 = 1 << 5;
+const BDF_ACCELERATORS: u32 = 1 << 8;
+
+/// Set in a table's format if its numbers are big-endian.
+const BYTE_MSB: u32 = 1 << 2;
+/// Set in a table's format if the leftmost pixel of a bitmap is in the most
+/// significant bit.
+const BIT_MSB: u32 = 1 << 3;
+const COMPRESSED_METRICS: u32 = 0x100;
+
+/// Reads the numbers in a table.
+struct Table<'a> {
+	data: &'a [u8],
+	format: u32,
+	position: usize,
+}
+
+impl<'a> Table<'a> {
+	/// Finds a table of type `kind`, and reads its format.
+	fn find(file: &'a [u8], kind: u32) -> Result<Option<Table<'a>>, Error> {
+		let mut header = Table {
+			data: file,
+			format: 0,
+			position: MAGIC.len(),
+		};
+		let count = header.u32()?;
+		for _ in 0..count {
+			let (table_kind, _, size, offset) =
+				(header.u32()?, header.u32()?, header.u32()?, header.u32()?);
+			if table_kind != kind {
+				continue;
+			}
+			let start = offset as usize;
+			let data = file
+				.get(start..start.saturating_add(size as usize))
+				.ok_or(Error::Pcf("table is cut off"))?;
+			let mut table = Table {
+				data,
+				format: 0,
+				position: 0,
+			};
+			// The format is always little-endian
+			table.format = table.u32()?;
+			return Ok(Some(table));
+		}
+		Ok(None)
+	}
+
+	fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+		let bytes = self
+			.data
+			.get(self.position..self.position.saturating_add(len))
+			.ok_or(Error::Pcf("table is cut off"))?;
+		self.position += len;
+		Ok(bytes)
+	}
+
+	fn u8(&mut self) -> Result<u8, Error> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, Error> {
+		let bytes = self.bytes(2)?.try_into().unwrap();
+		Ok(if self.format & BYTE_MSB != 0 {
+			u16::from_be_bytes(bytes)
+		} else {
+			u16::from_le_bytes(bytes)
+		})
+	}
+
+	fn u32(&mut self) -> Result<u32, Error> {
+		let bytes = self.bytes(4)?.try_into().unwrap();
+		Ok(if self.format & BYTE_MSB != 0 {
+			u32::from_be_bytes(bytes)
+		} else {
+			u32::from_le_bytes(bytes)
+		})
+	}
+
+	fn i32(&mut self) -> Result<i32, Error> {
+		Ok(self.u32()? as i32)
+	}
+
+	/// A count of things that take up at least `size` bytes each, checked so
+	/// that broken files can't make huge allocations.
+	fn count(&mut self, value: u32, size: usize) -> Result<usize, Error> {
+		let count = value as usize;
+		if count.saturating_mul(size) > self.data.len() {
+			return Err(Error::Pcf("table is cut off"));
+		}
+		Ok(count)
+	}
+}
+
+/// The size of a glyph, and where it's drawn.
+#[derive(Clone, Copy)]
+struct Metrics {
+	left: i32,
+	right: i32,
+	advance: i32,
+	ascent: i32,
+	descent: i32,
+}
+
+fn required<'a>(file: &'a [u8], kind: u32, name: &'static str) -> Result<Table<'a>, Error> {
+	Table::find(file, kind)?.ok_or(Error::Pcf(name))
+}
+
+fn properties(file: &[u8]) -> Result<HashMap<String, Result<String, i32>>, Error> {
+	let mut properties = HashMap::new();
+	let mut table = match Table::find(file, PROPERTIES)? {
+		Some(table) => table,
+		None => return Ok(properties),
+	};
+	let count = table.u32()?;
+	let count = table.count(count, 9)?;
+	let mut entries = Vec::with_capacity(count);
+	for _ in 0..count {
+		entries.push((table.u32()?, table.u8()? != 0, table.i32()?));
+	}
+	// The entries are padded to a multiple of four bytes
+	table.bytes((4 - count % 4) % 4)?;
+	let len = table.u32()? as usize;
+	let strings = table.bytes(len)?;
+	let string = |offset: u32| -> Result<String, Error> {
+		let bytes = strings
+			.get(offset as usize..)
+			.ok_or(Error::Pcf("invalid property"))?;
+		let end = bytes
+			.iter()
+			.position(|&byte| byte == 0)
+			.unwrap_or(bytes.len());
+		Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+	};
+	for (name, is_string, value) in entries {
+		let value = if is_string {
+			Ok(string(value as u32)?)
+		} else {
+			Err(value)
+		};
+		properties.insert(string(name)?, value);
+	}
+	Ok(properties)
+}
+
+fn metrics(file: &[u8]) -> Result<Vec<Metrics>, Error> {
+	let mut table = required(file, METRICS, "no metrics")?;
+	let mut metrics = Vec::new();
+	if table.format & COMPRESSED_METRICS != 0 {
+		let count = table.u16()?;
+		for _ in 0..table.count(count as u32, 5)? {
+			let mut next = || Ok::<_, Error>(table.u8()? as i32 - 0x80);
+			metrics.push(Metrics {
+				left: next()?,
+				right: next()?,
+				advance: next()?,
+				ascent: next()?,
+				descent: next()?,
+			});
+		}
+	} else {
+		let count = table.u32()?;
+		for _ in 0..table.count(count, 12)? {
+			let mut next = || Ok::<_, Error>(table.u16()? as i16 as i32);
+			metrics.push(Metrics {
+				left: next()?,
+				right: next()?,
+				advance: next()?,
+				ascent: next()?,
+				descent: next()?,
+			});
+			// Attributes
+			table.u16()?;
+		}
+	}
+	Ok(metrics)
+}
+
+/// Reads each glyph's bitmap, converting it to rows padded to a byte with
+/// the leftmost pixel in the most significant bit.
+fn bitmaps(file: &[u8], metrics: &[Metrics]) -> Result<Vec<Glyph>, Error> {
+	let mut table = required(file, BITMAPS, "no bitmaps")?;
+	let count = table.u32()? as usize;
+	if count != metrics.len() {
+		return Err(Error::Pcf("wrong number of bitmaps"));
+	}
+	let mut offsets = Vec::with_capacity(count);
+	for _ in 0..count {
+		offsets.push(table.u32()? as usize);
+	}
+	// The size of the data with each of the four kinds of padding
+	let mut sizes = [0; 4];
+	for size in &mut sizes {
+		*size = table.u32()? as usize;
+	}
+	let format = table.format;
+	let pad = 1 << (format & 3);
+	let unit = 1 << ((format >> 4) & 3);
+	let data = table.bytes(sizes[(format & 3) as usize])?;
+	let mut glyphs = Vec::with_capacity(count);
+	for (metrics, offset) in metrics.iter().zip(offsets) {
+		if metrics.right < metrics.left || metrics.ascent + metrics.descent < 0 {
+			return Err(Error::Pcf("invalid metrics"));
+		}
+		let mut glyph = Glyph {
+			ch: '\0',
+			advance: metrics.advance,
+			width: (metrics.right - metrics.left) as u32,
+			height: (metrics.ascent + metrics.descent) as u32,
+			x: metrics.left,
+			y: -metrics.descent,
+			bitmap: Vec::new(),
+		};
+		let stride = glyph.stride();
+		let padded = stride.div_ceil(pad) * pad;
+		for row in 0..glyph.height as usize {
+			let start = offset + row * padded;
+			let mut bytes = data
+				.get(start..start + padded)
+				.ok_or(Error::Pcf("bitmap is cut off"))?
+				.to_vec();
+			if format & BIT_MSB == 0 {
+				bytes
+					.iter_mut()
+					.for_each(|byte| *byte = byte.reverse_bits());
+			}
+			// Bytes are stored in units of up to four, in the order that the
+			// bits are
+			if (format & BYTE_MSB != 0) != (format & BIT_MSB != 0) && unit > 1 {
+				bytes.chunks_mut(unit).for_each(|unit| unit.reverse());
+			}
+			glyph.bitmap.extend_from_slice(&bytes[..stride]);
+		}
+		glyphs.push(glyph);
+	}
+	Ok(glyphs)
+}
+
+/// Parses the contents of a PCF font.
+pub fn parse_pcf(file: &[u8]) -> Result<Font, Error> {
+	if !file.starts_with(MAGIC) {
+		return Err(Error::Pcf("not a PCF font"));
+	}
+	let properties = properties(file)?;
+	let string = |name: &str| match properties.get(name) {
+		Some(Ok(value)) => value.as_str(),
+		_ => "",
+	};
+	let to_char = charset(string("CHARSET_REGISTRY"), string("CHARSET_ENCODING"))?;
+
+	// The BDF accelerators are more accurate, if there are any
+	let accelerators = match Table::find(file, BDF_ACCELERATORS)? {
+		Some(table) => table,
+		None => required(file, ACCELERATORS, "no accelerators")?,
+	};
+	let mut accelerators = accelerators;
+	accelerators.bytes(8)?;
+	let ascent = accelerators.i32()?;
+	let descent = accelerators.i32()?;
+
+	let mut glyphs = bitmaps(file, &metrics(file)?)?;
+	let mut table = required(file, BDF_ENCODINGS, "no encodings")?;
+	let (min_low, max_low) = (table.u16()?, table.u16()?);
+	let (min_high, max_high) = (table.u16()?, table.u16()?);
+	let default_code = table.u16()?;
+	let mut font = Font {
+		name: string("FONT").to_string(),
+		ascent,
+		descent,
+		default_char: to_char(default_code as u32),
+		glyphs: Vec::new(),
+	};
+	for high in min_high..=max_high {
+		for low in min_low..=max_low {
+			let index = table.u16()?;
+			let glyph = match glyphs.get_mut(index as usize) {
+				Some(glyph) => glyph,
+				// Including 0xFFFF, which means there's no glyph
+				None => continue,
+			};
+			if let Some(c) = to_char((high as u32) << 8 | low as u32) {
+				let mut glyph = glyph.clone();
+				glyph.ch = c;
+				font.glyphs.push(glyph);
+			}
+		}
+	}
+	glyphs.clear();
+	font.glyphs.sort_by_key(|glyph| glyph.ch);
+	Ok(font)
+}
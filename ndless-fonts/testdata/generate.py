@@ -0,0 +1,139 @@
+"""Writes sample.bdf as PCF files in the different layouts that PCF allows.
+
+Run with Python 3 from this directory after changing sample.bdf.
+"""
+import struct
+
+PROPERTIES, ACCELERATORS, METRICS, BITMAPS = 1 << 0, 1 << 1, 1 << 2, 1 << 3
+BDF_ENCODINGS, BDF_ACCELERATORS = 1 << 5, 1 << 8
+BYTE_MSB, BIT_MSB, COMPRESSED_METRICS = 1 << 2, 1 << 3, 0x100
+
+
+def read_bdf(path):
+    font = {"props": {}, "glyphs": []}
+    lines = iter(open(path).read().splitlines())
+    for line in lines:
+        words = line.split()
+        if not words:
+            continue
+        if words[0] == "FONT":
+            font["name"] = line[5:]
+        elif words[0] == "STARTPROPERTIES":
+            for line in lines:
+                if line == "ENDPROPERTIES":
+                    break
+                key, value = line.split(" ", 1)
+                font["props"][key] = value.strip('"') if value.startswith('"') else int(value)
+        elif words[0] == "STARTCHAR":
+            glyph = {}
+            for line in lines:
+                words = line.split()
+                if words[0] == "ENCODING":
+                    glyph["code"] = int(words[1])
+                elif words[0] == "DWIDTH":
+                    glyph["advance"] = int(words[1])
+                elif words[0] == "BBX":
+                    glyph["w"], glyph["h"], glyph["x"], glyph["y"] = map(int, words[1:])
+                elif words[0] == "BITMAP":
+                    glyph["rows"] = [bytes.fromhex(next(lines)) for _ in range(glyph["h"])]
+                elif words[0] == "ENDCHAR":
+                    break
+            if glyph["code"] >= 0:
+                font["glyphs"].append(glyph)
+    return font
+
+
+def write_pcf(font, path, byte_msb, bit_msb, pad, unit, compressed):
+    order = ">" if byte_msb else "<"
+    fmt = (BYTE_MSB if byte_msb else 0) | (BIT_MSB if bit_msb else 0)
+    glyphs = font["glyphs"]
+
+    def table(kind, format, body):
+        return kind, format, struct.pack("<I", format) + body
+
+    # Properties, with the font's name as one of them
+    props = [("FONT", font["name"])] + list(font["props"].items())
+    strings = b""
+    entries = b""
+    for name, value in props:
+        name_offset = len(strings)
+        strings += name.encode() + b"\0"
+        if isinstance(value, str):
+            entries += struct.pack(order + "iBi", name_offset, 1, len(strings))
+            strings += value.encode() + b"\0"
+        else:
+            entries += struct.pack(order + "iBi", name_offset, 0, value)
+    padding = b"\0" * ((4 - len(props) % 4) % 4)
+    properties = struct.pack(order + "i", len(props)) + entries + padding
+    properties += struct.pack(order + "i", len(strings)) + strings
+
+    ascent, descent = font["props"]["FONT_ASCENT"], font["props"]["FONT_DESCENT"]
+    accelerators = bytes(8) + struct.pack(order + "iii", ascent, descent, 0) + bytes(24)
+
+    def metric(g):
+        return (g["x"], g["x"] + g["w"], g["advance"], g["y"] + g["h"], -g["y"], 0)
+
+    if compressed:
+        metrics = struct.pack(order + "H", len(glyphs))
+        for g in glyphs:
+            metrics += bytes(v + 0x80 for v in metric(g)[:5])
+        metrics_format = fmt | COMPRESSED_METRICS
+    else:
+        metrics = struct.pack(order + "I", len(glyphs))
+        for g in glyphs:
+            metrics += struct.pack(order + "hhhhhH", *metric(g))
+        metrics_format = fmt
+
+    # Bitmaps: rows padded to `pad` bytes, with bytes swapped in units of
+    # `unit` if the byte order isn't the bit order, and bits reversed if the
+    # least significant bit is on the left
+    data = b""
+    offsets = []
+    for g in glyphs:
+        offsets.append(len(data))
+        for row in g["rows"]:
+            stride = (len(row) + pad - 1) // pad * pad
+            row = bytearray(row + bytes(stride - len(row)))
+            if byte_msb != bit_msb:
+                for i in range(0, stride, unit):
+                    row[i:i + unit] = row[i:i + unit][::-1]
+            if not bit_msb:
+                row = bytearray(int("{:08b}".format(b)[::-1], 2) for b in row)
+            data += row
+    bitmap_format = fmt | {1: 0, 2: 1, 4: 2}[pad] | {1: 0, 2: 1, 4: 2}[unit] << 4
+    sizes = [len(data)] * 4
+    bitmaps = struct.pack(order + "I", len(glyphs)) + struct.pack(order + "%dI" % len(glyphs), *offsets)
+    bitmaps += struct.pack(order + "4I", *sizes) + data
+
+    codes = {g["code"]: i for i, g in enumerate(glyphs)}
+    min_b2 = min(c & 0xFF for c in codes)
+    max_b2 = max(c & 0xFF for c in codes)
+    min_b1 = min(c >> 8 for c in codes)
+    max_b1 = max(c >> 8 for c in codes)
+    encodings = struct.pack(order + "5H", min_b2, max_b2, min_b1, max_b1, font["props"]["DEFAULT_CHAR"])
+    for b1 in range(min_b1, max_b1 + 1):
+        for b2 in range(min_b2, max_b2 + 1):
+            encodings += struct.pack(order + "H", codes.get(b1 << 8 | b2, 0xFFFF))
+
+    tables = [
+        table(PROPERTIES, fmt, properties),
+        table(BDF_ACCELERATORS if compressed else ACCELERATORS, fmt, accelerators),
+        table(METRICS, metrics_format, metrics),
+        table(BITMAPS, bitmap_format, bitmaps),
+        table(BDF_ENCODINGS, fmt, encodings),
+    ]
+    header = b"\x01fcp" + struct.pack("<I", len(tables))
+    offset = len(header) + 16 * len(tables)
+    body = b""
+    for kind, format, contents in tables:
+        contents += bytes((4 - len(contents) % 4) % 4)
+        header += struct.pack("<4I", kind, format, len(contents), offset + len(body))
+        body += contents
+    open(path, "wb").write(header + body)
+
+
+font = read_bdf("sample.bdf")
+write_pcf(font, "sample-lsb.pcf", False, False, 1, 1, True)
+write_pcf(font, "sample-msb.pcf", True, True, 4, 1, False)
+write_pcf(font, "sample-swapped.pcf", False, True, 4, 4, False)
+write_pcf(font, "sample-pad2.pcf", True, False, 2, 2, True)
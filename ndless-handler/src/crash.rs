@@ -0,0 +1,233 @@
+//! # Crash reports
+//! When the `crash-log` feature is enabled, the panic handler appends a
+//! [`CrashRecord`] to `crash.log.tns` in the program's directory before showing
+//! the error message. Students can then send the file along with their bug
+//! report.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::panic::PanicInfo;
+
+use ndless::fs::OpenOptions;
+use ndless::io::{self, Write};
+use ndless::path::PathBuf;
+
+use crate::stats::{heap_stats, HeapStats};
+
+/// The name of the file that crash records are appended to.
+pub const CRASH_LOG_NAME: &str = "crash.log.tns";
+
+static mut PROGRAM_VERSION: Option<&'static str> = None;
+
+/// Sets the version that is written to crash records. Usually called at the
+/// start of `main` with
+///
+/// ```rust,no_run
+/// ndless_handler::crash::set_program_version(env!("CARGO_PKG_VERSION"));
+/// ```
+pub fn set_program_version(version: &'static str) {
+	unsafe { PROGRAM_VERSION = Some(version) }
+}
+
+/// Returns the version set with [`set_program_version`].
+pub fn program_version() -> Option<&'static str> {
+	unsafe { PROGRAM_VERSION }
+}
+
+/// Everything that is known about a crash. The [`Display`](fmt::Display)
+/// implementation produces the text that is written to the crash log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CrashRecord {
+	/// The name of the program, without `.tns`.
+	pub program: String,
+	pub version: Option<String>,
+	/// The panic message, if there was one.
+	pub message: Option<String>,
+	/// The file, line and column of the panic.
+	pub location: Option<(String, u32, u32)>,
+	/// Timer ticks since the program started. See [`ndless::timer`].
+	pub uptime_ticks: u32,
+	/// A description of the calculator model and screen.
+	pub hardware: String,
+	/// The index that Ndless gives the OS version. See
+	/// [`ndless::ndless::os_index`].
+	pub os_index: Option<usize>,
+	pub ndless_rev: u32,
+	/// Heap usage, if the `alloc-stats` feature is enabled.
+	pub heap: Option<HeapStats>,
+}
+
+impl CrashRecord {
+	/// Gathers information about the program and calculator for a panic.
+	pub fn from_panic(info: &PanicInfo) -> CrashRecord {
+		CrashRecord {
+			message: info.message().map(|message| format!("{}", message)),
+			location: info
+				.location()
+				.map(|loc| (loc.file().to_string(), loc.line(), loc.column())),
+			..CrashRecord::from_message(None)
+		}
+	}
+
+	/// Gathers information about the program and calculator for a crash that
+	/// wasn't a panic, such as running out of memory.
+	pub fn from_message(message: Option<String>) -> CrashRecord {
+		let program = ndless::env::args()
+			.next()
+			.and_then(|path| {
+				PathBuf::from(path)
+					.file_stem()
+					.map(|stem| stem.to_string_lossy().into_owned())
+			})
+			.unwrap_or_else(|| "(unknown)".to_string());
+		CrashRecord {
+			program,
+			version: program_version().map(ToString::to_string),
+			message,
+			location: None,
+			uptime_ticks: ndless::timer::get_ticks(),
+			hardware: ndless::hw::Hardware::current().to_string(),
+			os_index: ndless::ndless::os_index(),
+			ndless_rev: ndless::ndless::ndless_rev(),
+			heap: heap_stats(),
+		}
+	}
+
+	/// Appends this record to [`CRASH_LOG_NAME`] in the directory that the
+	/// program is in, creating it if necessary.
+	pub fn append_to_log(&self) -> io::Result<()> {
+		let path = log_path();
+		let existed = path.exists();
+		let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
+		write!(file, "{}", self)?;
+		drop(file);
+		if !existed {
+			ndless::ndless::refresh_documents();
+		}
+		Ok(())
+	}
+}
+
+/// Returns the path of the crash log, next to the program's `.tns` file.
+pub fn log_path() -> PathBuf {
+	ndless::env::args()
+		.next()
+		.map(PathBuf::from)
+		.and_then(|path| path.parent().map(|dir| dir.join(CRASH_LOG_NAME)))
+		.unwrap_or_else(|| PathBuf::from(CRASH_LOG_NAME))
+}
+
+impl fmt::Display for CrashRecord {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "--- crash ---")?;
+		write!(f, "program: {}", self.program)?;
+		if let Some(version) = &self.version {
+			write!(f, " {}", version)?;
+		}
+		writeln!(f)?;
+		match &self.message {
+			// Keep one line per field so that the log is easy to read on the
+			// calculator
+			Some(message) => writeln!(f, "message: {}", message.replace('\n', " | "))?,
+			None => writeln!(f, "message: (none)")?,
+		}
+		match &self.location {
+			Some((file, line, column)) => writeln!(f, "location: {}:{}:{}", file, line, column)?,
+			None => writeln!(f, "location: (unknown)")?,
+		}
+		writeln!(
+			f,
+			"uptime: {} ticks ({}.{:03} s)",
+			self.uptime_ticks,
+			self.uptime_ticks / ndless::timer::TICKS_PER_SECOND,
+			self.uptime_ticks % ndless::timer::TICKS_PER_SECOND * 1000
+				/ ndless::timer::TICKS_PER_SECOND
+		)?;
+		writeln!(f, "hardware: {}", self.hardware)?;
+		match self.os_index {
+			Some(index) => writeln!(f, "os: Ndless index {}", index)?,
+			None => writeln!(f, "os: (unknown)")?,
+		}
+		writeln!(f, "ndless: r{}", self.ndless_rev)?;
+		match &self.heap {
+			Some(heap) => writeln!(f, "heap: {}", heap),
+			None => writeln!(f, "heap: (not tracked)"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+
+	use super::*;
+
+	fn record() -> CrashRecord {
+		CrashRecord {
+			program: "game".to_string(),
+			version: Some("0.2.1".to_string()),
+			message: Some("index out of bounds: the len is 3 but the index is 5".to_string()),
+			location: Some(("src/main.rs".to_string(), 42, 9)),
+			uptime_ticks: 3 * 32768 + 16384,
+			hardware: "TI-Nspire CX (hwtype 1, subtype 0, Screen320x240x565, keypad 3)".to_string(),
+			os_index: Some(11),
+			ndless_rev: 2015,
+			heap: Some(HeapStats {
+				current_bytes: 1024,
+				peak_bytes: 4096,
+				allocations: 12,
+				deallocations: 9,
+				..HeapStats::default()
+			}),
+		}
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(
+			record().to_string(),
+			"--- crash ---\n\
+			 program: game 0.2.1\n\
+			 message: index out of bounds: the len is 3 but the index is 5\n\
+			 location: src/main.rs:42:9\n\
+			 uptime: 114688 ticks (3.500 s)\n\
+			 hardware: TI-Nspire CX (hwtype 1, subtype 0, Screen320x240x565, keypad 3)\n\
+			 os: Ndless index 11\n\
+			 ndless: r2015\n\
+			 heap: 1024 bytes in use, 4096 bytes peak, 3 live allocations\n"
+		);
+	}
+
+	#[test]
+	fn missing_fields() {
+		let anonymous = CrashRecord {
+			version: None,
+			message: None,
+			location: None,
+			..record()
+		};
+		let text = anonymous.to_string();
+		assert!(text.contains("program: game\n"));
+		assert!(text.contains("message: (none)\n"));
+		assert!(text.contains("location: (unknown)\n"));
+
+		let untracked = CrashRecord {
+			os_index: None,
+			heap: None,
+			..record()
+		};
+		let text = untracked.to_string();
+		assert!(text.contains("os: (unknown)\n"));
+		assert!(text.contains("heap: (not tracked)\n"));
+	}
+
+	#[test]
+	fn multiline_message() {
+		let multiline = CrashRecord {
+			message: Some("first\nsecond".to_string()),
+			..record()
+		};
+		assert!(multiline.to_string().contains("message: first | second\n"));
+	}
+}
@@ -7,15 +7,36 @@ extern crate alloc;
 use alloc::format;
 use alloc::string::ToString;
 
-use crate::allocator::CAllocator;
+#[cfg(feature = "allocator")]
+use core::alloc::{GlobalAlloc, Layout};
 use core::slice;
 
+pub use crate::allocator::CAllocator;
+pub use crate::stats::{heap_stats, HeapStats};
+
 mod allocator;
+#[cfg(any(test, feature = "crash-log"))]
+pub mod crash;
+pub mod oom;
+pub mod panic;
+pub mod pool;
+pub mod stats;
 
 #[cfg(feature = "eh-personality")]
 #[lang = "eh_personality"]
 extern "C" fn eh_personality() {}
 
+/// Sets up the allocator after `ndless::__init`, before `main` runs. The
+/// global allocator registers this with `ndless` the first time that it's
+/// used, so it runs for both `fn main()` and `#[entry]` programs.
+#[cfg_attr(any(test, not(feature = "allocator")), allow(dead_code))]
+fn init() {
+	#[cfg(feature = "oom-handler")]
+	oom::reserve_emergency();
+	#[cfg(feature = "alloc-stats")]
+	stats::watch_leaks();
+}
+
 #[cfg(feature = "lang-start")]
 #[lang = "start"]
 fn lang_start<T: ndless::process::Termination + 'static>(
@@ -26,21 +47,32 @@ fn lang_start<T: ndless::process::Termination + 'static>(
 	unsafe {
 		ndless::__init(slice::from_raw_parts(argv as *const _, argc as usize));
 	}
-	main().report() as isize
+	let code = main().report();
+	ndless::process::__run_at_exit();
+	code as isize
 }
 
 #[cfg(feature = "oom-handler")]
 #[alloc_error_handler]
-fn on_oom(_layout: core::alloc::Layout) -> ! {
-	unsafe {
-		ndless_sys::abort();
-	}
+fn on_oom(layout: core::alloc::Layout) -> ! {
+	oom::report(layout)
 }
 
 #[cfg(feature = "panic-handler")]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
+	let first_panic = panic::run_hooks(info);
+	#[cfg(feature = "crash-log")]
 	{
+		if first_panic {
+			// There's nothing left to report a failure to
+			let _ = crash::CrashRecord::from_panic(info).append_to_log();
+		}
+	}
+	// Put the screen and timers back before showing the message. If one of the
+	// exit hooks panicked, this carries on with the rest.
+	ndless::process::__run_at_exit();
+	if first_panic {
 		let msg = match info.message() {
 			Some(err) => format!("An error occured: {}", err),
 			None => "An error occured!".to_string(),
@@ -59,9 +91,81 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
 	ndless::process::abort();
 }
 
+/// The allocator that does the work, before statistics are counted.
+#[cfg(all(feature = "allocator", not(feature = "pool-allocator")))]
+type Backing = oom::Reclaiming<CAllocator>;
+#[cfg(feature = "pool-allocator")]
+type Backing = oom::Reclaiming<pool::Pooled<CAllocator>>;
+
+#[cfg(feature = "allocator")]
+const fn backing() -> Backing {
+	#[cfg(not(feature = "pool-allocator"))]
+	{
+		oom::Reclaiming::new(CAllocator)
+	}
+	#[cfg(feature = "pool-allocator")]
+	{
+		oom::Reclaiming::new(pool::Pooled::new(CAllocator, pool::DEFAULT_ARENA_SIZE))
+	}
+}
+
+#[cfg(all(feature = "allocator", not(feature = "alloc-stats")))]
+static A: Backing = backing();
+
+#[cfg(feature = "alloc-stats")]
+static A: stats::Counting<Backing> = stats::Counting::new(backing());
+
+/// Passes everything on to [`A`], registering [`init`] with `ndless` first.
+#[cfg(feature = "allocator")]
+struct Global;
+
+#[cfg(feature = "allocator")]
+static mut REGISTERED: bool = false;
+
+#[cfg(feature = "allocator")]
+impl Global {
+	#[inline]
+	fn register(&self) {
+		// Tests never call `ndless::__init`, and can't link everything that
+		// `init` uses
+		#[cfg(not(test))]
+		unsafe {
+			if !REGISTERED {
+				REGISTERED = true;
+				ndless::RUNTIME_INIT = Some(init);
+			}
+		}
+	}
+}
+
+#[cfg(feature = "allocator")]
+unsafe impl GlobalAlloc for Global {
+	#[inline]
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		self.register();
+		A.alloc(layout)
+	}
+
+	#[inline]
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		self.register();
+		A.alloc_zeroed(layout)
+	}
+
+	#[inline]
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		A.dealloc(ptr, layout)
+	}
+
+	#[inline]
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		A.realloc(ptr, layout, new_size)
+	}
+}
+
 #[cfg(feature = "allocator")]
 #[global_allocator]
-static A: CAllocator = CAllocator;
+static GLOBAL: Global = Global;
 
 #[cfg(feature = "ctype-ptr")]
 #[no_mangle]
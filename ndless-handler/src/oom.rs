@@ -0,0 +1,366 @@
+//! # Running out of memory
+//! When an allocation fails, the global allocator first calls the hooks
+//! registered with [`add_low_memory_hook`], which can free caches such as
+//! glyphs or decoded images, and tries again. If there still isn't enough
+//! memory, the `oom-handler` feature shows which allocation failed and how
+//! much of the heap is in use, writes it to stderr and the
+//! [crash log](crate::crash), then exits.
+//!
+//! Showing a message needs memory too, so [`reserve_emergency`] sets aside
+//! [`EMERGENCY_RESERVE`] bytes before `main` runs, whether the program uses
+//! `fn main()` or `#[entry]`, which are freed just before the message is
+//! shown.
+//!
+//! Large buffers that the program can do without, such as a second frame
+//! buffer, should be allocated with [`try_vec`] or [`try_box`], which return
+//! an error instead of exiting.
+
+use alloc::alloc::alloc;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt;
+use core::ptr::{self, addr_of, addr_of_mut};
+
+type Hook = Box<dyn Fn(Layout) -> bool>;
+
+static mut HOOKS: Vec<Hook> = Vec::new();
+static mut RECLAIMING: bool = false;
+
+/// Registers a function that is called when an allocation fails, with the
+/// layout that couldn't be allocated. It should free whatever memory it can,
+/// and return `true` if it freed anything, in which case the allocation is
+/// tried again. Hooks are called in the order that they were added, until the
+/// allocation succeeds.
+///
+/// If a hook allocates memory and that fails too, the hooks aren't called
+/// again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use core::ptr::addr_of_mut;
+///
+/// static mut GLYPHS: Vec<Vec<u8>> = Vec::new();
+///
+/// ndless_handler::oom::add_low_memory_hook(|_layout| {
+///     let glyphs = unsafe { &mut *addr_of_mut!(GLYPHS) };
+///     let had_glyphs = !glyphs.is_empty();
+///     *glyphs = Vec::new();
+///     had_glyphs
+/// });
+/// ```
+pub fn add_low_memory_hook<F: Fn(Layout) -> bool + 'static>(hook: F) {
+	unsafe { (*addr_of_mut!(HOOKS)).push(Box::new(hook)) }
+}
+
+/// Removes all hooks registered with [`add_low_memory_hook`].
+pub fn clear_low_memory_hooks() {
+	unsafe { (*addr_of_mut!(HOOKS)).clear() }
+}
+
+/// Calls `attempt`, and if it fails, calls each hook and tries again after
+/// every one that freed memory.
+fn retry_with(hooks: &[Hook], layout: Layout, mut attempt: impl FnMut() -> *mut u8) -> *mut u8 {
+	let mut ptr = attempt();
+	for hook in hooks {
+		if !ptr.is_null() {
+			break;
+		}
+		if hook(layout) {
+			ptr = attempt();
+		}
+	}
+	ptr
+}
+
+/// An allocator that calls the [low memory hooks](add_low_memory_hook) when
+/// the allocator that it wraps fails, and tries again.
+pub struct Reclaiming<A> {
+	inner: A,
+}
+
+impl<A> Reclaiming<A> {
+	pub const fn new(inner: A) -> Reclaiming<A> {
+		Reclaiming { inner }
+	}
+
+	pub fn inner(&self) -> &A {
+		&self.inner
+	}
+
+	unsafe fn retry(&self, layout: Layout, mut attempt: impl FnMut() -> *mut u8) -> *mut u8 {
+		let ptr = attempt();
+		let hooks = &*addr_of!(HOOKS);
+		if !ptr.is_null() || RECLAIMING || hooks.is_empty() {
+			return ptr;
+		}
+		RECLAIMING = true;
+		let ptr = retry_with(hooks, layout, attempt);
+		RECLAIMING = false;
+		ptr
+	}
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Reclaiming<A> {
+	#[inline]
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		self.retry(layout, || self.inner.alloc(layout))
+	}
+
+	#[inline]
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		self.retry(layout, || self.inner.alloc_zeroed(layout))
+	}
+
+	#[inline]
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		self.inner.dealloc(ptr, layout)
+	}
+
+	#[inline]
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+		// A failed realloc leaves the old allocation alone, so it can be tried
+		// again
+		self.retry(new_layout, || self.inner.realloc(ptr, layout, new_size))
+	}
+}
+
+/// An allocation made with one of the `try_*` functions failed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AllocError {
+	/// The requested size doesn't fit in memory at all.
+	CapacityOverflow,
+	/// There wasn't enough memory for this layout, even after calling the
+	/// low memory hooks.
+	OutOfMemory(Layout),
+}
+
+impl fmt::Display for AllocError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			AllocError::CapacityOverflow => f.write_str("requested capacity is too large"),
+			AllocError::OutOfMemory(layout) => write!(
+				f,
+				"out of memory allocating {} bytes (aligned to {})",
+				layout.size(),
+				layout.align()
+			),
+		}
+	}
+}
+
+impl ndless::error::Error for AllocError {}
+
+/// Creates a `Vec` of `len` copies of `value`, or returns an error if there
+/// isn't enough memory.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// // A second frame buffer for a 320x240 screen, if there's room
+/// let back_buffer = ndless_handler::oom::try_vec(320 * 240, 0u16).ok();
+/// ```
+pub fn try_vec<T: Clone>(len: usize, value: T) -> Result<Vec<T>, AllocError> {
+	let layout = Layout::array::<T>(len).map_err(|_| AllocError::CapacityOverflow)?;
+	let mut vec = Vec::new();
+	vec.try_reserve_exact(len)
+		.map_err(|_| AllocError::OutOfMemory(layout))?;
+	vec.resize(len, value);
+	Ok(vec)
+}
+
+/// Moves `value` to the heap, or returns an error if there isn't enough
+/// memory.
+pub fn try_box<T>(value: T) -> Result<Box<T>, AllocError> {
+	let layout = Layout::new::<T>();
+	if layout.size() == 0 {
+		return Ok(Box::new(value));
+	}
+	unsafe {
+		let ptr = alloc(layout) as *mut T;
+		if ptr.is_null() {
+			return Err(AllocError::OutOfMemory(layout));
+		}
+		ptr::write(ptr, value);
+		Ok(Box::from_raw(ptr))
+	}
+}
+
+/// The number of bytes set aside for reporting that the program ran out of
+/// memory.
+pub const EMERGENCY_RESERVE: usize = 16 * 1024;
+
+#[cfg(feature = "oom-handler")]
+static mut RESERVE: *mut u8 = ptr::null_mut();
+
+#[cfg(feature = "oom-handler")]
+fn reserve_layout() -> Layout {
+	Layout::from_size_align(EMERGENCY_RESERVE, 4).unwrap()
+}
+
+/// Sets aside [`EMERGENCY_RESERVE`] bytes for the out of memory handler. This
+/// is called before `main`, so it only needs to be called again after the
+/// reserve was used up.
+#[cfg(feature = "oom-handler")]
+pub fn reserve_emergency() {
+	unsafe {
+		if RESERVE.is_null() {
+			RESERVE = alloc(reserve_layout());
+		}
+	}
+}
+
+/// Frees the memory set aside by [`reserve_emergency`].
+#[cfg(feature = "oom-handler")]
+fn release_emergency() {
+	unsafe {
+		if !RESERVE.is_null() {
+			alloc::alloc::dealloc(RESERVE, reserve_layout());
+			RESERVE = ptr::null_mut();
+		}
+	}
+}
+
+/// Formats into a fixed buffer, cutting off anything that doesn't fit.
+#[cfg(feature = "oom-handler")]
+struct Truncating<'a> {
+	buffer: &'a mut [u8],
+	len: usize,
+}
+
+#[cfg(feature = "oom-handler")]
+impl Truncating<'_> {
+	fn as_str(&self) -> &str {
+		match core::str::from_utf8(&self.buffer[..self.len]) {
+			Ok(text) => text,
+			// Cut off in the middle of a character
+			Err(err) => unsafe {
+				core::str::from_utf8_unchecked(&self.buffer[..err.valid_up_to()])
+			},
+		}
+	}
+}
+
+#[cfg(feature = "oom-handler")]
+impl fmt::Write for Truncating<'_> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		let count = s.len().min(self.buffer.len() - self.len);
+		self.buffer[self.len..self.len + count].copy_from_slice(&s.as_bytes()[..count]);
+		self.len += count;
+		Ok(())
+	}
+}
+
+#[cfg(feature = "oom-handler")]
+static mut MESSAGE: [u8; 256] = [0; 256];
+
+/// Reports that `layout` couldn't be allocated, then exits.
+#[cfg(feature = "oom-handler")]
+pub(crate) fn report(layout: Layout) -> ! {
+	use core::fmt::Write;
+
+	// Describe the failure before anything else is freed or allocated
+	let mut message = Truncating {
+		buffer: unsafe { &mut *addr_of_mut!(MESSAGE) },
+		len: 0,
+	};
+	let _ = write!(message, "{}", AllocError::OutOfMemory(layout));
+	if let Some(stats) = crate::stats::heap_stats() {
+		let _ = write!(message, "\nheap: {}", stats);
+	}
+	let message = message.as_str();
+	release_emergency();
+	ndless::eprintln!("{}", message);
+	#[cfg(feature = "crash-log")]
+	{
+		let record = crate::crash::CrashRecord::from_message(Some(message.into()));
+		let _ = record.append_to_log();
+	}
+	ndless::process::__run_at_exit();
+	ndless::msg::msg("Out of memory", message);
+	ndless::process::abort();
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use alloc::boxed::Box;
+	use alloc::vec;
+	use core::cell::Cell;
+	use std::rc::Rc;
+
+	use super::*;
+
+	#[test]
+	fn retries() {
+		let calls = Rc::new(Cell::new(0));
+		let freed = Rc::new(Cell::new(false));
+		let hooks: Vec<Hook> = vec![
+			// Has nothing to free
+			Box::new({
+				let calls = calls.clone();
+				move |_| {
+					calls.set(calls.get() + 1);
+					false
+				}
+			}),
+			Box::new({
+				let calls = calls.clone();
+				let freed = freed.clone();
+				move |layout| {
+					assert_eq!(layout.size(), 100);
+					calls.set(calls.get() + 1);
+					freed.set(true);
+					true
+				}
+			}),
+			Box::new(|_| panic!("not needed")),
+		];
+		let layout = Layout::from_size_align(100, 4).unwrap();
+		let mut block = [0u8; 1];
+		let mut attempts = 0;
+		let ptr = retry_with(&hooks, layout, || {
+			attempts += 1;
+			if freed.get() {
+				block.as_mut_ptr()
+			} else {
+				ptr::null_mut()
+			}
+		});
+		assert!(!ptr.is_null());
+		assert_eq!(attempts, 2);
+		assert_eq!(calls.get(), 2);
+
+		// Hooks aren't called when the first attempt succeeds
+		let ptr = retry_with(&hooks[2..], layout, || block.as_mut_ptr());
+		assert!(!ptr.is_null());
+
+		// Or fails with nothing to free
+		let ptr = retry_with(&hooks[..1], layout, ptr::null_mut);
+		assert!(ptr.is_null());
+		assert_eq!(calls.get(), 3);
+	}
+
+	#[test]
+	fn fallible() {
+		assert_eq!(try_vec(5, 7u16).unwrap(), vec![7; 5]);
+		assert_eq!(try_vec(0, 0u8).unwrap(), vec![]);
+		assert_eq!(try_vec(usize::MAX, 0u32), Err(AllocError::CapacityOverflow));
+		let too_large = isize::MAX as usize / 2;
+		assert_eq!(
+			try_vec(too_large, 0u8),
+			Err(AllocError::OutOfMemory(
+				Layout::array::<u8>(too_large).unwrap()
+			))
+		);
+		assert_eq!(*try_box([1u32; 64]).unwrap(), [1; 64]);
+		assert_eq!(*try_box(()).unwrap(), ());
+		assert_eq!(
+			std::format!("{}", AllocError::OutOfMemory(Layout::new::<u64>())),
+			"out of memory allocating 8 bytes (aligned to 8)"
+		);
+	}
+}
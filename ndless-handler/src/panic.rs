@@ -0,0 +1,58 @@
+//! # Panic hooks
+//! Hooks are called by the panic handler before the default behaviour of
+//! writing the [crash log](crate::crash) and showing the error message. They
+//! can be used to save the user's progress or restore hardware state.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::panic::PanicInfo;
+use core::ptr::{addr_of, addr_of_mut};
+
+type Hook = Box<dyn Fn(&PanicInfo)>;
+
+static mut HOOKS: Vec<Hook> = Vec::new();
+static mut PANICKING: bool = false;
+
+/// Registers a function that is called when the program panics. Hooks are
+/// called in the order that they were added.
+///
+/// If a hook panics itself, the remaining hooks, the crash log and the error
+/// message are skipped. The [exit hooks](ndless::process::at_exit) that
+/// haven't run yet still put the hardware back before the program aborts.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// ndless_handler::panic::add_hook(|info| {
+///     ndless::println!("panicked: {}", info);
+/// });
+/// ```
+pub fn add_hook<F: Fn(&PanicInfo) + 'static>(hook: F) {
+	unsafe { (*addr_of_mut!(HOOKS)).push(Box::new(hook)) }
+}
+
+/// Removes all hooks registered with [`add_hook`].
+pub fn clear_hooks() {
+	unsafe { (*addr_of_mut!(HOOKS)).clear() }
+}
+
+/// Returns `true` if the program is currently panicking.
+pub fn panicking() -> bool {
+	unsafe { PANICKING }
+}
+
+/// Runs the hooks for a panic. Returns `false` if this is a panic from within
+/// the panic handler, in which case nothing is run.
+#[cfg_attr(not(feature = "panic-handler"), allow(dead_code))]
+pub(crate) fn run_hooks(info: &PanicInfo) -> bool {
+	unsafe {
+		if PANICKING {
+			return false;
+		}
+		PANICKING = true;
+		for hook in (*addr_of!(HOOKS)).iter() {
+			hook(info);
+		}
+	}
+	true
+}
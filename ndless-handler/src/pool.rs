@@ -0,0 +1,651 @@
+//! # Pooled allocator
+//! The OS `malloc` is slow when a program makes many small allocations, such as
+//! a game that builds `Vec`s every frame, and [`CAllocator`](crate::CAllocator)
+//! has to over-allocate by `2 * align + 4` bytes for anything aligned to more
+//! than 4 bytes, which includes `u64` and `f64`.
+//!
+//! When the `pool-allocator` feature is enabled, the global allocator is a
+//! [`Pooled`] allocator instead. It takes an arena of [`DEFAULT_ARENA_SIZE`]
+//! bytes from `malloc` the first time that it's used, and splits it into blocks
+//! of a few [size classes](CLASS_SIZES), each with its own list of free
+//! blocks. Allocations that are larger than [`MAX_POOLED_SIZE`], need more
+//! than 8-byte alignment, or don't fit in the arena any more are passed on to
+//! `malloc`.
+//!
+//! Freed blocks are only reused for allocations of the same size class, so a
+//! program that first makes many small allocations and then many large ones
+//! will use more memory than it would with `malloc`. [`Pooled::stats`] shows
+//! how much of the arena is in use and how much is waiting to be reused.
+//!
+//! To use a different arena size, disable the `allocator` feature and declare
+//! the allocator yourself:
+//!
+//! ```rust,ignore
+//! use ndless_handler::pool::Pooled;
+//! use ndless_handler::CAllocator;
+//!
+//! #[global_allocator]
+//! static A: Pooled<CAllocator> = Pooled::new(CAllocator, 4 * 1024 * 1024);
+//! ```
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::cmp;
+use core::ptr;
+
+/// The size of the arena used by the global allocator when the
+/// `pool-allocator` feature is enabled.
+pub const DEFAULT_ARENA_SIZE: usize = 1024 * 1024;
+
+/// The smallest arena that is worth allocating. If `malloc` can't provide the
+/// requested size, smaller arenas are tried down to this size.
+const MIN_ARENA_SIZE: usize = 16 * 1024;
+
+/// The alignment of every block in the arena.
+const BLOCK_ALIGN: usize = 8;
+
+/// The number of size classes.
+pub const CLASSES: usize = 16;
+
+/// The sizes of blocks in the arena. Allocations are rounded up to the next
+/// size in the list.
+pub const CLASS_SIZES: [usize; CLASSES] = [
+	8, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1024, 1536, 2048,
+];
+
+/// The largest allocation that is served from the arena.
+pub const MAX_POOLED_SIZE: usize = CLASS_SIZES[CLASSES - 1];
+
+/// Returns the index in [`CLASS_SIZES`] of the class used for `size` bytes, or
+/// `None` if it's too large to be pooled.
+pub fn class_for(size: usize) -> Option<usize> {
+	if size <= 32 {
+		return Some(cmp::max(size, 1).saturating_sub(1) / 8);
+	}
+	if size > MAX_POOLED_SIZE {
+		return None;
+	}
+	// The highest bit of `size - 1`, so that 2^bit < size <= 2^(bit + 1).
+	// There are two classes between each power of two, 1.5 * 2^bit and
+	// 2^(bit + 1).
+	let bit = (usize::BITS - 1 - (size - 1).leading_zeros()) as usize;
+	let upper_half = size > 3 << (bit - 1);
+	Some(4 + 2 * (bit - 5) + upper_half as usize)
+}
+
+/// A block on one of the free lists.
+struct FreeBlock {
+	next: *mut FreeBlock,
+}
+
+/// How the arena is being used, as returned by [`Pooled::stats`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PoolStats {
+	/// The size of the arena, or `0` if it hasn't been allocated yet or
+	/// couldn't be.
+	pub arena_bytes: usize,
+	/// Bytes of the arena that have been split into blocks. The rest has never
+	/// been used.
+	pub carved_bytes: usize,
+	/// Bytes in blocks that are on the free lists, waiting to be reused.
+	pub free_bytes: usize,
+	/// Bytes that were asked for by allocations that are currently in the
+	/// arena. The difference between this and the blocks in use is lost to
+	/// rounding up to the size classes.
+	pub requested_bytes: usize,
+	/// Allocations that are currently in the arena.
+	pub pooled_allocations: usize,
+	/// Allocations that are currently passed on to the inner allocator.
+	pub fallback_allocations: usize,
+}
+
+impl PoolStats {
+	/// Bytes in blocks that are currently allocated.
+	pub fn used_bytes(&self) -> usize {
+		self.carved_bytes - self.free_bytes
+	}
+
+	/// Bytes lost to rounding up to the size classes.
+	pub fn rounding_bytes(&self) -> usize {
+		self.used_bytes() - self.requested_bytes
+	}
+}
+
+struct State {
+	arena_start: usize,
+	arena_end: usize,
+	/// The start of the part of the arena that hasn't been split into blocks.
+	next: usize,
+	initialised: bool,
+	free: [*mut FreeBlock; CLASSES],
+	stats: PoolStats,
+}
+
+/// An allocator that serves small allocations from pools of fixed-size
+/// blocks in an arena, and passes everything else on to another allocator.
+///
+/// Like [`Counting`](crate::stats::Counting), this isn't synchronised, since
+/// the calculator only has one thread and interrupt handlers must not
+/// allocate.
+pub struct Pooled<A> {
+	inner: A,
+	arena_size: usize,
+	state: UnsafeCell<State>,
+}
+
+unsafe impl<A: Sync> Sync for Pooled<A> {}
+
+impl<A> Pooled<A> {
+	/// Creates an allocator that will take an arena of `arena_size` bytes from
+	/// `inner` when it's first used.
+	pub const fn new(inner: A, arena_size: usize) -> Pooled<A> {
+		Pooled {
+			inner,
+			arena_size,
+			state: UnsafeCell::new(State {
+				arena_start: 0,
+				arena_end: 0,
+				next: 0,
+				initialised: false,
+				free: [ptr::null_mut(); CLASSES],
+				stats: PoolStats {
+					arena_bytes: 0,
+					carved_bytes: 0,
+					free_bytes: 0,
+					requested_bytes: 0,
+					pooled_allocations: 0,
+					fallback_allocations: 0,
+				},
+			}),
+		}
+	}
+
+	pub fn stats(&self) -> PoolStats {
+		unsafe { (*self.state.get()).stats }
+	}
+
+	pub fn inner(&self) -> &A {
+		&self.inner
+	}
+
+	#[allow(clippy::mut_from_ref)]
+	unsafe fn state(&self) -> &mut State {
+		&mut *self.state.get()
+	}
+
+	fn owns(&self, ptr: *mut u8) -> bool {
+		let state = unsafe { self.state() };
+		let addr = ptr as usize;
+		addr >= state.arena_start && addr < state.arena_end
+	}
+}
+
+/// Returns the size class to use for `layout`, if it can go in the arena.
+fn pooled_class(layout: &Layout) -> Option<usize> {
+	if layout.align() <= BLOCK_ALIGN {
+		class_for(layout.size())
+	} else {
+		None
+	}
+}
+
+impl<A: GlobalAlloc> Pooled<A> {
+	/// Takes the arena from the inner allocator, trying smaller sizes if
+	/// there isn't enough memory.
+	unsafe fn initialise(&self) {
+		let state = self.state();
+		state.initialised = true;
+		let mut size = self.arena_size - self.arena_size % BLOCK_ALIGN;
+		while size >= MIN_ARENA_SIZE {
+			let arena = self
+				.inner
+				.alloc(Layout::from_size_align_unchecked(size, BLOCK_ALIGN));
+			if !arena.is_null() {
+				state.arena_start = arena as usize;
+				state.arena_end = arena as usize + size;
+				state.next = arena as usize;
+				state.stats.arena_bytes = size;
+				return;
+			}
+			size /= 2;
+		}
+	}
+
+	/// Allocates a block from the arena, or returns null if it's full.
+	unsafe fn alloc_pooled(&self, class: usize, size: usize) -> *mut u8 {
+		if !self.state().initialised {
+			self.initialise();
+		}
+		let state = self.state();
+		let class_size = CLASS_SIZES[class];
+		let block = if !state.free[class].is_null() {
+			let block = state.free[class];
+			state.free[class] = (*block).next;
+			state.stats.free_bytes -= class_size;
+			block as *mut u8
+		} else if state.arena_end - state.next >= class_size {
+			let block = state.next as *mut u8;
+			state.next += class_size;
+			state.stats.carved_bytes += class_size;
+			block
+		} else {
+			return ptr::null_mut();
+		};
+		state.stats.requested_bytes += size;
+		state.stats.pooled_allocations += 1;
+		block
+	}
+
+	unsafe fn dealloc_pooled(&self, ptr: *mut u8, class: usize, size: usize) {
+		let state = self.state();
+		let block = ptr as *mut FreeBlock;
+		(*block).next = state.free[class];
+		state.free[class] = block;
+		state.stats.free_bytes += CLASS_SIZES[class];
+		state.stats.requested_bytes -= size;
+		state.stats.pooled_allocations -= 1;
+	}
+
+	unsafe fn alloc_fallback(&self, layout: Layout, zeroed: bool) -> *mut u8 {
+		let ptr = if zeroed {
+			self.inner.alloc_zeroed(layout)
+		} else {
+			self.inner.alloc(layout)
+		};
+		if !ptr.is_null() {
+			self.state().stats.fallback_allocations += 1;
+		}
+		ptr
+	}
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Pooled<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		if let Some(class) = pooled_class(&layout) {
+			let ptr = self.alloc_pooled(class, layout.size());
+			if !ptr.is_null() {
+				return ptr;
+			}
+		}
+		self.alloc_fallback(layout, false)
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		if let Some(class) = pooled_class(&layout) {
+			let ptr = self.alloc_pooled(class, layout.size());
+			if !ptr.is_null() {
+				// Blocks may have been used before
+				ptr::write_bytes(ptr, 0, layout.size());
+				return ptr;
+			}
+		}
+		self.alloc_fallback(layout, true)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		if self.owns(ptr) {
+			// Anything in the arena was allocated with a class
+			let class = class_for(layout.size()).unwrap();
+			self.dealloc_pooled(ptr, class, layout.size());
+		} else {
+			self.inner.dealloc(ptr, layout);
+			self.state().stats.fallback_allocations -= 1;
+		}
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+		if self.owns(ptr) {
+			let class = class_for(layout.size()).unwrap();
+			if pooled_class(&new_layout) == Some(class) {
+				// Still fits in the same block
+				let stats = &mut self.state().stats;
+				stats.requested_bytes = stats.requested_bytes - layout.size() + new_size;
+				return ptr;
+			}
+		} else if pooled_class(&new_layout).is_none() {
+			// Both are too large for the arena, which `malloc` may be able to
+			// resize without copying
+			return self.inner.realloc(ptr, layout, new_size);
+		}
+		let new_ptr = self.alloc(new_layout);
+		if !new_ptr.is_null() {
+			ptr::copy_nonoverlapping(ptr, new_ptr, cmp::min(layout.size(), new_size));
+			self.dealloc(ptr, layout);
+		}
+		new_ptr
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use std::alloc::System;
+	use std::println;
+	use std::time::Instant;
+	use std::vec::Vec;
+
+	use super::*;
+
+	#[test]
+	fn classes() {
+		for size in 0..=MAX_POOLED_SIZE {
+			let expected = CLASS_SIZES.iter().position(|&class| class >= size).unwrap();
+			assert_eq!(class_for(size), Some(expected), "size {}", size);
+		}
+		assert_eq!(class_for(MAX_POOLED_SIZE + 1), None);
+		assert_eq!(class_for(usize::MAX), None);
+	}
+
+	#[test]
+	fn reuse() {
+		let pool = Pooled::new(System, MIN_ARENA_SIZE);
+		unsafe {
+			let layout = Layout::from_size_align(20, 4).unwrap();
+			let a = pool.alloc(layout);
+			assert!(pool.owns(a));
+			assert_eq!(a as usize % BLOCK_ALIGN, 0);
+			let stats = pool.stats();
+			assert_eq!(stats.arena_bytes, MIN_ARENA_SIZE);
+			assert_eq!(stats.carved_bytes, 24);
+			assert_eq!(stats.rounding_bytes(), 4);
+
+			pool.dealloc(a, layout);
+			assert_eq!(pool.stats().free_bytes, 24);
+			// Same class, so the block is reused
+			let b = pool.alloc(Layout::from_size_align(17, 1).unwrap());
+			assert_eq!(a, b);
+			assert_eq!(pool.stats().free_bytes, 0);
+
+			// Grows within the block, then moves to a larger class
+			let b = pool.realloc(b, Layout::from_size_align(17, 1).unwrap(), 24);
+			assert_eq!(a, b);
+			*b.add(23) = 0xAB;
+			let c = pool.realloc(b, Layout::from_size_align(24, 1).unwrap(), 100);
+			assert_ne!(b, c);
+			assert_eq!(*c.add(23), 0xAB);
+			assert_eq!(pool.stats().pooled_allocations, 1);
+			pool.dealloc(c, Layout::from_size_align(100, 1).unwrap());
+
+			let zeroed = pool.alloc_zeroed(Layout::from_size_align(100, 1).unwrap());
+			assert_eq!(zeroed, c);
+			assert!((0..100).all(|i| *zeroed.add(i) == 0));
+			pool.dealloc(zeroed, Layout::from_size_align(100, 1).unwrap());
+		}
+	}
+
+	#[test]
+	fn fallback() {
+		let pool = Pooled::new(System, MIN_ARENA_SIZE);
+		unsafe {
+			let large = Layout::from_size_align(MAX_POOLED_SIZE + 1, 4).unwrap();
+			let aligned = Layout::from_size_align(64, 64).unwrap();
+			let a = pool.alloc(large);
+			let b = pool.alloc(aligned);
+			assert!(!pool.owns(a) && !pool.owns(b));
+			assert_eq!(b as usize % 64, 0);
+			assert_eq!(pool.stats().fallback_allocations, 2);
+
+			// Fill the arena, after which allocations go to the inner allocator
+			let block = Layout::from_size_align(MAX_POOLED_SIZE, 8).unwrap();
+			let mut blocks = Vec::new();
+			loop {
+				let ptr = pool.alloc(block);
+				if !pool.owns(ptr) {
+					pool.dealloc(ptr, block);
+					break;
+				}
+				blocks.push(ptr);
+			}
+			assert_eq!(blocks.len(), MIN_ARENA_SIZE / MAX_POOLED_SIZE);
+			// A small allocation still doesn't fit, since blocks aren't split
+			let small = Layout::new::<u32>();
+			let c = pool.alloc(small);
+			assert!(!pool.owns(c));
+			pool.dealloc(c, small);
+			for ptr in blocks {
+				pool.dealloc(ptr, block);
+			}
+
+			// Moves into one of the freed blocks when it shrinks
+			let a = pool.realloc(a, large, MAX_POOLED_SIZE);
+			assert!(pool.owns(a));
+			pool.dealloc(a, Layout::from_size_align(MAX_POOLED_SIZE, 4).unwrap());
+			pool.dealloc(b, aligned);
+			let stats = pool.stats();
+			assert_eq!(stats.fallback_allocations, 0);
+			assert_eq!(stats.pooled_allocations, 0);
+			assert_eq!(stats.free_bytes, stats.carved_bytes);
+		}
+	}
+
+	/// Refuses anything that isn't small enough.
+	struct Limited(usize);
+
+	unsafe impl GlobalAlloc for Limited {
+		unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+			if layout.size() <= self.0 {
+				System.alloc(layout)
+			} else {
+				ptr::null_mut()
+			}
+		}
+
+		unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+			System.dealloc(ptr, layout)
+		}
+	}
+
+	#[test]
+	fn smaller_arena() {
+		let pool = Pooled::new(Limited(100_000), DEFAULT_ARENA_SIZE);
+		unsafe {
+			let layout = Layout::new::<u64>();
+			let ptr = pool.alloc(layout);
+			assert!(pool.owns(ptr));
+			assert_eq!(pool.stats().arena_bytes, DEFAULT_ARENA_SIZE / 16);
+			pool.dealloc(ptr, layout);
+		}
+
+		let pool = Pooled::new(Limited(MIN_ARENA_SIZE - 1), DEFAULT_ARENA_SIZE);
+		unsafe {
+			let layout = Layout::new::<u64>();
+			let ptr = pool.alloc(layout);
+			assert!(!ptr.is_null() && !pool.owns(ptr));
+			assert_eq!(pool.stats().arena_bytes, 0);
+			pool.dealloc(ptr, layout);
+		}
+	}
+
+	/// A small deterministic random number generator.
+	struct XorShift(u32);
+
+	impl XorShift {
+		fn next(&mut self) -> u32 {
+			self.0 ^= self.0 << 13;
+			self.0 ^= self.0 >> 17;
+			self.0 ^= self.0 << 5;
+			self.0
+		}
+
+		fn below(&mut self, max: u32) -> usize {
+			(self.next() % max) as usize
+		}
+	}
+
+	struct Live {
+		ptr: *mut u8,
+		layout: Layout,
+		fill: u8,
+	}
+
+	unsafe fn fill(live: &Live) {
+		ptr::write_bytes(live.ptr, live.fill, live.layout.size());
+	}
+
+	unsafe fn check(live: &Live) {
+		for i in 0..live.layout.size() {
+			assert_eq!(*live.ptr.add(i), live.fill, "corrupted allocation");
+		}
+	}
+
+	/// Random sizes, mostly small, with the occasional large or over-aligned
+	/// one.
+	fn random_layout(rng: &mut XorShift) -> Layout {
+		let size = match rng.below(20) {
+			0 => 2049 + rng.below(10_000),
+			1..=4 => 1 + rng.below(2048),
+			_ => 1 + rng.below(128),
+		};
+		let align = match rng.below(10) {
+			0 => 64,
+			1 => 16,
+			2..=4 => 8,
+			_ => 4,
+		};
+		Layout::from_size_align(size, align).unwrap()
+	}
+
+	#[test]
+	fn stress() {
+		let pool = Pooled::new(System, 64 * 1024);
+		let mut rng = XorShift(0x1234_5678);
+		let mut live: Vec<Live> = Vec::new();
+		unsafe {
+			for round in 0..50_000u32 {
+				match rng.below(3) {
+					0 | 1 if live.len() < 500 => {
+						let layout = random_layout(&mut rng);
+						let ptr = pool.alloc(layout);
+						assert!(!ptr.is_null());
+						assert_eq!(ptr as usize % layout.align(), 0);
+						let entry = Live {
+							ptr,
+							layout,
+							fill: round as u8,
+						};
+						fill(&entry);
+						live.push(entry);
+					}
+					2 if !live.is_empty() => {
+						let index = rng.below(live.len() as u32);
+						let entry = &mut live[index];
+						check(entry);
+						let new_size = random_layout(&mut rng).size();
+						entry.ptr = pool.realloc(entry.ptr, entry.layout, new_size);
+						assert!(!entry.ptr.is_null());
+						let kept = cmp::min(entry.layout.size(), new_size);
+						entry.layout =
+							Layout::from_size_align(new_size, entry.layout.align()).unwrap();
+						for i in 0..kept {
+							assert_eq!(*entry.ptr.add(i), entry.fill, "realloc lost data");
+						}
+						fill(entry);
+					}
+					_ if !live.is_empty() => {
+						let entry = live.swap_remove(rng.below(live.len() as u32));
+						check(&entry);
+						pool.dealloc(entry.ptr, entry.layout);
+					}
+					_ => {}
+				}
+			}
+			let stats = pool.stats();
+			assert_eq!(
+				stats.pooled_allocations + stats.fallback_allocations,
+				live.len()
+			);
+			for entry in live.drain(..) {
+				check(&entry);
+				pool.dealloc(entry.ptr, entry.layout);
+			}
+		}
+		let stats = pool.stats();
+		assert_eq!(stats.pooled_allocations, 0);
+		assert_eq!(stats.fallback_allocations, 0);
+		assert_eq!(stats.requested_bytes, 0);
+		assert_eq!(stats.free_bytes, stats.carved_bytes);
+	}
+
+	/// A game loop that keeps a few long-lived objects and builds short-lived
+	/// buffers every frame. Run with `--nocapture` to see the numbers.
+	#[test]
+	fn fragmentation() {
+		let pool = Pooled::new(System, 256 * 1024);
+		let mut rng = XorShift(42);
+		let mut entities = Vec::new();
+		unsafe {
+			for _ in 0..200 {
+				let layout = Layout::from_size_align(16 + rng.below(112), 4).unwrap();
+				entities.push((pool.alloc(layout), layout));
+			}
+			let mut worst = 0;
+			for frame in 0..1000 {
+				let mut frame_buffers = Vec::new();
+				for _ in 0..50 {
+					let layout = Layout::from_size_align(1 + rng.below(512), 4).unwrap();
+					frame_buffers.push((pool.alloc(layout), layout));
+				}
+				// Replace an entity now and then
+				if frame % 10 == 0 {
+					let index = rng.below(entities.len() as u32);
+					let (ptr, layout) = entities[index];
+					pool.dealloc(ptr, layout);
+					let layout = Layout::from_size_align(16 + rng.below(112), 4).unwrap();
+					entities[index] = (pool.alloc(layout), layout);
+				}
+				let stats = pool.stats();
+				worst = cmp::max(worst, stats.carved_bytes);
+				for (ptr, layout) in frame_buffers {
+					pool.dealloc(ptr, layout);
+				}
+			}
+			let stats = pool.stats();
+			println!(
+				"arena used: {} bytes at most, {} requested, {} lost to rounding, {} free",
+				worst,
+				stats.requested_bytes,
+				stats.rounding_bytes(),
+				stats.free_bytes
+			);
+			// Every allocation fits, and the steady state doesn't keep growing
+			assert_eq!(stats.fallback_allocations, 0);
+			assert!(worst < 64 * 1024, "used {} bytes", worst);
+			assert!(stats.rounding_bytes() * 3 < stats.requested_bytes);
+			for (ptr, layout) in entities {
+				pool.dealloc(ptr, layout);
+			}
+		}
+	}
+
+	/// Compares the speed of small allocations with the system allocator. Run
+	/// with `cargo test --release -- --ignored --nocapture bench`.
+	#[test]
+	#[ignore]
+	fn bench() {
+		unsafe fn churn<A: GlobalAlloc>(allocator: &A) -> u128 {
+			let start = Instant::now();
+			let mut rng = XorShift(7);
+			let mut live = Vec::with_capacity(64);
+			for _ in 0..1_000_000 {
+				let layout = Layout::from_size_align(8 + rng.below(120), 4).unwrap();
+				live.push((allocator.alloc(layout), layout));
+				if live.len() == 64 {
+					for (ptr, layout) in live.drain(..) {
+						allocator.dealloc(ptr, layout);
+					}
+				}
+			}
+			for (ptr, layout) in live {
+				allocator.dealloc(ptr, layout);
+			}
+			start.elapsed().as_micros()
+		}
+		unsafe {
+			let system = churn(&System);
+			let pooled = churn(&Pooled::new(System, DEFAULT_ARENA_SIZE));
+			println!("system: {} µs, pooled: {} µs", system, pooled);
+		}
+	}
+}
@@ -0,0 +1,387 @@
+//! # Heap statistics
+//! When the `alloc-stats` feature is enabled, the global allocator is wrapped
+//! in [`Counting`], which keeps track of how much memory is in use, the most
+//! that has been in use, how many allocations have been made and how large they
+//! were. Read them with [`heap_stats`], for example to show them in an
+//! on-screen overlay:
+//!
+//! ```rust,no_run
+//! if let Some(stats) = ndless_handler::heap_stats() {
+//!     ndless::println!("{}", stats);
+//! }
+//! ```
+//!
+//! Allocations that are still alive when the program exits are reported as
+//! leaks on stderr, which is the serial port. This is checked after the
+//! [exit hooks](ndless::process::at_exit) that the program registered have run.
+//! Memory that the runtime keeps on purpose, such as the logger and buffered
+//! input, is freed before that, so it isn't counted.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::fmt;
+
+/// The number of size classes in [`HeapStats::histogram`].
+pub const HISTOGRAM_BUCKETS: usize = 16;
+
+/// The largest allocation counted in the first bucket of the histogram.
+const SMALLEST_BUCKET: usize = 16;
+
+/// Returns the index of the histogram bucket for an allocation of `size`
+/// bytes. Bucket `0` holds allocations of up to 16 bytes, and each following
+/// bucket holds allocations up to twice as large as the one before, except for
+/// the last one, which holds everything larger.
+pub fn bucket(size: usize) -> usize {
+	if size <= SMALLEST_BUCKET {
+		return 0;
+	}
+	// Rounds up to the next power of two
+	let bits = (usize::BITS - (size - 1).leading_zeros()) as usize;
+	(bits - SMALLEST_BUCKET.trailing_zeros() as usize).min(HISTOGRAM_BUCKETS - 1)
+}
+
+/// Returns the largest allocation that goes into a bucket, or `None` for the
+/// last bucket, which has no limit.
+pub fn bucket_limit(bucket: usize) -> Option<usize> {
+	if bucket < HISTOGRAM_BUCKETS - 1 {
+		Some(SMALLEST_BUCKET << bucket)
+	} else {
+		None
+	}
+}
+
+/// Heap usage of the global allocator, as returned by [`heap_stats`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct HeapStats {
+	/// Bytes that are currently allocated, not counting the allocator's
+	/// overhead.
+	pub current_bytes: usize,
+	/// The highest that `current_bytes` has been since the program started, or
+	/// since [`Counting::reset_peak`] was called.
+	pub peak_bytes: usize,
+	/// Successful allocations, including those made with `alloc_zeroed`.
+	pub allocations: usize,
+	pub deallocations: usize,
+	/// Successful reallocations. These aren't counted as allocations.
+	pub reallocations: usize,
+	/// Allocations and reallocations that failed because there wasn't enough
+	/// memory.
+	pub failures: usize,
+	/// The number of allocations and reallocations of each size class. See
+	/// [`bucket`] for the sizes.
+	pub histogram: [usize; HISTOGRAM_BUCKETS],
+}
+
+impl HeapStats {
+	const EMPTY: HeapStats = HeapStats {
+		current_bytes: 0,
+		peak_bytes: 0,
+		allocations: 0,
+		deallocations: 0,
+		reallocations: 0,
+		failures: 0,
+		histogram: [0; HISTOGRAM_BUCKETS],
+	};
+
+	/// The number of allocations that haven't been freed yet.
+	pub fn live_allocations(&self) -> usize {
+		self.allocations.saturating_sub(self.deallocations)
+	}
+
+	/// Compares these statistics with ones taken earlier, and returns what
+	/// was allocated since then and not freed, if anything.
+	///
+	/// Only the totals are compared, so memory that was allocated before the
+	/// baseline and freed since then makes up for the same amount that was
+	/// leaked afterwards, and such a leak isn't reported.
+	pub fn leaked_since(&self, baseline: &HeapStats) -> Option<LeakReport> {
+		let report = LeakReport {
+			allocations: self
+				.live_allocations()
+				.saturating_sub(baseline.live_allocations()),
+			bytes: self.current_bytes.saturating_sub(baseline.current_bytes),
+		};
+		if report.allocations > 0 || report.bytes > 0 {
+			Some(report)
+		} else {
+			None
+		}
+	}
+
+	fn record_alloc(&mut self, size: usize) {
+		self.current_bytes += size;
+		self.peak_bytes = self.peak_bytes.max(self.current_bytes);
+		self.histogram[bucket(size)] += 1;
+	}
+}
+
+impl fmt::Display for HeapStats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} bytes in use, {} bytes peak, {} live allocations",
+			self.current_bytes,
+			self.peak_bytes,
+			self.live_allocations()
+		)
+	}
+}
+
+/// Memory that was still allocated when the program exited.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LeakReport {
+	pub allocations: usize,
+	pub bytes: usize,
+}
+
+impl fmt::Display for LeakReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"leaked {} bytes in {} allocations",
+			self.bytes, self.allocations
+		)
+	}
+}
+
+/// An allocator that keeps [statistics](HeapStats) about the allocator that
+/// it wraps.
+///
+/// The calculator only has one thread, and interrupt handlers must not
+/// allocate, so the statistics aren't synchronised.
+pub struct Counting<A> {
+	inner: A,
+	stats: UnsafeCell<HeapStats>,
+}
+
+unsafe impl<A: Sync> Sync for Counting<A> {}
+
+impl<A> Counting<A> {
+	pub const fn new(inner: A) -> Counting<A> {
+		Counting {
+			inner,
+			stats: UnsafeCell::new(HeapStats::EMPTY),
+		}
+	}
+
+	/// Returns the statistics so far.
+	pub fn stats(&self) -> HeapStats {
+		unsafe { *self.stats.get() }
+	}
+
+	/// Sets the peak to the amount of memory that is currently in use, so that
+	/// the peak of a part of the program can be measured.
+	pub fn reset_peak(&self) {
+		let stats = unsafe { &mut *self.stats.get() };
+		stats.peak_bytes = stats.current_bytes;
+	}
+
+	pub fn inner(&self) -> &A {
+		&self.inner
+	}
+
+	#[allow(clippy::mut_from_ref)]
+	unsafe fn stats_mut(&self) -> &mut HeapStats {
+		&mut *self.stats.get()
+	}
+
+	unsafe fn record_alloc(&self, ptr: *mut u8, size: usize) -> *mut u8 {
+		let stats = self.stats_mut();
+		if ptr.is_null() {
+			stats.failures += 1;
+		} else {
+			stats.allocations += 1;
+			stats.record_alloc(size);
+		}
+		ptr
+	}
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+	#[inline]
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		self.record_alloc(self.inner.alloc(layout), layout.size())
+	}
+
+	#[inline]
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		self.record_alloc(self.inner.alloc_zeroed(layout), layout.size())
+	}
+
+	#[inline]
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		self.inner.dealloc(ptr, layout);
+		let stats = self.stats_mut();
+		stats.deallocations += 1;
+		stats.current_bytes = stats.current_bytes.saturating_sub(layout.size());
+	}
+
+	#[inline]
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_ptr = self.inner.realloc(ptr, layout, new_size);
+		let stats = self.stats_mut();
+		if new_ptr.is_null() {
+			stats.failures += 1;
+		} else {
+			stats.reallocations += 1;
+			stats.current_bytes = stats.current_bytes.saturating_sub(layout.size());
+			stats.record_alloc(new_size);
+		}
+		new_ptr
+	}
+}
+
+/// Returns the current heap usage, or `None` if the `alloc-stats` feature
+/// isn't enabled. Only allocations made through Rust are counted, so memory
+/// allocated by C libraries such as SDL is not included.
+pub fn heap_stats() -> Option<HeapStats> {
+	#[cfg(feature = "alloc-stats")]
+	{
+		Some(crate::A.stats())
+	}
+	#[cfg(not(feature = "alloc-stats"))]
+	{
+		None
+	}
+}
+
+/// The statistics that leaks are counted from, taken before `main` runs.
+#[cfg(feature = "alloc-stats")]
+static mut BASELINE: HeapStats = HeapStats::EMPTY;
+
+/// Takes the baseline that [`report_leaks`] compares against once every exit
+/// hook has run.
+#[cfg(feature = "alloc-stats")]
+pub(crate) fn watch_leaks() {
+	unsafe {
+		BASELINE = crate::A.stats();
+		ndless::RUNTIME_EXIT = Some(report_leaks);
+	}
+}
+
+/// Writes a [`LeakReport`] to stderr if anything allocated since
+/// [`watch_leaks`] hasn't been freed. The memory that the runtime keeps for
+/// the whole program, such as the logger, is freed first.
+#[cfg(feature = "alloc-stats")]
+fn report_leaks() {
+	// A panic leaves behind whatever was in use when it happened, and a
+	// resident program keeps running
+	if crate::panic::panicking() || ndless::ndless::is_resident() {
+		return;
+	}
+	unsafe { ndless::process::__release_runtime_state() };
+	let baseline = unsafe { BASELINE };
+	if let Some(leaks) = crate::A.stats().leaked_since(&baseline) {
+		ndless::eprintln!("{}", leaks);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use std::alloc::System;
+
+	use super::*;
+
+	#[test]
+	fn buckets() {
+		assert_eq!(bucket(0), 0);
+		assert_eq!(bucket(16), 0);
+		assert_eq!(bucket(17), 1);
+		assert_eq!(bucket(32), 1);
+		assert_eq!(bucket(33), 2);
+		assert_eq!(bucket(4096), 8);
+		assert_eq!(bucket(usize::MAX), HISTOGRAM_BUCKETS - 1);
+		for i in 0..HISTOGRAM_BUCKETS - 1 {
+			let limit = bucket_limit(i).unwrap();
+			assert_eq!(bucket(limit), i);
+			assert_eq!(bucket(limit + 1), i + 1);
+		}
+		assert_eq!(bucket_limit(HISTOGRAM_BUCKETS - 1), None);
+	}
+
+	#[test]
+	fn counting() {
+		let allocator = Counting::new(System);
+		unsafe {
+			let small = Layout::from_size_align(10, 1).unwrap();
+			let large = Layout::from_size_align(1000, 64).unwrap();
+			let a = allocator.alloc(small);
+			let b = allocator.alloc_zeroed(large);
+			assert_eq!(*b.add(999), 0);
+			let stats = allocator.stats();
+			assert_eq!(stats.current_bytes, 1010);
+			assert_eq!(stats.allocations, 2);
+			assert_eq!(stats.histogram[0], 1);
+			assert_eq!(stats.histogram[bucket(1000)], 1);
+
+			let a = allocator.realloc(a, small, 100);
+			allocator.dealloc(b, large);
+			let stats = allocator.stats();
+			assert_eq!(stats.current_bytes, 100);
+			assert_eq!(stats.peak_bytes, 1100);
+			assert_eq!(stats.reallocations, 1);
+			assert_eq!(stats.deallocations, 1);
+			assert_eq!(stats.live_allocations(), 1);
+			assert_eq!(stats.histogram[bucket(100)], 1);
+
+			allocator.reset_peak();
+			assert_eq!(allocator.stats().peak_bytes, 100);
+			allocator.dealloc(a, Layout::from_size_align(100, 1).unwrap());
+			assert_eq!(allocator.stats().current_bytes, 0);
+			assert_eq!(allocator.stats().failures, 0);
+		}
+	}
+
+	struct Exhausted;
+
+	unsafe impl GlobalAlloc for Exhausted {
+		unsafe fn alloc(&self, _layout: Layout) -> *mut u8 {
+			core::ptr::null_mut()
+		}
+
+		unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+	}
+
+	#[test]
+	fn failures() {
+		let allocator = Counting::new(Exhausted);
+		unsafe {
+			assert!(allocator.alloc(Layout::new::<u64>()).is_null());
+		}
+		let stats = allocator.stats();
+		assert_eq!(stats.failures, 1);
+		assert_eq!(stats.allocations, 0);
+		assert_eq!(stats.histogram, [0; HISTOGRAM_BUCKETS]);
+	}
+
+	#[test]
+	fn leaks() {
+		let allocator = Counting::new(System);
+		let layout = Layout::from_size_align(24, 4).unwrap();
+		unsafe {
+			let kept = allocator.alloc(layout);
+			let baseline = allocator.stats();
+			let freed = allocator.alloc(layout);
+			allocator.dealloc(freed, layout);
+			assert_eq!(allocator.stats().leaked_since(&baseline), None);
+
+			let leaked = allocator.alloc(layout);
+			let report = allocator.stats().leaked_since(&baseline).unwrap();
+			assert_eq!(
+				report,
+				LeakReport {
+					allocations: 1,
+					bytes: 24
+				}
+			);
+			assert_eq!(
+				std::format!("{}", report),
+				"leaked 24 bytes in 1 allocations"
+			);
+			allocator.dealloc(leaked, layout);
+			allocator.dealloc(kept, layout);
+		}
+	}
+}
@@ -2,56 +2,438 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 
-use proc_macro2::Span;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::parse_macro_input;
-use syn::{parse, spanned::Spanned, ItemFn};
+use syn::{
+	parse, parse2, spanned::Spanned, AttributeArgs, FnArg, ItemFn, Lit, Meta, NestedMeta, Path,
+};
 
+/// Marks the function that the program starts at.
+///
+/// The function can return anything that implements
+/// `ndless::process::Termination`. Arguments can be used to configure the
+/// program before it starts:
+///
+/// - `error = "..."` sets how errors returned from the function are shown:
+///   `"message_box"` (the default), `"console"`, `"log_file"` or `"silent"`.
+/// - `resident` keeps the program in memory after it exits, with
+///   `ndless::ndless::set_resident`.
+/// - `startup_only` only runs the function when the program is run at boot from
+///   the Ndless startup folder, and `skip_at_startup` only runs it when it
+///   isn't.
+/// - `no_screen_redraw` stops the OS from redrawing the screen when the program
+///   exits.
+/// - `cpu = "..."` sets the CPU speed: `"slow"`, `"normal"` or `"fast"`.
+///
+/// The function may also be `async`, in which case it is run with
+/// `ndless_async::task::block_on`, and may take an
+/// `&ndless_async::task::AsyncListeners`. This requires a dependency on
+/// `ndless-async`.
+///
+/// ```ignore
+/// #[entry(error = "console", cpu = "fast")]
+/// fn main() -> Result<(), ndless::io::Error> {
+///     let file = ndless::fs::read_to_string("save.tns")?;
+///     Ok(())
+/// }
+///
+/// #[entry]
+/// async fn main(listeners: &AsyncListeners) {
+///     listeners.timer().sleep_ms(1000).await;
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
-	let f = parse_macro_input!(input as ItemFn);
+	let args = syn::parse_macro_input!(args as AttributeArgs);
+	match expand(args, input.into()) {
+		Ok(tokens) => tokens.into(),
+		Err(err) => err.to_compile_error().into(),
+	}
+}
+
+/// When the entry function should run, based on whether the program was
+/// started from the Ndless startup folder.
+enum Startup {
+	Only,
+	Skip,
+}
+
+#[derive(Default)]
+struct Options {
+	error: Option<TokenStream2>,
+	cpu: Option<TokenStream2>,
+	resident: bool,
+	startup: Option<Startup>,
+	no_screen_redraw: bool,
+}
+
+impl Options {
+	fn parse(args: AttributeArgs) -> parse::Result<Options> {
+		let mut options = Options::default();
+		for arg in args {
+			match &arg {
+				NestedMeta::Meta(Meta::Path(path)) => options.flag(path)?,
+				NestedMeta::Meta(Meta::NameValue(pair)) => {
+					let value = match &pair.lit {
+						Lit::Str(value) => value,
+						lit => return Err(parse::Error::new(lit.span(), "expected a string")),
+					};
+					let (slot, tokens) = if pair.path.is_ident("error") {
+						let presenter = match value.value().as_str() {
+							"message_box" => quote!(MessageBox),
+							"console" => quote!(Console),
+							"log_file" => quote!(LogFile),
+							"silent" => quote!(Silent),
+							_ => return Err(parse::Error::new(
+								value.span(),
+								"expected one of `message_box`, `console`, `log_file` or `silent`",
+							)),
+						};
+						(
+							&mut options.error,
+							quote!(::ndless::process::set_error_presenter(
+								::ndless::process::ErrorPresenter::#presenter
+							);),
+						)
+					} else if pair.path.is_ident("cpu") {
+						let speed = match value.value().as_str() {
+							"slow" => quote!(Slow),
+							"normal" => quote!(Normal),
+							"fast" => quote!(Fast),
+							_ => {
+								return Err(parse::Error::new(
+									value.span(),
+									"expected one of `slow`, `normal` or `fast`",
+								))
+							}
+						};
+						(
+							&mut options.cpu,
+							quote!(::ndless::hw::set_cpu_speed(::ndless::hw::CpuSpeed::#speed);),
+						)
+					} else {
+						return Err(parse::Error::new(pair.path.span(), "unknown argument"));
+					};
+					if slot.is_some() {
+						return Err(parse::Error::new(pair.path.span(), "duplicate argument"));
+					}
+					*slot = Some(tokens);
+				}
+				_ => {
+					return Err(parse::Error::new(
+						arg.span(),
+						"expected a flag or `name = \"value\"`",
+					))
+				}
+			}
+		}
+		Ok(options)
+	}
+
+	fn flag(&mut self, path: &Path) -> parse::Result<()> {
+		let duplicate = || Err(parse::Error::new(path.span(), "duplicate argument"));
+		if path.is_ident("resident") {
+			if self.resident {
+				return duplicate();
+			}
+			self.resident = true;
+		} else if path.is_ident("no_screen_redraw") {
+			if self.no_screen_redraw {
+				return duplicate();
+			}
+			self.no_screen_redraw = true;
+		} else if path.is_ident("startup_only") || path.is_ident("skip_at_startup") {
+			if self.startup.is_some() {
+				return Err(parse::Error::new(
+					path.span(),
+					"only one of `startup_only` and `skip_at_startup` may be used",
+				));
+			}
+			self.startup = Some(if path.is_ident("startup_only") {
+				Startup::Only
+			} else {
+				Startup::Skip
+			});
+		} else {
+			return Err(parse::Error::new(path.span(), "unknown argument"));
+		}
+		Ok(())
+	}
+
+	/// Code that runs after `ndless::__init`, before the entry function.
+	fn setup(&self) -> TokenStream2 {
+		// `__init` has already changed the hardware, so the exit hooks need to
+		// put it back before returning early
+		let skip = quote!({
+			::ndless::process::__run_at_exit();
+			return 0;
+		});
+		let startup = match self.startup {
+			Some(Startup::Only) => quote!(if !::ndless::ndless::is_startup() #skip),
+			Some(Startup::Skip) => quote!(if ::ndless::ndless::is_startup() #skip),
+			None => quote!(),
+		};
+		let resident = if self.resident {
+			quote!(::ndless::ndless::set_resident();)
+		} else {
+			quote!()
+		};
+		let no_screen_redraw = if self.no_screen_redraw {
+			quote!(::ndless::ndless::no_screen_redraw();)
+		} else {
+			quote!()
+		};
+		let error = &self.error;
+		let cpu = &self.cpu;
+		quote!(
+			#startup
+			#resident
+			#no_screen_redraw
+			#error
+			#cpu
+		)
+	}
+}
+
+fn expand(args: AttributeArgs, input: TokenStream2) -> parse::Result<TokenStream2> {
+	let f: ItemFn = parse2(input)?;
+	let is_async = f.sig.asyncness.is_some();
 	// check the function signature
 	let valid_signature = f.sig.constness.is_none()
 		&& f.sig.abi.is_none()
-		&& f.sig.inputs.is_empty()
+		&& (f.sig.inputs.is_empty() || (is_async && f.sig.inputs.len() == 1))
 		&& f.sig.generics.params.is_empty()
 		&& f.sig.generics.where_clause.is_none()
 		&& f.sig.variadic.is_none();
 
 	if !valid_signature {
-		return parse::Error::new(
+		return Err(parse::Error::new(
 			f.span(),
 			"`#[entry]` function does not meet specifications!",
-		)
-		.to_compile_error()
-		.into();
+		));
 	}
-
-	if !args.is_empty() {
-		return parse::Error::new(Span::call_site(), "This attribute accepts no arguments")
-			.to_compile_error()
-			.into();
+	if let Some(FnArg::Receiver(receiver)) = f.sig.inputs.first() {
+		return Err(parse::Error::new(
+			receiver.span(),
+			"`#[entry]` function can't take `self`",
+		));
 	}
 
-	let attrs = f.attrs;
-	let stmts = f.block.stmts;
-	let ret = f.sig.output;
-	let name = f.sig.ident;
-	let unsafety = f.sig.unsafety;
-	let vis = f.vis;
+	let options = Options::parse(args)?;
+	let setup = options.setup();
+
+	let name = &f.sig.ident;
+	let call = if is_async {
+		let listeners = if f.sig.inputs.is_empty() {
+			quote!()
+		} else {
+			quote!(&listeners)
+		};
+		quote!({
+			let listeners = ::ndless_async::task::AsyncListeners::new();
+			::ndless_async::task::block_on(&listeners, #name(#listeners))
+		})
+	} else {
+		quote!(#name())
+	};
 
-	quote!(
-        #[export_name = "main"]
-        unsafe fn __ndless_start(argc: ::ndless::cty::c_int, argv: *const *const ::ndless::cty::c_char) -> ::ndless::cty::c_int {
-            let args: &[*const ::ndless::cty::c_char] = unsafe { ::core::slice::from_raw_parts(argv, argc as usize) };
+	Ok(quote!(
+		#[export_name = "main"]
+		unsafe fn __ndless_start(argc: ::ndless::cty::c_int, argv: *const *const ::ndless::cty::c_char) -> ::ndless::cty::c_int {
+			let args: &[*const ::ndless::cty::c_char] = unsafe { ::core::slice::from_raw_parts(argv, argc as usize) };
 			::ndless::__init(args);
-			::ndless::process::Termination::report(#name())
-        }
-
-        #(#attrs)*
-        #vis #unsafety fn #name() #ret {
-            #(#stmts)*
-        }
-    )
-		.into()
+			#setup
+			let code = ::ndless::process::Termination::report(#call);
+			::ndless::process::__run_at_exit();
+			code
+		}
+
+		#f
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use quote::quote;
+	use syn::parse::Parser;
+	use syn::punctuated::Punctuated;
+	use syn::{AttributeArgs, NestedMeta, Token};
+
+	use super::expand;
+
+	fn args(tokens: proc_macro2::TokenStream) -> AttributeArgs {
+		Punctuated::<NestedMeta, Token![,]>::parse_terminated
+			.parse2(tokens)
+			.unwrap()
+			.into_iter()
+			.collect()
+	}
+
+	fn expand_str(attr: proc_macro2::TokenStream, item: proc_macro2::TokenStream) -> String {
+		expand(args(attr), item).unwrap().to_string()
+	}
+
+	fn error_str(attr: proc_macro2::TokenStream, item: proc_macro2::TokenStream) -> String {
+		expand(args(attr), item).unwrap_err().to_string()
+	}
+
+	#[test]
+	fn plain() {
+		let out = expand_str(
+			quote!(),
+			quote!(
+				fn main() {}
+			),
+		);
+		assert!(out.contains("ndless :: __init (args) ;"));
+		assert!(!out.contains("ndless_handler"));
+		assert!(out.contains("Termination :: report (main ())"));
+		assert!(out.contains("__run_at_exit"));
+		assert!(!out.contains("set_resident"));
+		assert!(!out.contains("is_startup"));
+	}
+
+	#[test]
+	fn options() {
+		let out = expand_str(
+			quote!(
+				resident,
+				startup_only,
+				no_screen_redraw,
+				cpu = "fast",
+				error = "silent"
+			),
+			quote!(
+				fn main() {}
+			),
+		);
+		assert!(out.contains(
+			"if ! :: ndless :: ndless :: is_startup () { :: ndless :: process :: __run_at_exit () ; return 0 ; }"
+		));
+		assert!(out.contains(":: ndless :: ndless :: set_resident () ;"));
+		assert!(out.contains(":: ndless :: ndless :: no_screen_redraw () ;"));
+		assert!(out.contains("set_cpu_speed (:: ndless :: hw :: CpuSpeed :: Fast)"));
+		assert!(out.contains("ErrorPresenter :: Silent"));
+		// Options are applied after initialization and before main
+		let init = out.find("__init").unwrap();
+		let report = out.find("report").unwrap();
+		for option in &["is_startup", "set_resident", "set_cpu_speed"] {
+			let pos = out.find(option).unwrap();
+			assert!(init < pos && pos < report, "{} is out of order", option);
+		}
+
+		let out = expand_str(
+			quote!(skip_at_startup),
+			quote!(
+				fn main() {}
+			),
+		);
+		assert!(out.contains(
+			"if :: ndless :: ndless :: is_startup () { :: ndless :: process :: __run_at_exit () ; return 0 ; }"
+		));
+	}
+
+	#[test]
+	fn early_return() {
+		for attr in &[quote!(startup_only), quote!(skip_at_startup)] {
+			let out = expand_str(
+				attr.clone(),
+				quote!(
+					fn main() {}
+				),
+			);
+			// Skipping the program still restores what `__init` changed
+			let init = out.find("__init").unwrap();
+			let skip = out.find("return 0").unwrap();
+			let exit = out[init..skip].rfind("__run_at_exit").map(|pos| pos + init);
+			assert!(matches!(exit, Some(pos) if pos > init), "{}", out);
+			assert!(!out[exit.unwrap()..skip].contains("is_startup"));
+		}
+	}
+
+	#[test]
+	fn async_main() {
+		let out = expand_str(
+			quote!(),
+			quote!(
+				async fn main() {}
+			),
+		);
+		assert!(out.contains("block_on (& listeners , main ())"));
+
+		let out = expand_str(
+			quote!(),
+			quote!(
+				async fn main(listeners: &AsyncListeners) {}
+			),
+		);
+		assert!(out.contains("block_on (& listeners , main (& listeners))"));
+	}
+
+	#[test]
+	fn errors() {
+		assert_eq!(
+			error_str(
+				quote!(colour = "red"),
+				quote!(
+					fn main() {}
+				)
+			),
+			"unknown argument"
+		);
+		assert_eq!(
+			error_str(
+				quote!(fast),
+				quote!(
+					fn main() {}
+				)
+			),
+			"unknown argument"
+		);
+		assert_eq!(
+			error_str(
+				quote!(cpu = "turbo"),
+				quote!(
+					fn main() {}
+				)
+			),
+			"expected one of `slow`, `normal` or `fast`"
+		);
+		assert_eq!(
+			error_str(
+				quote!(cpu = 150),
+				quote!(
+					fn main() {}
+				)
+			),
+			"expected a string"
+		);
+		assert_eq!(
+			error_str(
+				quote!(resident, resident),
+				quote!(
+					fn main() {}
+				)
+			),
+			"duplicate argument"
+		);
+		assert_eq!(
+			error_str(
+				quote!(startup_only, skip_at_startup),
+				quote!(
+					fn main() {}
+				)
+			),
+			"only one of `startup_only` and `skip_at_startup` may be used"
+		);
+		assert_eq!(
+			error_str(
+				quote!(),
+				quote!(
+					fn main(argc: i32) {}
+				)
+			),
+			"`#[entry]` function does not meet specifications!"
+		);
+	}
 }
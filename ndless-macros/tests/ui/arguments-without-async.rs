@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry]
+fn main(listeners: &u32) {}
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry(cpu = "turbo")]
+fn main() {}
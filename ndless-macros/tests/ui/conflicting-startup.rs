@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry(startup_only, skip_at_startup)]
+fn main() {}
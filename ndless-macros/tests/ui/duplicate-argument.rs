@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry(error = "console", error = "silent")]
+fn main() {}
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry(cpu = 150)]
+fn main() {}
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[ndless_macros::entry(colour = "red")]
+fn main() {}
@@ -101,14 +101,24 @@ pub enum Error {
 	Unsupported = ll::SDL_UNSUPPORTED as isize,
 }
 
+static mut EXIT_HOOK_REGISTERED: bool = false;
+
+/// Initializes SDL. [`quit`] is called automatically when the program exits.
 pub fn init(flags: &[InitFlag]) -> bool {
-	unsafe {
+	let ok = unsafe {
 		ll::SDL_Init(
 			flags
 				.iter()
 				.fold(0u32, |flags, &flag| flags | flag as ll::SDL_InitFlag),
 		) == 0
+	};
+	unsafe {
+		if ok && !EXIT_HOOK_REGISTERED {
+			EXIT_HOOK_REGISTERED = true;
+			ndless::process::at_exit(quit);
+		}
 	}
+	ok
 }
 
 pub fn init_default() -> Result<crate::video::Surface, String> {
@@ -16,9 +16,16 @@ pub static mut PROGRAM_STATE: ProgramState = ProgramState::Normal;
 
 pub static mut ARGUMENTS: Option<&[*const cty::c_char]> = None;
 
+pub static mut RUNTIME_INIT: Option<fn()> = None;
+
+pub static mut RUNTIME_EXIT: Option<fn()> = None;
+
 pub static mut ORIG_DIVIDER: u32 = 0;
 pub static mut ORIG_CONTROL: u32 = 0;
 pub static mut ORIG_LOAD: u32 = 0;
 
 pub static mut TICK_SUM: u32 = 0;
 pub static mut START_VALUE: u32 = 0;
+
+pub static mut ORIG_TICK_CONTROL: u32 = 0;
+pub static mut ORIG_TICK_SETTING: u32 = 0;
@@ -8,6 +8,8 @@ read -r -d '' PREFIX <<"EOF"
 extern "C" {
 	pub fn abort() -> !;
 	pub fn exit(__status: cty::c_int) -> !;
+	// Not in the headers that these bindings were generated from
+	pub fn nl_no_scr_redraw();
 }
 EOF
 
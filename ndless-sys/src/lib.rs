@@ -7,6 +7,8 @@
 extern "C" {
 	pub fn abort() -> !;
 	pub fn exit(__status: cty::c_int) -> !;
+	// Not in the headers that these bindings were generated from
+	pub fn nl_no_scr_redraw();
 }
 
 pub type __u_char = cty::c_uchar;
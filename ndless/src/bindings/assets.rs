@@ -0,0 +1,203 @@
+//! # Assets
+//! Files such as images, fonts and levels that are packed into the program's
+//! `.tns` file, so that they can't get lost. Put them in a directory in your
+//! package and add it to `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.zehn]
+//! assets = "assets"
+//! ```
+//!
+//! `cargo ndless` then appends everything in `assets/` to the `.tns` file,
+//! and it can be read with [`open`] using paths relative to that directory:
+//!
+//! ```no_run
+//! use ndless::io::Read;
+//!
+//! let mut sprite = Vec::new();
+//! ndless::assets::open("sprites/player.png")
+//!     .unwrap()
+//!     .read_to_end(&mut sprite)
+//!     .unwrap();
+//! let level = ndless::assets::read_to_string("levels/1.txt").unwrap();
+//! ```
+//!
+//! Assets are read from the `.tns` file when they are used, rather than being
+//! loaded into memory when the program starts.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub use ndless_assets::Bundle;
+use ndless_assets::{Footer, Index, FOOTER_LEN};
+
+use crate::fs::File;
+use crate::io::{self, Read, Seek, SeekFrom};
+use crate::path::PathBuf;
+
+impl crate::error::Error for ndless_assets::Error {}
+
+/// The bundle at the end of the program's `.tns` file.
+struct Appended {
+	path: PathBuf,
+	start: u64,
+	index: Index,
+}
+
+program_state! {
+	fn appended_state() -> Option<Appended> = None;
+}
+
+fn invalid(err: ndless_assets::Error) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn load() -> io::Result<Appended> {
+	let path = crate::env::args()
+		.next()
+		.map(PathBuf::from)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "program path is unknown"))?;
+	let mut file = File::open(&path)?;
+	let len = file.seek(SeekFrom::End(0))?;
+	if len < FOOTER_LEN as u64 {
+		return Err(invalid(ndless_assets::Error::BadMagic));
+	}
+	file.seek(SeekFrom::Start(len - FOOTER_LEN as u64))?;
+	let mut footer = [0; FOOTER_LEN];
+	file.read_exact(&mut footer)?;
+	let footer = Footer::parse(&footer).map_err(invalid)?;
+	if footer.bundle_len as u64 > len {
+		return Err(invalid(ndless_assets::Error::Truncated));
+	}
+	let start = len - footer.bundle_len as u64;
+	file.seek(SeekFrom::Start(start + footer.index_offset as u64))?;
+	let mut index = vec![0; footer.index_len()];
+	file.read_exact(&mut index)?;
+	let index = Index::parse(&footer, &index).map_err(invalid)?;
+	Ok(Appended { path, start, index })
+}
+
+fn appended() -> io::Result<&'static Appended> {
+	// It's never changed once it's loaded, and only freed by `release` once the
+	// program is done, so the reference stays valid
+	let appended = unsafe { &mut *appended_state() };
+	if appended.is_none() {
+		*appended = Some(load()?);
+	}
+	Ok(appended.as_ref().unwrap())
+}
+
+/// Frees the index. See [`process::__release_runtime_state`](crate::process::__release_runtime_state).
+pub(crate) unsafe fn release() {
+	*appended_state() = None;
+}
+
+/// Opens an asset. Paths are relative to the assets directory, and may use
+/// `/` or `\` to separate directories.
+pub fn open(path: &str) -> io::Result<Asset> {
+	let appended = appended()?;
+	let entry = appended
+		.index
+		.get(path)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "asset not found"))?;
+	let mut file = File::open(&appended.path)?;
+	let start = appended.start + entry.offset as u64;
+	file.seek(SeekFrom::Start(start))?;
+	Ok(Asset {
+		file,
+		start,
+		len: entry.len as u64,
+		pos: 0,
+	})
+}
+
+/// Reads the entire contents of an asset.
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+	let mut asset = open(path)?;
+	let mut data = Vec::with_capacity(asset.len() as usize);
+	asset.read_to_end(&mut data)?;
+	Ok(data)
+}
+
+/// Reads the entire contents of an asset into a string.
+pub fn read_to_string(path: &str) -> io::Result<String> {
+	let mut asset = open(path)?;
+	let mut string = String::with_capacity(asset.len() as usize);
+	asset.read_to_string(&mut string)?;
+	Ok(string)
+}
+
+/// Returns `true` if there is an asset at `path`.
+pub fn exists(path: &str) -> bool {
+	appended()
+		.map(|appended| appended.index.get(path).is_some())
+		.unwrap_or(false)
+}
+
+/// The path of every asset, in sorted order.
+pub fn paths() -> io::Result<impl Iterator<Item = &'static str>> {
+	Ok(appended()?
+		.index
+		.entries()
+		.iter()
+		.map(|entry| entry.path.as_str()))
+}
+
+/// An asset opened with [`open`].
+#[derive(Debug)]
+pub struct Asset {
+	file: File,
+	start: u64,
+	len: u64,
+	pos: u64,
+}
+
+impl Asset {
+	/// The size of the asset in bytes.
+	pub fn len(&self) -> u64 {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
+
+impl Read for Asset {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let remaining = self.len.saturating_sub(self.pos);
+		let max = (buf.len() as u64).min(remaining) as usize;
+		let read = self.file.read(&mut buf[..max])?;
+		self.pos += read as u64;
+		Ok(read)
+	}
+}
+
+impl Seek for Asset {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		let (base, offset) = match pos {
+			SeekFrom::Start(offset) => (0, i64::try_from(offset).ok()),
+			SeekFrom::End(offset) => (self.len, Some(offset)),
+			SeekFrom::Current(offset) => (self.pos, Some(offset)),
+		};
+		let pos = offset
+			.and_then(|offset| {
+				if offset >= 0 {
+					base.checked_add(offset as u64)
+				} else {
+					base.checked_sub(offset.wrapping_neg() as u64)
+				}
+			})
+			.ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"invalid seek to a negative or overflowing position",
+				)
+			})?;
+		self.file.seek(SeekFrom::Start(self.start + pos))?;
+		self.pos = pos;
+		Ok(pos)
+	}
+}
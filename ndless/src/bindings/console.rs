@@ -0,0 +1,258 @@
+//! # On-screen console
+//! A scrolling text terminal drawn on the LCD, similar to nspireio. Unlike the
+//! serial port, this is visible to people using the program on a real
+//! calculator.
+//!
+//! Output is processed by a [`Terminal`], which supports line wrapping, a
+//! scrollback buffer and the common ANSI escape sequences for colors and
+//! moving the cursor. Text is drawn with the built-in
+//! [8x8 font](crate::font::MONO_8X8), giving 40 columns and 30 rows.
+//!
+//! [`print!`](crate::print) and [`println!`](crate::println) can be sent to the
+//! console, the serial port or both with [`set_output`]:
+//!
+//! ```no_run
+//! use ndless::console::{self, Output};
+//!
+//! console::set_output(Output::Both);
+//! ndless::println!("\x1b[32mHello\x1b[0m, world!");
+//! ndless::msg::msg("", "Done");
+//! console::close();
+//! ```
+//!
+//! A [`Console`] can also be used directly, and implements both
+//! [`io::Write`] and [`fmt::Write`]. Either way, the LCD is restored when the
+//! console is closed or dropped, or when the program exits.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::font::MONO_8X8;
+use crate::hw::screen::{Screen, HEIGHT, WIDTH};
+use crate::io;
+use crate::lcd;
+
+mod terminal;
+
+pub use self::terminal::{Cell, Color, Terminal};
+
+/// Every glyph of [`MONO_8X8`] is a whole 8x8 cell.
+const GLYPH_WIDTH: usize = 8;
+const GLYPH_HEIGHT: usize = 8;
+
+/// The RGB565 colors used to draw the console.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Palette {
+	pub foreground: u16,
+	pub background: u16,
+	/// The 16 ANSI colors, as described in [`Color::Ansi`].
+	pub ansi: [u16; 16],
+}
+
+impl Default for Palette {
+	/// Black text on a white background, like the rest of the OS.
+	fn default() -> Self {
+		Palette {
+			foreground: 0x0000,
+			background: 0xFFFF,
+			ansi: [
+				0x0000, 0xA800, 0x0540, 0xAAA0, 0x0015, 0xA815, 0x0555, 0xAD55, 0x52AA, 0xFAAA,
+				0x57EA, 0xFFEA, 0x52BF, 0xFABF, 0x57FF, 0xFFFF,
+			],
+		}
+	}
+}
+
+impl Palette {
+	pub fn resolve(&self, color: Color) -> u16 {
+		match color {
+			Color::Foreground => self.foreground,
+			Color::Background => self.background,
+			Color::Ansi(n) => self.ansi[n as usize % 16],
+		}
+	}
+}
+
+/// A [`Terminal`] that fills the screen.
+pub struct Console {
+	terminal: Terminal,
+	palette: Palette,
+	buffer: Vec<u16>,
+}
+
+impl Console {
+	/// Switches the LCD to RGB565 mode and clears it.
+	pub fn new() -> Console {
+		unsafe {
+			if !*exit_hook_registered() {
+				*exit_hook_registered() = true;
+				// The console used by print! is never dropped otherwise, which
+				// would leave the LCD in the wrong mode. Dropping it gives the
+				// LCD back to the OS.
+				crate::process::at_exit(close);
+			}
+		}
+		lcd::init(Screen::Screen320x240x565);
+		let mut console = Console {
+			terminal: Terminal::new(WIDTH / GLYPH_WIDTH, HEIGHT / GLYPH_HEIGHT),
+			palette: Palette::default(),
+			buffer: vec![0; WIDTH * HEIGHT],
+		};
+		console.refresh();
+		console
+	}
+
+	pub fn terminal(&self) -> &Terminal {
+		&self.terminal
+	}
+
+	/// Changes to the terminal are shown after the next
+	/// [`refresh`](Console::refresh).
+	pub fn terminal_mut(&mut self) -> &mut Terminal {
+		&mut self.terminal
+	}
+
+	pub fn palette(&self) -> Palette {
+		self.palette
+	}
+
+	pub fn set_palette(&mut self, palette: Palette) {
+		self.palette = palette;
+		self.terminal.mark_all_dirty();
+	}
+
+	/// Scrolls the view back into the scrollback by `lines`, for example when
+	/// the up arrow is pressed.
+	pub fn scroll_up(&mut self, lines: usize) {
+		self.terminal.scroll_view_up(lines);
+		self.refresh();
+	}
+
+	/// Scrolls the view towards the current output by `lines`.
+	pub fn scroll_down(&mut self, lines: usize) {
+		self.terminal.scroll_view_down(lines);
+		self.refresh();
+	}
+
+	/// Clears the screen and moves the cursor to the top left.
+	pub fn clear(&mut self) {
+		self.terminal.reset();
+		self.refresh();
+	}
+
+	/// Draws the rows that have changed and shows them on the screen.
+	pub fn refresh(&mut self) {
+		let cursor = self.terminal.cursor();
+		for row in 0..self.terminal.rows() {
+			if !self.terminal.is_dirty(row) {
+				continue;
+			}
+			for (col, cell) in self.terminal.visible_row(row).iter().enumerate() {
+				let fg = self.palette.resolve(cell.fg);
+				let bg = self.palette.resolve(cell.bg);
+				let has_cursor = cursor == Some((col, row));
+				let glyph = MONO_8X8.bitmap(MONO_8X8.glyph(cell.ch));
+				for (y, bits) in glyph.iter().enumerate() {
+					// The cursor is an inverted underline
+					let invert = has_cursor && y >= GLYPH_HEIGHT - 2;
+					let start = (row * GLYPH_HEIGHT + y) * WIDTH + col * GLYPH_WIDTH;
+					for (x, pixel) in self.buffer[start..start + GLYPH_WIDTH]
+						.iter_mut()
+						.enumerate()
+					{
+						let on = bits & (0x80 >> x) != 0;
+						*pixel = if on != invert { fg } else { bg };
+					}
+				}
+			}
+		}
+		self.terminal.mark_clean();
+		lcd::blit(&self.buffer, Screen::Screen320x240x565);
+	}
+}
+
+impl Default for Console {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Drop for Console {
+	/// Gives the LCD back to the OS.
+	fn drop(&mut self) {
+		lcd::deinit();
+	}
+}
+
+impl io::Write for Console {
+	/// Adds output to the terminal without redrawing the screen. Call
+	/// [`flush`](io::Write::flush) to show it.
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.terminal.write_bytes(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.refresh();
+		Ok(())
+	}
+}
+
+impl fmt::Write for Console {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.terminal.write_bytes(s.as_bytes());
+		Ok(())
+	}
+}
+
+/// Where [`print!`](crate::print) and [`println!`](crate::println) send their
+/// output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Output {
+	/// The serial port. This is the default.
+	Serial,
+	/// The on-screen console.
+	Console,
+	/// Both the serial port and the on-screen console.
+	Both,
+}
+
+program_state! {
+	fn output_state() -> Output = Output::Serial;
+}
+program_state! {
+	fn console() -> Option<Console> = None;
+}
+program_state! {
+	fn exit_hook_registered() -> bool = false;
+}
+
+/// Returns the current [`Output`].
+pub fn output() -> Output {
+	unsafe { *output_state() }
+}
+
+/// Sets where [`print!`](crate::print) and [`println!`](crate::println) send
+/// their output. The console is opened the first time it is printed to, and
+/// stays open until [`close`] is called.
+pub fn set_output(output: Output) {
+	unsafe { *output_state() = output }
+}
+
+/// Runs a function with the console used by [`print!`](crate::print), opening
+/// it if necessary.
+pub fn with_console<R>(f: impl FnOnce(&mut Console) -> R) -> R {
+	let open = unsafe { &mut *console() };
+	f(open.get_or_insert_with(Console::new))
+}
+
+/// Closes the console used by [`print!`](crate::print), restoring the LCD, and
+/// sends output back to the serial port. This is called automatically when the
+/// program exits.
+pub fn close() {
+	unsafe {
+		*output_state() = Output::Serial;
+		*console() = None;
+	}
+}
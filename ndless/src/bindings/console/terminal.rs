@@ -0,0 +1,741 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{cmp, fmt, str};
+
+const MAX_PARAMS: usize = 16;
+const TAB_WIDTH: usize = 8;
+
+/// The color of text or its background.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Color {
+	/// The console's default text color.
+	Foreground,
+	/// The console's default background color.
+	Background,
+	/// One of the 16 ANSI colors. 0 to 7 are black, red, green, yellow, blue,
+	/// magenta, cyan and white, and 8 to 15 are their bright versions.
+	Ansi(u8),
+}
+
+/// A character on the screen.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Cell {
+	pub ch: char,
+	pub fg: Color,
+	pub bg: Color,
+}
+
+impl Default for Cell {
+	fn default() -> Self {
+		Cell {
+			ch: ' ',
+			fg: Color::Foreground,
+			bg: Color::Background,
+		}
+	}
+}
+
+/// The attributes set with `ESC [ ... m`.
+#[derive(Debug, Clone, Copy)]
+struct Style {
+	fg: Color,
+	bg: Color,
+	bold: bool,
+	inverse: bool,
+}
+
+impl Default for Style {
+	fn default() -> Self {
+		Style {
+			fg: Color::Foreground,
+			bg: Color::Background,
+			bold: false,
+			inverse: false,
+		}
+	}
+}
+
+impl Style {
+	fn cell(&self, ch: char) -> Cell {
+		let fg = match self.fg {
+			Color::Ansi(n) if self.bold && n < 8 => Color::Ansi(n + 8),
+			fg => fg,
+		};
+		if self.inverse {
+			Cell {
+				ch,
+				fg: self.bg,
+				bg: fg,
+			}
+		} else {
+			Cell {
+				ch,
+				fg,
+				bg: self.bg,
+			}
+		}
+	}
+
+	fn blank(&self) -> Cell {
+		Cell {
+			bg: self.bg,
+			..Cell::default()
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum State {
+	Ground,
+	Escape,
+	Csi,
+}
+
+/// A grid of character cells that understands a subset of the ANSI escape
+/// sequences used by terminals such as xterm. This only keeps track of what
+/// should be on the screen; [`Console`](super::Console) draws it.
+///
+/// The supported control characters are `\n` (which also returns to the
+/// start of the line), `\r`, `\t` and backspace. The supported escape sequences
+/// are:
+///
+/// - `ESC [ n m`: colors and attributes. `0` resets, `1` is bold (bright
+///   colors), `7` is inverse, `30`-`37` and `90`-`97` set the text color, and
+///   `40`-`47` and `100`-`107` set the background. `22`, `27`, `39` and `49`
+///   undo these.
+/// - `ESC [ row ; col H`: move the cursor, counting from 1.
+/// - `ESC [ n A`, `B`, `C`, `D`: move the cursor up, down, right and left.
+/// - `ESC [ n J`: erase below (`0`), above (`1`), or the whole screen (`2`), or
+///   the whole screen and the scrollback (`3`).
+/// - `ESC [ n K`: erase to the right (`0`), left (`1`) or the whole line (`2`).
+/// - `ESC [ s` and `ESC [ u`, or `ESC 7` and `ESC 8`: save and restore the
+///   cursor position.
+/// - `ESC [ ? 25 l` and `ESC [ ? 25 h`: hide and show the cursor.
+/// - `ESC c`: reset the screen.
+///
+/// Anything else is ignored.
+#[derive(Debug, Clone)]
+pub struct Terminal {
+	cols: usize,
+	rows: usize,
+	grid: Vec<Vec<Cell>>,
+	scrollback: VecDeque<Vec<Cell>>,
+	scrollback_limit: usize,
+	view: usize,
+	dirty: Vec<bool>,
+	// `col == cols` means that the next character goes on the next line
+	col: usize,
+	row: usize,
+	saved: (usize, usize),
+	cursor_visible: bool,
+	style: Style,
+	state: State,
+	params: Vec<u16>,
+	param: u16,
+	private: bool,
+	utf8: [u8; 4],
+	utf8_len: usize,
+}
+
+impl Terminal {
+	/// Creates a blank terminal with room for 200 lines of scrollback.
+	pub fn new(cols: usize, rows: usize) -> Terminal {
+		assert!(cols > 0 && rows > 0, "terminal must not be empty");
+		Terminal {
+			cols,
+			rows,
+			grid: vec![vec![Cell::default(); cols]; rows],
+			scrollback: VecDeque::new(),
+			scrollback_limit: 200,
+			view: 0,
+			dirty: vec![true; rows],
+			col: 0,
+			row: 0,
+			saved: (0, 0),
+			cursor_visible: true,
+			style: Style::default(),
+			state: State::Ground,
+			params: Vec::new(),
+			param: 0,
+			private: false,
+			utf8: [0; 4],
+			utf8_len: 0,
+		}
+	}
+
+	pub fn cols(&self) -> usize {
+		self.cols
+	}
+
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	/// Sets how many lines that have scrolled off the top are kept.
+	pub fn set_scrollback_limit(&mut self, lines: usize) {
+		self.scrollback_limit = lines;
+		while self.scrollback.len() > lines {
+			self.scrollback.pop_front();
+		}
+		self.set_view(self.view);
+	}
+
+	/// The lines that have scrolled off the top of the screen, oldest first.
+	pub fn scrollback(&self) -> impl Iterator<Item = &[Cell]> + '_ {
+		self.scrollback.iter().map(Vec::as_slice)
+	}
+
+	/// A row of the screen, ignoring any scrolling back.
+	pub fn row(&self, row: usize) -> &[Cell] {
+		&self.grid[row]
+	}
+
+	/// The text of a row of the screen, without trailing spaces.
+	pub fn row_text(&self, row: usize) -> String {
+		let text: String = self.grid[row].iter().map(|cell| cell.ch).collect();
+		String::from(text.trim_end())
+	}
+
+	/// A row as it should be displayed, which is from the scrollback if the
+	/// view has been scrolled back.
+	pub fn visible_row(&self, row: usize) -> &[Cell] {
+		if row < self.view {
+			&self.scrollback[self.scrollback.len() - self.view + row]
+		} else {
+			&self.grid[row - self.view]
+		}
+	}
+
+	/// The column and row of the cursor, or `None` if it is hidden or the view
+	/// has been scrolled back.
+	pub fn cursor(&self) -> Option<(usize, usize)> {
+		if self.cursor_visible && self.view == 0 {
+			Some((cmp::min(self.col, self.cols - 1), self.row))
+		} else {
+			None
+		}
+	}
+
+	/// How many lines the view is scrolled back by.
+	pub fn view_offset(&self) -> usize {
+		self.view
+	}
+
+	/// Scrolls the view back into the scrollback by `lines`.
+	pub fn scroll_view_up(&mut self, lines: usize) {
+		self.set_view(self.view.saturating_add(lines));
+	}
+
+	/// Scrolls the view towards the current screen by `lines`.
+	pub fn scroll_view_down(&mut self, lines: usize) {
+		self.set_view(self.view.saturating_sub(lines));
+	}
+
+	fn set_view(&mut self, view: usize) {
+		let view = cmp::min(view, self.scrollback.len());
+		if view != self.view {
+			self.view = view;
+			self.mark_all_dirty();
+		}
+	}
+
+	/// Whether a row has changed since [`mark_clean`](Terminal::mark_clean)
+	/// was last called.
+	pub fn is_dirty(&self, row: usize) -> bool {
+		self.dirty[row]
+	}
+
+	pub fn mark_clean(&mut self) {
+		self.dirty.iter_mut().for_each(|dirty| *dirty = false);
+	}
+
+	pub fn mark_all_dirty(&mut self) {
+		self.dirty.iter_mut().for_each(|dirty| *dirty = true);
+	}
+
+	/// Clears the screen, resets colors and moves the cursor to the top left.
+	/// The scrollback is kept.
+	pub fn reset(&mut self) {
+		self.style = Style::default();
+		self.erase_rows(0, self.rows);
+		self.col = 0;
+		self.row = 0;
+		self.saved = (0, 0);
+		self.cursor_visible = true;
+		self.state = State::Ground;
+	}
+
+	/// Processes output. UTF-8 sequences and escape sequences may be split
+	/// across calls.
+	pub fn write_bytes(&mut self, bytes: &[u8]) {
+		self.set_view(0);
+		for &byte in bytes {
+			self.push_byte(byte);
+		}
+	}
+
+	fn push_byte(&mut self, byte: u8) {
+		if self.utf8_len > 0 {
+			if byte & 0xC0 == 0x80 {
+				self.utf8[self.utf8_len] = byte;
+				self.utf8_len += 1;
+				if self.utf8_len == utf8_width(self.utf8[0]) {
+					let c = str::from_utf8(&self.utf8[..self.utf8_len])
+						.ok()
+						.and_then(|s| s.chars().next())
+						.unwrap_or(char::REPLACEMENT_CHARACTER);
+					self.utf8_len = 0;
+					self.input(c);
+				}
+				return;
+			}
+			self.utf8_len = 0;
+			self.input(char::REPLACEMENT_CHARACTER);
+		}
+		match utf8_width(byte) {
+			0 => self.input(char::REPLACEMENT_CHARACTER),
+			1 => self.input(byte as char),
+			_ => {
+				self.utf8[0] = byte;
+				self.utf8_len = 1;
+			}
+		}
+	}
+
+	fn input(&mut self, c: char) {
+		let row = self.row;
+		match self.state {
+			State::Ground => self.ground(c),
+			State::Escape => self.escape(c),
+			State::Csi => self.csi(c),
+		}
+		// Redraw where the cursor was and is
+		self.dirty[row] = true;
+		self.dirty[self.row] = true;
+	}
+
+	fn ground(&mut self, c: char) {
+		match c {
+			'\x1b' => self.state = State::Escape,
+			'\n' => {
+				self.col = 0;
+				self.line_feed();
+			}
+			'\r' => self.col = 0,
+			'\t' => {
+				if self.col < self.cols {
+					self.col = cmp::min((self.col / TAB_WIDTH + 1) * TAB_WIDTH, self.cols - 1);
+				}
+			}
+			'\x08' => self.col = cmp::min(self.col, self.cols - 1).saturating_sub(1),
+			c if c.is_control() => {}
+			c => {
+				if self.col >= self.cols {
+					self.col = 0;
+					self.line_feed();
+				}
+				self.grid[self.row][self.col] = self.style.cell(c);
+				self.col += 1;
+			}
+		}
+	}
+
+	fn escape(&mut self, c: char) {
+		self.state = State::Ground;
+		match c {
+			'[' => {
+				self.state = State::Csi;
+				self.params.clear();
+				self.param = 0;
+				self.private = false;
+			}
+			'7' => self.save_cursor(),
+			'8' => self.restore_cursor(),
+			'c' => self.reset(),
+			_ => {}
+		}
+	}
+
+	fn csi(&mut self, c: char) {
+		match c {
+			'0'..='9' => {
+				self.param = self
+					.param
+					.saturating_mul(10)
+					.saturating_add(c as u16 - '0' as u16);
+			}
+			';' | ':' => self.push_param(),
+			'<'..='?' => self.private = true,
+			// Intermediate bytes aren't used by any supported sequence
+			' '..='/' => {}
+			'@'..='~' => {
+				self.push_param();
+				self.state = State::Ground;
+				if self.private {
+					self.private_sequence(c);
+				} else {
+					self.sequence(c);
+				}
+			}
+			_ => self.state = State::Ground,
+		}
+	}
+
+	fn push_param(&mut self) {
+		if self.params.len() < MAX_PARAMS {
+			self.params.push(self.param);
+		}
+		self.param = 0;
+	}
+
+	fn param(&self, index: usize) -> u16 {
+		self.params.get(index).copied().unwrap_or(0)
+	}
+
+	/// A count, where 0 means 1.
+	fn count(&self) -> usize {
+		cmp::max(self.param(0), 1) as usize
+	}
+
+	fn private_sequence(&mut self, c: char) {
+		if self.params.contains(&25) {
+			match c {
+				'h' => self.cursor_visible = true,
+				'l' => self.cursor_visible = false,
+				_ => {}
+			}
+		}
+	}
+
+	fn sequence(&mut self, c: char) {
+		let col = cmp::min(self.col, self.cols - 1);
+		match c {
+			'A' => self.row = self.row.saturating_sub(self.count()),
+			'B' => self.row = cmp::min(self.row + self.count(), self.rows - 1),
+			'C' => self.col = cmp::min(col + self.count(), self.cols - 1),
+			'D' => self.col = col.saturating_sub(self.count()),
+			'H' | 'f' => {
+				self.row = cmp::min(cmp::max(self.param(0), 1) as usize - 1, self.rows - 1);
+				self.col = cmp::min(cmp::max(self.param(1), 1) as usize - 1, self.cols - 1);
+			}
+			'J' => match self.param(0) {
+				0 => {
+					self.erase_cells(self.row, col, self.cols);
+					self.erase_rows(self.row + 1, self.rows);
+				}
+				1 => {
+					self.erase_rows(0, self.row);
+					self.erase_cells(self.row, 0, col + 1);
+				}
+				2 => self.erase_rows(0, self.rows),
+				3 => {
+					self.erase_rows(0, self.rows);
+					self.scrollback.clear();
+				}
+				_ => {}
+			},
+			'K' => match self.param(0) {
+				0 => self.erase_cells(self.row, col, self.cols),
+				1 => self.erase_cells(self.row, 0, col + 1),
+				2 => self.erase_cells(self.row, 0, self.cols),
+				_ => {}
+			},
+			'm' => self.select_graphic_rendition(),
+			's' => self.save_cursor(),
+			'u' => self.restore_cursor(),
+			_ => {}
+		}
+	}
+
+	fn select_graphic_rendition(&mut self) {
+		let mut i = 0;
+		while i < self.params.len() {
+			match self.params[i] {
+				0 => self.style = Style::default(),
+				1 => self.style.bold = true,
+				7 => self.style.inverse = true,
+				22 => self.style.bold = false,
+				27 => self.style.inverse = false,
+				n @ 30..=37 => self.style.fg = Color::Ansi((n - 30) as u8),
+				39 => self.style.fg = Color::Foreground,
+				n @ 40..=47 => self.style.bg = Color::Ansi((n - 40) as u8),
+				49 => self.style.bg = Color::Background,
+				n @ 90..=97 => self.style.fg = Color::Ansi((n - 90 + 8) as u8),
+				n @ 100..=107 => self.style.bg = Color::Ansi((n - 100 + 8) as u8),
+				38 | 48 => {
+					// 256-color and RGB colors. Only the first 16 colors can be
+					// shown, but the arguments still need to be skipped.
+					match self.param(i + 1) {
+						5 => {
+							let index = self.param(i + 2);
+							if index < 16 {
+								let color = Color::Ansi(index as u8);
+								if self.params[i] == 38 {
+									self.style.fg = color;
+								} else {
+									self.style.bg = color;
+								}
+							}
+							i += 2;
+						}
+						2 => i += 4,
+						_ => {}
+					}
+				}
+				_ => {}
+			}
+			i += 1;
+		}
+	}
+
+	fn save_cursor(&mut self) {
+		self.saved = (self.col, self.row);
+	}
+
+	fn restore_cursor(&mut self) {
+		let (col, row) = self.saved;
+		self.col = col;
+		self.row = row;
+	}
+
+	fn line_feed(&mut self) {
+		if self.row + 1 < self.rows {
+			self.row += 1;
+		} else {
+			let top = self.grid.remove(0);
+			if self.scrollback_limit > 0 {
+				if self.scrollback.len() == self.scrollback_limit {
+					self.scrollback.pop_front();
+				}
+				self.scrollback.push_back(top);
+			}
+			self.grid.push(vec![self.style.blank(); self.cols]);
+			self.mark_all_dirty();
+		}
+	}
+
+	fn erase_cells(&mut self, row: usize, start: usize, end: usize) {
+		let blank = self.style.blank();
+		self.grid[row][start..end]
+			.iter_mut()
+			.for_each(|cell| *cell = blank);
+		self.dirty[row] = true;
+	}
+
+	fn erase_rows(&mut self, start: usize, end: usize) {
+		for row in start..end {
+			self.erase_cells(row, 0, self.cols);
+		}
+	}
+}
+
+impl fmt::Write for Terminal {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.write_bytes(s.as_bytes());
+		Ok(())
+	}
+}
+
+/// The length of a UTF-8 sequence starting with `byte`, or 0 if it can't
+/// start one.
+fn utf8_width(byte: u8) -> usize {
+	match byte {
+		0x00..=0x7F => 1,
+		0xC2..=0xDF => 2,
+		0xE0..=0xEF => 3,
+		0xF0..=0xF4 => 4,
+		_ => 0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+
+	use super::{Cell, Color, Terminal};
+
+	fn terminal(cols: usize, rows: usize, input: &str) -> Terminal {
+		let mut terminal = Terminal::new(cols, rows);
+		terminal.write_bytes(input.as_bytes());
+		terminal
+	}
+
+	fn screen(terminal: &Terminal) -> Vec<String> {
+		(0..terminal.rows())
+			.map(|row| terminal.row_text(row))
+			.collect()
+	}
+
+	fn visible(terminal: &Terminal) -> Vec<String> {
+		(0..terminal.rows())
+			.map(|row| {
+				let text: String = terminal
+					.visible_row(row)
+					.iter()
+					.map(|cell| cell.ch)
+					.collect();
+				String::from(text.trim_end())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn wrapping() {
+		let t = terminal(5, 3, "hello world");
+		assert_eq!(screen(&t), ["hello", " worl", "d"]);
+		assert_eq!(t.cursor(), Some((1, 2)));
+
+		// Filling the last column doesn't move to the next line yet
+		let t = terminal(5, 3, "hello");
+		assert_eq!(t.cursor(), Some((4, 0)));
+		let t = terminal(5, 3, "hello\nx");
+		assert_eq!(screen(&t), ["hello", "x", ""]);
+	}
+
+	#[test]
+	fn control_characters() {
+		let t = terminal(20, 3, "abc\rX\nline\x08\x08LI\n\tb\x07");
+		assert_eq!(screen(&t), ["Xbc", "liLI", "        b"]);
+
+		let t = terminal(10, 1, "a\tb\tc");
+		assert_eq!(screen(&t), ["a       bc"]);
+	}
+
+	#[test]
+	fn scrolling() {
+		let mut t = terminal(4, 2, "a\nb\nc\nd");
+		assert_eq!(screen(&t), ["c", "d"]);
+		let scrollback: Vec<String> = t
+			.scrollback()
+			.map(|row| {
+				row.iter()
+					.map(|cell| cell.ch)
+					.collect::<String>()
+					.trim_end()
+					.into()
+			})
+			.collect();
+		assert_eq!(scrollback, ["a", "b"]);
+
+		t.scroll_view_up(1);
+		assert_eq!(visible(&t), ["b", "c"]);
+		assert_eq!(t.cursor(), None);
+		t.scroll_view_up(10);
+		assert_eq!(t.view_offset(), 2);
+		assert_eq!(visible(&t), ["a", "b"]);
+		t.scroll_view_down(1);
+		assert_eq!(visible(&t), ["b", "c"]);
+
+		// New output jumps back to the screen
+		t.write_bytes(b"!");
+		assert_eq!(t.view_offset(), 0);
+		assert_eq!(visible(&t), ["c", "d!"]);
+	}
+
+	#[test]
+	fn scrollback_limit() {
+		let mut t = Terminal::new(4, 1);
+		t.set_scrollback_limit(2);
+		t.write_bytes(b"1\n2\n3\n4");
+		assert_eq!(t.scrollback().count(), 2);
+		assert_eq!(t.scrollback().next().unwrap()[0].ch, '2');
+		t.set_scrollback_limit(0);
+		t.write_bytes(b"\n5");
+		assert_eq!(t.scrollback().count(), 0);
+	}
+
+	#[test]
+	fn colors() {
+		let t = terminal(
+			8,
+			1,
+			"\x1b[31mA\x1b[1;44mB\x1b[22;7mC\x1b[0mD\x1b[92;49mE\x1b[38;5;3;48;2;1;2;3mF",
+		);
+		let cells = t.row(0);
+		let cell = |ch, fg, bg| Cell { ch, fg, bg };
+		assert_eq!(cells[0], cell('A', Color::Ansi(1), Color::Background));
+		assert_eq!(cells[1], cell('B', Color::Ansi(9), Color::Ansi(4)));
+		assert_eq!(cells[2], cell('C', Color::Ansi(4), Color::Ansi(1)));
+		assert_eq!(
+			cells[3],
+			Cell {
+				ch: 'D',
+				..Cell::default()
+			}
+		);
+		assert_eq!(cells[4], cell('E', Color::Ansi(10), Color::Background));
+		assert_eq!(cells[5], cell('F', Color::Ansi(3), Color::Background));
+	}
+
+	#[test]
+	fn cursor_movement() {
+		let t = terminal(5, 3, "\x1b[2;3HX\x1b[AY\x1b[2DZ\x1b[5BW\x1b[10CV");
+		assert_eq!(screen(&t), ["  ZY", "  X", "   WV"]);
+
+		let t = terminal(5, 2, "ab\x1b[sxyz\x1b[u!\x1b7\x1b[H?\x1b8.");
+		assert_eq!(screen(&t), ["?b!.z", ""]);
+
+		let t = terminal(5, 2, "\x1b[?25l");
+		assert_eq!(t.cursor(), None);
+		let t = terminal(5, 2, "\x1b[?25l\x1b[?25h");
+		assert_eq!(t.cursor(), Some((0, 0)));
+	}
+
+	#[test]
+	fn erasing() {
+		let fill = "abcde\nfghij\nklmno\x1b[2;3H";
+		let erase = |seq: &str| {
+			let mut input = String::from(fill);
+			input.push_str(seq);
+			screen(&terminal(5, 3, &input))
+		};
+		assert_eq!(erase("\x1b[K"), ["abcde", "fg", "klmno"]);
+		assert_eq!(erase("\x1b[1K"), ["abcde", "   ij", "klmno"]);
+		assert_eq!(erase("\x1b[2K"), ["abcde", "", "klmno"]);
+		assert_eq!(erase("\x1b[J"), ["abcde", "fg", ""]);
+		assert_eq!(erase("\x1b[1J"), ["", "   ij", "klmno"]);
+		assert_eq!(erase("\x1b[2J"), ["", "", ""]);
+
+		let t = terminal(5, 1, "a\x1b[44m\x1b[2J");
+		assert_eq!(t.row(0)[0].bg, Color::Ansi(4));
+		let t = terminal(5, 1, "a\nb\x1b[3J");
+		assert_eq!(t.scrollback().count(), 0);
+	}
+
+	#[test]
+	fn utf8() {
+		let mut t = Terminal::new(8, 1);
+		let bytes = "é€x".as_bytes();
+		t.write_bytes(&bytes[..1]);
+		t.write_bytes(&bytes[1..3]);
+		t.write_bytes(&bytes[3..]);
+		assert_eq!(screen(&t), ["é€x"]);
+
+		let mut t = Terminal::new(8, 1);
+		t.write_bytes(b"a\xffb\xe2\x82c");
+		assert_eq!(screen(&t), ["a\u{fffd}b\u{fffd}c"]);
+	}
+
+	#[test]
+	fn unknown_sequences() {
+		let t = terminal(10, 1, "a\x1b[5i\x1b]b\x1b[>1;2Xc\x1b(d");
+		assert_eq!(screen(&t), ["abcd"]);
+	}
+
+	#[test]
+	fn dirty_rows() {
+		let mut t = Terminal::new(5, 3);
+		t.mark_clean();
+		t.write_bytes(b"\x1b[2;1Hx");
+		assert_eq!(
+			(0..3).map(|row| t.is_dirty(row)).collect::<Vec<_>>(),
+			[true, true, false]
+		);
+		t.mark_clean();
+		t.write_bytes(b"\n\n");
+		assert!((0..3).all(|row| t.is_dirty(row)));
+	}
+}
@@ -55,7 +55,7 @@ pub fn args() -> Args {
 				})
 				.collect::<Vec<_>>()
 		})
-		.unwrap_or_default()
+		.unwrap_or_else(crate::resident::saved_args)
 		.into_iter()
 }
 
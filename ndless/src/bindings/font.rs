@@ -0,0 +1,500 @@
+//! # Bitmap fonts
+//! Draws text straight into a frame, without SDL or FreeType. Three fonts
+//! are built in:
+//!
+//! - [`MONO_8X8`], the console's font, with every glyph 8 pixels wide.
+//! - [`PROPORTIONAL_8`], the same glyphs with their empty columns removed,
+//!   which fits more text on a line.
+//! - [`TINY_4X6`], for when space is short: 80 columns by 40 rows.
+//!
+//! All three cover printable ASCII and a few symbols (`° × ÷ π √` and the
+//! arrows). Characters that a font doesn't have are drawn as its
+//! [fallback](Font::fallback) glyph, a box.
+//!
+//! Text can be drawn on anything that implements [`Target`], such as a
+//! [`Canvas`] or a [`Framebuffer`] in any screen mode:
+//!
+//! ```no_run
+//! use ndless::font::{Style, MONO_8X8, PROPORTIONAL_8};
+//! use ndless::hw::screen::lcd_type;
+//! use ndless::lcd::{self, Framebuffer};
+//!
+//! let screen = lcd_type();
+//! let mut data = vec![0u8; screen.byte_len()];
+//! let mut frame = Framebuffer::new(screen, &mut data);
+//! frame.fill(frame.color([255, 255, 255]));
+//! let title = Style::new([0, 0, 128]).scale(2);
+//! MONO_8X8.draw(&mut frame, "Score", 10, 10, &title);
+//! let area = PROPORTIONAL_8.draw(&mut frame, "π = 3.14\nNext line", 10, 40, &Style::default());
+//! lcd::init(screen);
+//! lcd::blit(frame.data(), screen);
+//! ```
+//!
+//! [`measure`](Font::measure) gives the size of some text before it's
+//! drawn, for centering it or lining it up to the right.
+//!
+//! Other fonts can be converted from BDF or PCF files at build time with the
+//! `ndless-fonts` crate.
+//!
+//! [`Canvas`]: crate::graphics::Canvas
+//! [`Framebuffer`]: crate::lcd::Framebuffer
+
+use crate::graphics::{rgb565, Canvas, Rect};
+use crate::lcd::Framebuffer;
+
+pub use self::mono_8x8::MONO_8X8;
+pub use self::proportional_8::PROPORTIONAL_8;
+pub use self::tiny_4x6::TINY_4X6;
+
+#[rustfmt::skip]
+mod mono_8x8;
+#[rustfmt::skip]
+mod proportional_8;
+#[rustfmt::skip]
+mod tiny_4x6;
+
+/// A character's picture in a [`Font`], and where it goes relative to the
+/// pen.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Glyph {
+	pub ch: char,
+	/// How far the pen moves right after the glyph is drawn.
+	pub advance: u8,
+	/// The size of the bitmap.
+	pub width: u8,
+	pub height: u8,
+	/// How far right of the pen the bitmap starts.
+	pub left: i8,
+	/// How far above the baseline the bitmap starts.
+	pub top: i8,
+	/// Where the bitmap starts in the font's data. Each row takes a whole
+	/// number of bytes, with the leftmost pixel in the most significant bit.
+	pub offset: u32,
+}
+
+impl Glyph {
+	/// The number of bytes in each row of the bitmap.
+	pub fn stride(&self) -> usize {
+		(self.width as usize).div_ceil(8)
+	}
+}
+
+/// A bitmap font. Fonts are `static`s, either [built in](self) or made by
+/// `ndless-fonts`.
+#[derive(Debug)]
+pub struct Font {
+	ascent: u8,
+	descent: u8,
+	fallback: usize,
+	glyphs: &'static [Glyph],
+	bitmap: &'static [u8],
+}
+
+impl Font {
+	/// A font with its `glyphs` sorted by character, and their bitmaps in
+	/// `bitmap`. `fallback` is the index of the glyph that's drawn for
+	/// characters that the font doesn't have.
+	pub const fn new(
+		ascent: u8,
+		descent: u8,
+		fallback: usize,
+		glyphs: &'static [Glyph],
+		bitmap: &'static [u8],
+	) -> Font {
+		Font {
+			ascent,
+			descent,
+			fallback,
+			glyphs,
+			bitmap,
+		}
+	}
+
+	/// How far above the baseline the font reaches.
+	pub fn ascent(&self) -> u32 {
+		self.ascent as u32
+	}
+
+	/// How far below the baseline the font reaches.
+	pub fn descent(&self) -> u32 {
+		self.descent as u32
+	}
+
+	/// The distance from one line to the next.
+	pub fn line_height(&self) -> u32 {
+		self.ascent() + self.descent()
+	}
+
+	pub fn glyphs(&self) -> &'static [Glyph] {
+		self.glyphs
+	}
+
+	/// The glyph for `c`, if the font has one.
+	pub fn get(&self, c: char) -> Option<&'static Glyph> {
+		let index = self.glyphs.binary_search_by_key(&c, |glyph| glyph.ch);
+		index.ok().map(|index| &self.glyphs[index])
+	}
+
+	/// The glyph that's drawn for characters that the font doesn't have.
+	pub fn fallback(&self) -> &'static Glyph {
+		&self.glyphs[self.fallback]
+	}
+
+	/// The glyph for `c`, or the [fallback](Font::fallback).
+	pub fn glyph(&self, c: char) -> &'static Glyph {
+		self.get(c).unwrap_or_else(|| self.fallback())
+	}
+
+	/// The rows of a glyph's bitmap. See [`Glyph::offset`].
+	pub fn bitmap(&self, glyph: &Glyph) -> &'static [u8] {
+		let start = glyph.offset as usize;
+		&self.bitmap[start..start + glyph.stride() * glyph.height as usize]
+	}
+
+	/// Whether the pixel `x` from the left and `y` from the top of a glyph's
+	/// bitmap is set.
+	pub fn pixel(&self, glyph: &Glyph, x: u32, y: u32) -> bool {
+		if x >= glyph.width as u32 || y >= glyph.height as u32 {
+			return false;
+		}
+		let byte = self.bitmap(glyph)[y as usize * glyph.stride() + x as usize / 8];
+		byte & (0x80 >> (x % 8)) != 0
+	}
+
+	/// Where each character of `text` goes, one line after another.
+	pub fn layout<'a>(&'a self, text: &'a str) -> Layout<'a> {
+		Layout {
+			font: self,
+			chars: text.chars(),
+			x: 0,
+			y: 0,
+		}
+	}
+
+	/// The width and height of `text`, before it's scaled. The width is that
+	/// of the longest line, and each `\n` starts a new line.
+	pub fn measure(&self, text: &str) -> (u32, u32) {
+		let mut width = 0;
+		let mut lines = 1;
+		let mut line_width = 0;
+		for c in text.chars() {
+			if c == '\n' {
+				lines += 1;
+				line_width = 0;
+			} else {
+				line_width += self.glyph(c).advance as u32;
+				width = width.max(line_width);
+			}
+		}
+		(width, lines * self.line_height())
+	}
+
+	/// Draws `text` with the top left of its first line at `x` and `y`,
+	/// returning the area that it covers. Each `\n` starts a new line.
+	pub fn draw(
+		&self,
+		target: &mut impl Target,
+		text: &str,
+		x: i32,
+		y: i32,
+		style: &Style,
+	) -> Rect {
+		let scale = style.scale.max(1);
+		let color = target.color(style.color);
+		if let Some(background) = style.background {
+			let background = target.color(background);
+			let height = self.line_height() * scale;
+			for (line, text) in text.split('\n').enumerate() {
+				let top = y + (line as u32 * height) as i32;
+				let width = self.measure(text).0 * scale;
+				for row in top..top + height as i32 {
+					target.fill_span(x, row, width, background);
+				}
+			}
+		}
+		for placed in self.layout(text) {
+			let glyph = placed.glyph;
+			// Spaces usually have no bitmap at all
+			if glyph.width == 0 || glyph.height == 0 {
+				continue;
+			}
+			let left = x + (placed.x + glyph.left as i32) * scale as i32;
+			let top = y + (placed.y + self.ascent as i32 - glyph.top as i32) * scale as i32;
+			for (row, bits) in self.bitmap(glyph).chunks(glyph.stride()).enumerate() {
+				let row_y = top + (row as u32 * scale) as i32;
+				// Runs of set pixels are filled a span at a time
+				let mut column = 0;
+				while column < glyph.width as u32 {
+					let set = |column: u32| bits[column as usize / 8] & (0x80 >> (column % 8)) != 0;
+					if !set(column) {
+						column += 1;
+						continue;
+					}
+					let start = column;
+					while column < glyph.width as u32 && set(column) {
+						column += 1;
+					}
+					let span_x = left + (start * scale) as i32;
+					for offset in 0..scale as i32 {
+						target.fill_span(span_x, row_y + offset, (column - start) * scale, color);
+					}
+				}
+			}
+		}
+		let (width, height) = self.measure(text);
+		Rect::new(x, y, width * scale, height * scale)
+	}
+}
+
+/// A glyph placed by [`Font::layout`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Placed {
+	pub glyph: &'static Glyph,
+	/// Where the pen is, from the left of the text.
+	pub x: i32,
+	/// Where the top of the line is, from the top of the text.
+	pub y: i32,
+}
+
+/// An iterator over where the glyphs of some text go, made by
+/// [`Font::layout`]. Line breaks take up no glyph.
+pub struct Layout<'a> {
+	font: &'a Font,
+	chars: core::str::Chars<'a>,
+	x: i32,
+	y: i32,
+}
+
+impl<'a> Iterator for Layout<'a> {
+	type Item = Placed;
+
+	fn next(&mut self) -> Option<Placed> {
+		loop {
+			let c = self.chars.next()?;
+			if c == '\n' {
+				self.x = 0;
+				self.y += self.font.line_height() as i32;
+				continue;
+			}
+			let glyph = self.font.glyph(c);
+			let placed = Placed {
+				glyph,
+				x: self.x,
+				y: self.y,
+			};
+			self.x += glyph.advance as i32;
+			return Some(placed);
+		}
+	}
+}
+
+/// How text is drawn.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Style {
+	/// The color of the text, as 8-bit RGB.
+	pub color: [u8; 3],
+	/// A color to fill behind each line first, or `None` to draw only the
+	/// text.
+	pub background: Option<[u8; 3]>,
+	/// How many pixels each pixel of the font becomes, across and down.
+	pub scale: u32,
+}
+
+impl Style {
+	pub fn new(color: [u8; 3]) -> Style {
+		Style {
+			color,
+			background: None,
+			scale: 1,
+		}
+	}
+
+	pub fn background(mut self, background: [u8; 3]) -> Style {
+		self.background = Some(background);
+		self
+	}
+
+	pub fn scale(mut self, scale: u32) -> Style {
+		self.scale = scale;
+		self
+	}
+}
+
+/// Black text at its normal size, with no background.
+impl Default for Style {
+	fn default() -> Style {
+		Style::new([0; 3])
+	}
+}
+
+/// Something that text can be drawn on.
+pub trait Target {
+	/// The raw value of an 8-bit RGB color.
+	fn color(&self, rgb: [u8; 3]) -> u16;
+
+	/// Sets `width` pixels to the right of and including `x` in row `y`,
+	/// skipping any that can't be drawn on.
+	fn fill_span(&mut self, x: i32, y: i32, width: u32, color: u16);
+}
+
+impl Target for Canvas<'_> {
+	fn color(&self, rgb: [u8; 3]) -> u16 {
+		rgb565(rgb[0], rgb[1], rgb[2])
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, width: u32, color: u16) {
+		self.fill_rect(Rect::new(x, y, width, 1), color);
+	}
+}
+
+impl Target for Framebuffer<'_> {
+	fn color(&self, rgb: [u8; 3]) -> u16 {
+		Framebuffer::color(self, rgb)
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, width: u32, color: u16) {
+		Framebuffer::fill_span(self, x, y, width, color);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec;
+
+	use super::*;
+	use crate::hw::screen::{Screen, HEIGHT, WIDTH};
+
+	const BUILT_IN: [&Font; 3] = [&MONO_8X8, &PROPORTIONAL_8, &TINY_4X6];
+
+	/// What's on a canvas, with `#` for pixels that aren't 0.
+	fn picture(canvas: &Canvas) -> String {
+		let mut picture = String::new();
+		for row in canvas.pixels().chunks(canvas.width() as usize) {
+			picture.extend(row.iter().map(|&pixel| if pixel != 0 { '#' } else { '.' }));
+			picture.push('\n');
+		}
+		picture
+	}
+
+	#[test]
+	fn built_in() {
+		for font in &BUILT_IN {
+			assert!(font.glyphs().windows(2).all(|pair| pair[0].ch < pair[1].ch));
+			for glyph in font.glyphs() {
+				// Panics if the bitmap is cut off
+				font.bitmap(glyph);
+				assert!(glyph.top as i32 <= font.ascent as i32);
+				assert!(glyph.top as i32 - glyph.height as i32 >= -(font.descent as i32));
+			}
+			assert_eq!(font.fallback().ch, '\u{FFFD}');
+			for c in (' '..='~').chain("°×÷π←↑→↓√".chars()) {
+				assert!(font.get(c).is_some(), "{:?}", c);
+			}
+		}
+		assert_eq!(MONO_8X8.line_height(), 8);
+		assert_eq!(TINY_4X6.line_height(), 6);
+	}
+
+	#[test]
+	fn measuring() {
+		assert_eq!(MONO_8X8.measure("Hello"), (40, 8));
+		assert_eq!(MONO_8X8.measure(""), (0, 8));
+		assert_eq!(MONO_8X8.measure("ab\nabcd\n"), (32, 24));
+		assert_eq!(TINY_4X6.measure("80 columns"), (40, 6));
+		// Narrow letters take less space
+		let (i, _) = PROPORTIONAL_8.measure("iii");
+		let (m, _) = PROPORTIONAL_8.measure("mmm");
+		assert!(i < m);
+		assert_eq!(PROPORTIONAL_8.measure(" "), (3, 8));
+	}
+
+	#[test]
+	fn fallback() {
+		// Multi-byte characters are one glyph each
+		assert_eq!(MONO_8X8.glyph('π').ch, 'π');
+		assert_eq!(MONO_8X8.glyph('ж').ch, '\u{FFFD}');
+		assert_eq!(MONO_8X8.glyph('\t').ch, '\u{FFFD}');
+		assert_eq!(MONO_8X8.measure("π≠"), (16, 8));
+		let placed: vec::Vec<_> = TINY_4X6
+			.layout("aπ\nж")
+			.map(|placed| (placed.glyph.ch, placed.x, placed.y))
+			.collect();
+		assert_eq!(placed, [('a', 0, 0), ('π', 4, 0), ('\u{FFFD}', 0, 6)]);
+	}
+
+	#[test]
+	fn drawing() {
+		let mut pixels = vec![0; 12 * 8];
+		let mut canvas = Canvas::new(&mut pixels, 12, 8);
+		let area = TINY_4X6.draw(&mut canvas, "Hi\n", 1, 1, &Style::new([255; 3]));
+		assert_eq!(area, Rect::new(1, 1, 8, 12));
+		#[rustfmt::skip]
+		assert_eq!(picture(&canvas), "\
+			............\n\
+			.#.#..#.....\n\
+			.#.#........\n\
+			.###..#.....\n\
+			.#.#..#.....\n\
+			.#.#..#.....\n\
+			............\n\
+			............\n");
+
+		// Scaled, with a background, and cut off by the edges
+		let mut pixels = vec![0; 10 * 6];
+		let mut canvas = Canvas::new(&mut pixels, 10, 6);
+		let style = Style::new([255; 3]).scale(2).background([0, 0, 8]);
+		TINY_4X6.draw(&mut canvas, "-", -1, -3, &style);
+		#[rustfmt::skip]
+		assert_eq!(picture(&canvas), "\
+			#######...\n\
+			#######...\n\
+			#######...\n\
+			#######...\n\
+			#######...\n\
+			#######...\n");
+		assert_eq!(canvas.pixel(6, 2), Some(0x0001));
+		assert_eq!(canvas.pixel(4, 2), Some(0xFFFF));
+		assert_eq!(canvas.pixel(5, 2), Some(0x0001));
+	}
+
+	#[test]
+	fn empty_glyphs() {
+		for font in &BUILT_IN {
+			let mut pixels = vec![0; 80 * 20];
+			let mut canvas = Canvas::new(&mut pixels, 80, 20);
+			let text = "π = 3.14\nNext line";
+			let area = font.draw(&mut canvas, text, 0, 0, &Style::new([255; 3]));
+			let (width, height) = font.measure(text);
+			assert_eq!(area, Rect::new(0, 0, width, height));
+			assert!(canvas.pixels().iter().any(|&pixel| pixel != 0));
+
+			let mut pixels = vec![0; 20 * 10];
+			let mut canvas = Canvas::new(&mut pixels, 20, 10);
+			font.draw(&mut canvas, "  ", 0, 0, &Style::new([255; 3]));
+			assert!(canvas.pixels().iter().all(|&pixel| pixel == 0));
+		}
+		assert_eq!(PROPORTIONAL_8.glyph(' ').width, 0);
+	}
+
+	#[test]
+	fn framebuffers() {
+		for &screen in &[Screen::Screen320x240x4, Screen::Screen240x320x565] {
+			let mut data = vec![0; screen.byte_len()];
+			let mut frame = Framebuffer::new(screen, &mut data);
+			MONO_8X8.draw(&mut frame, "|", 0, 232, &Style::new([255; 3]));
+			// The bar is two pixels wide with a gap in the middle, and the
+			// glyph's bottom row is empty
+			assert_eq!(frame.pixel(3, 232), Some([255; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(4, 238), Some([255; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(4, 235), Some([0; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(3, 239), Some([0; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(2, 232), Some([0; 3]), "{:?}", screen);
+			let drawn = (0..HEIGHT as i32)
+				.flat_map(|y| (0..WIDTH as i32).map(move |x| (x, y)))
+				.filter(|&(x, y)| frame.pixel(x, y) != Some([0; 3]))
+				.count();
+			assert_eq!(drawn, 12, "{:?}", screen);
+		}
+	}
+}
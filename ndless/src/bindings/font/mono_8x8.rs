@@ -0,0 +1,168 @@
+// Generated by ndless-fonts from "-ndless-mono-medium-r-normal--8-80-75-75-c-80-iso10646-1".
+pub static MONO_8X8: crate::font::Font = crate::font::Font::new(
+	7,
+	1,
+	104,
+	&[
+		crate::font::Glyph { ch: ' ', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 0 },
+		crate::font::Glyph { ch: '!', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 8 },
+		crate::font::Glyph { ch: '"', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 16 },
+		crate::font::Glyph { ch: '#', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 24 },
+		crate::font::Glyph { ch: '$', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 32 },
+		crate::font::Glyph { ch: '%', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 40 },
+		crate::font::Glyph { ch: '&', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 48 },
+		crate::font::Glyph { ch: '\'', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 56 },
+		crate::font::Glyph { ch: '(', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 64 },
+		crate::font::Glyph { ch: ')', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 72 },
+		crate::font::Glyph { ch: '*', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 80 },
+		crate::font::Glyph { ch: '+', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 88 },
+		crate::font::Glyph { ch: ',', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 96 },
+		crate::font::Glyph { ch: '-', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 104 },
+		crate::font::Glyph { ch: '.', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 112 },
+		crate::font::Glyph { ch: '/', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 120 },
+		crate::font::Glyph { ch: '0', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 128 },
+		crate::font::Glyph { ch: '1', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 136 },
+		crate::font::Glyph { ch: '2', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 144 },
+		crate::font::Glyph { ch: '3', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 152 },
+		crate::font::Glyph { ch: '4', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 160 },
+		crate::font::Glyph { ch: '5', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 168 },
+		crate::font::Glyph { ch: '6', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 176 },
+		crate::font::Glyph { ch: '7', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 184 },
+		crate::font::Glyph { ch: '8', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 192 },
+		crate::font::Glyph { ch: '9', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 200 },
+		crate::font::Glyph { ch: ':', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 208 },
+		crate::font::Glyph { ch: ';', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 216 },
+		crate::font::Glyph { ch: '<', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 224 },
+		crate::font::Glyph { ch: '=', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 232 },
+		crate::font::Glyph { ch: '>', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 240 },
+		crate::font::Glyph { ch: '?', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 248 },
+		crate::font::Glyph { ch: '@', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 256 },
+		crate::font::Glyph { ch: 'A', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 264 },
+		crate::font::Glyph { ch: 'B', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 272 },
+		crate::font::Glyph { ch: 'C', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 280 },
+		crate::font::Glyph { ch: 'D', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 288 },
+		crate::font::Glyph { ch: 'E', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 296 },
+		crate::font::Glyph { ch: 'F', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 304 },
+		crate::font::Glyph { ch: 'G', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 312 },
+		crate::font::Glyph { ch: 'H', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 320 },
+		crate::font::Glyph { ch: 'I', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 328 },
+		crate::font::Glyph { ch: 'J', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 336 },
+		crate::font::Glyph { ch: 'K', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 344 },
+		crate::font::Glyph { ch: 'L', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 352 },
+		crate::font::Glyph { ch: 'M', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 360 },
+		crate::font::Glyph { ch: 'N', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 368 },
+		crate::font::Glyph { ch: 'O', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 376 },
+		crate::font::Glyph { ch: 'P', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 384 },
+		crate::font::Glyph { ch: 'Q', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 392 },
+		crate::font::Glyph { ch: 'R', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 400 },
+		crate::font::Glyph { ch: 'S', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 408 },
+		crate::font::Glyph { ch: 'T', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 416 },
+		crate::font::Glyph { ch: 'U', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 424 },
+		crate::font::Glyph { ch: 'V', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 432 },
+		crate::font::Glyph { ch: 'W', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 440 },
+		crate::font::Glyph { ch: 'X', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 448 },
+		crate::font::Glyph { ch: 'Y', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 456 },
+		crate::font::Glyph { ch: 'Z', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 464 },
+		crate::font::Glyph { ch: '[', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 472 },
+		crate::font::Glyph { ch: '\\', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 480 },
+		crate::font::Glyph { ch: ']', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 488 },
+		crate::font::Glyph { ch: '^', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 496 },
+		crate::font::Glyph { ch: '_', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 504 },
+		crate::font::Glyph { ch: '`', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 512 },
+		crate::font::Glyph { ch: 'a', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 520 },
+		crate::font::Glyph { ch: 'b', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 528 },
+		crate::font::Glyph { ch: 'c', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 536 },
+		crate::font::Glyph { ch: 'd', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 544 },
+		crate::font::Glyph { ch: 'e', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 552 },
+		crate::font::Glyph { ch: 'f', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 560 },
+		crate::font::Glyph { ch: 'g', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 568 },
+		crate::font::Glyph { ch: 'h', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 576 },
+		crate::font::Glyph { ch: 'i', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 584 },
+		crate::font::Glyph { ch: 'j', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 592 },
+		crate::font::Glyph { ch: 'k', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 600 },
+		crate::font::Glyph { ch: 'l', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 608 },
+		crate::font::Glyph { ch: 'm', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 616 },
+		crate::font::Glyph { ch: 'n', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 624 },
+		crate::font::Glyph { ch: 'o', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 632 },
+		crate::font::Glyph { ch: 'p', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 640 },
+		crate::font::Glyph { ch: 'q', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 648 },
+		crate::font::Glyph { ch: 'r', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 656 },
+		crate::font::Glyph { ch: 's', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 664 },
+		crate::font::Glyph { ch: 't', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 672 },
+		crate::font::Glyph { ch: 'u', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 680 },
+		crate::font::Glyph { ch: 'v', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 688 },
+		crate::font::Glyph { ch: 'w', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 696 },
+		crate::font::Glyph { ch: 'x', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 704 },
+		crate::font::Glyph { ch: 'y', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 712 },
+		crate::font::Glyph { ch: 'z', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 720 },
+		crate::font::Glyph { ch: '{', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 728 },
+		crate::font::Glyph { ch: '|', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 736 },
+		crate::font::Glyph { ch: '}', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 744 },
+		crate::font::Glyph { ch: '~', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 752 },
+		crate::font::Glyph { ch: '°', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 760 },
+		crate::font::Glyph { ch: '×', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 768 },
+		crate::font::Glyph { ch: '÷', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 776 },
+		crate::font::Glyph { ch: 'π', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 784 },
+		crate::font::Glyph { ch: '←', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 792 },
+		crate::font::Glyph { ch: '↑', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 800 },
+		crate::font::Glyph { ch: '→', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 808 },
+		crate::font::Glyph { ch: '↓', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 816 },
+		crate::font::Glyph { ch: '√', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 824 },
+		crate::font::Glyph { ch: '�', advance: 8, width: 8, height: 8, left: 0, top: 7, offset: 832 },
+	],
+	&[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00,
+		0x6C, 0x6C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x6C, 0x6C, 0xFE, 0x6C, 0xFE, 0x6C, 0x6C, 0x00,
+		0x30, 0x7C, 0xC0, 0x78, 0x0C, 0xF8, 0x30, 0x00, 0x00, 0xC6, 0xCC, 0x18, 0x30, 0x66, 0xC6, 0x00,
+		0x38, 0x6C, 0x38, 0x76, 0xDC, 0xCC, 0x76, 0x00, 0x60, 0x60, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x18, 0x30, 0x60, 0x60, 0x60, 0x30, 0x18, 0x00, 0x60, 0x30, 0x18, 0x18, 0x18, 0x30, 0x60, 0x00,
+		0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00, 0x00, 0x30, 0x30, 0xFC, 0x30, 0x30, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x30, 0x60, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x30, 0x00, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xC0, 0x80, 0x00,
+		0x7C, 0xC6, 0xCE, 0xDE, 0xF6, 0xE6, 0x7C, 0x00, 0x30, 0x70, 0x30, 0x30, 0x30, 0x30, 0xFC, 0x00,
+		0x78, 0xCC, 0x0C, 0x38, 0x60, 0xCC, 0xFC, 0x00, 0x78, 0xCC, 0x0C, 0x38, 0x0C, 0xCC, 0x78, 0x00,
+		0x1C, 0x3C, 0x6C, 0xCC, 0xFE, 0x0C, 0x1E, 0x00, 0xFC, 0xC0, 0xF8, 0x0C, 0x0C, 0xCC, 0x78, 0x00,
+		0x38, 0x60, 0xC0, 0xF8, 0xCC, 0xCC, 0x78, 0x00, 0xFC, 0xCC, 0x0C, 0x18, 0x30, 0x30, 0x30, 0x00,
+		0x78, 0xCC, 0xCC, 0x78, 0xCC, 0xCC, 0x78, 0x00, 0x78, 0xCC, 0xCC, 0x7C, 0x0C, 0x18, 0x70, 0x00,
+		0x00, 0x30, 0x30, 0x00, 0x00, 0x30, 0x30, 0x00, 0x00, 0x30, 0x30, 0x00, 0x00, 0x30, 0x30, 0x60,
+		0x18, 0x30, 0x60, 0xC0, 0x60, 0x30, 0x18, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0xFC, 0x00, 0x00,
+		0x60, 0x30, 0x18, 0x0C, 0x18, 0x30, 0x60, 0x00, 0x78, 0xCC, 0x0C, 0x18, 0x30, 0x00, 0x30, 0x00,
+		0x7C, 0xC6, 0xDE, 0xDE, 0xDE, 0xC0, 0x78, 0x00, 0x30, 0x78, 0xCC, 0xCC, 0xFC, 0xCC, 0xCC, 0x00,
+		0xFC, 0x66, 0x66, 0x7C, 0x66, 0x66, 0xFC, 0x00, 0x3C, 0x66, 0xC0, 0xC0, 0xC0, 0x66, 0x3C, 0x00,
+		0xF8, 0x6C, 0x66, 0x66, 0x66, 0x6C, 0xF8, 0x00, 0xFE, 0x62, 0x68, 0x78, 0x68, 0x62, 0xFE, 0x00,
+		0xFE, 0x62, 0x68, 0x78, 0x68, 0x60, 0xF0, 0x00, 0x3C, 0x66, 0xC0, 0xC0, 0xCE, 0x66, 0x3E, 0x00,
+		0xCC, 0xCC, 0xCC, 0xFC, 0xCC, 0xCC, 0xCC, 0x00, 0x78, 0x30, 0x30, 0x30, 0x30, 0x30, 0x78, 0x00,
+		0x1E, 0x0C, 0x0C, 0x0C, 0xCC, 0xCC, 0x78, 0x00, 0xE6, 0x66, 0x6C, 0x78, 0x6C, 0x66, 0xE6, 0x00,
+		0xF0, 0x60, 0x60, 0x60, 0x62, 0x66, 0xFE, 0x00, 0xC6, 0xEE, 0xFE, 0xFE, 0xD6, 0xC6, 0xC6, 0x00,
+		0xC6, 0xE6, 0xF6, 0xDE, 0xCE, 0xC6, 0xC6, 0x00, 0x38, 0x6C, 0xC6, 0xC6, 0xC6, 0x6C, 0x38, 0x00,
+		0xFC, 0x66, 0x66, 0x7C, 0x60, 0x60, 0xF0, 0x00, 0x78, 0xCC, 0xCC, 0xCC, 0xDC, 0x78, 0x1C, 0x00,
+		0xFC, 0x66, 0x66, 0x7C, 0x6C, 0x66, 0xE6, 0x00, 0x78, 0xCC, 0xE0, 0x70, 0x1C, 0xCC, 0x78, 0x00,
+		0xFC, 0xB4, 0x30, 0x30, 0x30, 0x30, 0x78, 0x00, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xFC, 0x00,
+		0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0x00, 0xC6, 0xC6, 0xC6, 0xD6, 0xFE, 0xEE, 0xC6, 0x00,
+		0xC6, 0xC6, 0x6C, 0x38, 0x38, 0x6C, 0xC6, 0x00, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0x30, 0x78, 0x00,
+		0xFE, 0xC6, 0x8C, 0x18, 0x32, 0x66, 0xFE, 0x00, 0x78, 0x60, 0x60, 0x60, 0x60, 0x60, 0x78, 0x00,
+		0xC0, 0x60, 0x30, 0x18, 0x0C, 0x06, 0x02, 0x00, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0x78, 0x00,
+		0x10, 0x38, 0x6C, 0xC6, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF,
+		0x30, 0x30, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x78, 0x0C, 0x7C, 0xCC, 0x76, 0x00,
+		0xE0, 0x60, 0x60, 0x7C, 0x66, 0x66, 0xDC, 0x00, 0x00, 0x00, 0x78, 0xCC, 0xC0, 0xCC, 0x78, 0x00,
+		0x1C, 0x0C, 0x0C, 0x7C, 0xCC, 0xCC, 0x76, 0x00, 0x00, 0x00, 0x78, 0xCC, 0xFC, 0xC0, 0x78, 0x00,
+		0x38, 0x6C, 0x60, 0xF0, 0x60, 0x60, 0xF0, 0x00, 0x00, 0x00, 0x76, 0xCC, 0xCC, 0x7C, 0x0C, 0xF8,
+		0xE0, 0x60, 0x6C, 0x76, 0x66, 0x66, 0xE6, 0x00, 0x30, 0x00, 0x70, 0x30, 0x30, 0x30, 0x78, 0x00,
+		0x0C, 0x00, 0x0C, 0x0C, 0x0C, 0xCC, 0xCC, 0x78, 0xE0, 0x60, 0x66, 0x6C, 0x78, 0x6C, 0xE6, 0x00,
+		0x70, 0x30, 0x30, 0x30, 0x30, 0x30, 0x78, 0x00, 0x00, 0x00, 0xCC, 0xFE, 0xFE, 0xD6, 0xC6, 0x00,
+		0x00, 0x00, 0xF8, 0xCC, 0xCC, 0xCC, 0xCC, 0x00, 0x00, 0x00, 0x78, 0xCC, 0xCC, 0xCC, 0x78, 0x00,
+		0x00, 0x00, 0xDC, 0x66, 0x66, 0x7C, 0x60, 0xF0, 0x00, 0x00, 0x76, 0xCC, 0xCC, 0x7C, 0x0C, 0x1E,
+		0x00, 0x00, 0xDC, 0x76, 0x66, 0x60, 0xF0, 0x00, 0x00, 0x00, 0x7C, 0xC0, 0x78, 0x0C, 0xF8, 0x00,
+		0x10, 0x30, 0x7C, 0x30, 0x30, 0x34, 0x18, 0x00, 0x00, 0x00, 0xCC, 0xCC, 0xCC, 0xCC, 0x76, 0x00,
+		0x00, 0x00, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0x00, 0x00, 0x00, 0xC6, 0xD6, 0xFE, 0xFE, 0x6C, 0x00,
+		0x00, 0x00, 0xC6, 0x6C, 0x38, 0x6C, 0xC6, 0x00, 0x00, 0x00, 0xCC, 0xCC, 0xCC, 0x7C, 0x0C, 0xF8,
+		0x00, 0x00, 0xFC, 0x98, 0x30, 0x64, 0xFC, 0x00, 0x1C, 0x30, 0x30, 0xE0, 0x30, 0x30, 0x1C, 0x00,
+		0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00, 0xE0, 0x30, 0x30, 0x1C, 0x30, 0x30, 0xE0, 0x00,
+		0x76, 0xDC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x38, 0x6C, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x66, 0x3C, 0x18, 0x3C, 0x66, 0x00, 0x00, 0x00, 0x18, 0x00, 0xFE, 0x00, 0x18, 0x00, 0x00,
+		0x00, 0x00, 0xFE, 0x6C, 0x6C, 0x6C, 0x6C, 0x00, 0x00, 0x10, 0x30, 0x7F, 0x30, 0x10, 0x00, 0x00,
+		0x10, 0x38, 0x54, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x08, 0x0C, 0xFE, 0x0C, 0x08, 0x00, 0x00,
+		0x10, 0x10, 0x10, 0x10, 0x54, 0x38, 0x10, 0x00, 0x0F, 0x08, 0x08, 0x88, 0x48, 0x28, 0x18, 0x00,
+		0xFE, 0x82, 0x82, 0x82, 0x82, 0x82, 0xFE, 0x00,
+	],
+);
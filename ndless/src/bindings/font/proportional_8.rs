@@ -0,0 +1,155 @@
+// Generated by ndless-fonts from "-ndless-proportional-medium-r-normal--8-80-75-75-p-60-iso10646-1".
+pub static PROPORTIONAL_8: crate::font::Font = crate::font::Font::new(
+	7,
+	1,
+	104,
+	&[
+		crate::font::Glyph { ch: ' ', advance: 3, width: 0, height: 0, left: 0, top: 0, offset: 0 },
+		crate::font::Glyph { ch: '!', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 0 },
+		crate::font::Glyph { ch: '"', advance: 6, width: 5, height: 2, left: 0, top: 7, offset: 7 },
+		crate::font::Glyph { ch: '#', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 9 },
+		crate::font::Glyph { ch: '$', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 16 },
+		crate::font::Glyph { ch: '%', advance: 8, width: 7, height: 6, left: 0, top: 6, offset: 23 },
+		crate::font::Glyph { ch: '&', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 29 },
+		crate::font::Glyph { ch: '\'', advance: 4, width: 3, height: 3, left: 0, top: 7, offset: 36 },
+		crate::font::Glyph { ch: '(', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 39 },
+		crate::font::Glyph { ch: ')', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 46 },
+		crate::font::Glyph { ch: '*', advance: 9, width: 8, height: 5, left: 0, top: 6, offset: 53 },
+		crate::font::Glyph { ch: '+', advance: 7, width: 6, height: 5, left: 0, top: 6, offset: 58 },
+		crate::font::Glyph { ch: ',', advance: 4, width: 3, height: 3, left: 0, top: 2, offset: 63 },
+		crate::font::Glyph { ch: '-', advance: 7, width: 6, height: 1, left: 0, top: 4, offset: 66 },
+		crate::font::Glyph { ch: '.', advance: 3, width: 2, height: 2, left: 0, top: 2, offset: 67 },
+		crate::font::Glyph { ch: '/', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 69 },
+		crate::font::Glyph { ch: '0', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 76 },
+		crate::font::Glyph { ch: '1', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 83 },
+		crate::font::Glyph { ch: '2', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 90 },
+		crate::font::Glyph { ch: '3', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 97 },
+		crate::font::Glyph { ch: '4', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 104 },
+		crate::font::Glyph { ch: '5', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 111 },
+		crate::font::Glyph { ch: '6', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 118 },
+		crate::font::Glyph { ch: '7', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 125 },
+		crate::font::Glyph { ch: '8', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 132 },
+		crate::font::Glyph { ch: '9', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 139 },
+		crate::font::Glyph { ch: ':', advance: 3, width: 2, height: 6, left: 0, top: 6, offset: 146 },
+		crate::font::Glyph { ch: ';', advance: 4, width: 3, height: 7, left: 0, top: 6, offset: 152 },
+		crate::font::Glyph { ch: '<', advance: 6, width: 5, height: 7, left: 0, top: 7, offset: 159 },
+		crate::font::Glyph { ch: '=', advance: 7, width: 6, height: 4, left: 0, top: 5, offset: 166 },
+		crate::font::Glyph { ch: '>', advance: 6, width: 5, height: 7, left: 0, top: 7, offset: 170 },
+		crate::font::Glyph { ch: '?', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 177 },
+		crate::font::Glyph { ch: '@', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 184 },
+		crate::font::Glyph { ch: 'A', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 191 },
+		crate::font::Glyph { ch: 'B', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 198 },
+		crate::font::Glyph { ch: 'C', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 205 },
+		crate::font::Glyph { ch: 'D', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 212 },
+		crate::font::Glyph { ch: 'E', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 219 },
+		crate::font::Glyph { ch: 'F', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 226 },
+		crate::font::Glyph { ch: 'G', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 233 },
+		crate::font::Glyph { ch: 'H', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 240 },
+		crate::font::Glyph { ch: 'I', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 247 },
+		crate::font::Glyph { ch: 'J', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 254 },
+		crate::font::Glyph { ch: 'K', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 261 },
+		crate::font::Glyph { ch: 'L', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 268 },
+		crate::font::Glyph { ch: 'M', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 275 },
+		crate::font::Glyph { ch: 'N', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 282 },
+		crate::font::Glyph { ch: 'O', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 289 },
+		crate::font::Glyph { ch: 'P', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 296 },
+		crate::font::Glyph { ch: 'Q', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 303 },
+		crate::font::Glyph { ch: 'R', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 310 },
+		crate::font::Glyph { ch: 'S', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 317 },
+		crate::font::Glyph { ch: 'T', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 324 },
+		crate::font::Glyph { ch: 'U', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 331 },
+		crate::font::Glyph { ch: 'V', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 338 },
+		crate::font::Glyph { ch: 'W', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 345 },
+		crate::font::Glyph { ch: 'X', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 352 },
+		crate::font::Glyph { ch: 'Y', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 359 },
+		crate::font::Glyph { ch: 'Z', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 366 },
+		crate::font::Glyph { ch: '[', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 373 },
+		crate::font::Glyph { ch: '\\', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 380 },
+		crate::font::Glyph { ch: ']', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 387 },
+		crate::font::Glyph { ch: '^', advance: 8, width: 7, height: 4, left: 0, top: 7, offset: 394 },
+		crate::font::Glyph { ch: '_', advance: 9, width: 8, height: 1, left: 0, top: 0, offset: 398 },
+		crate::font::Glyph { ch: '`', advance: 4, width: 3, height: 3, left: 0, top: 7, offset: 399 },
+		crate::font::Glyph { ch: 'a', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 402 },
+		crate::font::Glyph { ch: 'b', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 407 },
+		crate::font::Glyph { ch: 'c', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 414 },
+		crate::font::Glyph { ch: 'd', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 419 },
+		crate::font::Glyph { ch: 'e', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 426 },
+		crate::font::Glyph { ch: 'f', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 431 },
+		crate::font::Glyph { ch: 'g', advance: 8, width: 7, height: 6, left: 0, top: 5, offset: 438 },
+		crate::font::Glyph { ch: 'h', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 444 },
+		crate::font::Glyph { ch: 'i', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 451 },
+		crate::font::Glyph { ch: 'j', advance: 7, width: 6, height: 8, left: 0, top: 7, offset: 458 },
+		crate::font::Glyph { ch: 'k', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 466 },
+		crate::font::Glyph { ch: 'l', advance: 5, width: 4, height: 7, left: 0, top: 7, offset: 473 },
+		crate::font::Glyph { ch: 'm', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 480 },
+		crate::font::Glyph { ch: 'n', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 485 },
+		crate::font::Glyph { ch: 'o', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 490 },
+		crate::font::Glyph { ch: 'p', advance: 8, width: 7, height: 6, left: 0, top: 5, offset: 495 },
+		crate::font::Glyph { ch: 'q', advance: 8, width: 7, height: 6, left: 0, top: 5, offset: 501 },
+		crate::font::Glyph { ch: 'r', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 507 },
+		crate::font::Glyph { ch: 's', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 512 },
+		crate::font::Glyph { ch: 't', advance: 6, width: 5, height: 7, left: 0, top: 7, offset: 517 },
+		crate::font::Glyph { ch: 'u', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 524 },
+		crate::font::Glyph { ch: 'v', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 529 },
+		crate::font::Glyph { ch: 'w', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 534 },
+		crate::font::Glyph { ch: 'x', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 539 },
+		crate::font::Glyph { ch: 'y', advance: 7, width: 6, height: 6, left: 0, top: 5, offset: 544 },
+		crate::font::Glyph { ch: 'z', advance: 7, width: 6, height: 5, left: 0, top: 5, offset: 550 },
+		crate::font::Glyph { ch: '{', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 555 },
+		crate::font::Glyph { ch: '|', advance: 3, width: 2, height: 7, left: 0, top: 7, offset: 562 },
+		crate::font::Glyph { ch: '}', advance: 7, width: 6, height: 7, left: 0, top: 7, offset: 569 },
+		crate::font::Glyph { ch: '~', advance: 8, width: 7, height: 2, left: 0, top: 7, offset: 576 },
+		crate::font::Glyph { ch: '°', advance: 6, width: 5, height: 3, left: 0, top: 7, offset: 578 },
+		crate::font::Glyph { ch: '×', advance: 7, width: 6, height: 5, left: 0, top: 6, offset: 581 },
+		crate::font::Glyph { ch: '÷', advance: 8, width: 7, height: 5, left: 0, top: 6, offset: 586 },
+		crate::font::Glyph { ch: 'π', advance: 8, width: 7, height: 5, left: 0, top: 5, offset: 591 },
+		crate::font::Glyph { ch: '←', advance: 8, width: 7, height: 5, left: 0, top: 6, offset: 596 },
+		crate::font::Glyph { ch: '↑', advance: 6, width: 5, height: 7, left: 0, top: 7, offset: 601 },
+		crate::font::Glyph { ch: '→', advance: 8, width: 7, height: 5, left: 0, top: 6, offset: 608 },
+		crate::font::Glyph { ch: '↓', advance: 6, width: 5, height: 7, left: 0, top: 7, offset: 613 },
+		crate::font::Glyph { ch: '√', advance: 9, width: 8, height: 7, left: 0, top: 7, offset: 620 },
+		crate::font::Glyph { ch: '�', advance: 8, width: 7, height: 7, left: 0, top: 7, offset: 627 },
+	],
+	&[
+		0x60, 0xF0, 0xF0, 0x60, 0x60, 0x00, 0x60, 0xD8, 0xD8, 0x6C, 0x6C, 0xFE, 0x6C, 0xFE, 0x6C, 0x6C,
+		0x30, 0x7C, 0xC0, 0x78, 0x0C, 0xF8, 0x30, 0xC6, 0xCC, 0x18, 0x30, 0x66, 0xC6, 0x38, 0x6C, 0x38,
+		0x76, 0xDC, 0xCC, 0x76, 0x60, 0x60, 0xC0, 0x30, 0x60, 0xC0, 0xC0, 0xC0, 0x60, 0x30, 0xC0, 0x60,
+		0x30, 0x30, 0x30, 0x60, 0xC0, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x30, 0x30, 0xFC, 0x30, 0x30, 0x60,
+		0x60, 0xC0, 0xFC, 0xC0, 0xC0, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xC0, 0x80, 0x7C, 0xC6, 0xCE, 0xDE,
+		0xF6, 0xE6, 0x7C, 0x30, 0x70, 0x30, 0x30, 0x30, 0x30, 0xFC, 0x78, 0xCC, 0x0C, 0x38, 0x60, 0xCC,
+		0xFC, 0x78, 0xCC, 0x0C, 0x38, 0x0C, 0xCC, 0x78, 0x1C, 0x3C, 0x6C, 0xCC, 0xFE, 0x0C, 0x1E, 0xFC,
+		0xC0, 0xF8, 0x0C, 0x0C, 0xCC, 0x78, 0x38, 0x60, 0xC0, 0xF8, 0xCC, 0xCC, 0x78, 0xFC, 0xCC, 0x0C,
+		0x18, 0x30, 0x30, 0x30, 0x78, 0xCC, 0xCC, 0x78, 0xCC, 0xCC, 0x78, 0x78, 0xCC, 0xCC, 0x7C, 0x0C,
+		0x18, 0x70, 0xC0, 0xC0, 0x00, 0x00, 0xC0, 0xC0, 0x60, 0x60, 0x00, 0x00, 0x60, 0x60, 0xC0, 0x18,
+		0x30, 0x60, 0xC0, 0x60, 0x30, 0x18, 0xFC, 0x00, 0x00, 0xFC, 0xC0, 0x60, 0x30, 0x18, 0x30, 0x60,
+		0xC0, 0x78, 0xCC, 0x0C, 0x18, 0x30, 0x00, 0x30, 0x7C, 0xC6, 0xDE, 0xDE, 0xDE, 0xC0, 0x78, 0x30,
+		0x78, 0xCC, 0xCC, 0xFC, 0xCC, 0xCC, 0xFC, 0x66, 0x66, 0x7C, 0x66, 0x66, 0xFC, 0x3C, 0x66, 0xC0,
+		0xC0, 0xC0, 0x66, 0x3C, 0xF8, 0x6C, 0x66, 0x66, 0x66, 0x6C, 0xF8, 0xFE, 0x62, 0x68, 0x78, 0x68,
+		0x62, 0xFE, 0xFE, 0x62, 0x68, 0x78, 0x68, 0x60, 0xF0, 0x3C, 0x66, 0xC0, 0xC0, 0xCE, 0x66, 0x3E,
+		0xCC, 0xCC, 0xCC, 0xFC, 0xCC, 0xCC, 0xCC, 0xF0, 0x60, 0x60, 0x60, 0x60, 0x60, 0xF0, 0x1E, 0x0C,
+		0x0C, 0x0C, 0xCC, 0xCC, 0x78, 0xE6, 0x66, 0x6C, 0x78, 0x6C, 0x66, 0xE6, 0xF0, 0x60, 0x60, 0x60,
+		0x62, 0x66, 0xFE, 0xC6, 0xEE, 0xFE, 0xFE, 0xD6, 0xC6, 0xC6, 0xC6, 0xE6, 0xF6, 0xDE, 0xCE, 0xC6,
+		0xC6, 0x38, 0x6C, 0xC6, 0xC6, 0xC6, 0x6C, 0x38, 0xFC, 0x66, 0x66, 0x7C, 0x60, 0x60, 0xF0, 0x78,
+		0xCC, 0xCC, 0xCC, 0xDC, 0x78, 0x1C, 0xFC, 0x66, 0x66, 0x7C, 0x6C, 0x66, 0xE6, 0x78, 0xCC, 0xE0,
+		0x70, 0x1C, 0xCC, 0x78, 0xFC, 0xB4, 0x30, 0x30, 0x30, 0x30, 0x78, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC,
+		0xCC, 0xFC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0xC6, 0xC6, 0xC6, 0xD6, 0xFE, 0xEE, 0xC6,
+		0xC6, 0xC6, 0x6C, 0x38, 0x38, 0x6C, 0xC6, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0x30, 0x78, 0xFE, 0xC6,
+		0x8C, 0x18, 0x32, 0x66, 0xFE, 0xF0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xF0, 0xC0, 0x60, 0x30, 0x18,
+		0x0C, 0x06, 0x02, 0xF0, 0x30, 0x30, 0x30, 0x30, 0x30, 0xF0, 0x10, 0x38, 0x6C, 0xC6, 0xFF, 0xC0,
+		0xC0, 0x60, 0x78, 0x0C, 0x7C, 0xCC, 0x76, 0xE0, 0x60, 0x60, 0x7C, 0x66, 0x66, 0xDC, 0x78, 0xCC,
+		0xC0, 0xCC, 0x78, 0x1C, 0x0C, 0x0C, 0x7C, 0xCC, 0xCC, 0x76, 0x78, 0xCC, 0xFC, 0xC0, 0x78, 0x38,
+		0x6C, 0x60, 0xF0, 0x60, 0x60, 0xF0, 0x76, 0xCC, 0xCC, 0x7C, 0x0C, 0xF8, 0xE0, 0x60, 0x6C, 0x76,
+		0x66, 0x66, 0xE6, 0x60, 0x00, 0xE0, 0x60, 0x60, 0x60, 0xF0, 0x0C, 0x00, 0x0C, 0x0C, 0x0C, 0xCC,
+		0xCC, 0x78, 0xE0, 0x60, 0x66, 0x6C, 0x78, 0x6C, 0xE6, 0xE0, 0x60, 0x60, 0x60, 0x60, 0x60, 0xF0,
+		0xCC, 0xFE, 0xFE, 0xD6, 0xC6, 0xF8, 0xCC, 0xCC, 0xCC, 0xCC, 0x78, 0xCC, 0xCC, 0xCC, 0x78, 0xDC,
+		0x66, 0x66, 0x7C, 0x60, 0xF0, 0x76, 0xCC, 0xCC, 0x7C, 0x0C, 0x1E, 0xDC, 0x76, 0x66, 0x60, 0xF0,
+		0x7C, 0xC0, 0x78, 0x0C, 0xF8, 0x20, 0x60, 0xF8, 0x60, 0x60, 0x68, 0x30, 0xCC, 0xCC, 0xCC, 0xCC,
+		0x76, 0xCC, 0xCC, 0xCC, 0x78, 0x30, 0xC6, 0xD6, 0xFE, 0xFE, 0x6C, 0xC6, 0x6C, 0x38, 0x6C, 0xC6,
+		0xCC, 0xCC, 0xCC, 0x7C, 0x0C, 0xF8, 0xFC, 0x98, 0x30, 0x64, 0xFC, 0x1C, 0x30, 0x30, 0xE0, 0x30,
+		0x30, 0x1C, 0xC0, 0xC0, 0xC0, 0x00, 0xC0, 0xC0, 0xC0, 0xE0, 0x30, 0x30, 0x1C, 0x30, 0x30, 0xE0,
+		0x76, 0xDC, 0x70, 0xD8, 0x70, 0xCC, 0x78, 0x30, 0x78, 0xCC, 0x18, 0x00, 0xFE, 0x00, 0x18, 0xFE,
+		0x6C, 0x6C, 0x6C, 0x6C, 0x20, 0x60, 0xFE, 0x60, 0x20, 0x20, 0x70, 0xA8, 0x20, 0x20, 0x20, 0x20,
+		0x08, 0x0C, 0xFE, 0x0C, 0x08, 0x20, 0x20, 0x20, 0x20, 0xA8, 0x70, 0x20, 0x0F, 0x08, 0x08, 0x88,
+		0x48, 0x28, 0x18, 0xFE, 0x82, 0x82, 0x82, 0x82, 0x82, 0xFE,
+	],
+);
@@ -0,0 +1,155 @@
+// Generated by ndless-fonts from "-ndless-tiny-medium-r-normal--6-60-75-75-c-40-iso10646-1".
+pub static TINY_4X6: crate::font::Font = crate::font::Font::new(
+	5,
+	1,
+	104,
+	&[
+		crate::font::Glyph { ch: ' ', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 0 },
+		crate::font::Glyph { ch: '!', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 6 },
+		crate::font::Glyph { ch: '"', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 12 },
+		crate::font::Glyph { ch: '#', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 18 },
+		crate::font::Glyph { ch: '$', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 24 },
+		crate::font::Glyph { ch: '%', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 30 },
+		crate::font::Glyph { ch: '&', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 36 },
+		crate::font::Glyph { ch: '\'', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 42 },
+		crate::font::Glyph { ch: '(', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 48 },
+		crate::font::Glyph { ch: ')', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 54 },
+		crate::font::Glyph { ch: '*', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 60 },
+		crate::font::Glyph { ch: '+', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 66 },
+		crate::font::Glyph { ch: ',', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 72 },
+		crate::font::Glyph { ch: '-', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 78 },
+		crate::font::Glyph { ch: '.', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 84 },
+		crate::font::Glyph { ch: '/', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 90 },
+		crate::font::Glyph { ch: '0', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 96 },
+		crate::font::Glyph { ch: '1', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 102 },
+		crate::font::Glyph { ch: '2', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 108 },
+		crate::font::Glyph { ch: '3', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 114 },
+		crate::font::Glyph { ch: '4', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 120 },
+		crate::font::Glyph { ch: '5', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 126 },
+		crate::font::Glyph { ch: '6', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 132 },
+		crate::font::Glyph { ch: '7', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 138 },
+		crate::font::Glyph { ch: '8', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 144 },
+		crate::font::Glyph { ch: '9', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 150 },
+		crate::font::Glyph { ch: ':', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 156 },
+		crate::font::Glyph { ch: ';', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 162 },
+		crate::font::Glyph { ch: '<', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 168 },
+		crate::font::Glyph { ch: '=', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 174 },
+		crate::font::Glyph { ch: '>', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 180 },
+		crate::font::Glyph { ch: '?', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 186 },
+		crate::font::Glyph { ch: '@', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 192 },
+		crate::font::Glyph { ch: 'A', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 198 },
+		crate::font::Glyph { ch: 'B', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 204 },
+		crate::font::Glyph { ch: 'C', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 210 },
+		crate::font::Glyph { ch: 'D', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 216 },
+		crate::font::Glyph { ch: 'E', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 222 },
+		crate::font::Glyph { ch: 'F', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 228 },
+		crate::font::Glyph { ch: 'G', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 234 },
+		crate::font::Glyph { ch: 'H', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 240 },
+		crate::font::Glyph { ch: 'I', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 246 },
+		crate::font::Glyph { ch: 'J', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 252 },
+		crate::font::Glyph { ch: 'K', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 258 },
+		crate::font::Glyph { ch: 'L', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 264 },
+		crate::font::Glyph { ch: 'M', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 270 },
+		crate::font::Glyph { ch: 'N', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 276 },
+		crate::font::Glyph { ch: 'O', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 282 },
+		crate::font::Glyph { ch: 'P', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 288 },
+		crate::font::Glyph { ch: 'Q', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 294 },
+		crate::font::Glyph { ch: 'R', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 300 },
+		crate::font::Glyph { ch: 'S', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 306 },
+		crate::font::Glyph { ch: 'T', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 312 },
+		crate::font::Glyph { ch: 'U', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 318 },
+		crate::font::Glyph { ch: 'V', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 324 },
+		crate::font::Glyph { ch: 'W', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 330 },
+		crate::font::Glyph { ch: 'X', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 336 },
+		crate::font::Glyph { ch: 'Y', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 342 },
+		crate::font::Glyph { ch: 'Z', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 348 },
+		crate::font::Glyph { ch: '[', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 354 },
+		crate::font::Glyph { ch: '\\', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 360 },
+		crate::font::Glyph { ch: ']', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 366 },
+		crate::font::Glyph { ch: '^', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 372 },
+		crate::font::Glyph { ch: '_', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 378 },
+		crate::font::Glyph { ch: '`', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 384 },
+		crate::font::Glyph { ch: 'a', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 390 },
+		crate::font::Glyph { ch: 'b', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 396 },
+		crate::font::Glyph { ch: 'c', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 402 },
+		crate::font::Glyph { ch: 'd', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 408 },
+		crate::font::Glyph { ch: 'e', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 414 },
+		crate::font::Glyph { ch: 'f', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 420 },
+		crate::font::Glyph { ch: 'g', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 426 },
+		crate::font::Glyph { ch: 'h', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 432 },
+		crate::font::Glyph { ch: 'i', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 438 },
+		crate::font::Glyph { ch: 'j', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 444 },
+		crate::font::Glyph { ch: 'k', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 450 },
+		crate::font::Glyph { ch: 'l', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 456 },
+		crate::font::Glyph { ch: 'm', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 462 },
+		crate::font::Glyph { ch: 'n', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 468 },
+		crate::font::Glyph { ch: 'o', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 474 },
+		crate::font::Glyph { ch: 'p', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 480 },
+		crate::font::Glyph { ch: 'q', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 486 },
+		crate::font::Glyph { ch: 'r', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 492 },
+		crate::font::Glyph { ch: 's', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 498 },
+		crate::font::Glyph { ch: 't', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 504 },
+		crate::font::Glyph { ch: 'u', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 510 },
+		crate::font::Glyph { ch: 'v', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 516 },
+		crate::font::Glyph { ch: 'w', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 522 },
+		crate::font::Glyph { ch: 'x', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 528 },
+		crate::font::Glyph { ch: 'y', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 534 },
+		crate::font::Glyph { ch: 'z', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 540 },
+		crate::font::Glyph { ch: '{', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 546 },
+		crate::font::Glyph { ch: '|', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 552 },
+		crate::font::Glyph { ch: '}', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 558 },
+		crate::font::Glyph { ch: '~', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 564 },
+		crate::font::Glyph { ch: '°', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 570 },
+		crate::font::Glyph { ch: '×', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 576 },
+		crate::font::Glyph { ch: '÷', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 582 },
+		crate::font::Glyph { ch: 'π', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 588 },
+		crate::font::Glyph { ch: '←', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 594 },
+		crate::font::Glyph { ch: '↑', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 600 },
+		crate::font::Glyph { ch: '→', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 606 },
+		crate::font::Glyph { ch: '↓', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 612 },
+		crate::font::Glyph { ch: '√', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 618 },
+		crate::font::Glyph { ch: '�', advance: 4, width: 3, height: 6, left: 0, top: 5, offset: 624 },
+	],
+	&[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x00, 0x40, 0x00, 0xA0, 0xA0, 0x00, 0x00,
+		0x00, 0x00, 0xA0, 0xE0, 0xA0, 0xE0, 0xA0, 0x00, 0x60, 0xC0, 0x40, 0x60, 0xC0, 0x00, 0x80, 0x20,
+		0x40, 0x80, 0x20, 0x00, 0x40, 0xA0, 0x60, 0xA0, 0x60, 0x00, 0x40, 0x40, 0x00, 0x00, 0x00, 0x00,
+		0x20, 0x40, 0x40, 0x40, 0x20, 0x00, 0x80, 0x40, 0x40, 0x40, 0x80, 0x00, 0x00, 0xA0, 0x40, 0xA0,
+		0x00, 0x00, 0x00, 0x40, 0xE0, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x40, 0x80, 0x00, 0x00,
+		0xE0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x20, 0x20, 0x40, 0x80, 0x80, 0x00,
+		0x60, 0xA0, 0xA0, 0xA0, 0xC0, 0x00, 0x40, 0xC0, 0x40, 0x40, 0xE0, 0x00, 0xC0, 0x20, 0x40, 0x80,
+		0xE0, 0x00, 0xC0, 0x20, 0x40, 0x20, 0xC0, 0x00, 0xA0, 0xA0, 0xE0, 0x20, 0x20, 0x00, 0xE0, 0x80,
+		0xC0, 0x20, 0xC0, 0x00, 0x60, 0x80, 0xE0, 0xA0, 0xE0, 0x00, 0xE0, 0x20, 0x40, 0x40, 0x40, 0x00,
+		0xE0, 0xA0, 0xE0, 0xA0, 0xE0, 0x00, 0xE0, 0xA0, 0xE0, 0x20, 0xC0, 0x00, 0x00, 0x40, 0x00, 0x40,
+		0x00, 0x00, 0x00, 0x40, 0x00, 0x40, 0x80, 0x00, 0x20, 0x40, 0x80, 0x40, 0x20, 0x00, 0x00, 0xE0,
+		0x00, 0xE0, 0x00, 0x00, 0x80, 0x40, 0x20, 0x40, 0x80, 0x00, 0xC0, 0x20, 0x40, 0x00, 0x40, 0x00,
+		0x40, 0xA0, 0xE0, 0x80, 0x60, 0x00, 0x40, 0xA0, 0xE0, 0xA0, 0xA0, 0x00, 0xC0, 0xA0, 0xC0, 0xA0,
+		0xC0, 0x00, 0x60, 0x80, 0x80, 0x80, 0x60, 0x00, 0xC0, 0xA0, 0xA0, 0xA0, 0xC0, 0x00, 0xE0, 0x80,
+		0xE0, 0x80, 0xE0, 0x00, 0xE0, 0x80, 0xE0, 0x80, 0x80, 0x00, 0x60, 0x80, 0xA0, 0xA0, 0x60, 0x00,
+		0xA0, 0xA0, 0xE0, 0xA0, 0xA0, 0x00, 0xE0, 0x40, 0x40, 0x40, 0xE0, 0x00, 0x20, 0x20, 0x20, 0xA0,
+		0x40, 0x00, 0xA0, 0xA0, 0xC0, 0xA0, 0xA0, 0x00, 0x80, 0x80, 0x80, 0x80, 0xE0, 0x00, 0xA0, 0xE0,
+		0xE0, 0xA0, 0xA0, 0x00, 0xA0, 0xE0, 0xE0, 0xE0, 0xA0, 0x00, 0x40, 0xA0, 0xA0, 0xA0, 0x40, 0x00,
+		0xC0, 0xA0, 0xC0, 0x80, 0x80, 0x00, 0x40, 0xA0, 0xA0, 0xE0, 0x60, 0x00, 0xC0, 0xA0, 0xC0, 0xA0,
+		0xA0, 0x00, 0x60, 0x80, 0x40, 0x20, 0xC0, 0x00, 0xE0, 0x40, 0x40, 0x40, 0x40, 0x00, 0xA0, 0xA0,
+		0xA0, 0xA0, 0xE0, 0x00, 0xA0, 0xA0, 0xA0, 0x40, 0x40, 0x00, 0xA0, 0xA0, 0xE0, 0xE0, 0xA0, 0x00,
+		0xA0, 0xA0, 0x40, 0xA0, 0xA0, 0x00, 0xA0, 0xA0, 0x40, 0x40, 0x40, 0x00, 0xE0, 0x20, 0x40, 0x80,
+		0xE0, 0x00, 0xC0, 0x80, 0x80, 0x80, 0xC0, 0x00, 0x80, 0x80, 0x40, 0x20, 0x20, 0x00, 0x60, 0x20,
+		0x20, 0x20, 0x60, 0x00, 0x40, 0xA0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0x00,
+		0x80, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x60, 0xA0, 0xE0, 0x00, 0x80, 0xC0, 0xA0, 0xA0,
+		0xC0, 0x00, 0x00, 0x60, 0x80, 0x80, 0x60, 0x00, 0x20, 0x60, 0xA0, 0xA0, 0x60, 0x00, 0x00, 0x40,
+		0xE0, 0x80, 0x60, 0x00, 0x20, 0x40, 0xE0, 0x40, 0x40, 0x00, 0x00, 0x60, 0xA0, 0x60, 0x20, 0xC0,
+		0x80, 0xC0, 0xA0, 0xA0, 0xA0, 0x00, 0x40, 0x00, 0x40, 0x40, 0x40, 0x00, 0x20, 0x00, 0x20, 0x20,
+		0xA0, 0x40, 0x80, 0xA0, 0xC0, 0xC0, 0xA0, 0x00, 0xC0, 0x40, 0x40, 0x40, 0xE0, 0x00, 0x00, 0xE0,
+		0xE0, 0xE0, 0xA0, 0x00, 0x00, 0xC0, 0xA0, 0xA0, 0xA0, 0x00, 0x00, 0x40, 0xA0, 0xA0, 0x40, 0x00,
+		0x00, 0xC0, 0xA0, 0xA0, 0xC0, 0x80, 0x00, 0x60, 0xA0, 0xA0, 0x60, 0x20, 0x00, 0x60, 0x80, 0x80,
+		0x80, 0x00, 0x00, 0x60, 0xC0, 0x20, 0xC0, 0x00, 0x40, 0xE0, 0x40, 0x40, 0x60, 0x00, 0x00, 0xA0,
+		0xA0, 0xA0, 0x60, 0x00, 0x00, 0xA0, 0xA0, 0xA0, 0x40, 0x00, 0x00, 0xA0, 0xA0, 0xE0, 0xE0, 0x00,
+		0x00, 0xA0, 0x40, 0x40, 0xA0, 0x00, 0x00, 0xA0, 0xA0, 0x60, 0x20, 0xC0, 0x00, 0xE0, 0x60, 0xC0,
+		0xE0, 0x00, 0x60, 0x40, 0xC0, 0x40, 0x60, 0x00, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00, 0xC0, 0x40,
+		0x60, 0x40, 0xC0, 0x00, 0x00, 0x60, 0xC0, 0x00, 0x00, 0x00, 0x40, 0xA0, 0x40, 0x00, 0x00, 0x00,
+		0x00, 0xA0, 0x40, 0xA0, 0x00, 0x00, 0x40, 0x00, 0xE0, 0x00, 0x40, 0x00, 0x00, 0xE0, 0xA0, 0xA0,
+		0xA0, 0x00, 0x40, 0x80, 0xE0, 0x80, 0x40, 0x00, 0x40, 0xE0, 0x40, 0x40, 0x40, 0x00, 0x40, 0x20,
+		0xE0, 0x20, 0x40, 0x00, 0x40, 0x40, 0x40, 0xE0, 0x40, 0x00, 0x20, 0x20, 0x20, 0xA0, 0x40, 0x00,
+		0xE0, 0xA0, 0xA0, 0xA0, 0xE0, 0x00,
+	],
+);
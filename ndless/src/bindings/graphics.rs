@@ -0,0 +1,506 @@
+//! # Sprites and tilemaps
+//! Draws sprites and scrolling tilemaps into an RGB565 frame, which can then
+//! be shown with [`lcd::blit`](crate::lcd::blit).
+//!
+//! A [`Canvas`] borrows the frame and clips everything drawn to it. Pictures
+//! are [`Bitmap`]s, which can be [decoded](Bitmap::decode) from PNG, BMP or
+//! QOI images, and are split into frames of the same size by a
+//! [`SpriteSheet`]:
+//!
+//! ```no_run
+//! use ndless::graphics::{Animation, Bitmap, Blend, Canvas, Flip, Sprite, SpriteSheet};
+//! use ndless::hw::screen::{Screen, HEIGHT, WIDTH};
+//! use ndless::lcd;
+//! use ndless::time::Duration;
+//!
+//! let data = ndless::assets::read("hero.png").unwrap();
+//! let sheet = SpriteSheet::new(Bitmap::decode(&data).unwrap(), 16, 16);
+//! // Frames 0 to 3, four times a second
+//! let walk = Animation::new(vec![0, 1, 2, 3], Duration::from_millis(250));
+//! let mut hero = Sprite::new(walk);
+//! hero.flip = Flip::Horizontal;
+//!
+//! let mut frame = vec![0u16; WIDTH * HEIGHT];
+//! lcd::init(Screen::Screen320x240x565);
+//! loop {
+//! 	hero.update(Duration::from_millis(20));
+//! 	hero.x += 1;
+//! 	let mut canvas = Canvas::screen(&mut frame);
+//! 	canvas.fill(0xFFFF);
+//! 	hero.draw(&mut canvas, &sheet);
+//! 	lcd::blit(&frame, Screen::Screen320x240x565);
+//! }
+//! ```
+//!
+//! Sprites can be drawn solid, without the pixels of a
+//! [transparent color](Blend::ColorKey), or [blended](Blend::Alpha) using the
+//! transparency stored in the image. Blending costs the most, so sprites
+//! without partly transparent edges are best drawn with a color key.
+//!
+//! A [`Tilemap`] draws a grid of tiles from a sprite sheet, as seen by a
+//! [`Camera`] that looks at part of the map through a viewport on the
+//! screen. Sprites drawn [through the camera](Sprite::draw_in) scroll with
+//! the map.
+//!
+//! Everything is clipped once per call, and then copied a row at a time
+//! without bounds checks. Solid rows are copied with `memcpy`, and alpha is
+//! blended for all three channels at once.
+
+pub use self::bitmap::Bitmap;
+pub use self::blit::{blend, Blend, Flip};
+pub use self::sprite::{Animation, Sprite, SpriteSheet};
+pub use self::tilemap::{Camera, Tilemap};
+use crate::hw::screen::{HEIGHT, WIDTH};
+
+mod bitmap;
+mod blit;
+mod sprite;
+mod tilemap;
+
+/// Packs 8-bit red, green and blue into an RGB565 color, dropping the low
+/// bits.
+pub const fn rgb565(r: u8, g: u8, b: u8) -> u16 {
+	(r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3
+}
+
+/// A rectangle, which may be partly or completely off the canvas.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+pub struct Rect {
+	pub x: i32,
+	pub y: i32,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Rect {
+	pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+		Rect {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+
+	/// The x coordinate just past the right edge.
+	pub fn right(&self) -> i32 {
+		self.x + self.width as i32
+	}
+
+	/// The y coordinate just past the bottom edge.
+	pub fn bottom(&self) -> i32 {
+		self.y + self.height as i32
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.width == 0 || self.height == 0
+	}
+
+	pub fn contains(&self, x: i32, y: i32) -> bool {
+		self.x <= x && x < self.right() && self.y <= y && y < self.bottom()
+	}
+
+	/// The part of both rectangles that overlaps, which is empty if they
+	/// don't.
+	pub fn intersect(&self, other: Rect) -> Rect {
+		let x = self.x.max(other.x);
+		let y = self.y.max(other.y);
+		let right = self.right().min(other.right());
+		let bottom = self.bottom().min(other.bottom());
+		if right <= x || bottom <= y {
+			return Rect::new(x, y, 0, 0);
+		}
+		Rect::new(x, y, (right - x) as u32, (bottom - y) as u32)
+	}
+
+	/// The rectangle moved by `dx` and `dy`.
+	pub fn offset(&self, dx: i32, dy: i32) -> Rect {
+		Rect::new(self.x + dx, self.y + dy, self.width, self.height)
+	}
+}
+
+/// Draws into a buffer of RGB565 pixels, row by row from the top left.
+///
+/// Drawing is limited to the [clip rectangle](Canvas::set_clip), which is
+/// the whole canvas to start with.
+pub struct Canvas<'a> {
+	pixels: &'a mut [u16],
+	width: u32,
+	height: u32,
+	clip: Rect,
+}
+
+impl<'a> Canvas<'a> {
+	/// # Panics
+	/// If `pixels` is smaller than `width` by `height`.
+	pub fn new(pixels: &'a mut [u16], width: u32, height: u32) -> Canvas<'a> {
+		assert!(
+			pixels.len() >= width as usize * height as usize,
+			"a {}x{} canvas needs {} pixels",
+			width,
+			height,
+			width as usize * height as usize
+		);
+		Canvas {
+			pixels,
+			width,
+			height,
+			clip: Rect::new(0, 0, width, height),
+		}
+	}
+
+	/// A canvas for a whole frame in
+	/// [`Screen320x240x565`](crate::hw::screen::Screen::Screen320x240x565)
+	/// mode.
+	pub fn screen(pixels: &'a mut [u16]) -> Canvas<'a> {
+		Canvas::new(pixels, WIDTH as u32, HEIGHT as u32)
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn bounds(&self) -> Rect {
+		Rect::new(0, 0, self.width, self.height)
+	}
+
+	pub fn pixels(&self) -> &[u16] {
+		self.pixels
+	}
+
+	pub fn clip(&self) -> Rect {
+		self.clip
+	}
+
+	/// Limits drawing to `clip`, returning the previous clip rectangle so it
+	/// can be put back.
+	pub fn set_clip(&mut self, clip: Rect) -> Rect {
+		let clip = clip.intersect(self.bounds());
+		core::mem::replace(&mut self.clip, clip)
+	}
+
+	pub fn pixel(&self, x: i32, y: i32) -> Option<u16> {
+		if self.bounds().contains(x, y) {
+			Some(self.pixels[y as usize * self.width as usize + x as usize])
+		} else {
+			None
+		}
+	}
+
+	pub fn set_pixel(&mut self, x: i32, y: i32, color: u16) {
+		if self.clip.contains(x, y) {
+			self.pixels[y as usize * self.width as usize + x as usize] = color;
+		}
+	}
+
+	/// Fills the clip rectangle with `color`.
+	pub fn fill(&mut self, color: u16) {
+		self.fill_rect(self.clip, color);
+	}
+
+	pub fn fill_rect(&mut self, rect: Rect, color: u16) {
+		let rect = rect.intersect(self.clip);
+		for y in rect.y..rect.bottom() {
+			self.row_mut(y, rect.x, rect.width).fill(color);
+		}
+	}
+
+	/// Draws the part of `bitmap` inside `source` with its top left corner at
+	/// `x` and `y`. Flipping mirrors the part that's drawn, so the corner
+	/// stays in the same place.
+	///
+	/// # Panics
+	/// If `source` isn't inside the bitmap.
+	pub fn blit(
+		&mut self,
+		bitmap: &Bitmap,
+		source: Rect,
+		x: i32,
+		y: i32,
+		blend: Blend,
+		flip: Flip,
+	) {
+		assert!(
+			source.intersect(bitmap.bounds()) == source || source.is_empty(),
+			"{:?} isn't inside the bitmap",
+			source
+		);
+		let target = Rect::new(x, y, source.width, source.height).intersect(self.clip);
+		if target.is_empty() {
+			return;
+		}
+		// How far into `source` the visible part starts, counting from the
+		// side that's drawn first
+		let skip_x = (target.x - x) as u32;
+		let from_x = if flip.horizontal() {
+			source.right() as u32 - skip_x - target.width
+		} else {
+			source.x as u32 + skip_x
+		};
+		for row in 0..target.height {
+			let offset = (target.y - y) as u32 + row;
+			let from_y = if flip.vertical() {
+				source.bottom() as u32 - 1 - offset
+			} else {
+				source.y as u32 + offset
+			};
+			let start = (from_y * bitmap.width() + from_x) as usize;
+			let end = start + target.width as usize;
+			let alpha = bitmap.alpha().map(|alpha| &alpha[start..end]);
+			blit::row(
+				self.row_mut(target.y + row as i32, target.x, target.width),
+				&bitmap.pixels()[start..end],
+				alpha,
+				blend,
+				flip.horizontal(),
+			);
+		}
+	}
+
+	/// Draws the whole of `bitmap` with its top left corner at `x` and `y`.
+	pub fn draw(&mut self, bitmap: &Bitmap, x: i32, y: i32, blend: Blend) {
+		self.blit(bitmap, bitmap.bounds(), x, y, blend, Flip::None);
+	}
+
+	/// `width` pixels from `x` in row `y`, which must be on the canvas.
+	fn row_mut(&mut self, y: i32, x: i32, width: u32) -> &mut [u16] {
+		let start = y as usize * self.width as usize + x as usize;
+		&mut self.pixels[start..start + width as usize]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use alloc::vec;
+	use alloc::vec::Vec;
+	use core::time::Duration;
+	use std::path::PathBuf;
+
+	use super::*;
+
+	const KEY: u16 = rgb565(255, 0, 255);
+	const RED: u16 = rgb565(255, 0, 0);
+	const BLUE: u16 = rgb565(0, 0, 255);
+	const YELLOW: u16 = rgb565(255, 255, 0);
+	const GRAY: u16 = rgb565(128, 128, 128);
+
+	/// Two 8x8 frames of an arrow pointing right, on the key color, so flips
+	/// can be told apart. The second frame is the same arrow in blue.
+	fn arrows() -> SpriteSheet {
+		let mut pixels = vec![KEY; 16 * 8];
+		for frame in 0..2 {
+			let color = if frame == 0 { RED } else { BLUE };
+			for y in 0..8 {
+				// The arrow's point is at the right, a little above the middle
+				let reach = if y < 4 { y + 4 } else { 10 - y };
+				for x in 0..reach.min(8) {
+					pixels[y * 16 + frame * 8 + x] = color;
+				}
+			}
+			// A yellow tail at the top left
+			pixels[frame * 8] = YELLOW;
+		}
+		SpriteSheet::new(Bitmap::new(16, 8, pixels), 8, 8)
+	}
+
+	/// A white disc that fades out towards its edge.
+	fn glow() -> Bitmap {
+		let mut rgba = Vec::new();
+		for y in 0..12 {
+			for x in 0..12 {
+				let distance = ((x * 2 - 11) * (x * 2 - 11) + (y * 2 - 11) * (y * 2 - 11)) as f32;
+				let alpha = (255.0 - distance * 2.2).max(0.0) as u8;
+				rgba.push([255, 255, 255, alpha]);
+			}
+		}
+		Bitmap::from_rgba(12, 12, &rgba)
+	}
+
+	/// Four 4x4 tiles: solid gray, a checkerboard, a red outline and a tile
+	/// with transparent corners.
+	fn tiles() -> SpriteSheet {
+		let mut pixels = vec![GRAY; 16 * 4];
+		for y in 0..4 {
+			for x in 0..4 {
+				let checker = if (x + y) % 2 == 0 { BLUE } else { YELLOW };
+				pixels[y * 16 + 4 + x] = checker;
+				let edge = x == 0 || x == 3 || y == 0 || y == 3;
+				pixels[y * 16 + 8 + x] = if edge { RED } else { 0 };
+				let corner = (x == 0 || x == 3) && (y == 0 || y == 3);
+				pixels[y * 16 + 12 + x] = if corner { KEY } else { BLUE };
+			}
+		}
+		SpriteSheet::new(Bitmap::new(16, 4, pixels), 4, 4)
+	}
+
+	fn to_rgb888(canvas: &Canvas) -> Vec<u8> {
+		let channel = |value: u16, bits: u32| {
+			let max = (1 << bits) - 1;
+			((value as u32 & max) * 255 / max) as u8
+		};
+		canvas
+			.pixels()
+			.iter()
+			.flat_map(|&pixel| {
+				vec![
+					channel(pixel >> 11, 5),
+					channel(pixel >> 5, 6),
+					channel(pixel, 5),
+				]
+			})
+			.collect()
+	}
+
+	/// Compares what's on `canvas` with `testdata/<name>.png`. Set
+	/// `NDLESS_UPDATE_GOLDEN` to write the image instead, after checking that
+	/// the new one looks right.
+	fn check_golden(name: &str, canvas: &Canvas) {
+		let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("src/bindings/graphics/testdata")
+			.join(name)
+			.with_extension("png");
+		let rgb = to_rgb888(canvas);
+		let png = crate::lcd::png::encode(canvas.width(), canvas.height(), &rgb);
+		if std::env::var_os("NDLESS_UPDATE_GOLDEN").is_some() {
+			std::fs::write(&path, png).unwrap();
+			return;
+		}
+		let golden = std::fs::read(&path).unwrap_or_else(|_| panic!("{:?} is missing", path));
+		let golden = crate::image::Decoder::new(&golden[..])
+			.unwrap()
+			.decode_rgba()
+			.unwrap();
+		let matches = golden.len() * 3 == rgb.len()
+			&& golden
+				.iter()
+				.zip(rgb.chunks(3))
+				.all(|(expected, actual)| expected[..3] == *actual);
+		if !matches {
+			let actual = std::env::temp_dir().join(name).with_extension("png");
+			std::fs::write(&actual, png).unwrap();
+			panic!("{} doesn't match {:?}, see {:?}", name, path, actual);
+		}
+	}
+
+	#[test]
+	fn rects() {
+		let rect = Rect::new(-2, 3, 4, 5);
+		assert_eq!((rect.right(), rect.bottom()), (2, 8));
+		assert!(rect.contains(-2, 7) && !rect.contains(2, 3));
+		assert_eq!(
+			rect.intersect(Rect::new(0, 0, 10, 5)),
+			Rect::new(0, 3, 2, 2)
+		);
+		assert!(rect.intersect(Rect::new(2, 0, 1, 10)).is_empty());
+		assert_eq!(rect.offset(1, -1), Rect::new(-1, 2, 4, 5));
+	}
+
+	#[test]
+	fn clipping() {
+		let mut pixels = vec![0; 6 * 4];
+		let mut canvas = Canvas::new(&mut pixels, 6, 4);
+		assert_eq!(
+			canvas.set_clip(Rect::new(1, 1, 10, 2)),
+			Rect::new(0, 0, 6, 4)
+		);
+		assert_eq!(canvas.clip(), Rect::new(1, 1, 5, 2));
+		canvas.fill(1);
+		canvas.set_pixel(0, 0, 2);
+		canvas.set_pixel(5, 2, 2);
+		let sprite = Bitmap::new(2, 2, vec![3; 4]);
+		canvas.draw(&sprite, -1, 2, Blend::Opaque);
+		canvas.draw(&sprite, 100, -100, Blend::Opaque);
+		assert_eq!(canvas.pixel(0, 0), Some(0));
+		assert_eq!(canvas.pixel(6, 0), None);
+		#[rustfmt::skip]
+		assert_eq!(pixels, [
+			0, 0, 0, 0, 0, 0,
+			0, 1, 1, 1, 1, 1,
+			0, 1, 1, 1, 1, 2,
+			0, 0, 0, 0, 0, 0,
+		]);
+	}
+
+	#[test]
+	fn sprites() {
+		let sheet = arrows();
+		let mut pixels = vec![0; 48 * 32];
+		let mut canvas = Canvas::new(&mut pixels, 48, 32);
+		canvas.fill(GRAY);
+		let flips = [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both];
+		for (i, &flip) in flips.iter().enumerate() {
+			let x = 2 + i as i32 * 10;
+			sheet.draw(&mut canvas, 0, x, 2, Blend::Opaque, flip);
+			sheet.draw(&mut canvas, 0, x, 12, Blend::ColorKey(KEY), flip);
+		}
+		// Cut off by each edge of the canvas
+		for &(x, y) in &[(-3, 22), (43, 22), (20, -5), (20, 29)] {
+			sheet.draw(&mut canvas, 1, x, y, Blend::ColorKey(KEY), Flip::None);
+		}
+		// And by a clip rectangle
+		canvas.set_clip(Rect::new(30, 24, 5, 5));
+		sheet.draw(
+			&mut canvas,
+			1,
+			28,
+			22,
+			Blend::ColorKey(KEY),
+			Flip::Horizontal,
+		);
+		check_golden("sprites", &canvas);
+	}
+
+	#[test]
+	fn alpha() {
+		let glow = glow();
+		let mut pixels = vec![0; 40 * 16];
+		let mut canvas = Canvas::new(&mut pixels, 40, 16);
+		for x in 0..40 {
+			let color = if x / 4 % 2 == 0 { RED } else { BLUE };
+			canvas.fill_rect(Rect::new(x, 0, 1, 16), color);
+		}
+		canvas.draw(&glow, 2, 2, Blend::Alpha);
+		canvas.draw(&glow, 16, 2, Blend::Opaque);
+		canvas.draw(&glow, 32, -4, Blend::Alpha);
+		check_golden("alpha", &canvas);
+	}
+
+	#[test]
+	fn tilemap() {
+		let tiles = tiles();
+		#[rustfmt::skip]
+		let map = Tilemap::from_cells(8, 6, vec![
+			0, 0, 0, 0, 0, 0, 0, 0,
+			0, 1, 1, 2, 2, 3, 3, 0,
+			0, 1, 1, 2, 2, 3, 3, 0,
+			0, Tilemap::EMPTY, Tilemap::EMPTY, 2, 2, 1, 1, 0,
+			0, 3, 3, 2, 2, 1, 1, 0,
+			0, 0, 0, 0, 0, 0, 0, 0,
+		]);
+		let sheet = arrows();
+		let mut hero = Sprite::new(Animation::new(vec![0, 1], Duration::from_millis(100)));
+		hero.x = 14;
+		hero.y = 6;
+		hero.blend = Blend::ColorKey(KEY);
+		hero.update(Duration::from_millis(150));
+
+		let mut pixels = vec![0; 32 * 24];
+		let mut canvas = Canvas::new(&mut pixels, 32, 24);
+		canvas.fill(YELLOW);
+		// The map is 32x24, seen through a 20x14 window that's scrolled part
+		// of the way through a tile
+		let mut camera = Camera::new(Rect::new(4, 6, 20, 14));
+		camera.center_on(15, 13);
+		assert_eq!((camera.x, camera.y), (5, 6));
+		map.draw(&mut canvas, &tiles, &camera, Blend::ColorKey(KEY));
+		hero.draw_in(&mut canvas, &sheet, &camera);
+		assert_eq!(canvas.clip(), canvas.bounds());
+		check_golden("tilemap", &canvas);
+	}
+}
@@ -0,0 +1,139 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::Rect;
+use crate::image::{Converter, Decoder, ImageError, PixelFormat, Rgba};
+use crate::io::Read;
+
+/// A picture in RGB565, optionally with the transparency of each pixel.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Bitmap {
+	width: u32,
+	height: u32,
+	pixels: Vec<u16>,
+	/// Empty if every pixel is opaque.
+	alpha: Vec<u8>,
+}
+
+impl Bitmap {
+	/// A bitmap of `pixels`, row by row from the top left, without
+	/// transparency.
+	///
+	/// # Panics
+	/// If there aren't `width` times `height` pixels.
+	pub fn new(width: u32, height: u32, pixels: Vec<u16>) -> Bitmap {
+		assert_eq!(
+			pixels.len(),
+			width as usize * height as usize,
+			"wrong number of pixels"
+		);
+		Bitmap {
+			width,
+			height,
+			pixels,
+			alpha: Vec::new(),
+		}
+	}
+
+	/// Converts 8-bit pixels, keeping their transparency if any aren't
+	/// opaque.
+	///
+	/// # Panics
+	/// If there aren't `width` times `height` pixels.
+	pub fn from_rgba(width: u32, height: u32, rgba: &[Rgba]) -> Bitmap {
+		assert_eq!(
+			rgba.len(),
+			width as usize * height as usize,
+			"wrong number of pixels"
+		);
+		let mut bitmap = Bitmap::new(width, height, vec![0; rgba.len()]);
+		if width == 0 {
+			return bitmap;
+		}
+		let mut converter = Converter::new(PixelFormat::Rgb565, width);
+		let mut opaque = vec![[0; 4]; width as usize];
+		let mut row = vec![0; PixelFormat::Rgb565.stride(width)];
+		for (y, pixels) in rgba.chunks(width as usize).enumerate() {
+			// The alpha is kept separately, rather than blending with black
+			for (opaque, &[r, g, b, _]) in opaque.iter_mut().zip(pixels) {
+				*opaque = [r, g, b, 255];
+			}
+			converter.convert_row(y as u32, &opaque, &mut row);
+			let start = y * width as usize;
+			for (pixel, bytes) in bitmap.pixels[start..].iter_mut().zip(row.chunks(2)) {
+				*pixel = u16::from_le_bytes([bytes[0], bytes[1]]);
+			}
+		}
+		if rgba.iter().any(|pixel| pixel[3] != 255) {
+			bitmap.alpha = rgba.iter().map(|pixel| pixel[3]).collect();
+		}
+		bitmap
+	}
+
+	/// Reads a PNG, BMP or QOI image.
+	pub fn read(reader: impl Read) -> Result<Bitmap, ImageError> {
+		let decoder = Decoder::new(reader)?;
+		let info = decoder.info();
+		let rgba = decoder.decode_rgba()?;
+		Ok(Bitmap::from_rgba(info.width, info.height, &rgba))
+	}
+
+	/// Decodes a PNG, BMP or QOI image from memory.
+	pub fn decode(data: &[u8]) -> Result<Bitmap, ImageError> {
+		Bitmap::read(data)
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn bounds(&self) -> Rect {
+		Rect::new(0, 0, self.width, self.height)
+	}
+
+	/// The pixels, row by row from the top left.
+	pub fn pixels(&self) -> &[u16] {
+		&self.pixels
+	}
+
+	pub fn pixels_mut(&mut self) -> &mut [u16] {
+		&mut self.pixels
+	}
+
+	/// How opaque each pixel is, or `None` if they all are.
+	pub fn alpha(&self) -> Option<&[u8]> {
+		if self.alpha.is_empty() {
+			None
+		} else {
+			Some(&self.alpha)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decoding() {
+		let opaque = [[255, 0, 0, 255], [0, 255, 0, 255]];
+		let bitmap = Bitmap::from_rgba(2, 1, &opaque);
+		assert_eq!(bitmap.pixels(), [0xF800, 0x07E0]);
+		assert_eq!(bitmap.alpha(), None);
+		let rgb: Vec<u8> = opaque
+			.iter()
+			.flat_map(|pixel| pixel[..3].to_vec())
+			.collect();
+		let png = crate::lcd::png::encode(2, 1, &rgb);
+		assert_eq!(Bitmap::decode(&png).unwrap(), bitmap);
+
+		let translucent = Bitmap::from_rgba(2, 1, &[[255, 255, 255, 0], [0, 0, 255, 128]]);
+		assert_eq!(translucent.pixels(), [0xFFFF, 0x001F]);
+		assert_eq!(translucent.alpha(), Some(&[0, 128][..]));
+		assert!(Bitmap::decode(b"not an image").is_err());
+	}
+}
@@ -0,0 +1,211 @@
+/// How a bitmap is combined with what's already on the canvas.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+pub enum Blend {
+	/// Every pixel is copied, which is the fastest.
+	#[default]
+	Opaque,
+	/// Pixels of this color are left out.
+	ColorKey(u16),
+	/// Pixels are mixed with the canvas using the bitmap's
+	/// [alpha](super::Bitmap::alpha). Bitmaps without alpha are drawn as if
+	/// they were opaque.
+	Alpha,
+}
+
+/// Which way a bitmap is mirrored when it's drawn.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+pub enum Flip {
+	#[default]
+	None,
+	/// Left to right.
+	Horizontal,
+	/// Top to bottom.
+	Vertical,
+	/// Both ways, which is the same as turning it half a turn.
+	Both,
+}
+
+impl Flip {
+	pub fn horizontal(self) -> bool {
+		self == Flip::Horizontal || self == Flip::Both
+	}
+
+	pub fn vertical(self) -> bool {
+		self == Flip::Vertical || self == Flip::Both
+	}
+}
+
+/// Mixes RGB565 colors, with `alpha` from `0` for all `background` to `255`
+/// for all `color`.
+///
+/// The channels are spread out across a 32-bit word with room between them,
+/// so they're all mixed with the same two multiplications. Alpha is rounded
+/// to 32 levels.
+#[inline]
+pub fn blend(background: u16, color: u16, alpha: u8) -> u16 {
+	// Green in the top half, red and blue in the bottom
+	const MASK: u32 = 0x07E0_F81F;
+	let spread = |color: u16| (color as u32 | (color as u32) << 16) & MASK;
+	let alpha = (alpha as u32 + 4) >> 3;
+	let mixed = ((spread(background) * (32 - alpha) + spread(color) * alpha) >> 5) & MASK;
+	(mixed | mixed >> 16) as u16
+}
+
+/// Draws one row of a bitmap over `out`, which is the same length. The row
+/// is drawn backwards if `mirror` is set.
+pub(super) fn row(out: &mut [u16], pixels: &[u16], alpha: Option<&[u8]>, how: Blend, mirror: bool) {
+	// Each case gets its own loop, so nothing is decided per pixel
+	match (how, alpha, mirror) {
+		(Blend::Opaque, _, false) | (Blend::Alpha, None, false) => out.copy_from_slice(pixels),
+		(Blend::Opaque, _, true) | (Blend::Alpha, None, true) => {
+			fast::copy(out, pixels.iter().rev())
+		}
+		(Blend::ColorKey(key), _, false) => fast::copy_keyed(out, pixels.iter(), key),
+		(Blend::ColorKey(key), _, true) => fast::copy_keyed(out, pixels.iter().rev(), key),
+		(Blend::Alpha, Some(alpha), false) => copy_blended(out, pixels.iter().zip(alpha)),
+		(Blend::Alpha, Some(alpha), true) => {
+			copy_blended(out, pixels.iter().rev().zip(alpha.iter().rev()))
+		}
+	}
+}
+
+/// The loops that [`row`] uses: the ones that write pairs of pixels on the
+/// calculator, and the plain ones below everywhere else.
+#[cfg(target_arch = "arm")]
+use self::paired as fast;
+#[cfg(not(target_arch = "arm"))]
+mod fast {
+	pub(super) use super::{copy, copy_keyed};
+}
+
+fn copy<'a>(out: &mut [u16], pixels: impl Iterator<Item = &'a u16>) {
+	for (out, &pixel) in out.iter_mut().zip(pixels) {
+		*out = pixel;
+	}
+}
+
+fn copy_keyed<'a>(out: &mut [u16], pixels: impl Iterator<Item = &'a u16>, key: u16) {
+	for (out, &pixel) in out.iter_mut().zip(pixels) {
+		if pixel != key {
+			*out = pixel;
+		}
+	}
+}
+
+fn copy_blended<'a>(out: &mut [u16], pixels: impl Iterator<Item = (&'a u16, &'a u8)>) {
+	for (out, (&pixel, &alpha)) in out.iter_mut().zip(pixels) {
+		// Sprites are mostly completely opaque or transparent
+		match alpha {
+			0 => {}
+			255 => *out = pixel,
+			_ => *out = blend(*out, pixel, alpha),
+		}
+	}
+}
+
+/// Copies that write two pixels at once as a 32-bit word. The ARM926 takes as
+/// long to store a word as a halfword, so this halves the stores for rows
+/// that are mirrored or have a color key. Rows that are copied as they are
+/// already go through `memcpy`.
+///
+/// The plain loops are the reference for these, and they're compared with
+/// each other in the tests on the computer.
+#[cfg(any(target_arch = "arm", test))]
+mod paired {
+	/// Joins two pixels into the word that stores them, with the first one at
+	/// the lower address, as both the calculator and the computer are little
+	/// endian.
+	#[inline(always)]
+	fn pair(first: u16, second: u16) -> u32 {
+		first as u32 | (second as u32) << 16
+	}
+
+	pub(super) fn copy<'a>(out: &mut [u16], mut pixels: impl Iterator<Item = &'a u16>) {
+		// Any two pixels make a valid word
+		let (head, words, tail) = unsafe { out.align_to_mut::<u32>() };
+		// `zip` stops at the end of `head` without taking another pixel
+		super::copy(head, pixels.by_ref());
+		for word in words.iter_mut() {
+			match (pixels.next(), pixels.next()) {
+				(Some(&first), Some(&second)) => *word = pair(first, second),
+				_ => return,
+			}
+		}
+		super::copy(tail, pixels);
+	}
+
+	pub(super) fn copy_keyed<'a>(
+		out: &mut [u16],
+		mut pixels: impl Iterator<Item = &'a u16>,
+		key: u16,
+	) {
+		let (head, words, tail) = unsafe { out.align_to_mut::<u32>() };
+		super::copy_keyed(head, pixels.by_ref(), key);
+		for word in words.iter_mut() {
+			let (first, second) = match (pixels.next(), pixels.next()) {
+				(Some(&first), Some(&second)) => (first, second),
+				_ => return,
+			};
+			match (first != key, second != key) {
+				(true, true) => *word = pair(first, second),
+				(true, false) => *word = *word & 0xFFFF_0000 | first as u32,
+				(false, true) => *word = *word & 0x0000_FFFF | (second as u32) << 16,
+				(false, false) => {}
+			}
+		}
+		super::copy_keyed(tail, pixels, key);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use super::*;
+
+	#[test]
+	fn blending() {
+		assert_eq!(blend(0x0000, 0xFFFF, 255), 0xFFFF);
+		assert_eq!(blend(0x0000, 0xFFFF, 0), 0x0000);
+		assert_eq!(blend(0xF800, 0x001F, 128), 0x780F);
+		assert_eq!(blend(0x0000, 0x07E0, 128), 0x03E0);
+
+		let mut out = [1, 1, 1];
+		row(&mut out, &[2, 3, 4], None, Blend::ColorKey(3), true);
+		assert_eq!(out, [4, 1, 2]);
+		row(
+			&mut out,
+			&[0xFFFF, 0xFFFF, 0xFFFF],
+			Some(&[0, 255, 128]),
+			Blend::Alpha,
+			false,
+		);
+		assert_eq!(out, [4, 0xFFFF, 0x7BF0]);
+		row(&mut out, &[5, 6, 7], Some(&[0, 0, 0]), Blend::Opaque, false);
+		assert_eq!(out, [5, 6, 7]);
+	}
+
+	#[test]
+	fn pairs() {
+		let pixels: Vec<u16> = (1..=9).collect();
+		// Every length, and both alignments of the output
+		for len in 0..=pixels.len() {
+			for start in 0..2 {
+				let pixels = &pixels[..len];
+				let mut plain = [0xAAAA; 12];
+				let mut paired = plain;
+				copy(&mut plain[start..start + len], pixels.iter().rev());
+				paired::copy(&mut paired[start..start + len], pixels.iter().rev());
+				assert_eq!(plain, paired, "copy {} at {}", len, start);
+
+				for &key in &[0, 2, 3, 4] {
+					let mut plain = [0xAAAA; 12];
+					let mut paired = plain;
+					copy_keyed(&mut plain[start..start + len], pixels.iter(), key);
+					paired::copy_keyed(&mut paired[start..start + len], pixels.iter(), key);
+					assert_eq!(plain, paired, "key {} for {} at {}", key, len, start);
+				}
+			}
+		}
+	}
+}
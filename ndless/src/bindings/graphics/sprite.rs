@@ -0,0 +1,276 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use super::{Bitmap, Blend, Camera, Canvas, Flip, Rect};
+
+/// A bitmap split into a grid of frames of the same size, numbered from the
+/// top left, row by row.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct SpriteSheet {
+	bitmap: Bitmap,
+	frame_width: u32,
+	frame_height: u32,
+	columns: u32,
+	len: usize,
+}
+
+impl SpriteSheet {
+	/// Splits `bitmap` into frames. Any space on the right or at the bottom
+	/// that's too small for a whole frame is ignored.
+	///
+	/// # Panics
+	/// If either size is zero.
+	pub fn new(bitmap: Bitmap, frame_width: u32, frame_height: u32) -> SpriteSheet {
+		assert!(frame_width > 0 && frame_height > 0, "frames can't be empty");
+		let columns = bitmap.width() / frame_width;
+		let rows = bitmap.height() / frame_height;
+		SpriteSheet {
+			bitmap,
+			frame_width,
+			frame_height,
+			columns,
+			len: (columns * rows) as usize,
+		}
+	}
+
+	pub fn bitmap(&self) -> &Bitmap {
+		&self.bitmap
+	}
+
+	pub fn frame_width(&self) -> u32 {
+		self.frame_width
+	}
+
+	pub fn frame_height(&self) -> u32 {
+		self.frame_height
+	}
+
+	/// The number of frames.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Where frame `index` is in the bitmap.
+	///
+	/// # Panics
+	/// If there's no such frame.
+	pub fn frame(&self, index: usize) -> Rect {
+		assert!(index < self.len, "there's no frame {}", index);
+		let column = index as u32 % self.columns;
+		let row = index as u32 / self.columns;
+		Rect::new(
+			(column * self.frame_width) as i32,
+			(row * self.frame_height) as i32,
+			self.frame_width,
+			self.frame_height,
+		)
+	}
+
+	/// Draws frame `index` with its top left corner at `x` and `y`.
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		index: usize,
+		x: i32,
+		y: i32,
+		blend: Blend,
+		flip: Flip,
+	) {
+		canvas.blit(&self.bitmap, self.frame(index), x, y, blend, flip);
+	}
+}
+
+/// A sequence of frames from a [`SpriteSheet`], each shown for the same
+/// time.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Animation {
+	frames: Vec<usize>,
+	frame_time: Duration,
+	looping: bool,
+}
+
+impl Animation {
+	/// An animation that repeats forever.
+	///
+	/// # Panics
+	/// If there are no frames, or `frame_time` is zero.
+	pub fn new(frames: Vec<usize>, frame_time: Duration) -> Animation {
+		assert!(!frames.is_empty(), "animations need at least one frame");
+		assert!(
+			frame_time > Duration::from_secs(0),
+			"frames can't take no time"
+		);
+		Animation {
+			frames,
+			frame_time,
+			looping: true,
+		}
+	}
+
+	/// A single frame that never changes.
+	pub fn still(frame: usize) -> Animation {
+		Animation::new(alloc::vec![frame], Duration::from_secs(1))
+	}
+
+	/// Plays the animation once, then stays on the last frame.
+	pub fn once(mut self) -> Animation {
+		self.looping = false;
+		self
+	}
+
+	pub fn frames(&self) -> &[usize] {
+		&self.frames
+	}
+
+	/// How long it takes to play every frame once.
+	pub fn duration(&self) -> Duration {
+		self.frame_time * self.frames.len() as u32
+	}
+
+	/// Whether an animation that's only played [once](Animation::once) is
+	/// over after `elapsed`. Looping animations never finish.
+	pub fn is_finished(&self, elapsed: Duration) -> bool {
+		!self.looping && elapsed >= self.duration()
+	}
+
+	/// The frame of the sprite sheet to show once `elapsed` has passed since
+	/// the animation started.
+	pub fn frame_at(&self, elapsed: Duration) -> usize {
+		let step = (elapsed.as_micros() / self.frame_time.as_micros()) as usize;
+		let step = if self.looping {
+			step % self.frames.len()
+		} else {
+			step.min(self.frames.len() - 1)
+		};
+		self.frames[step]
+	}
+}
+
+/// An animated sprite, which keeps track of how far through its animation it
+/// is.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Sprite {
+	/// Where the top left corner is drawn.
+	pub x: i32,
+	pub y: i32,
+	pub flip: Flip,
+	/// How the sprite is drawn, which is [`Blend::Alpha`] to start with.
+	pub blend: Blend,
+	animation: Animation,
+	elapsed: Duration,
+}
+
+impl Sprite {
+	pub fn new(animation: Animation) -> Sprite {
+		Sprite {
+			x: 0,
+			y: 0,
+			flip: Flip::None,
+			blend: Blend::Alpha,
+			animation,
+			elapsed: Duration::from_secs(0),
+		}
+	}
+
+	pub fn animation(&self) -> &Animation {
+		&self.animation
+	}
+
+	/// Starts playing `animation` from the beginning, unless it's already
+	/// playing.
+	pub fn play(&mut self, animation: Animation) {
+		if self.animation != animation {
+			self.animation = animation;
+			self.restart();
+		}
+	}
+
+	/// Goes back to the first frame.
+	pub fn restart(&mut self) {
+		self.elapsed = Duration::from_secs(0);
+	}
+
+	/// Moves the animation forward by `time`.
+	pub fn update(&mut self, time: Duration) {
+		self.elapsed += time;
+		if self.animation.looping {
+			// Keep the time small, so it doesn't lose precision or overflow
+			let duration = self.animation.duration();
+			while self.elapsed >= duration {
+				self.elapsed -= duration;
+			}
+		}
+	}
+
+	/// The frame of the sprite sheet that's being shown.
+	pub fn frame(&self) -> usize {
+		self.animation.frame_at(self.elapsed)
+	}
+
+	/// Whether an animation that's only played once has finished.
+	pub fn is_finished(&self) -> bool {
+		self.animation.is_finished(self.elapsed)
+	}
+
+	/// Draws the current frame, at a position on the canvas.
+	pub fn draw(&self, canvas: &mut Canvas, sheet: &SpriteSheet) {
+		sheet.draw(canvas, self.frame(), self.x, self.y, self.blend, self.flip);
+	}
+
+	/// Draws the current frame through `camera`, so the sprite's position is
+	/// in the world that the camera looks at.
+	pub fn draw_in(&self, canvas: &mut Canvas, sheet: &SpriteSheet, camera: &Camera) {
+		let previous = canvas.set_clip(camera.viewport.intersect(canvas.clip()));
+		let (x, y) = camera.to_screen(self.x, self.y);
+		sheet.draw(canvas, self.frame(), x, y, self.blend, self.flip);
+		canvas.set_clip(previous);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+	#[test]
+	fn sheets() {
+		let sheet = SpriteSheet::new(Bitmap::new(10, 5, vec![0; 50]), 4, 2);
+		assert_eq!(sheet.len(), 4);
+		assert_eq!(sheet.frame(0), Rect::new(0, 0, 4, 2));
+		assert_eq!(sheet.frame(3), Rect::new(4, 2, 4, 2));
+	}
+
+	#[test]
+	fn animations() {
+		let ms = Duration::from_millis;
+		let walk = Animation::new(vec![4, 5, 6], ms(100));
+		assert_eq!(walk.duration(), ms(300));
+		let frames: Vec<_> = [0, 99, 100, 250, 300, 1000]
+			.iter()
+			.map(|&time| walk.frame_at(ms(time)))
+			.collect();
+		assert_eq!(frames, [4, 4, 5, 6, 4, 5]);
+		let jump = walk.clone().once();
+		assert_eq!(jump.frame_at(ms(1000)), 6);
+		assert!(jump.is_finished(ms(300)) && !walk.is_finished(ms(300)));
+
+		let mut sprite = Sprite::new(walk.clone());
+		sprite.update(ms(150));
+		assert_eq!(sprite.frame(), 5);
+		sprite.update(ms(9_900));
+		assert_eq!(sprite.frame(), 5);
+		// Playing the same animation again doesn't restart it
+		sprite.play(walk);
+		assert_eq!(sprite.frame(), 5);
+		sprite.play(jump);
+		assert_eq!(sprite.frame(), 4);
+		sprite.update(ms(10_000));
+		assert!(sprite.is_finished());
+		assert_eq!(sprite.frame(), 6);
+	}
+}
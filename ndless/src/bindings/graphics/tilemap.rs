@@ -0,0 +1,198 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Blend, Canvas, Flip, Rect, SpriteSheet};
+
+/// Looks at part of a world, such as a [`Tilemap`], through a viewport on
+/// the canvas.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Camera {
+	/// The point in the world that's shown at the top left of the viewport.
+	pub x: i32,
+	pub y: i32,
+	/// Where the world is shown on the canvas. Nothing is drawn outside of
+	/// it.
+	pub viewport: Rect,
+}
+
+impl Camera {
+	/// A camera looking at the top left of the world.
+	pub fn new(viewport: Rect) -> Camera {
+		Camera {
+			x: 0,
+			y: 0,
+			viewport,
+		}
+	}
+
+	/// Moves the camera so that a point in the world is in the middle of the
+	/// viewport.
+	pub fn center_on(&mut self, x: i32, y: i32) {
+		self.x = x - self.viewport.width as i32 / 2;
+		self.y = y - self.viewport.height as i32 / 2;
+	}
+
+	/// Moves the camera as little as possible so that it doesn't show
+	/// anything past the edges of a world `width` by `height` in size. Worlds
+	/// smaller than the viewport are kept at the top left.
+	pub fn keep_inside(&mut self, width: u32, height: u32) {
+		let limit =
+			|position: i32, view: u32, world: u32| position.min(world as i32 - view as i32).max(0);
+		self.x = limit(self.x, self.viewport.width, width);
+		self.y = limit(self.y, self.viewport.height, height);
+	}
+
+	/// Where a point in the world is on the canvas.
+	pub fn to_screen(&self, x: i32, y: i32) -> (i32, i32) {
+		(x - self.x + self.viewport.x, y - self.y + self.viewport.y)
+	}
+
+	/// Where a point on the canvas is in the world.
+	pub fn to_world(&self, x: i32, y: i32) -> (i32, i32) {
+		(x - self.viewport.x + self.x, y - self.viewport.y + self.y)
+	}
+
+	/// The part of the world that can be seen.
+	pub fn visible(&self) -> Rect {
+		Rect::new(self.x, self.y, self.viewport.width, self.viewport.height)
+	}
+}
+
+/// A grid of tiles from a [`SpriteSheet`], where each cell holds the index of
+/// a frame or [`EMPTY`](Tilemap::EMPTY).
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Tilemap {
+	width: u32,
+	height: u32,
+	cells: Vec<u16>,
+}
+
+impl Tilemap {
+	/// A cell with no tile, where whatever's underneath shows through.
+	pub const EMPTY: u16 = u16::MAX;
+
+	/// A map `width` by `height` cells in size, with every cell empty.
+	pub fn new(width: u32, height: u32) -> Tilemap {
+		Tilemap::from_cells(
+			width,
+			height,
+			vec![Tilemap::EMPTY; width as usize * height as usize],
+		)
+	}
+
+	/// A map of `cells`, row by row from the top left.
+	///
+	/// # Panics
+	/// If there aren't `width` times `height` cells.
+	pub fn from_cells(width: u32, height: u32, cells: Vec<u16>) -> Tilemap {
+		assert_eq!(
+			cells.len(),
+			width as usize * height as usize,
+			"wrong number of cells"
+		);
+		Tilemap {
+			width,
+			height,
+			cells,
+		}
+	}
+
+	/// The width in cells.
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// The height in cells.
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn cells(&self) -> &[u16] {
+		&self.cells
+	}
+
+	/// The tile in a cell, or `None` if it's empty or off the map.
+	pub fn get(&self, x: i32, y: i32) -> Option<u16> {
+		if !Rect::new(0, 0, self.width, self.height).contains(x, y) {
+			return None;
+		}
+		match self.cells[y as usize * self.width as usize + x as usize] {
+			Tilemap::EMPTY => None,
+			tile => Some(tile),
+		}
+	}
+
+	/// # Panics
+	/// If the cell is off the map.
+	pub fn set(&mut self, x: u32, y: u32, tile: u16) {
+		assert!(
+			x < self.width && y < self.height,
+			"({}, {}) is off the map",
+			x,
+			y
+		);
+		self.cells[y as usize * self.width as usize + x as usize] = tile;
+	}
+
+	/// The cell under a point in the world, for tiles from `tiles`. The cell
+	/// may be off the map.
+	pub fn cell_at(&self, tiles: &SpriteSheet, x: i32, y: i32) -> (i32, i32) {
+		(
+			x.div_euclid(tiles.frame_width() as i32),
+			y.div_euclid(tiles.frame_height() as i32),
+		)
+	}
+
+	/// Draws the part of the map that `camera` can see, clipped to its
+	/// viewport. Only the tiles that can be seen are drawn.
+	///
+	/// # Panics
+	/// If a cell has a tile that isn't in `tiles`.
+	pub fn draw(&self, canvas: &mut Canvas, tiles: &SpriteSheet, camera: &Camera, blend: Blend) {
+		let previous = canvas.set_clip(camera.viewport.intersect(canvas.clip()));
+		let visible = camera.visible();
+		let (left, top) = self.cell_at(tiles, visible.x, visible.y);
+		let (right, bottom) = self.cell_at(tiles, visible.right() - 1, visible.bottom() - 1);
+		let (tile_width, tile_height) = (tiles.frame_width() as i32, tiles.frame_height() as i32);
+		for y in top.max(0)..=bottom.min(self.height as i32 - 1) {
+			for x in left.max(0)..=right.min(self.width as i32 - 1) {
+				if let Some(tile) = self.get(x, y) {
+					let (screen_x, screen_y) = camera.to_screen(x * tile_width, y * tile_height);
+					tiles.draw(canvas, tile as usize, screen_x, screen_y, blend, Flip::None);
+				}
+			}
+		}
+		canvas.set_clip(previous);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::graphics::Bitmap;
+
+	#[test]
+	fn cameras() {
+		let mut camera = Camera::new(Rect::new(10, 20, 100, 50));
+		camera.center_on(20, 300);
+		assert_eq!((camera.x, camera.y), (-30, 275));
+		assert_eq!(camera.to_screen(20, 300), (60, 45));
+		assert_eq!(camera.to_world(60, 45), (20, 300));
+		camera.keep_inside(400, 310);
+		assert_eq!(camera.visible(), Rect::new(0, 260, 100, 50));
+		camera.keep_inside(40, 40);
+		assert_eq!((camera.x, camera.y), (0, 0));
+	}
+
+	#[test]
+	fn cells() {
+		let tiles = SpriteSheet::new(Bitmap::new(8, 8, vec![0; 64]), 8, 8);
+		let mut map = Tilemap::new(3, 2);
+		map.set(2, 1, 0);
+		assert_eq!(map.get(2, 1), Some(0));
+		assert_eq!(map.get(1, 1), None);
+		assert_eq!(map.get(-1, 0), None);
+		assert_eq!(map.cell_at(&tiles, 17, 8), (2, 1));
+		assert_eq!(map.cell_at(&tiles, -1, 7), (-1, 0));
+	}
+}
@@ -1,7 +1,16 @@
 //! # Tools to interact with the hardware
 //! This module contains functions to gather information about the calculator.
 
-/// Returned by [`hw_type`]
+
+pub mod mmio;
+pub mod model;
+pub mod power;
+pub mod screen;
+
+pub use self::model::{Hardware, Model};
+
+/// Returned by [`hw_type`]. Use [`model`] to find out exactly which
+/// calculator the program is running on.
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 pub enum Type {
 	Nspire,
@@ -10,7 +19,7 @@ pub enum Type {
 }
 
 pub fn hw_type() -> Type {
-	match unsafe { ndless_sys::hwtype() } {
+	match raw_hwtype() {
 		0 => Type::Nspire,
 		1 => Type::NspireCX,
 		future => Type::Future(future),
@@ -18,14 +27,33 @@ pub fn hw_type() -> Type {
 }
 
 pub fn hw_subtype() -> u32 {
-	unsafe { ndless_sys::nl_hwsubtype() }
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::nl_hwsubtype() }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::hardware().subtype
+	}
+}
+
+/// `hwtype()`, or the simulated one.
+pub(crate) fn raw_hwtype() -> u32 {
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::hwtype() }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::hardware().hwtype
+	}
 }
 
 /// since Ndless v3.1. TRUE on classic TI-Nspire.
 ///
 /// This is the preferred way to check CX/CM-specific features.
 pub fn is_classic() -> bool {
-	unsafe { ndless_sys::hwtype() < 1 }
+	raw_hwtype() < 1
 }
 
 /// since Ndless v3.1 r863. TRUE on TI-Nspire CM/CM-C.
@@ -40,50 +68,105 @@ pub fn has_colors() -> bool {
 
 /// TRUE on a TI-Nspire Touchpad or on a TI-Nspire CX.
 pub fn has_touchpad() -> bool {
-	unsafe { ndless_sys::_is_touchpad() > 0 }
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::_is_touchpad() > 0 }
+	}
+	#[cfg(feature = "host")]
+	{
+		model::Keypad::from_raw(crate::sim::hardware().keypad) == model::Keypad::Touchpad
+	}
 }
 
 /// flush the data cache and invalidate the instruction and data caches of the
 /// processor. Should be called before loading code dynamically, after a code
 /// patch or with self-modifying code.
 pub fn clear_cache() {
-	unsafe { ndless_sys::clear_cache() }
-}
-
-pub mod screen {
-	/// Returned by [`lcd_type`]
-	#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
-	pub enum Screen {
-		/// 4bit grayscale. Native on classic calcs.
-		Screen320x240x4,
-		/// 8bit paletted mode.
-		Screen320x240x8,
-		/// RGB444
-		Screen320x240x16,
-		/// RGB565. Native on CX before HW-W
-		Screen320x240x565,
-		/// RGB565. Native on CX HW-W
-		Screen240x320x565,
-		Screen320x240x555,
-		Screen240x320x555,
-		Unknown,
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::clear_cache()
 	}
+}
 
-	pub fn lcd_type() -> Screen {
-		match unsafe { ndless_sys::lcd_type() } {
-			ndless_sys::scr_type_t_SCR_320x240_4 => Screen::Screen320x240x4,
-			ndless_sys::scr_type_t_SCR_320x240_8 => Screen::Screen320x240x8,
-			ndless_sys::scr_type_t_SCR_320x240_16 => Screen::Screen320x240x16,
-			ndless_sys::scr_type_t_SCR_320x240_565 => Screen::Screen320x240x565,
-			ndless_sys::scr_type_t_SCR_240x320_565 => Screen::Screen240x320x565,
-			ndless_sys::scr_type_t_SCR_320x240_555 => Screen::Screen320x240x555,
-			ndless_sys::scr_type_t_SCR_240x320_555 => Screen::Screen240x320x555,
-			_ => Screen::Unknown,
+/// CPU clock speeds for [`set_cpu_speed`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum CpuSpeed {
+	/// 90 MHz
+	Slow,
+	/// 120 MHz
+	Normal,
+	/// 150 MHz
+	Fast,
+	/// A setting that this crate doesn't know about.
+	Other(u32),
+}
+
+impl CpuSpeed {
+	fn raw(self) -> u32 {
+		match self {
+			CpuSpeed::Slow => 0x0014_1002,
+			CpuSpeed::Normal => 0x000A_1002,
+			CpuSpeed::Fast => 0x0000_0002,
+			CpuSpeed::Other(raw) => raw,
 		}
 	}
+
+	fn from_raw(raw: u32) -> CpuSpeed {
+		match raw {
+			0x0014_1002 => CpuSpeed::Slow,
+			0x000A_1002 => CpuSpeed::Normal,
+			0x0000_0002 => CpuSpeed::Fast,
+			raw => CpuSpeed::Other(raw),
+		}
+	}
+}
+
+program_state! {
+	fn original_cpu_speed() -> Option<CpuSpeed> = None;
+}
+
+/// Changes the speed of the CPU, returning the previous speed. The original
+/// speed is restored when the program exits.
+pub fn set_cpu_speed(speed: CpuSpeed) -> CpuSpeed {
+	let previous = CpuSpeed::from_raw(raw_set_cpu_speed(speed.raw()));
+	unsafe {
+		if (*original_cpu_speed()).is_none() {
+			*original_cpu_speed() = Some(previous);
+			crate::process::at_exit(|| {
+				if let Some(original) = (*original_cpu_speed()).take() {
+					raw_set_cpu_speed(original.raw());
+				}
+			});
+		}
+	}
+	previous
 }
 
-/// Go to sleep until an interrupt occurs
+fn raw_set_cpu_speed(raw: u32) -> u32 {
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::set_cpu_speed(raw) }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::set_cpu_speed(raw)
+	}
+}
+
+/// The model of the calculator that the program is running on. See
+/// [`Hardware`] for more details, such as the hardware revision.
+pub fn model() -> Model {
+	Hardware::current().model
+}
+
+/// Go to sleep until an interrupt occurs. With the `host` feature, this
+/// moves the [simulated clock](crate::sim::advance) forward to the next
+/// alarm or scripted key press instead.
 pub fn idle() {
-	unsafe { ndless_sys::idle() }
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::idle()
+	}
+	#[cfg(feature = "host")]
+	crate::sim::idle()
 }
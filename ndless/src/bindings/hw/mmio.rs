@@ -0,0 +1,744 @@
+//! # Memory-mapped registers
+//! Typed access to the calculator's peripherals, such as timers and the LCD
+//! controller. Some peripherals are at different addresses, or work
+//! differently, on the classic TI-Nspire and the CX, so [`IoAddr`] and
+//! [`select_io`] pick the right one like the `IO()` macro in libndls.
+//!
+//! Registers are accessed through a [`Bus`]. On the calculator this is
+//! [`Mmio`], which uses volatile reads and writes, but tests can use their own
+//! bus to check which registers are touched.
+//!
+//! ```no_run
+//! use ndless::hw::mmio;
+//!
+//! let keypad = mmio::keypad();
+//! let row = keypad.row(2);
+//! ```
+//!
+//! See [Hackspire](https://hackspire.org/index.php/Memory-mapped_I/O_ports)
+//! for what the registers do.
+#![allow(clippy::unreadable_literal)]
+
+#[cfg(not(feature = "host"))]
+use core::ptr::{read_volatile, write_volatile};
+
+/// The two families of hardware, which have different peripherals.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Family {
+	/// The original TI-Nspire, Touchpad and CM.
+	Classic,
+	/// The TI-Nspire CX and CX CAS.
+	Cx,
+}
+
+impl Family {
+	/// The family of the calculator that the program is running on.
+	pub fn current() -> Family {
+		if super::is_classic() {
+			Family::Classic
+		} else {
+			Family::Cx
+		}
+	}
+}
+
+/// An address that depends on the [`Family`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct IoAddr {
+	pub classic: usize,
+	pub cx: usize,
+}
+
+impl IoAddr {
+	pub const fn new(classic: usize, cx: usize) -> IoAddr {
+		IoAddr { classic, cx }
+	}
+
+	/// An address that is the same on every calculator.
+	pub const fn both(address: usize) -> IoAddr {
+		IoAddr::new(address, address)
+	}
+
+	pub fn select(self, family: Family) -> usize {
+		match family {
+			Family::Classic => self.classic,
+			Family::Cx => self.cx,
+		}
+	}
+
+	/// The address for the calculator that the program is running on.
+	pub fn get(self) -> usize {
+		self.select(Family::current())
+	}
+}
+
+/// Returns `classic` on a classic TI-Nspire, and `cx` on a CX, like the `IO()`
+/// macro in libndls.
+pub fn select_io(classic: usize, cx: usize) -> usize {
+	IoAddr::new(classic, cx).get()
+}
+
+/// The second timer, which ticks at 32768 Hz and is used by
+/// [`timer`](crate::timer) to count ticks.
+pub const FAST_TIMER: IoAddr = IoAddr::both(0x900C0000);
+/// The third timer, which is used by [`timer`](crate::timer) to wake up from
+/// [`idle`](crate::hw::idle).
+pub const SLEEP_TIMER: IoAddr = IoAddr::both(0x900D0000);
+/// The PL110 or PL111 LCD controller.
+pub const LCD: IoAddr = IoAddr::both(0xC0000000);
+pub const KEYPAD: IoAddr = IoAddr::both(0x900E0000);
+pub const POWER: IoAddr = IoAddr::both(0x900B0000);
+pub const ADC: IoAddr = IoAddr::both(0xC4000000);
+/// Miscellaneous display settings, such as the contrast or backlight.
+pub const DISPLAY: IoAddr = IoAddr::both(0x900F0000);
+
+/// Reads and writes 32-bit registers.
+pub trait Bus {
+	fn read(&self, address: usize) -> u32;
+	fn write(&self, address: usize, value: u32);
+}
+
+impl<B: Bus + ?Sized> Bus for &B {
+	fn read(&self, address: usize) -> u32 {
+		(**self).read(address)
+	}
+
+	fn write(&self, address: usize, value: u32) {
+		(**self).write(address, value)
+	}
+}
+
+/// The calculator's own memory-mapped registers. With the `host` feature,
+/// these are plain memory in the [simulator](crate::sim), which starts out
+/// zeroed.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+pub struct Mmio;
+
+#[cfg(not(feature = "host"))]
+impl Bus for Mmio {
+	fn read(&self, address: usize) -> u32 {
+		unsafe { read_volatile(address as *const u32) }
+	}
+
+	fn write(&self, address: usize, value: u32) {
+		unsafe { write_volatile(address as *mut u32, value) }
+	}
+}
+
+#[cfg(feature = "host")]
+impl Bus for Mmio {
+	fn read(&self, address: usize) -> u32 {
+		crate::sim::read_register(address)
+	}
+
+	fn write(&self, address: usize, value: u32) {
+		crate::sim::write_register(address, value)
+	}
+}
+
+/// A range of bits in a register.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Field {
+	pub shift: u32,
+	pub width: u32,
+}
+
+impl Field {
+	pub const fn new(shift: u32, width: u32) -> Field {
+		Field { shift, width }
+	}
+
+	pub const fn bit(bit: u32) -> Field {
+		Field::new(bit, 1)
+	}
+
+	/// The bits of the field, in place.
+	pub const fn mask(self) -> u32 {
+		(u32::MAX >> (32 - self.width)) << self.shift
+	}
+
+	/// Extracts the field from a register value.
+	pub const fn get(self, register: u32) -> u32 {
+		(register & self.mask()) >> self.shift
+	}
+
+	/// Replaces the field in a register value. Bits of `value` that don't fit
+	/// are ignored.
+	pub const fn set(self, register: u32, value: u32) -> u32 {
+		(register & !self.mask()) | ((value << self.shift) & self.mask())
+	}
+}
+
+/// A 32-bit register.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Register<B> {
+	bus: B,
+	address: usize,
+}
+
+impl<B: Bus> Register<B> {
+	/// # Safety
+	/// With [`Mmio`], `address` must be a register that is safe to read and
+	/// write.
+	pub const unsafe fn new(bus: B, address: usize) -> Register<B> {
+		Register { bus, address }
+	}
+
+	pub fn address(&self) -> usize {
+		self.address
+	}
+
+	pub fn read(&self) -> u32 {
+		self.bus.read(self.address)
+	}
+
+	pub fn write(&self, value: u32) {
+		self.bus.write(self.address, value)
+	}
+
+	/// Reads the register, changes the value, and writes it back.
+	pub fn modify(&self, f: impl FnOnce(u32) -> u32) {
+		self.write(f(self.read()))
+	}
+
+	pub fn get(&self, field: Field) -> u32 {
+		field.get(self.read())
+	}
+
+	/// Changes one field, leaving the rest of the register alone.
+	pub fn set(&self, field: Field, value: u32) {
+		self.modify(|register| field.set(register, value))
+	}
+}
+
+/// Defines a peripheral that is a block of registers at a base address.
+macro_rules! block {
+	($(#[$meta:meta])* $name:ident) => {
+		$(#[$meta])*
+		#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+		pub struct $name<B> {
+			bus: B,
+			base: usize,
+			family: Family,
+		}
+
+		impl<B: Bus + Copy> $name<B> {
+			/// # Safety
+			/// With [`Mmio`], `base` must be the address of this peripheral
+			/// on a calculator of this family.
+			pub const unsafe fn new(bus: B, base: usize, family: Family) -> $name<B> {
+				$name { bus, base, family }
+			}
+
+			pub fn base(&self) -> usize {
+				self.base
+			}
+
+			fn register(&self, offset: usize) -> Register<B> {
+				unsafe { Register::new(self.bus, self.base + offset) }
+			}
+
+			#[allow(dead_code)]
+			fn select(&self, offset: IoAddr) -> Register<B> {
+				self.register(offset.select(self.family))
+			}
+		}
+	};
+}
+
+block! {
+	/// A timer on the classic TI-Nspire, which counts down from a 16-bit
+	/// value.
+	ClassicTimer
+}
+
+impl<B: Bus + Copy> ClassicTimer<B> {
+	pub fn value(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	pub fn divider(&self) -> Register<B> {
+		self.register(0x04)
+	}
+
+	pub fn control(&self) -> Register<B> {
+		self.register(0x08)
+	}
+}
+
+block! {
+	/// An ARM SP804 timer on the CX.
+	Sp804
+}
+
+/// Fields of [`Sp804::control`].
+pub mod sp804_control {
+	use super::Field;
+
+	/// Stops after counting down once, instead of wrapping.
+	pub const ONE_SHOT: Field = Field::bit(0);
+	/// Counts with 32 bits instead of 16.
+	pub const SIZE_32: Field = Field::bit(1);
+	/// Divides the clock by 1, 16 or 256.
+	pub const PRESCALE: Field = Field::new(2, 2);
+	pub const INTERRUPT_ENABLE: Field = Field::bit(5);
+	/// Reloads from [`load`](super::Sp804::load) instead of wrapping around.
+	pub const PERIODIC: Field = Field::bit(6);
+	pub const ENABLE: Field = Field::bit(7);
+}
+
+/// Selects the 32768 Hz clock in [`Sp804::clock_select`].
+pub const SP804_CLOCK_32K: u32 = 0xA;
+
+impl<B: Bus + Copy> Sp804<B> {
+	/// The value that the timer counts down from.
+	pub fn load(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	pub fn value(&self) -> Register<B> {
+		self.register(0x04)
+	}
+
+	pub fn control(&self) -> Register<B> {
+		self.register(0x08)
+	}
+
+	/// Writing anything clears the interrupt.
+	pub fn interrupt_clear(&self) -> Register<B> {
+		self.register(0x0C)
+	}
+
+	pub fn raw_interrupt(&self) -> Register<B> {
+		self.register(0x10)
+	}
+
+	pub fn masked_interrupt(&self) -> Register<B> {
+		self.register(0x14)
+	}
+
+	/// Like [`load`](Sp804::load), but only used after the next reload.
+	pub fn background_load(&self) -> Register<B> {
+		self.register(0x18)
+	}
+
+	/// The clock that the timer counts, which is added by TI.
+	pub fn clock_select(&self) -> Register<B> {
+		self.register(0x80)
+	}
+}
+
+/// A timer, which is different on each [`Family`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Timer<B> {
+	Classic(ClassicTimer<B>),
+	Cx(Sp804<B>),
+}
+
+impl<B: Bus + Copy> Timer<B> {
+	/// # Safety
+	/// With [`Mmio`], `base` must be the address of a timer on a calculator
+	/// of this family.
+	pub unsafe fn new(bus: B, base: usize, family: Family) -> Timer<B> {
+		match family {
+			Family::Classic => Timer::Classic(ClassicTimer::new(bus, base, family)),
+			Family::Cx => Timer::Cx(Sp804::new(bus, base, family)),
+		}
+	}
+}
+
+block! {
+	/// The PL110 LCD controller on the classic TI-Nspire, or the PL111 on the
+	/// CX. The control and interrupt mask registers are swapped between them.
+	Lcd
+}
+
+/// Fields of [`Lcd::control`].
+pub mod lcd_control {
+	use super::Field;
+
+	pub const ENABLE: Field = Field::bit(0);
+	/// `0` to `7` for 1, 2, 4, 8, 16, 24 bits per pixel, 565 and 444.
+	pub const BITS_PER_PIXEL: Field = Field::new(1, 3);
+	pub const BGR: Field = Field::bit(8);
+	pub const POWER: Field = Field::bit(11);
+}
+
+impl<B: Bus + Copy> Lcd<B> {
+	pub fn timing(&self, index: usize) -> Register<B> {
+		assert!(index < 4, "there are 4 timing registers");
+		self.register(index * 4)
+	}
+
+	/// The address of the frame buffer.
+	pub fn upper_base(&self) -> Register<B> {
+		self.register(0x10)
+	}
+
+	pub fn lower_base(&self) -> Register<B> {
+		self.register(0x14)
+	}
+
+	pub fn control(&self) -> Register<B> {
+		self.select(IoAddr::new(0x1C, 0x18))
+	}
+
+	pub fn interrupt_mask(&self) -> Register<B> {
+		self.select(IoAddr::new(0x18, 0x1C))
+	}
+
+	pub fn raw_interrupt(&self) -> Register<B> {
+		self.register(0x20)
+	}
+
+	pub fn masked_interrupt(&self) -> Register<B> {
+		self.register(0x24)
+	}
+
+	pub fn interrupt_clear(&self) -> Register<B> {
+		self.register(0x28)
+	}
+
+	/// Two 16-bit palette entries, for paletted modes.
+	pub fn palette(&self, index: usize) -> Register<B> {
+		assert!(index < 128, "there are 128 palette registers");
+		self.register(0x200 + index * 4)
+	}
+}
+
+block! {
+	/// The keypad scanner. Each row is 16 bits, with a bit set for each key
+	/// that is pressed on the CX, or clear on the classic.
+	Keypad
+}
+
+/// The number of rows returned by [`Keypad::row`].
+pub const KEYPAD_ROWS: usize = 8;
+
+impl<B: Bus + Copy> Keypad<B> {
+	pub fn control(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	pub fn size(&self) -> Register<B> {
+		self.register(0x04)
+	}
+
+	pub fn interrupt_status(&self) -> Register<B> {
+		self.register(0x08)
+	}
+
+	pub fn interrupt_enable(&self) -> Register<B> {
+		self.register(0x0C)
+	}
+
+	/// The register that holds rows `2 * index` and `2 * index + 1`.
+	pub fn data(&self, index: usize) -> Register<B> {
+		assert!(index < KEYPAD_ROWS / 2, "there are 4 data registers");
+		self.register(0x10 + index * 4)
+	}
+
+	/// Returns the bits of one row, which is half of a data register.
+	pub fn row(&self, row: usize) -> u16 {
+		let data = self.data(row / 2).read();
+		(data >> (16 * (row % 2))) as u16
+	}
+}
+
+block! {
+	/// Power management, including clocks and turning peripherals off.
+	Power
+}
+
+impl<B: Bus + Copy> Power<B> {
+	/// The CPU and bus clock dividers, as set by
+	/// [`set_cpu_speed`](crate::hw::set_cpu_speed).
+	pub fn clocks(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	/// Writing `4` applies a change to [`clocks`](Power::clocks).
+	pub fn clock_change(&self) -> Register<B> {
+		self.register(0x0C)
+	}
+
+	pub fn wake_reason(&self) -> Register<B> {
+		self.register(0x14)
+	}
+
+	/// A bit for each peripheral that is turned off.
+	pub fn disabled(&self) -> Register<B> {
+		self.register(0x18)
+	}
+
+	/// More peripherals, which only the CX has.
+	pub fn disabled_cx(&self) -> Option<Register<B>> {
+		match self.family {
+			Family::Classic => None,
+			Family::Cx => Some(self.register(0x20)),
+		}
+	}
+}
+
+block! {
+	/// The analog to digital converter, which measures the batteries among
+	/// other things.
+	Adc
+}
+
+/// The number of channels returned by [`Adc::channel`].
+pub const ADC_CHANNELS: usize = 7;
+
+impl<B: Bus + Copy> Adc<B> {
+	pub fn interrupt_status(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	pub fn interrupt_mask(&self) -> Register<B> {
+		self.register(0x04)
+	}
+
+	pub fn interrupt_ack(&self) -> Register<B> {
+		self.register(0x08)
+	}
+
+	pub fn channel(&self, channel: usize) -> AdcChannel<B> {
+		assert!(channel < ADC_CHANNELS, "there are 7 ADC channels");
+		AdcChannel {
+			base: self.register(0x100 + channel * 0x20),
+		}
+	}
+}
+
+/// One input of the [`Adc`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct AdcChannel<B> {
+	base: Register<B>,
+}
+
+impl<B: Bus + Copy> AdcChannel<B> {
+	fn register(&self, offset: usize) -> Register<B> {
+		unsafe { Register::new(self.base.bus, self.base.address + offset) }
+	}
+
+	/// Writing `1` starts a measurement.
+	pub fn command(&self) -> Register<B> {
+		self.register(0x00)
+	}
+
+	pub fn status(&self) -> Register<B> {
+		self.register(0x04)
+	}
+
+	/// The result of the last measurement, out of `0x3FF`.
+	pub fn value(&self) -> Register<B> {
+		self.register(0x10)
+	}
+}
+
+block! {
+	/// Display settings that aren't part of the LCD controller.
+	Display
+}
+
+impl<B: Bus + Copy> Display<B> {
+	/// The contrast on a classic TI-Nspire, or the backlight brightness on a
+	/// CX. See [`screen`](crate::hw::screen) for the range of values.
+	pub fn level(&self) -> Register<B> {
+		self.register(0x20)
+	}
+}
+
+fn peripheral<T>(address: IoAddr, new: unsafe fn(Mmio, usize, Family) -> T) -> T {
+	let family = Family::current();
+	unsafe { new(Mmio, address.select(family), family) }
+}
+
+/// The timer at [`FAST_TIMER`].
+pub fn fast_timer() -> Timer<Mmio> {
+	peripheral(FAST_TIMER, Timer::new)
+}
+
+/// The timer at [`SLEEP_TIMER`].
+pub fn sleep_timer() -> Timer<Mmio> {
+	peripheral(SLEEP_TIMER, Timer::new)
+}
+
+pub fn lcd() -> Lcd<Mmio> {
+	peripheral(LCD, Lcd::new)
+}
+
+pub fn keypad() -> Keypad<Mmio> {
+	peripheral(KEYPAD, Keypad::new)
+}
+
+pub fn power() -> Power<Mmio> {
+	peripheral(POWER, Power::new)
+}
+
+pub fn adc() -> Adc<Mmio> {
+	peripheral(ADC, Adc::new)
+}
+
+pub fn display() -> Display<Mmio> {
+	peripheral(DISPLAY, Display::new)
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use core::cell::RefCell;
+	use std::collections::BTreeMap;
+	use std::vec::Vec;
+
+	use super::*;
+
+	/// Memory that records every access.
+	#[derive(Default)]
+	struct MockBus {
+		memory: RefCell<BTreeMap<usize, u32>>,
+		log: RefCell<Vec<(char, usize, u32)>>,
+	}
+
+	impl MockBus {
+		fn take_log(&self) -> Vec<(char, usize, u32)> {
+			self.log.replace(Vec::new())
+		}
+	}
+
+	impl Bus for MockBus {
+		fn read(&self, address: usize) -> u32 {
+			let value = *self.memory.borrow().get(&address).unwrap_or(&0);
+			self.log.borrow_mut().push(('r', address, value));
+			value
+		}
+
+		fn write(&self, address: usize, value: u32) {
+			self.memory.borrow_mut().insert(address, value);
+			self.log.borrow_mut().push(('w', address, value));
+		}
+	}
+
+	#[test]
+	fn fields() {
+		let field = Field::new(4, 3);
+		assert_eq!(field.mask(), 0b111_0000);
+		assert_eq!(field.get(0b1101_1111), 0b101);
+		assert_eq!(field.set(0xFFFF_FFFF, 0), 0xFFFF_FF8F);
+		assert_eq!(field.set(0, 0b1111), 0b111_0000);
+		assert_eq!(Field::new(0, 32).mask(), u32::MAX);
+		assert_eq!(Field::bit(31).mask(), 1 << 31);
+	}
+
+	#[test]
+	fn registers() {
+		let bus = MockBus::default();
+		let register = unsafe { Register::new(&bus, 0x1000) };
+		register.write(0b1010);
+		register.set(Field::new(0, 2), 0b11);
+		assert_eq!(register.read(), 0b1011);
+		assert_eq!(register.get(Field::bit(3)), 1);
+		assert_eq!(
+			bus.take_log(),
+			[
+				('w', 0x1000, 0b1010),
+				('r', 0x1000, 0b1010),
+				('w', 0x1000, 0b1011),
+				('r', 0x1000, 0b1011),
+				('r', 0x1000, 0b1011),
+			]
+		);
+	}
+
+	#[test]
+	fn addresses() {
+		let io = IoAddr::new(0xDC00000C, 0xDC000010);
+		assert_eq!(io.select(Family::Classic), 0xDC00000C);
+		assert_eq!(io.select(Family::Cx), 0xDC000010);
+		assert_eq!(FAST_TIMER.select(Family::Cx), 0x900C0000);
+	}
+
+	fn addresses_of<B: Bus + Copy>(registers: &[Register<B>]) -> Vec<usize> {
+		registers.iter().map(Register::address).collect()
+	}
+
+	#[test]
+	fn timers() {
+		let bus = MockBus::default();
+		let timer = unsafe { Timer::new(&bus, 0x900C0000, Family::Cx) };
+		let timer = match timer {
+			Timer::Cx(timer) => timer,
+			Timer::Classic(_) => panic!("CX timers are SP804s"),
+		};
+		assert_eq!(
+			addresses_of(&[
+				timer.load(),
+				timer.value(),
+				timer.control(),
+				timer.interrupt_clear(),
+				timer.background_load(),
+				timer.clock_select(),
+			]),
+			[0x900C0000, 0x900C0004, 0x900C0008, 0x900C000C, 0x900C0018, 0x900C0080]
+		);
+		let mut control = 0;
+		control = sp804_control::ENABLE.set(control, 1);
+		control = sp804_control::SIZE_32.set(control, 1);
+		assert_eq!(control, 0b1000_0010);
+
+		let timer = unsafe { Timer::new(&bus, 0x900D0000, Family::Classic) };
+		let timer = match timer {
+			Timer::Classic(timer) => timer,
+			Timer::Cx(_) => panic!("classic timers aren't SP804s"),
+		};
+		assert_eq!(
+			addresses_of(&[timer.value(), timer.divider(), timer.control()]),
+			[0x900D0000, 0x900D0004, 0x900D0008]
+		);
+	}
+
+	#[test]
+	fn lcd() {
+		let bus = MockBus::default();
+		let classic = unsafe { Lcd::new(&bus, 0xC0000000, Family::Classic) };
+		let cx = unsafe { Lcd::new(&bus, 0xC0000000, Family::Cx) };
+		assert_eq!(classic.control().address(), 0xC000001C);
+		assert_eq!(classic.interrupt_mask().address(), 0xC0000018);
+		assert_eq!(cx.control().address(), 0xC0000018);
+		assert_eq!(cx.interrupt_mask().address(), 0xC000001C);
+		assert_eq!(cx.upper_base().address(), 0xC0000010);
+		assert_eq!(cx.timing(3).address(), 0xC000000C);
+		assert_eq!(cx.palette(127).address(), 0xC00003FC);
+
+		cx.control().write(0x0000_092D);
+		assert_eq!(cx.control().get(lcd_control::BITS_PER_PIXEL), 6);
+		assert_eq!(cx.control().get(lcd_control::POWER), 1);
+	}
+
+	#[test]
+	fn keypad() {
+		let bus = MockBus::default();
+		let keypad = unsafe { Keypad::new(&bus, 0x900E0000, Family::Cx) };
+		bus.write(0x900E0014, 0xBEEF_0010);
+		assert_eq!(keypad.row(2), 0x0010);
+		assert_eq!(keypad.row(3), 0xBEEF);
+		assert_eq!(keypad.row(0), 0);
+		assert_eq!(keypad.data(3).address(), 0x900E001C);
+	}
+
+	#[test]
+	fn power_and_adc() {
+		let bus = MockBus::default();
+		let classic = unsafe { Power::new(&bus, 0x900B0000, Family::Classic) };
+		let cx = unsafe { Power::new(&bus, 0x900B0000, Family::Cx) };
+		assert_eq!(classic.disabled().address(), 0x900B0018);
+		assert!(classic.disabled_cx().is_none());
+		assert_eq!(cx.disabled_cx().unwrap().address(), 0x900B0020);
+
+		let adc = unsafe { Adc::new(&bus, 0xC4000000, Family::Cx) };
+		let channel = adc.channel(3);
+		assert_eq!(channel.command().address(), 0xC4000160);
+		assert_eq!(channel.value().address(), 0xC4000170);
+	}
+}
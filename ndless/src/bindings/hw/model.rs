@@ -0,0 +1,340 @@
+//! # Calculator models
+//! Works out which calculator the program is running on from the IDs that
+//! Ndless and the OS report, and what it can do.
+//!
+//! ```no_run
+//! use ndless::hw::model::Hardware;
+//!
+//! let hardware = Hardware::current();
+//! if hardware.model.has_color() {
+//!     // Load the color sprites
+//! }
+//! ndless::println!("Running on {}", hardware);
+//! ```
+//!
+//! The CAS and non-CAS versions of each calculator have the same hardware, and
+//! Ndless doesn't say which OS is installed, so [`Hardware::current`] always
+//! reports the non-CAS model. Use [`Model::with_cas`] if the program knows
+//! better.
+
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use super::mmio::Family;
+use super::screen::Screen;
+
+/// The product that the program is running on.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Model {
+	/// The original TI-Nspire, with a Clickpad or TI-84 Plus keypad.
+	Clickpad,
+	/// The grayscale TI-Nspire with a Touchpad keypad.
+	Touchpad,
+	/// The TI-Nspire CM, a version of the CX sold in China.
+	Cm,
+	Cx,
+	CxCas,
+	CxII,
+	CxIICas,
+	/// A calculator that this crate doesn't know about.
+	Unknown,
+}
+
+impl Model {
+	/// The marketing name, like `TI-Nspire CX CAS`.
+	pub fn name(self) -> &'static str {
+		match self {
+			Model::Clickpad => "TI-Nspire",
+			Model::Touchpad => "TI-Nspire Touchpad",
+			Model::Cm => "TI-Nspire CM",
+			Model::Cx => "TI-Nspire CX",
+			Model::CxCas => "TI-Nspire CX CAS",
+			Model::CxII => "TI-Nspire CX II",
+			Model::CxIICas => "TI-Nspire CX II CAS",
+			Model::Unknown => "unknown TI-Nspire",
+		}
+	}
+
+	/// Returns the CAS or non-CAS version of this model. Models that don't
+	/// have a separate CAS version are returned unchanged.
+	pub fn with_cas(self, cas: bool) -> Model {
+		match (self, cas) {
+			(Model::Cx, true) | (Model::CxCas, true) => Model::CxCas,
+			(Model::Cx, false) | (Model::CxCas, false) => Model::Cx,
+			(Model::CxII, true) | (Model::CxIICas, true) => Model::CxIICas,
+			(Model::CxII, false) | (Model::CxIICas, false) => Model::CxII,
+			(model, _) => model,
+		}
+	}
+
+	pub fn is_cas(self) -> bool {
+		self == Model::CxCas || self == Model::CxIICas
+	}
+
+	/// Whether the peripherals are those of the classic or the CX
+	/// [`Family`].
+	pub fn family(self) -> Family {
+		match self {
+			Model::Clickpad | Model::Touchpad => Family::Classic,
+			_ => Family::Cx,
+		}
+	}
+
+	pub fn is_cx_ii(self) -> bool {
+		self == Model::CxII || self == Model::CxIICas
+	}
+
+	pub fn has_color(self) -> bool {
+		self.family() == Family::Cx
+	}
+
+	/// Whether the battery can be recharged. Classic models take AAA batteries,
+	/// although some can also be fitted with a rechargeable pack.
+	pub fn has_rechargeable_battery(self) -> bool {
+		self.family() == Family::Cx
+	}
+
+	/// The size of the SDRAM, in bytes.
+	pub fn ram_bytes(self) -> usize {
+		match self.family() {
+			Family::Classic => 32 * 1024 * 1024,
+			Family::Cx => 64 * 1024 * 1024,
+		}
+	}
+
+	/// The speeds that the CPU can run at, in MHz. Only the older models can
+	/// be changed with [`set_cpu_speed`](super::set_cpu_speed).
+	pub fn cpu_mhz(self) -> RangeInclusive<u32> {
+		if self.is_cx_ii() {
+			396..=396
+		} else {
+			90..=150
+		}
+	}
+}
+
+impl fmt::Display for Model {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Hardware revisions that behave differently from earlier ones.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Revision {
+	Original,
+	/// A CX with the LCD panel mounted sideways, which has to be drawn to in
+	/// columns instead of rows.
+	HwW,
+}
+
+/// Which way round the LCD panel scans.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Orientation {
+	/// 320 pixels wide and 240 tall, like the screen is held.
+	Landscape,
+	/// 240 pixels wide and 320 tall, so the picture has to be rotated.
+	Portrait,
+}
+
+/// The keypad, as reported by `keypad_type`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Keypad {
+	Clickpad,
+	/// The keypad of the TI-84 Plus, which can be fitted to a Clickpad.
+	Ti84,
+	/// The keypad with a touchpad, including those of the CX and CX II.
+	Touchpad,
+	Unknown(u8),
+}
+
+impl Keypad {
+	pub fn from_raw(raw: u8) -> Keypad {
+		match raw {
+			1 => Keypad::Clickpad,
+			2 => Keypad::Ti84,
+			3 | 4 => Keypad::Touchpad,
+			raw => Keypad::Unknown(raw),
+		}
+	}
+}
+
+/// The raw values that a model is worked out from.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct HardwareIds {
+	/// `hwtype()`: `0` for classic, `1` for CX, `2` for CX II.
+	pub hwtype: u32,
+	/// `nl_hwsubtype()`: `1` for the CM.
+	pub subtype: u32,
+	pub screen: Screen,
+	/// `keypad_type()`.
+	pub keypad: u8,
+}
+
+impl HardwareIds {
+	/// The IDs of the calculator that the program is running on.
+	pub fn current() -> HardwareIds {
+		#[cfg(not(feature = "host"))]
+		{
+			HardwareIds {
+				hwtype: unsafe { ndless_sys::hwtype() },
+				subtype: super::hw_subtype(),
+				screen: super::screen::lcd_type(),
+				keypad: unsafe { *ndless_sys::keypad_type() },
+			}
+		}
+		#[cfg(feature = "host")]
+		{
+			crate::sim::hardware()
+		}
+	}
+}
+
+/// Everything known about the calculator.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Hardware {
+	pub model: Model,
+	pub revision: Revision,
+	pub keypad: Keypad,
+	pub ids: HardwareIds,
+}
+
+impl Hardware {
+	/// Identifies the calculator that the program is running on.
+	pub fn current() -> Hardware {
+		Hardware::identify(HardwareIds::current())
+	}
+
+	/// Works out the model from its IDs.
+	pub fn identify(ids: HardwareIds) -> Hardware {
+		let keypad = Keypad::from_raw(ids.keypad);
+		let model = match (ids.hwtype, ids.subtype) {
+			(0, _) if keypad == Keypad::Touchpad => Model::Touchpad,
+			(0, _) => Model::Clickpad,
+			(1, 1) => Model::Cm,
+			(1, _) => Model::Cx,
+			(2, _) => Model::CxII,
+			_ => Model::Unknown,
+		};
+		let revision = match (model, orientation(ids.screen)) {
+			(Model::Cx, Orientation::Portrait) | (Model::Cm, Orientation::Portrait) => {
+				Revision::HwW
+			}
+			_ => Revision::Original,
+		};
+		Hardware {
+			model,
+			revision,
+			keypad,
+			ids,
+		}
+	}
+
+	pub fn screen(&self) -> Screen {
+		self.ids.screen
+	}
+
+	pub fn orientation(&self) -> Orientation {
+		orientation(self.ids.screen)
+	}
+
+	pub fn has_touchpad(&self) -> bool {
+		self.keypad == Keypad::Touchpad
+	}
+}
+
+fn orientation(screen: Screen) -> Orientation {
+	match screen {
+		Screen::Screen240x320x565 | Screen::Screen240x320x555 => Orientation::Portrait,
+		_ => Orientation::Landscape,
+	}
+}
+
+impl fmt::Display for Hardware {
+	/// Shows the model and the raw IDs, for support requests.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.model)?;
+		if self.revision == Revision::HwW {
+			f.write_str(" HW-W")?;
+		}
+		write!(
+			f,
+			" (hwtype {}, subtype {}, {:?}, keypad {})",
+			self.ids.hwtype, self.ids.subtype, self.ids.screen, self.ids.keypad
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use super::*;
+
+	fn identify(hwtype: u32, subtype: u32, screen: Screen, keypad: u8) -> Hardware {
+		Hardware::identify(HardwareIds {
+			hwtype,
+			subtype,
+			screen,
+			keypad,
+		})
+	}
+
+	#[test]
+	fn models() {
+		let clickpad = identify(0, 0, Screen::Screen320x240x4, 1);
+		assert_eq!(clickpad.model, Model::Clickpad);
+		assert!(!clickpad.has_touchpad());
+		assert!(!clickpad.model.has_color());
+		assert_eq!(clickpad.model.family(), Family::Classic);
+		assert_eq!(clickpad.model.ram_bytes(), 32 * 1024 * 1024);
+		assert_eq!(
+			identify(0, 0, Screen::Screen320x240x4, 2).keypad,
+			Keypad::Ti84
+		);
+
+		let touchpad = identify(0, 0, Screen::Screen320x240x4, 3);
+		assert_eq!(touchpad.model, Model::Touchpad);
+		assert!(touchpad.has_touchpad());
+
+		let cx = identify(1, 0, Screen::Screen320x240x565, 3);
+		assert_eq!(cx.model, Model::Cx);
+		assert_eq!(cx.revision, Revision::Original);
+		assert_eq!(cx.orientation(), Orientation::Landscape);
+		assert!(cx.model.has_color());
+		assert_eq!(cx.model.cpu_mhz(), 90..=150);
+
+		let hw_w = identify(1, 0, Screen::Screen240x320x565, 3);
+		assert_eq!(hw_w.revision, Revision::HwW);
+		assert_eq!(hw_w.orientation(), Orientation::Portrait);
+		assert_eq!(
+			std::format!("{}", hw_w),
+			"TI-Nspire CX HW-W (hwtype 1, subtype 0, Screen240x320x565, keypad 3)"
+		);
+
+		assert_eq!(
+			identify(1, 1, Screen::Screen320x240x565, 3).model,
+			Model::Cm
+		);
+		let cx_ii = identify(2, 0, Screen::Screen320x240x565, 4);
+		assert_eq!(cx_ii.model, Model::CxII);
+		assert!(cx_ii.has_touchpad());
+		assert_eq!(cx_ii.model.cpu_mhz(), 396..=396);
+		assert_eq!(identify(7, 0, Screen::Unknown, 9).model, Model::Unknown);
+		assert_eq!(
+			identify(7, 0, Screen::Unknown, 9).keypad,
+			Keypad::Unknown(9)
+		);
+	}
+
+	#[test]
+	fn cas() {
+		assert_eq!(Model::Cx.with_cas(true), Model::CxCas);
+		assert_eq!(Model::CxIICas.with_cas(false), Model::CxII);
+		assert_eq!(Model::CxII.with_cas(true).name(), "TI-Nspire CX II CAS");
+		assert!(Model::CxIICas.is_cas());
+		assert!(Model::CxIICas.is_cx_ii());
+		assert_eq!(Model::Touchpad.with_cas(true), Model::Touchpad);
+		assert!(!Model::Cx.is_cas());
+	}
+}
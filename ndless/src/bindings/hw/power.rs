@@ -0,0 +1,336 @@
+//! # Battery and charging
+//! Reads the battery voltage from the [ADC](super::mmio::Adc) and turns it
+//! into a percentage using a [`Calibration`] for the calculator's batteries.
+//!
+//! ```no_run
+//! use ndless::hw::power;
+//!
+//! let battery = power::battery_status();
+//! if battery.is_low(power::LOW_BATTERY_PERCENT) {
+//!     ndless::println!("Battery low: {}", battery);
+//! }
+//! ```
+//!
+//! The calibration tables are approximate: they are based on the typical
+//! discharge curves of the batteries, not on measurements of each calculator,
+//! and the voltage drops a little while the CPU is busy. Treat the percentage
+//! as a rough guide, like the OS's own battery icon.
+//!
+//! To be notified when the battery gets low while running an `async` program,
+//! see `ndless_async::power`.
+#![allow(clippy::unreadable_literal)]
+
+use core::fmt;
+
+use super::mmio::{self, Bus, Family};
+
+/// The largest value that the ADC returns.
+pub const ADC_MAX: u16 = 0x3FF;
+
+/// The percentage below which [`BatteryStatus::is_low`] is usually checked.
+pub const LOW_BATTERY_PERCENT: u8 = 10;
+
+/// How to turn ADC readings into a voltage and a percentage for one kind of
+/// battery.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Calibration {
+	/// The ADC channel that measures the battery.
+	pub channel: usize,
+	/// The voltage, in millivolts, that a reading of [`ADC_MAX`] stands for.
+	pub full_scale_mv: u32,
+	/// Pairs of millivolts and the percentage at that voltage, sorted by
+	/// voltage. Voltages in between are interpolated, and those outside the
+	/// table are clamped to its first or last percentage. The percentages don't
+	/// have to go up with the voltage.
+	pub curve: &'static [(u32, u8)],
+	/// How to tell if the calculator is plugged in, if it can be charged.
+	pub charger: Option<ChargerSense>,
+}
+
+/// Detects a charger by the voltage on an ADC channel.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct ChargerSense {
+	pub channel: usize,
+	/// The voltage that a reading of [`ADC_MAX`] stands for.
+	pub full_scale_mv: u32,
+	/// The voltage above which the calculator is plugged in.
+	pub threshold_mv: u32,
+}
+
+/// Four AAA batteries in an original TI-Nspire or Touchpad.
+pub const CLASSIC: Calibration = Calibration {
+	channel: 1,
+	full_scale_mv: 6600,
+	curve: &[
+		(4400, 0),
+		(4640, 5),
+		(4800, 10),
+		(4960, 25),
+		(5080, 50),
+		(5240, 75),
+		(5600, 100),
+	],
+	charger: None,
+};
+
+/// The rechargeable battery of the TI-Nspire CX, CX II and CM.
+pub const CX: Calibration = Calibration {
+	channel: 2,
+	full_scale_mv: 4400,
+	curve: LITHIUM_ION,
+	charger: Some(ChargerSense {
+		channel: 1,
+		full_scale_mv: 6600,
+		threshold_mv: 4300,
+	}),
+};
+
+/// A single lithium-ion cell, which all of the rechargeable calculators use.
+const LITHIUM_ION: &[(u32, u8)] = &[
+	(3300, 0),
+	(3550, 5),
+	(3650, 10),
+	(3720, 25),
+	(3790, 50),
+	(3900, 75),
+	(4150, 100),
+];
+
+impl Calibration {
+	/// The calibration for the calculator that the program is running on.
+	pub fn current() -> Calibration {
+		match super::model().family() {
+			Family::Cx => CX,
+			Family::Classic => CLASSIC,
+		}
+	}
+
+	/// Converts a reading from the battery channel to millivolts.
+	pub fn millivolts(&self, raw: u16) -> u32 {
+		scale(raw, self.full_scale_mv)
+	}
+
+	/// Looks up the percentage for a battery voltage in
+	/// [`curve`](Calibration::curve).
+	pub fn percent(&self, millivolts: u32) -> u8 {
+		let curve = self.curve;
+		let (first, last) = match (curve.first(), curve.last()) {
+			(Some(first), Some(last)) => (*first, *last),
+			_ => return 0,
+		};
+		if millivolts <= first.0 {
+			return first.1;
+		}
+		if millivolts >= last.0 {
+			return last.1;
+		}
+		let upper = curve
+			.iter()
+			.position(|&(mv, _)| mv > millivolts)
+			.unwrap_or(curve.len() - 1);
+		let (low_mv, low_percent) = curve[upper - 1];
+		let (high_mv, high_percent) = curve[upper];
+		// Signed, since the percentage may go down between two points
+		let offset = (millivolts - low_mv) as i64 * (high_percent as i64 - low_percent as i64);
+		(low_percent as i64 + offset / (high_mv - low_mv) as i64) as u8
+	}
+
+	/// Works out the battery status from raw ADC readings. `charger` is the
+	/// reading from the [`ChargerSense`] channel, if there is one.
+	pub fn status(&self, battery: u16, charger: Option<u16>) -> BatteryStatus {
+		let millivolts = self.millivolts(battery);
+		let percent = self.percent(millivolts);
+		let plugged_in = match (self.charger, charger) {
+			(Some(sense), Some(raw)) => scale(raw, sense.full_scale_mv) > sense.threshold_mv,
+			_ => false,
+		};
+		let charge = if !plugged_in {
+			ChargeState::Discharging
+		} else if percent >= 100 {
+			ChargeState::Full
+		} else {
+			ChargeState::Charging
+		};
+		BatteryStatus {
+			millivolts,
+			percent,
+			charge,
+		}
+	}
+}
+
+fn scale(raw: u16, full_scale_mv: u32) -> u32 {
+	raw.min(ADC_MAX) as u32 * full_scale_mv / ADC_MAX as u32
+}
+
+/// Whether the battery is being charged.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum ChargeState {
+	/// Running on battery. Calculators that take AAA batteries are always
+	/// discharging.
+	Discharging,
+	/// Plugged in and charging.
+	Charging,
+	/// Plugged in with a full battery.
+	Full,
+}
+
+impl ChargeState {
+	pub fn is_plugged_in(self) -> bool {
+		self != ChargeState::Discharging
+	}
+}
+
+/// The battery level and charging state, as returned by [`battery_status`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct BatteryStatus {
+	pub millivolts: u32,
+	/// From `0` to `100`. See the [module documentation](self) for how exact
+	/// this is.
+	pub percent: u8,
+	pub charge: ChargeState,
+}
+
+impl BatteryStatus {
+	/// Returns `true` if the battery is at or below `threshold` percent and
+	/// isn't being charged.
+	pub fn is_low(&self, threshold: u8) -> bool {
+		self.percent <= threshold && !self.charge.is_plugged_in()
+	}
+}
+
+impl fmt::Display for BatteryStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}% ({}.{:02} V)",
+			self.percent,
+			self.millivolts / 1000,
+			self.millivolts % 1000 / 10
+		)?;
+		match self.charge {
+			ChargeState::Discharging => Ok(()),
+			ChargeState::Charging => f.write_str(", charging"),
+			ChargeState::Full => f.write_str(", charged"),
+		}
+	}
+}
+
+/// The longest to wait for a measurement before using the previous one.
+const MEASURE_SPINS: u32 = 10000;
+
+/// Measures one ADC channel. If the measurement takes too long, the last value
+/// that the OS measured is returned instead.
+pub fn read_channel<B: Bus + Copy>(adc: &mmio::Adc<B>, channel: usize) -> u16 {
+	let channel = adc.channel(channel);
+	channel.command().write(1);
+	for _ in 0..MEASURE_SPINS {
+		if channel.status().read() & 1 == 0 {
+			break;
+		}
+	}
+	(channel.value().read() & ADC_MAX as u32) as u16
+}
+
+/// Measures the battery with `calibration`.
+pub fn read_status<B: Bus + Copy>(adc: &mmio::Adc<B>, calibration: &Calibration) -> BatteryStatus {
+	let battery = read_channel(adc, calibration.channel);
+	let charger = calibration
+		.charger
+		.map(|sense| read_channel(adc, sense.channel));
+	calibration.status(battery, charger)
+}
+
+/// Measures the battery of this calculator.
+pub fn battery_status() -> BatteryStatus {
+	#[cfg(not(feature = "host"))]
+	{
+		read_status(&mmio::adc(), &Calibration::current())
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::battery()
+	}
+}
+
+/// Measures the battery, from `0` to `100` percent.
+pub fn battery_percent() -> u8 {
+	battery_status().percent
+}
+
+/// Returns `true` if the calculator is plugged in to a charger or computer.
+pub fn is_plugged_in() -> bool {
+	battery_status().charge.is_plugged_in()
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use super::*;
+
+	fn raw_for(mv: u32, full_scale_mv: u32) -> u16 {
+		(mv * ADC_MAX as u32).div_ceil(full_scale_mv) as u16
+	}
+
+	#[test]
+	fn conversion() {
+		assert_eq!(CX.millivolts(0), 0);
+		assert_eq!(CX.millivolts(ADC_MAX), 4400);
+		assert_eq!(CX.millivolts(u16::MAX), 4400);
+		assert_eq!(CLASSIC.millivolts(ADC_MAX / 2), 3296);
+
+		assert_eq!(CX.percent(0), 0);
+		assert_eq!(CX.percent(3300), 0);
+		assert_eq!(CX.percent(3650), 10);
+		assert_eq!(CX.percent(3755), 37);
+		assert_eq!(CX.percent(4150), 100);
+		assert_eq!(CX.percent(5000), 100);
+		assert_eq!(CLASSIC.percent(5080), 50);
+		assert_eq!(CLASSIC.percent(4720), 7);
+
+		for calibration in &[CLASSIC, CX] {
+			assert!(calibration
+				.curve
+				.windows(2)
+				.all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1));
+			let mut last = 0;
+			for raw in 0..=ADC_MAX {
+				let percent = calibration.percent(calibration.millivolts(raw));
+				assert!(percent >= last && percent <= 100);
+				last = percent;
+			}
+		}
+		let empty = Calibration { curve: &[], ..CX };
+		assert_eq!(empty.percent(3700), 0);
+		let dipping = Calibration {
+			curve: &[(3000, 0), (3500, 60), (4000, 40), (4500, 100)],
+			..CX
+		};
+		assert_eq!(dipping.percent(3750), 50);
+		assert_eq!(dipping.percent(4000), 40);
+		assert_eq!(dipping.percent(4250), 70);
+	}
+
+	#[test]
+	fn status() {
+		let battery = raw_for(3790, 4400);
+		let status = CX.status(battery, Some(raw_for(5000, 6600)));
+		assert_eq!(status.percent, 50);
+		assert_eq!(status.charge, ChargeState::Charging);
+		assert!(!status.is_low(LOW_BATTERY_PERCENT));
+		assert_eq!(std::format!("{}", status), "50% (3.79 V), charging");
+
+		let status = CX.status(ADC_MAX, Some(ADC_MAX));
+		assert_eq!(status.charge, ChargeState::Full);
+
+		let status = CX.status(raw_for(3500, 4400), Some(0));
+		assert_eq!(status.charge, ChargeState::Discharging);
+		assert!(status.is_low(LOW_BATTERY_PERCENT));
+
+		let status = CLASSIC.status(raw_for(5600, 6600), None);
+		assert_eq!(status.percent, 100);
+		assert_eq!(status.charge, ChargeState::Discharging);
+		assert_eq!(std::format!("{}", status), "100% (5.60 V)");
+	}
+}
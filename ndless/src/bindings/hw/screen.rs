@@ -0,0 +1,200 @@
+//! # The screen
+//! The type of LCD, and its contrast or backlight.
+//!
+//! Classic calculators have a contrast setting, and the CX has a backlight
+//! instead. Both are set in percent, and are put back to how they were when
+//! the program exits. [`brightness`] and [`set_brightness`] use whichever one
+//! the calculator has:
+//!
+//! ```no_run
+//! use ndless::hw::screen;
+//!
+//! // Dim while the game is paused
+//! let previous = screen::set_brightness(30);
+//! // ...
+//! screen::set_brightness(previous);
+//! ```
+//!
+//! To dim the screen automatically when the keypad isn't used, see
+//! `ndless_async::screen`.
+
+pub use self::level::{DimPolicy, Dimmer, Range};
+
+
+use super::mmio::{self, Family};
+
+mod level;
+
+/// Returned by [`lcd_type`]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum Screen {
+	/// 4bit grayscale. Native on classic calcs.
+	Screen320x240x4,
+	/// 8bit paletted mode.
+	Screen320x240x8,
+	/// RGB444
+	Screen320x240x16,
+	/// RGB565. Native on CX before HW-W
+	Screen320x240x565,
+	/// RGB565. Native on CX HW-W
+	Screen240x320x565,
+	Screen320x240x555,
+	Screen240x320x555,
+	Unknown,
+}
+
+/// The width of the screen as it's held, in pixels.
+pub const WIDTH: usize = 320;
+/// The height of the screen as it's held, in pixels.
+pub const HEIGHT: usize = 240;
+
+impl Screen {
+	pub fn from_raw(raw: ndless_sys::scr_type_t) -> Screen {
+		match raw {
+			ndless_sys::scr_type_t_SCR_320x240_4 => Screen::Screen320x240x4,
+			ndless_sys::scr_type_t_SCR_320x240_8 => Screen::Screen320x240x8,
+			ndless_sys::scr_type_t_SCR_320x240_16 => Screen::Screen320x240x16,
+			ndless_sys::scr_type_t_SCR_320x240_565 => Screen::Screen320x240x565,
+			ndless_sys::scr_type_t_SCR_240x320_565 => Screen::Screen240x320x565,
+			ndless_sys::scr_type_t_SCR_320x240_555 => Screen::Screen320x240x555,
+			ndless_sys::scr_type_t_SCR_240x320_555 => Screen::Screen240x320x555,
+			_ => Screen::Unknown,
+		}
+	}
+
+	/// The value that `lcd_init` and `lcd_blit` take.
+	pub fn raw(self) -> ndless_sys::scr_type_t {
+		match self {
+			Screen::Screen320x240x4 => ndless_sys::scr_type_t_SCR_320x240_4,
+			Screen::Screen320x240x8 => ndless_sys::scr_type_t_SCR_320x240_8,
+			Screen::Screen320x240x16 => ndless_sys::scr_type_t_SCR_320x240_16,
+			Screen::Screen320x240x565 => ndless_sys::scr_type_t_SCR_320x240_565,
+			Screen::Screen240x320x565 => ndless_sys::scr_type_t_SCR_240x320_565,
+			Screen::Screen320x240x555 => ndless_sys::scr_type_t_SCR_320x240_555,
+			Screen::Screen240x320x555 => ndless_sys::scr_type_t_SCR_240x320_555,
+			Screen::Unknown => ndless_sys::scr_type_t_SCR_TYPE_INVALID,
+		}
+	}
+
+	pub fn bits_per_pixel(self) -> usize {
+		match self {
+			Screen::Screen320x240x4 => 4,
+			Screen::Screen320x240x8 => 8,
+			Screen::Unknown => 0,
+			_ => 16,
+		}
+	}
+
+	/// Whether the buffer is 240 pixels wide and 320 tall, and so is shown
+	/// turned on its side.
+	pub fn is_rotated(self) -> bool {
+		self == Screen::Screen240x320x565 || self == Screen::Screen240x320x555
+	}
+
+	/// The size of a buffer for the whole screen, in bytes.
+	pub fn byte_len(self) -> usize {
+		WIDTH * HEIGHT * self.bits_per_pixel() / 8
+	}
+}
+
+/// The native mode of the LCD.
+pub fn lcd_type() -> Screen {
+	#[cfg(not(feature = "host"))]
+	{
+		Screen::from_raw(unsafe { ndless_sys::lcd_type() })
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::hardware().screen
+	}
+}
+
+/// The contrast values of a classic TI-Nspire. The OS doesn't go outside of
+/// roughly this range, and the screen is hard to read outside of it.
+pub const CLASSIC_CONTRAST: Range = Range {
+	min: 0x60,
+	max: 0xC0,
+};
+
+/// The backlight values of a CX. `min` is dim but still readable.
+pub const CX_BACKLIGHT: Range = Range {
+	min: 0x10,
+	max: 0xFF,
+};
+
+program_state! {
+	/// The register value that was set when the program started.
+	fn original_level() -> Option<u32> = None;
+}
+
+fn range(family: Family) -> Range {
+	match family {
+		Family::Classic => CLASSIC_CONTRAST,
+		Family::Cx => CX_BACKLIGHT,
+	}
+}
+
+fn level(family: Family) -> u8 {
+	range(family).to_percent(mmio::display().level().read())
+}
+
+fn set_level(family: Family, percent: u8) -> u8 {
+	let previous = mmio::display().level().read();
+	if unsafe { *original_level() }.is_none() {
+		unsafe { *original_level() = Some(previous) };
+		crate::process::at_exit(|| {
+			if let Some(original) = unsafe { original_level().replace(None) } {
+				mmio::display().level().write(original);
+			}
+		});
+	}
+	mmio::display().level().write(range(family).to_raw(percent));
+	range(family).to_percent(previous)
+}
+
+/// The backlight brightness in percent, or `None` on a classic calculator,
+/// which doesn't have an adjustable backlight.
+pub fn backlight() -> Option<u8> {
+	match Family::current() {
+		Family::Cx => Some(level(Family::Cx)),
+		Family::Classic => None,
+	}
+}
+
+/// Sets the backlight brightness in percent, returning the previous
+/// brightness, or does nothing and returns `None` on a classic calculator.
+pub fn set_backlight(percent: u8) -> Option<u8> {
+	match Family::current() {
+		Family::Cx => Some(set_level(Family::Cx, percent)),
+		Family::Classic => None,
+	}
+}
+
+/// The contrast in percent, or `None` on a CX, which doesn't have a contrast
+/// setting.
+pub fn contrast() -> Option<u8> {
+	match Family::current() {
+		Family::Classic => Some(level(Family::Classic)),
+		Family::Cx => None,
+	}
+}
+
+/// Sets the contrast in percent, returning the previous contrast, or does
+/// nothing and returns `None` on a CX.
+pub fn set_contrast(percent: u8) -> Option<u8> {
+	match Family::current() {
+		Family::Classic => Some(set_level(Family::Classic, percent)),
+		Family::Cx => None,
+	}
+}
+
+/// The backlight brightness on a CX, or the contrast on a classic calculator.
+pub fn brightness() -> u8 {
+	level(Family::current())
+}
+
+/// Sets the backlight brightness on a CX, or the contrast on a classic
+/// calculator, returning the previous value.
+pub fn set_brightness(percent: u8) -> u8 {
+	set_level(Family::current(), percent)
+}
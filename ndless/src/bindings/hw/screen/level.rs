@@ -0,0 +1,156 @@
+//! Converting between percentages and register values, and deciding when to
+//! dim the screen. None of this touches the hardware.
+
+use core::time::Duration;
+
+/// The values that a display setting can be set to. Levels are given as a
+/// percentage, where `0` is [`min`](Range::min) and `100` is
+/// [`max`](Range::max).
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Range {
+	/// The darkest value that still leaves the screen readable.
+	pub min: u32,
+	/// The brightest value.
+	pub max: u32,
+}
+
+impl Range {
+	/// Converts a percentage to a register value. Percentages above `100` are
+	/// treated as `100`.
+	pub fn to_raw(&self, percent: u8) -> u32 {
+		let span = self.max - self.min;
+		self.min + (span * percent.min(100) as u32 + 50) / 100
+	}
+
+	/// Converts a register value to the nearest percentage. Values outside of
+	/// the range are clamped to it.
+	pub fn to_percent(&self, raw: u32) -> u8 {
+		let span = self.max - self.min;
+		if span == 0 {
+			return 100;
+		}
+		let raw = raw.max(self.min).min(self.max) - self.min;
+		((raw * 100 + span / 2) / span) as u8
+	}
+}
+
+/// When and how far to dim the screen while the keypad isn't used.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct DimPolicy {
+	/// How long without a key press before dimming.
+	pub idle_after: Duration,
+	/// The brightness to dim to, in percent. The screen is never brightened,
+	/// so if it's already darker than this, it's left alone.
+	pub dimmed_percent: u8,
+}
+
+impl Default for DimPolicy {
+	/// Dims to 20% after 30 seconds.
+	fn default() -> Self {
+		DimPolicy {
+			idle_after: Duration::from_secs(30),
+			dimmed_percent: 20,
+		}
+	}
+}
+
+/// Keeps track of whether the screen is dimmed, and the brightness to go back
+/// to. Call [`idle`](Dimmer::idle) when nothing has happened for
+/// [`timeout`](Dimmer::timeout), and [`activity`](Dimmer::activity) when a key
+/// is pressed, and set the brightness to what they return.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Dimmer {
+	policy: DimPolicy,
+	restore: Option<u8>,
+}
+
+impl Dimmer {
+	pub fn new(policy: DimPolicy) -> Self {
+		Dimmer {
+			policy,
+			restore: None,
+		}
+	}
+
+	pub fn policy(&self) -> DimPolicy {
+		self.policy
+	}
+
+	pub fn is_dimmed(&self) -> bool {
+		self.restore.is_some()
+	}
+
+	/// How long to wait for a key press before calling
+	/// [`idle`](Dimmer::idle), or `None` if the screen is already dimmed and
+	/// there's nothing to do until a key is pressed.
+	pub fn timeout(&self) -> Option<Duration> {
+		if self.is_dimmed() {
+			None
+		} else {
+			Some(self.policy.idle_after)
+		}
+	}
+
+	/// The keypad hasn't been used for [`timeout`](Dimmer::timeout). Returns
+	/// the brightness to dim to, given the `current` brightness, or `None` if
+	/// it shouldn't change.
+	pub fn idle(&mut self, current: u8) -> Option<u8> {
+		if self.is_dimmed() || current <= self.policy.dimmed_percent {
+			return None;
+		}
+		self.restore = Some(current);
+		Some(self.policy.dimmed_percent)
+	}
+
+	/// A key was pressed. Returns the brightness to go back to if the screen
+	/// was dimmed.
+	pub fn activity(&mut self) -> Option<u8> {
+		self.restore.take()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ranges() {
+		let range = Range {
+			min: 0x10,
+			max: 0x10F,
+		};
+		assert_eq!(range.to_raw(0), 0x10);
+		assert_eq!(range.to_raw(100), 0x10F);
+		assert_eq!(range.to_raw(255), 0x10F);
+		assert_eq!(range.to_raw(50), 0x90);
+		assert_eq!(range.to_percent(0), 0);
+		assert_eq!(range.to_percent(0x1000), 100);
+		for percent in 0..=100 {
+			assert_eq!(range.to_percent(range.to_raw(percent)), percent);
+		}
+		let fixed = Range { min: 5, max: 5 };
+		assert_eq!(fixed.to_raw(40), 5);
+		assert_eq!(fixed.to_percent(5), 100);
+	}
+
+	#[test]
+	fn dimming() {
+		let policy = DimPolicy::default();
+		let mut dimmer = Dimmer::new(policy);
+		assert_eq!(dimmer.activity(), None);
+		assert_eq!(dimmer.timeout(), Some(policy.idle_after));
+
+		assert_eq!(dimmer.idle(80), Some(policy.dimmed_percent));
+		assert!(dimmer.is_dimmed());
+		assert_eq!(dimmer.timeout(), None);
+		assert_eq!(dimmer.idle(policy.dimmed_percent), None);
+		assert_eq!(dimmer.activity(), Some(80));
+		assert_eq!(dimmer.activity(), None);
+		assert!(!dimmer.is_dimmed());
+
+		// Already darker than the dimmed level
+		assert_eq!(dimmer.idle(10), None);
+		assert!(!dimmer.is_dimmed());
+		assert_eq!(dimmer.activity(), None);
+	}
+}
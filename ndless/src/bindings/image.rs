@@ -0,0 +1,454 @@
+//! # Images
+//! Decodes PNG, BMP and QOI images into the formats that the screen uses,
+//! without SDL. Images can be read from anything that implements
+//! [`Read`], such as a [file](crate::fs::File) or an
+//! [asset](crate::assets), or from a byte slice:
+//!
+//! ```no_run
+//! use ndless::hw::screen::Screen;
+//! use ndless::image::{self, Dither, PixelFormat};
+//!
+//! let data = ndless::assets::read("title.png").unwrap();
+//! let title = image::decode(&data, PixelFormat::Rgb565, Dither::None).unwrap();
+//! ndless::lcd::blit(&title.data, Screen::Screen320x240x565);
+//! ```
+//!
+//! Classic calculators only have 16 shades of gray, so photos look much
+//! better [dithered](Dither) when they're converted to [`PixelFormat::Gray4`].
+//!
+//! Large images don't need to fit in memory: a [`Decoder`] produces one row
+//! at a time, which can be converted with a [`Converter`] and copied
+//! straight to where it's needed. Only interlaced PNGs and run-length encoded
+//! BMPs have to be decoded all at once.
+//!
+//! ```no_run
+//! use ndless::fs::File;
+//! use ndless::image::{Converter, Decoder, Dither, PixelFormat};
+//!
+//! let decoder = Decoder::new(File::open("/documents/map.qoi.tns").unwrap()).unwrap();
+//! let info = decoder.info();
+//! let converter = Converter::new(PixelFormat::Gray4, info.width).dither(Dither::FloydSteinberg);
+//! let mut map = vec![0; PixelFormat::Gray4.stride(info.width) * info.height as usize];
+//! decoder
+//! 	.convert_rows(converter, |y, row| {
+//! 		let start = y as usize * row.len();
+//! 		map[start..start + row.len()].copy_from_slice(row);
+//! 	})
+//! 	.unwrap();
+//! ```
+//!
+//! PNGs of any color type and bit depth are supported, with transparency.
+//! BMPs can be paletted, run-length encoded, 16, 24 or 32 bits per pixel
+//! with or without bit fields, but not contain an embedded JPEG or PNG.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+pub use self::convert::{Converter, Dither, PixelFormat};
+use self::input::Input;
+use crate::error::Error;
+use crate::io::{self, Read};
+
+mod bmp;
+mod convert;
+mod inflate;
+mod input;
+mod png;
+mod qoi;
+
+/// The largest width or height that can be decoded.
+pub const MAX_SIZE: u32 = 8192;
+
+/// A pixel with 8-bit red, green, blue and alpha channels. An alpha of 0 is
+/// fully transparent.
+pub type Rgba = [u8; 4];
+
+/// The kinds of image file that can be decoded.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum FileType {
+	Png,
+	Bmp,
+	Qoi,
+}
+
+impl FileType {
+	/// Works out the kind of image from the first few bytes of the file.
+	pub fn detect(data: &[u8]) -> Option<FileType> {
+		if data.starts_with(png::SIGNATURE) {
+			Some(FileType::Png)
+		} else if data.starts_with(bmp::SIGNATURE) {
+			Some(FileType::Bmp)
+		} else if data.starts_with(qoi::SIGNATURE) {
+			Some(FileType::Qoi)
+		} else {
+			None
+		}
+	}
+}
+
+/// What the header of an image says.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct ImageInfo {
+	pub width: u32,
+	pub height: u32,
+	pub file_type: FileType,
+	/// Whether any pixels may be transparent.
+	pub has_alpha: bool,
+}
+
+/// One row of an image.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct Row<'a> {
+	/// Where the row is, counting from the top. Rows are produced in the
+	/// order they're stored, which is from the bottom up for most BMPs.
+	pub y: u32,
+	pub pixels: &'a [Rgba],
+}
+
+/// The reason an image couldn't be decoded.
+#[derive(Debug)]
+pub enum ImageError {
+	Io(io::Error),
+	/// The file isn't a PNG, BMP or QOI image.
+	UnknownFormat,
+	/// The file ends before the image does.
+	Truncated,
+	/// The file is damaged, or wasn't written correctly.
+	Malformed(&'static str),
+	/// The file is valid, but uses a feature that isn't supported.
+	Unsupported(&'static str),
+	/// The image is wider or taller than [`MAX_SIZE`].
+	TooLarge {
+		width: u32,
+		height: u32,
+	},
+}
+
+impl fmt::Display for ImageError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ImageError::Io(err) => write!(f, "couldn't read image: {}", err),
+			ImageError::UnknownFormat => f.write_str("not a PNG, BMP or QOI image"),
+			ImageError::Truncated => f.write_str("image file is incomplete"),
+			ImageError::Malformed(reason) => write!(f, "image is damaged: {}", reason),
+			ImageError::Unsupported(feature) => write!(f, "unsupported image: {}", feature),
+			ImageError::TooLarge { width, height } => {
+				write!(f, "image is too large ({}x{})", width, height)
+			}
+		}
+	}
+}
+
+impl Error for ImageError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ImageError::Io(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for ImageError {
+	fn from(err: io::Error) -> Self {
+		if err.kind() == io::ErrorKind::UnexpectedEof {
+			ImageError::Truncated
+		} else {
+			ImageError::Io(err)
+		}
+	}
+}
+
+/// An image converted to one of the screen's formats.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Image {
+	pub width: u32,
+	pub height: u32,
+	pub format: PixelFormat,
+	/// The rows of the image from top to bottom, each
+	/// [`stride`](Image::stride) bytes long.
+	pub data: Vec<u8>,
+}
+
+impl Image {
+	/// The number of bytes in each row.
+	pub fn stride(&self) -> usize {
+		self.format.stride(self.width)
+	}
+
+	pub fn row(&self, y: u32) -> &[u8] {
+		let stride = self.stride();
+		&self.data[y as usize * stride..][..stride]
+	}
+}
+
+enum Format {
+	// The palette makes PNG decoders much larger than the others
+	Png(Box<png::Png>),
+	Bmp(bmp::Bmp),
+	Qoi(qoi::Qoi),
+}
+
+/// Reads an image one row at a time.
+///
+/// Reading is buffered, so there's no need to wrap a file in a
+/// [`BufReader`](crate::io::BufReader).
+pub struct Decoder<R> {
+	input: Input<R>,
+	info: ImageInfo,
+	format: Format,
+}
+
+impl<R: Read> Decoder<R> {
+	/// Reads the image's header, working out what kind of image it is.
+	pub fn new(reader: R) -> Result<Self, ImageError> {
+		let mut input = Input::new(reader);
+		let file_type = FileType::detect(input.peek(8)?).ok_or(ImageError::UnknownFormat)?;
+		let format = match file_type {
+			FileType::Png => Format::Png(Box::new(png::Png::new(&mut input)?)),
+			FileType::Bmp => Format::Bmp(bmp::Bmp::new(&mut input)?),
+			FileType::Qoi => Format::Qoi(qoi::Qoi::new(&mut input)?),
+		};
+		let (width, height, has_alpha) = match &format {
+			Format::Png(png) => (png.width(), png.height(), png.has_alpha()),
+			Format::Bmp(bmp) => (bmp.width(), bmp.height(), bmp.has_alpha()),
+			Format::Qoi(qoi) => (qoi.width(), qoi.height(), qoi.has_alpha()),
+		};
+		Ok(Decoder {
+			input,
+			info: ImageInfo {
+				width,
+				height,
+				file_type,
+				has_alpha,
+			},
+			format,
+		})
+	}
+
+	pub fn info(&self) -> ImageInfo {
+		self.info
+	}
+
+	/// Decodes the next row, or returns `None` once every row has been read.
+	pub fn next_row(&mut self) -> Result<Option<Row<'_>>, ImageError> {
+		let input = &mut self.input;
+		let row = match &mut self.format {
+			Format::Png(png) => png.next_row(input)?,
+			Format::Bmp(bmp) => bmp.next_row(input)?,
+			Format::Qoi(qoi) => qoi.next_row(input)?,
+		};
+		Ok(row.map(|(y, pixels)| Row { y, pixels }))
+	}
+
+	/// Converts each row with `converter` as it's decoded, and passes it to
+	/// `f` along with where it goes.
+	pub fn convert_rows(
+		mut self,
+		mut converter: Converter,
+		mut f: impl FnMut(u32, &[u8]),
+	) -> Result<(), ImageError> {
+		let mut out = vec![0; converter.format().stride(self.info.width)];
+		while let Some(row) = self.next_row()? {
+			converter.convert_row(row.y, row.pixels, &mut out);
+			f(row.y, &out);
+		}
+		Ok(())
+	}
+
+	/// Decodes the whole image, converting it with `converter`.
+	pub fn decode_with(self, converter: Converter) -> Result<Image, ImageError> {
+		let ImageInfo { width, height, .. } = self.info;
+		let format = converter.format();
+		let stride = format.stride(width);
+		let mut data = vec![0; stride * height as usize];
+		self.convert_rows(converter, |y, row| {
+			data[y as usize * stride..][..stride].copy_from_slice(row)
+		})?;
+		Ok(Image {
+			width,
+			height,
+			format,
+			data,
+		})
+	}
+
+	/// Decodes the whole image into `format`. Transparent pixels are drawn
+	/// over black: use [`decode_with`](Decoder::decode_with) for another
+	/// background.
+	pub fn decode(self, format: PixelFormat, dither: Dither) -> Result<Image, ImageError> {
+		let converter = Converter::new(format, self.info.width).dither(dither);
+		self.decode_with(converter)
+	}
+
+	/// Decodes the whole image without converting it, returning its pixels
+	/// from the top left, row by row.
+	pub fn decode_rgba(mut self) -> Result<Vec<Rgba>, ImageError> {
+		let width = self.info.width as usize;
+		let mut pixels = vec![[0; 4]; width * self.info.height as usize];
+		while let Some(row) = self.next_row()? {
+			pixels[row.y as usize * width..][..width].copy_from_slice(row.pixels);
+		}
+		Ok(pixels)
+	}
+}
+
+/// Decodes a whole image from memory into `format`.
+pub fn decode(data: &[u8], format: PixelFormat, dither: Dither) -> Result<Image, ImageError> {
+	Decoder::new(data)?.decode(format, dither)
+}
+
+/// Checks that an image's size is reasonable.
+fn check_size(width: u32, height: u32) -> Result<(), ImageError> {
+	if width == 0 || height == 0 {
+		Err(ImageError::Malformed("image is empty"))
+	} else if width > MAX_SIZE || height > MAX_SIZE {
+		Err(ImageError::TooLarge { width, height })
+	} else {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use core::convert::TryInto;
+	use std::path::Path;
+	use std::string::String;
+
+	use super::*;
+	use crate::lcd;
+
+	const TEST_DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/bindings/image/testdata");
+
+	/// Reads a reference image written by `testdata/generate.py`: the width
+	/// and height, then the pixels.
+	fn reference(path: &Path) -> (u32, u32, Vec<Rgba>) {
+		let data = std::fs::read(path).unwrap();
+		let size =
+			|range: core::ops::Range<usize>| u32::from_le_bytes(data[range].try_into().unwrap());
+		let pixels = data[8..]
+			.chunks(4)
+			.map(|pixel| pixel.try_into().unwrap())
+			.collect();
+		(size(0..4), size(4..8), pixels)
+	}
+
+	/// Each `name.png`, `.bmp` or `.qoi` file must decode to `name.rgba`, or
+	/// to the reference image that its name starts with, like `gradient` for
+	/// `gradient-rle8.bmp`. Files starting with `x-` must fail to decode.
+	#[test]
+	fn conformance() {
+		let mut checked = 0;
+		for entry in std::fs::read_dir(TEST_DATA).unwrap() {
+			let path = entry.unwrap().path();
+			let name: String = path.file_name().unwrap().to_str().unwrap().into();
+			if !["png", "bmp", "qoi"]
+				.iter()
+				.any(|extension| path.extension() == Some(extension.as_ref()))
+			{
+				continue;
+			}
+			let data = std::fs::read(&path).unwrap();
+			let decoded = Decoder::new(&data[..]).and_then(|decoder| {
+				let info = decoder.info();
+				decoder.decode_rgba().map(|pixels| (info, pixels))
+			});
+			checked += 1;
+			if name.starts_with("x-") {
+				assert!(decoded.is_err(), "{} was decoded", name);
+				continue;
+			}
+			let (info, pixels) = decoded.unwrap_or_else(|err| panic!("{}: {}", name, err));
+			let stem = path.file_stem().unwrap().to_str().unwrap();
+			let reference_name = stem.split('-').next().unwrap();
+			let (width, height, expected) =
+				reference(&path.with_file_name(std::format!("{}.rgba", reference_name)));
+			assert_eq!((info.width, info.height), (width, height), "{}", name);
+			if let Some((i, (pixel, expected))) = pixels
+				.iter()
+				.zip(&expected)
+				.enumerate()
+				.find(|(_, (pixel, expected))| pixel != expected)
+			{
+				panic!(
+					"{}: pixel ({}, {}) is {:?}, not {:?}",
+					name,
+					i as u32 % width,
+					i as u32 / width,
+					pixel,
+					expected
+				);
+			}
+		}
+		assert!(checked > 50, "only found {} test images", checked);
+	}
+
+	#[test]
+	fn errors() {
+		assert!(matches!(
+			Decoder::new(&b"GIF89a"[..]),
+			Err(ImageError::UnknownFormat)
+		));
+		assert!(matches!(
+			Decoder::new(&b"\x89PNG\r\n\x1a\n\0\0"[..]),
+			Err(ImageError::Truncated)
+		));
+		let png = lcd::png::encode(3, 2, &[9; 18]);
+		let decoder = Decoder::new(&png[..png.len() - 20]).unwrap();
+		assert!(matches!(decoder.decode_rgba(), Err(ImageError::Truncated)));
+	}
+
+	#[test]
+	fn screenshots() {
+		let rgb: Vec<u8> = (0..5 * 3 * 3).map(|i| (i * 5) as u8).collect();
+		let expected: Vec<Rgba> = rgb
+			.chunks(3)
+			.map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+			.collect();
+		for data in &[lcd::png::encode(5, 3, &rgb), lcd::bmp::encode(5, 3, &rgb)] {
+			let decoder = Decoder::new(&data[..]).unwrap();
+			assert_eq!(decoder.info().width, 5);
+			assert_eq!(decoder.info().height, 3);
+			assert!(!decoder.info().has_alpha);
+			assert_eq!(decoder.decode_rgba().unwrap(), expected);
+		}
+
+		let image = decode(
+			&lcd::png::encode(3, 1, &[255, 0, 0, 0, 255, 0, 0, 0, 255]),
+			PixelFormat::Rgb565,
+			Dither::None,
+		)
+		.unwrap();
+		assert_eq!(image.stride(), 6);
+		assert_eq!(image.row(0), [0x00, 0xF8, 0xE0, 0x07, 0x1F, 0x00]);
+	}
+
+	#[test]
+	fn streaming() {
+		// BMPs are stored from the bottom up
+		let rgb: Vec<u8> = (0..4 * 3 * 3).map(|i| i as u8).collect();
+		let bmp = lcd::bmp::encode(4, 3, &rgb);
+		let mut decoder = Decoder::new(&bmp[..]).unwrap();
+		let mut order = Vec::new();
+		while let Some(row) = decoder.next_row().unwrap() {
+			assert_eq!(row.pixels.len(), 4);
+			assert_eq!(row.pixels[0][0], rgb[row.y as usize * 12]);
+			order.push(row.y);
+		}
+		assert_eq!(order, [2, 1, 0]);
+
+		let converter = Converter::new(PixelFormat::Gray4, 4);
+		let mut rows = 0;
+		Decoder::new(&bmp[..])
+			.unwrap()
+			.convert_rows(converter, |_, row| {
+				assert_eq!(row.len(), 2);
+				rows += 1;
+			})
+			.unwrap();
+		assert_eq!(rows, 3);
+	}
+}
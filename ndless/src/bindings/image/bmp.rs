@@ -0,0 +1,359 @@
+//! BMP decoding.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::input::Input;
+use super::{check_size, ImageError, Rgba};
+use crate::io::Read;
+
+pub(super) const SIGNATURE: &[u8] = b"BM";
+
+const FILE_HEADER_LEN: u32 = 14;
+/// The header used by OS/2, which has 16-bit sizes and 3-byte colors.
+const CORE_HEADER_LEN: u32 = 12;
+const INFO_HEADER_LEN: u32 = 40;
+
+mod compression {
+	pub const RGB: u32 = 0;
+	pub const RLE8: u32 = 1;
+	pub const RLE4: u32 = 2;
+	pub const BITFIELDS: u32 = 3;
+	pub const JPEG: u32 = 4;
+	pub const PNG: u32 = 5;
+	pub const ALPHA_BITFIELDS: u32 = 6;
+}
+
+fn malformed(reason: &'static str) -> ImageError {
+	ImageError::Malformed(reason)
+}
+
+/// Where a channel is in a 16 or 32-bit pixel.
+#[derive(Clone, Copy)]
+struct Mask {
+	shift: u32,
+	bits: u32,
+}
+
+impl Mask {
+	fn new(mask: u32) -> Self {
+		if mask == 0 {
+			return Mask { shift: 0, bits: 0 };
+		}
+		let shift = mask.trailing_zeros();
+		Mask {
+			shift,
+			bits: (mask >> shift).trailing_ones().min(16),
+		}
+	}
+
+	/// Extracts the channel from `pixel`, scaled to 8 bits.
+	fn get(self, pixel: u32, default: u8) -> u8 {
+		if self.bits == 0 {
+			return default;
+		}
+		let max = (1u32 << self.bits) - 1;
+		let value = (pixel >> self.shift) & max;
+		if self.bits >= 8 {
+			(value >> (self.bits - 8)) as u8
+		} else {
+			(value * 255 / max) as u8
+		}
+	}
+}
+
+pub(super) struct Bmp {
+	width: u32,
+	height: u32,
+	top_down: bool,
+	bits_per_pixel: u32,
+	compression: u32,
+	/// Red, green, blue and alpha.
+	masks: [Mask; 4],
+	palette: Vec<Rgba>,
+	/// The current row as it's stored.
+	row: Vec<u8>,
+	/// The current row, or the whole image if it's run-length encoded.
+	pixels: Vec<Rgba>,
+	/// The number of rows read.
+	rows: u32,
+}
+
+impl Bmp {
+	pub fn new<R: Read>(input: &mut Input<R>) -> Result<Self, ImageError> {
+		input.skip(SIGNATURE.len() + 8)?;
+		let offset = input.u32_le()?;
+		let header_len = input.u32_le()?;
+		let mut read = FILE_HEADER_LEN + 4;
+		let (width, height, bits_per_pixel, compression, colors) = if header_len == CORE_HEADER_LEN
+		{
+			let width = input.u16_le()? as i32;
+			let height = input.u16_le()? as i32;
+			input.skip(2)?;
+			let bits_per_pixel = input.u16_le()? as u32;
+			read += CORE_HEADER_LEN - 4;
+			(width, height, bits_per_pixel, compression::RGB, 0)
+		} else if header_len >= INFO_HEADER_LEN {
+			let width = input.u32_le()? as i32;
+			let height = input.u32_le()? as i32;
+			input.skip(2)?;
+			let bits_per_pixel = input.u16_le()? as u32;
+			let compression = input.u32_le()?;
+			input.skip(12)?;
+			let colors = input.u32_le()?;
+			input.skip(4)?;
+			read += INFO_HEADER_LEN - 4;
+			(width, height, bits_per_pixel, compression, colors)
+		} else {
+			return Err(ImageError::Unsupported("unknown BMP header"));
+		};
+
+		// Bit fields come after the header, or in the later versions of it
+		let mask_count = match compression {
+			compression::BITFIELDS => 3,
+			compression::ALPHA_BITFIELDS => 4,
+			compression::JPEG | compression::PNG => {
+				return Err(ImageError::Unsupported("BMP contains a JPEG or PNG"))
+			}
+			compression::RGB | compression::RLE8 | compression::RLE4 => 0,
+			_ => return Err(ImageError::Unsupported("unknown BMP compression")),
+		};
+		let mut masks = match bits_per_pixel {
+			16 => [0x7C00, 0x03E0, 0x001F, 0],
+			_ => [0xFF_0000, 0xFF00, 0xFF, 0],
+		};
+		if mask_count > 0 {
+			// The later headers have room for some or all of the masks
+			let count = match header_len {
+				INFO_HEADER_LEN => mask_count,
+				52 => 3,
+				_ => 4,
+			};
+			for mask in &mut masks[..count] {
+				*mask = input.u32_le()?;
+			}
+			read += count as u32 * 4;
+		}
+		if header_len > INFO_HEADER_LEN {
+			let header_end = FILE_HEADER_LEN + header_len;
+			let rest = header_end
+				.checked_sub(read)
+				.ok_or_else(|| malformed("invalid header"))?;
+			input.skip(rest as usize)?;
+			read = header_end;
+		}
+
+		let valid = match compression {
+			compression::RLE8 => bits_per_pixel == 8,
+			compression::RLE4 => bits_per_pixel == 4,
+			compression::BITFIELDS | compression::ALPHA_BITFIELDS => {
+				bits_per_pixel == 16 || bits_per_pixel == 32
+			}
+			_ => [1, 2, 4, 8, 16, 24, 32].contains(&bits_per_pixel),
+		};
+		if !valid {
+			return Err(malformed("invalid bits per pixel"));
+		}
+
+		let mut palette = Vec::new();
+		if bits_per_pixel <= 8 {
+			let max = 1 << bits_per_pixel;
+			let count = if colors == 0 || colors > max {
+				max
+			} else {
+				colors
+			};
+			let entry_len = if header_len == CORE_HEADER_LEN { 3 } else { 4 };
+			// The palette can be cut short by the image data
+			let count = count.min(offset.saturating_sub(read) / entry_len);
+			for _ in 0..count {
+				let mut color = [0; 4];
+				input.read_exact(&mut color[..entry_len as usize])?;
+				palette.push([color[2], color[1], color[0], 255]);
+			}
+			read += count * entry_len;
+		}
+		let gap = offset
+			.checked_sub(read)
+			.ok_or_else(|| malformed("image data overlaps the header"))?;
+		input.skip(gap as usize)?;
+
+		let top_down = height < 0;
+		let (width, height) = (width as u32, height.unsigned_abs());
+		if width as i32 <= 0 {
+			return Err(malformed("invalid width"));
+		}
+		check_size(width, height)?;
+		let run_length_encoded =
+			compression == compression::RLE8 || compression == compression::RLE4;
+		if top_down && run_length_encoded {
+			return Err(malformed("run-length encoded BMPs can't be top-down"));
+		}
+
+		let mut bmp = Bmp {
+			width,
+			height,
+			top_down,
+			bits_per_pixel,
+			compression,
+			masks: [
+				Mask::new(masks[0]),
+				Mask::new(masks[1]),
+				Mask::new(masks[2]),
+				Mask::new(masks[3]),
+			],
+			palette,
+			row: vec![0; (width as usize * bits_per_pixel as usize).div_ceil(32) * 4],
+			pixels: vec![[0; 4]; width as usize],
+			rows: 0,
+		};
+		if bmp.palette.is_empty() && bits_per_pixel <= 8 {
+			bmp.palette.push([0, 0, 0, 255]);
+		}
+		Ok(bmp)
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn has_alpha(&self) -> bool {
+		self.masks[3].bits != 0
+	}
+
+	fn run_length_encoded(&self) -> bool {
+		self.compression == compression::RLE8 || self.compression == compression::RLE4
+	}
+
+	pub fn next_row<R: Read>(
+		&mut self,
+		input: &mut Input<R>,
+	) -> Result<Option<(u32, &[Rgba])>, ImageError> {
+		if self.rows >= self.height {
+			return Ok(None);
+		}
+		let row = self.rows;
+		self.rows += 1;
+		if self.run_length_encoded() {
+			if row == 0 {
+				self.decode_runs(input)?;
+			}
+			let width = self.width as usize;
+			return Ok(Some((row, &self.pixels[row as usize * width..][..width])));
+		}
+
+		input.read_exact(&mut self.row)?;
+		let y = if self.top_down {
+			row
+		} else {
+			self.height - 1 - row
+		};
+		let (row, palette, bits_per_pixel) = (&self.row, &self.palette, self.bits_per_pixel);
+		let color = |index: usize| palette.get(index).copied().unwrap_or(palette[0]);
+		for (x, pixel) in self.pixels.iter_mut().enumerate() {
+			*pixel = match bits_per_pixel {
+				1 | 2 | 4 | 8 => {
+					let bits = bits_per_pixel as usize;
+					let bit = x * bits;
+					color((row[bit / 8] >> (8 - bits - bit % 8)) as usize & ((1 << bits) - 1))
+				}
+				24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+				_ => {
+					let value = if bits_per_pixel == 16 {
+						u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+					} else {
+						u32::from_le_bytes([
+							row[x * 4],
+							row[x * 4 + 1],
+							row[x * 4 + 2],
+							row[x * 4 + 3],
+						])
+					};
+					let [red, green, blue, alpha] = self.masks;
+					[
+						red.get(value, 0),
+						green.get(value, 0),
+						blue.get(value, 0),
+						alpha.get(value, 255),
+					]
+				}
+			};
+		}
+		Ok(Some((y, &self.pixels)))
+	}
+
+	/// Decodes a run-length encoded image into `self.pixels`. Pixels that
+	/// are skipped over are given the first color in the palette.
+	fn decode_runs<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		let (width, height) = (self.width as usize, self.height as usize);
+		let mut indices = vec![0u8; width * height];
+		let four_bit = self.compression == compression::RLE4;
+		// Rows are stored from the bottom up
+		let (mut x, mut y) = (0usize, 0usize);
+		let mut put = |x: usize, y: usize, index: u8| {
+			if x < width && y < height {
+				indices[(height - 1 - y) * width + x] = index;
+			}
+		};
+		while y < height {
+			let (count, value) = (input.byte()? as usize, input.byte()?);
+			if count > 0 {
+				for i in 0..count {
+					let index = match (four_bit, i % 2) {
+						(false, _) => value,
+						(true, 0) => value >> 4,
+						(true, _) => value & 0xF,
+					};
+					put(x + i, y, index);
+				}
+				x += count;
+				continue;
+			}
+			match value {
+				0 => {
+					x = 0;
+					y += 1;
+				}
+				1 => break,
+				2 => {
+					x += input.byte()? as usize;
+					y += input.byte()? as usize;
+				}
+				count => {
+					let count = count as usize;
+					let mut byte = 0;
+					for i in 0..count {
+						let index = if four_bit {
+							if i % 2 == 0 {
+								byte = input.byte()?;
+								byte >> 4
+							} else {
+								byte & 0xF
+							}
+						} else {
+							input.byte()?
+						};
+						put(x + i, y, index);
+					}
+					x += count;
+					// Runs of literal pixels are padded to 16 bits
+					let len = if four_bit { count.div_ceil(2) } else { count };
+					if len % 2 == 1 {
+						input.byte()?;
+					}
+				}
+			}
+		}
+
+		let palette = &self.palette;
+		self.pixels = indices
+			.iter()
+			.map(|&index| palette.get(index as usize).copied().unwrap_or(palette[0]))
+			.collect();
+		Ok(())
+	}
+}
@@ -0,0 +1,321 @@
+//! Converting decoded pixels into the formats that the screen uses.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::Rgba;
+use crate::hw::screen::Screen;
+
+/// A 4x4 Bayer matrix, for ordered dithering.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// A format that images can be converted to. Pixels are stored in the same
+/// way as the [`Screen`] mode with the same format, so an image converted to
+/// the screen's size can be [blitted](crate::lcd::blit) directly.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum PixelFormat {
+	/// Little-endian 16-bit pixels, with 5 bits of red, 6 of green and 5 of
+	/// blue.
+	Rgb565,
+	/// Little-endian 16-bit pixels, with 4 bits each of red, green and blue
+	/// in the low 12 bits.
+	Rgb444,
+	/// 16 shades of gray, two pixels to a byte with the left pixel in the
+	/// high nibble. `0` is black.
+	Gray4,
+}
+
+impl PixelFormat {
+	/// The format of a screen mode, if images can be converted to it.
+	pub fn from_screen(screen: Screen) -> Option<PixelFormat> {
+		match screen {
+			Screen::Screen320x240x565 | Screen::Screen240x320x565 => Some(PixelFormat::Rgb565),
+			Screen::Screen320x240x16 => Some(PixelFormat::Rgb444),
+			Screen::Screen320x240x4 => Some(PixelFormat::Gray4),
+			_ => None,
+		}
+	}
+
+	pub fn bits_per_pixel(self) -> usize {
+		match self {
+			PixelFormat::Rgb565 | PixelFormat::Rgb444 => 16,
+			PixelFormat::Gray4 => 4,
+		}
+	}
+
+	/// The number of bytes in a row `width` pixels wide. Rows of
+	/// [`Gray4`](PixelFormat::Gray4) pixels are padded to a whole byte.
+	pub fn stride(self, width: u32) -> usize {
+		(width as usize * self.bits_per_pixel()).div_ceil(8)
+	}
+
+	/// The number of levels of each channel.
+	fn levels(self) -> &'static [u16] {
+		match self {
+			PixelFormat::Rgb565 => &[32, 64, 32],
+			PixelFormat::Rgb444 => &[16, 16, 16],
+			PixelFormat::Gray4 => &[16],
+		}
+	}
+}
+
+/// How colors that the format can't show are approximated.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+pub enum Dither {
+	/// Each pixel is rounded to the nearest color, which gives bands in
+	/// gradients.
+	#[default]
+	None,
+	/// Colors are mixed in a regular pattern, which is fast and looks the
+	/// same from frame to frame.
+	Ordered,
+	/// The rounding error is spread onto the pixels that haven't been
+	/// converted yet, which looks best for photos.
+	FloydSteinberg,
+}
+
+/// Converts rows of [`Rgba`] pixels to a [`PixelFormat`].
+///
+/// ```
+/// use ndless::image::{Converter, Dither, PixelFormat};
+///
+/// let mut converter = Converter::new(PixelFormat::Gray4, 2).dither(Dither::Ordered);
+/// let mut row = [0];
+/// converter.convert_row(0, &[[0, 0, 0, 255], [255, 255, 255, 255]], &mut row);
+/// assert_eq!(row, [0x0F]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Converter {
+	format: PixelFormat,
+	dither: Dither,
+	background: [u8; 3],
+	width: usize,
+	/// For Floyd-Steinberg dithering, the error carried onto this row and
+	/// the next, in sixteenths, with a pixel of padding on each side.
+	errors: Vec<i16>,
+	next_errors: Vec<i16>,
+}
+
+impl Converter {
+	/// Converts rows `width` pixels wide, without dithering.
+	pub fn new(format: PixelFormat, width: u32) -> Self {
+		Converter {
+			format,
+			dither: Dither::None,
+			background: [0; 3],
+			width: width as usize,
+			errors: Vec::new(),
+			next_errors: Vec::new(),
+		}
+	}
+
+	pub fn dither(mut self, dither: Dither) -> Self {
+		self.dither = dither;
+		let len = if dither == Dither::FloydSteinberg {
+			(self.width + 2) * self.format.levels().len()
+		} else {
+			0
+		};
+		self.errors = vec![0; len];
+		self.next_errors = vec![0; len];
+		self
+	}
+
+	/// Sets the color that transparent pixels are drawn over, which is black
+	/// by default.
+	pub fn background(mut self, rgb: [u8; 3]) -> Self {
+		self.background = rgb;
+		self
+	}
+
+	pub fn format(&self) -> PixelFormat {
+		self.format
+	}
+
+	/// Converts a row of pixels into `out`. `y` is the row's position in the
+	/// image, for ordered dithering. Floyd-Steinberg dithering carries
+	/// errors from one row to the next, so rows should be converted in the
+	/// order they're decoded.
+	///
+	/// # Panics
+	/// If `pixels` isn't as wide as the converter, or `out` is shorter than
+	/// a [row](PixelFormat::stride).
+	pub fn convert_row(&mut self, y: u32, pixels: &[Rgba], out: &mut [u8]) {
+		assert_eq!(pixels.len(), self.width, "wrong row width");
+		let out = &mut out[..self.format.stride(self.width as u32)];
+		let levels = self.format.levels();
+		let channels = levels.len();
+		if self.format == PixelFormat::Gray4 {
+			out.iter_mut().for_each(|byte| *byte = 0);
+		}
+		for (x, &pixel) in pixels.iter().enumerate() {
+			let [r, g, b] = self.blend(pixel);
+			let values = [r, g, b];
+			let gray = [((r as u32 * 77 + g as u32 * 150 + b as u32 * 29) >> 8) as u8];
+			let values: &[u8] = if channels == 1 { &gray } else { &values };
+			let mut quantized = [0u16; 3];
+			for (channel, (&value, &levels)) in values.iter().zip(levels).enumerate() {
+				quantized[channel] = self.quantize(x, y, channel, value, levels - 1);
+			}
+			let [r, g, b] = quantized;
+			match self.format {
+				PixelFormat::Rgb565 => {
+					out[x * 2..x * 2 + 2].copy_from_slice(&(r << 11 | g << 5 | b).to_le_bytes())
+				}
+				PixelFormat::Rgb444 => {
+					out[x * 2..x * 2 + 2].copy_from_slice(&(r << 8 | g << 4 | b).to_le_bytes())
+				}
+				PixelFormat::Gray4 => out[x / 2] |= (r as u8) << if x % 2 == 0 { 4 } else { 0 },
+			}
+		}
+		if self.dither == Dither::FloydSteinberg {
+			core::mem::swap(&mut self.errors, &mut self.next_errors);
+			self.next_errors.iter_mut().for_each(|error| *error = 0);
+		}
+	}
+
+	/// Draws `pixel` over the background.
+	fn blend(&self, [r, g, b, a]: Rgba) -> [u8; 3] {
+		if a == 255 {
+			return [r, g, b];
+		}
+		let mix = |color: u8, background: u8| {
+			((color as u32 * a as u32 + background as u32 * (255 - a as u32) + 127) / 255) as u8
+		};
+		[
+			mix(r, self.background[0]),
+			mix(g, self.background[1]),
+			mix(b, self.background[2]),
+		]
+	}
+
+	/// Rounds one channel of a pixel to a level from `0` to `max`.
+	fn quantize(&mut self, x: usize, y: u32, channel: usize, value: u8, max: u16) -> u16 {
+		let max = max as i32;
+		let round = |value: i32| ((value * max + 127) / 255) as u16;
+		match self.dither {
+			Dither::None => round(value as i32),
+			Dither::Ordered => {
+				let threshold = (BAYER[y as usize % 4][x % 4] as i32 * 2 + 1) * 255 / 32;
+				((value as i32 * max + threshold) / 255).min(max) as u16
+			}
+			Dither::FloydSteinberg => {
+				let channels = self.format.levels().len();
+				let index = |x: usize| x * channels + channel;
+				// `x + 1` is this pixel, as there's padding on the left
+				let error = self.errors[index(x + 1)] as i32;
+				let value = (value as i32 + (error + 8).div_euclid(16)).clamp(0, 255);
+				let level = round(value);
+				let error = (value - level as i32 * 255 / max) as i16;
+				self.errors[index(x + 2)] += error * 7;
+				self.next_errors[index(x)] += error * 3;
+				self.next_errors[index(x + 1)] += error * 5;
+				self.next_errors[index(x + 2)] += error;
+				level
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn convert(format: PixelFormat, dither: Dither, pixels: &[Rgba]) -> Vec<u8> {
+		let mut out = vec![0; format.stride(pixels.len() as u32)];
+		Converter::new(format, pixels.len() as u32)
+			.dither(dither)
+			.convert_row(0, pixels, &mut out);
+		out
+	}
+
+	#[test]
+	fn formats() {
+		let pixels = [
+			[255, 0, 0, 255],
+			[0, 255, 0, 255],
+			[0, 0, 255, 255],
+			[255, 255, 255, 255],
+			[128, 128, 128, 255],
+		];
+		assert_eq!(
+			convert(PixelFormat::Rgb565, Dither::None, &pixels),
+			[0x00, 0xF8, 0xE0, 0x07, 0x1F, 0x00, 0xFF, 0xFF, 0x10, 0x84]
+		);
+		assert_eq!(
+			convert(PixelFormat::Rgb444, Dither::None, &pixels),
+			[0x00, 0x0F, 0xF0, 0x00, 0x0F, 0x00, 0xFF, 0x0F, 0x88, 0x08]
+		);
+		// Pure green is brighter than pure red or blue
+		assert_eq!(
+			convert(PixelFormat::Gray4, Dither::None, &pixels),
+			[0x49, 0x2F, 0x80]
+		);
+		assert_eq!(PixelFormat::Gray4.stride(5), 3);
+		assert_eq!(PixelFormat::Rgb444.stride(5), 10);
+		assert_eq!(
+			PixelFormat::from_screen(Screen::Screen240x320x565),
+			Some(PixelFormat::Rgb565)
+		);
+		assert_eq!(PixelFormat::from_screen(Screen::Screen320x240x8), None);
+	}
+
+	#[test]
+	fn transparency() {
+		let pixels = [[255, 255, 255, 0], [255, 255, 255, 128], [0, 0, 0, 0]];
+		let mut out = [0; 2];
+		Converter::new(PixelFormat::Gray4, 3)
+			.background([255, 255, 255])
+			.convert_row(0, &pixels, &mut out);
+		assert_eq!(out, [0xFF, 0xF0]);
+		assert_eq!(
+			convert(PixelFormat::Gray4, Dither::None, &pixels),
+			[0x08, 0x00]
+		);
+	}
+
+	#[test]
+	fn dithering() {
+		// A gray between two levels should average out to the same gray
+		let width = 32;
+		let gray = 127;
+		let pixels = vec![[gray, gray, gray, 255]; width];
+		for &dither in &[Dither::Ordered, Dither::FloydSteinberg] {
+			let mut converter = Converter::new(PixelFormat::Gray4, width as u32).dither(dither);
+			let mut total = 0;
+			let mut levels = [false; 16];
+			for y in 0..8 {
+				let mut out = vec![0; width / 2];
+				converter.convert_row(y, &pixels, &mut out);
+				for byte in out {
+					for &level in &[byte >> 4, byte & 0xF] {
+						total += level as u32 * 17;
+						levels[level as usize] = true;
+					}
+				}
+			}
+			let average = total / (width as u32 * 8);
+			assert!((126..=128).contains(&average), "{:?}: {}", dither, average);
+			assert_eq!(
+				levels.iter().filter(|&&used| used).count(),
+				2,
+				"{:?}",
+				dither
+			);
+		}
+		assert_eq!(
+			convert(PixelFormat::Gray4, Dither::None, &pixels[..2]),
+			[0x77]
+		);
+
+		// Black and white stay black and white
+		let pixels = [[0, 0, 0, 255], [255, 255, 255, 255]];
+		for &dither in &[Dither::None, Dither::Ordered, Dither::FloydSteinberg] {
+			assert_eq!(convert(PixelFormat::Gray4, dither, &pixels), [0x0F]);
+			assert_eq!(
+				convert(PixelFormat::Rgb565, dither, &pixels),
+				[0, 0, 0xFF, 0xFF]
+			);
+		}
+	}
+}
@@ -0,0 +1,380 @@
+//! A deflate decoder that produces as much output as it's asked for, so that
+//! PNGs can be decoded a row at a time.
+//!
+//! Huffman codes are decoded a bit at a time, like zlib's `puff`, which is
+//! slower than a lookup table but needs very little memory.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::ImageError;
+
+/// Deflate can refer back to anything in the last 32 KB of output.
+const WINDOW_LEN: usize = 1 << 15;
+const MAX_BITS: usize = 15;
+const LITERALS: usize = 288;
+const DISTANCES: usize = 30;
+
+const LENGTH_BASE: [u16; 29] = [
+	3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+	163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+	0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+	1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+	2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+	0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+	13,
+];
+/// The order that the lengths of the code length code are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+	16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Where compressed data comes from.
+pub(super) trait Source {
+	fn byte(&mut self) -> Result<u8, ImageError>;
+}
+
+fn malformed(reason: &'static str) -> ImageError {
+	ImageError::Malformed(reason)
+}
+
+/// A canonical Huffman code.
+struct Huffman {
+	/// The number of codes of each length.
+	counts: [u16; MAX_BITS + 1],
+	/// The symbols, ordered by their codes.
+	symbols: [u16; LITERALS],
+}
+
+impl Huffman {
+	fn new() -> Self {
+		Huffman {
+			counts: [0; MAX_BITS + 1],
+			symbols: [0; LITERALS],
+		}
+	}
+
+	/// Builds the code from the length of each symbol's code, where `0`
+	/// means that the symbol isn't used.
+	fn build(&mut self, lengths: &[u8]) -> Result<(), ImageError> {
+		self.counts = [0; MAX_BITS + 1];
+		for &length in lengths {
+			self.counts[length as usize] += 1;
+		}
+		// Check that there aren't more codes of any length than can exist.
+		// Codes that are never used are allowed, as zlib writes them.
+		let mut left = 1i32;
+		for &count in &self.counts[1..] {
+			left = (left << 1) - count as i32;
+			if left < 0 {
+				return Err(malformed("invalid Huffman code"));
+			}
+		}
+		let mut offsets = [0u16; MAX_BITS + 1];
+		for length in 1..MAX_BITS {
+			offsets[length + 1] = offsets[length] + self.counts[length];
+		}
+		for (symbol, &length) in lengths.iter().enumerate() {
+			if length != 0 {
+				self.symbols[offsets[length as usize] as usize] = symbol as u16;
+				offsets[length as usize] += 1;
+			}
+		}
+		Ok(())
+	}
+}
+
+enum State {
+	/// About to read a block header.
+	Header,
+	/// In a stored block, with this many bytes left.
+	Stored(usize),
+	/// In a compressed block.
+	Codes,
+	/// Copying earlier output.
+	Copy {
+		len: usize,
+		distance: usize,
+	},
+	Done,
+}
+
+pub(super) struct Inflater {
+	bits: u32,
+	bit_count: u32,
+	window: Vec<u8>,
+	/// The total number of bytes produced.
+	written: usize,
+	state: State,
+	last_block: bool,
+	literals: Huffman,
+	distances: Huffman,
+}
+
+impl Inflater {
+	pub fn new() -> Self {
+		Inflater {
+			bits: 0,
+			bit_count: 0,
+			window: vec![0; WINDOW_LEN],
+			written: 0,
+			state: State::Header,
+			last_block: false,
+			literals: Huffman::new(),
+			distances: Huffman::new(),
+		}
+	}
+
+	/// Decompresses exactly enough to fill `out`.
+	pub fn read(&mut self, source: &mut impl Source, out: &mut [u8]) -> Result<(), ImageError> {
+		let mut filled = 0;
+		while filled < out.len() {
+			match self.state {
+				State::Header => self.header(source)?,
+				State::Stored(0) => self.end_block(),
+				State::Stored(len) => {
+					let byte = source.byte()?;
+					self.state = State::Stored(len - 1);
+					self.emit(byte, out, &mut filled);
+				}
+				State::Codes => self.symbol(source, out, &mut filled)?,
+				State::Copy { len, distance } => {
+					let byte = self.window[self.written.wrapping_sub(distance) % WINDOW_LEN];
+					self.state = if len > 1 {
+						State::Copy {
+							len: len - 1,
+							distance,
+						}
+					} else {
+						State::Codes
+					};
+					self.emit(byte, out, &mut filled);
+				}
+				State::Done => return Err(malformed("image data ends early")),
+			}
+		}
+		Ok(())
+	}
+
+	fn emit(&mut self, byte: u8, out: &mut [u8], filled: &mut usize) {
+		self.window[self.written % WINDOW_LEN] = byte;
+		self.written += 1;
+		out[*filled] = byte;
+		*filled += 1;
+	}
+
+	fn end_block(&mut self) {
+		self.state = if self.last_block {
+			State::Done
+		} else {
+			State::Header
+		};
+	}
+
+	fn bits(&mut self, source: &mut impl Source, count: u32) -> Result<u32, ImageError> {
+		while self.bit_count < count {
+			self.bits |= (source.byte()? as u32) << self.bit_count;
+			self.bit_count += 8;
+		}
+		let value = self.bits & ((1 << count) - 1);
+		self.bits >>= count;
+		self.bit_count -= count;
+		Ok(value)
+	}
+
+	fn decode(&mut self, source: &mut impl Source, distances: bool) -> Result<usize, ImageError> {
+		let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+		for length in 1..=MAX_BITS {
+			code |= self.bits(source, 1)? as i32;
+			let huffman = if distances {
+				&self.distances
+			} else {
+				&self.literals
+			};
+			let count = huffman.counts[length] as i32;
+			if code - first < count {
+				return Ok(huffman.symbols[(index + code - first) as usize] as usize);
+			}
+			index += count;
+			first = (first + count) << 1;
+			code <<= 1;
+		}
+		Err(malformed("invalid Huffman code"))
+	}
+
+	fn header(&mut self, source: &mut impl Source) -> Result<(), ImageError> {
+		self.last_block = self.bits(source, 1)? == 1;
+		match self.bits(source, 2)? {
+			0 => {
+				// Stored blocks start on a byte boundary
+				self.bits = 0;
+				self.bit_count = 0;
+				let len = self.bits(source, 16)?;
+				if self.bits(source, 16)? != !len & 0xFFFF {
+					return Err(malformed("invalid stored block"));
+				}
+				self.state = State::Stored(len as usize);
+			}
+			1 => {
+				let mut lengths = [0; LITERALS + DISTANCES];
+				lengths[..144].iter_mut().for_each(|length| *length = 8);
+				lengths[144..256].iter_mut().for_each(|length| *length = 9);
+				lengths[256..280].iter_mut().for_each(|length| *length = 7);
+				lengths[280..LITERALS]
+					.iter_mut()
+					.for_each(|length| *length = 8);
+				lengths[LITERALS..]
+					.iter_mut()
+					.for_each(|length| *length = 5);
+				self.literals.build(&lengths[..LITERALS])?;
+				self.distances.build(&lengths[LITERALS..])?;
+				self.state = State::Codes;
+			}
+			2 => {
+				self.dynamic_codes(source)?;
+				self.state = State::Codes;
+			}
+			_ => return Err(malformed("invalid block type")),
+		}
+		Ok(())
+	}
+
+	fn dynamic_codes(&mut self, source: &mut impl Source) -> Result<(), ImageError> {
+		let literal_count = self.bits(source, 5)? as usize + 257;
+		let distance_count = self.bits(source, 5)? as usize + 1;
+		let code_length_count = self.bits(source, 4)? as usize + 4;
+		if literal_count > 286 || distance_count > DISTANCES {
+			return Err(malformed("too many Huffman codes"));
+		}
+
+		let mut lengths = [0; LITERALS + DISTANCES];
+		for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+			lengths[symbol] = self.bits(source, 3)? as u8;
+		}
+		// The code length code is kept in `literals` while it's used
+		self.literals.build(&lengths[..CODE_LENGTH_ORDER.len()])?;
+
+		let total = literal_count + distance_count;
+		let mut index = 0;
+		while index < total {
+			let symbol = self.decode(source, false)?;
+			let (length, repeat) = match symbol {
+				0..=15 => (symbol as u8, 1),
+				16 => {
+					if index == 0 {
+						return Err(malformed("nothing to repeat"));
+					}
+					(lengths[index - 1], 3 + self.bits(source, 2)? as usize)
+				}
+				17 => (0, 3 + self.bits(source, 3)? as usize),
+				_ => (0, 11 + self.bits(source, 7)? as usize),
+			};
+			if index + repeat > total {
+				return Err(malformed("too many code lengths"));
+			}
+			lengths[index..index + repeat]
+				.iter_mut()
+				.for_each(|slot| *slot = length);
+			index += repeat;
+		}
+		if lengths[256] == 0 {
+			return Err(malformed("no end of block code"));
+		}
+		self.literals.build(&lengths[..literal_count])?;
+		self.distances
+			.build(&lengths[literal_count..literal_count + distance_count])?;
+		Ok(())
+	}
+
+	fn symbol(
+		&mut self,
+		source: &mut impl Source,
+		out: &mut [u8],
+		filled: &mut usize,
+	) -> Result<(), ImageError> {
+		let symbol = self.decode(source, false)?;
+		match symbol {
+			0..=255 => self.emit(symbol as u8, out, filled),
+			256 => self.end_block(),
+			_ => {
+				let symbol = symbol - 257;
+				if symbol >= LENGTH_BASE.len() {
+					return Err(malformed("invalid length code"));
+				}
+				let len = LENGTH_BASE[symbol] as usize
+					+ self.bits(source, LENGTH_EXTRA[symbol] as u32)? as usize;
+				let symbol = self.decode(source, true)?;
+				if symbol >= DISTANCES {
+					return Err(malformed("invalid distance code"));
+				}
+				let distance = DISTANCE_BASE[symbol] as usize
+					+ self.bits(source, DISTANCE_EXTRA[symbol] as u32)? as usize;
+				if distance > self.written {
+					return Err(malformed("distance is too far back"));
+				}
+				self.state = State::Copy { len, distance };
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	impl Source for &[u8] {
+		fn byte(&mut self) -> Result<u8, ImageError> {
+			let (&byte, rest) = self.split_first().ok_or(ImageError::Truncated)?;
+			*self = rest;
+			Ok(byte)
+		}
+	}
+
+	fn inflate(mut data: &[u8], len: usize) -> Result<Vec<u8>, ImageError> {
+		let mut out = vec![0; len];
+		Inflater::new().read(&mut data, &mut out)?;
+		Ok(out)
+	}
+
+	#[test]
+	fn blocks() {
+		// A stored block
+		assert_eq!(
+			inflate(&[1, 3, 0, 0xFC, 0xFF, 1, 2, 3], 3).unwrap(),
+			[1, 2, 3]
+		);
+		// "abcabcabca" with fixed codes, from zlib with Z_FIXED
+		let fixed = [0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x20, 0x00];
+		assert_eq!(inflate(&fixed, 10).unwrap(), b"abcabcabca");
+		// Reading past the last block
+		assert!(matches!(
+			inflate(&[1, 1, 0, 0xFE, 0xFF, 7], 2),
+			Err(ImageError::Malformed(_))
+		));
+		assert!(matches!(
+			inflate(&[1, 1, 0, 0xFE, 0xFE, 7], 1),
+			Err(ImageError::Malformed(_))
+		));
+		assert!(matches!(inflate(&[7], 1), Err(ImageError::Malformed(_))));
+	}
+
+	#[test]
+	fn partial_reads() {
+		let fixed = [0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x20, 0x00];
+		let mut inflater = Inflater::new();
+		let mut source = &fixed[..];
+		let mut out = [0; 4];
+		inflater.read(&mut source, &mut out).unwrap();
+		assert_eq!(out, *b"abca");
+		let mut out = [0; 6];
+		inflater.read(&mut source, &mut out).unwrap();
+		assert_eq!(out, *b"bcabca");
+	}
+}
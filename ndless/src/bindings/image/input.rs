@@ -0,0 +1,110 @@
+//! Reading the image file a few kilobytes at a time.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::ImageError;
+use crate::io::{ErrorKind, Read};
+
+const BUFFER_LEN: usize = 4096;
+
+pub(super) struct Input<R> {
+	reader: R,
+	buffer: Vec<u8>,
+	start: usize,
+	end: usize,
+}
+
+impl<R: Read> Input<R> {
+	pub fn new(reader: R) -> Self {
+		Input {
+			reader,
+			buffer: vec![0; BUFFER_LEN],
+			start: 0,
+			end: 0,
+		}
+	}
+
+	/// Reads more into the buffer, returning `false` at the end of the file.
+	fn fill(&mut self) -> Result<bool, ImageError> {
+		if self.start == self.end {
+			self.start = 0;
+			self.end = 0;
+		}
+		loop {
+			match self.reader.read(&mut self.buffer[self.end..]) {
+				Ok(len) => {
+					self.end += len;
+					return Ok(len > 0);
+				}
+				Err(err) if err.kind() == ErrorKind::Interrupted => {}
+				Err(err) => return Err(err.into()),
+			}
+		}
+	}
+
+	/// Returns the next `len` bytes without consuming them, or fewer at the
+	/// end of the file.
+	pub fn peek(&mut self, len: usize) -> Result<&[u8], ImageError> {
+		debug_assert!(len <= BUFFER_LEN);
+		if self.end - self.start < len {
+			self.buffer.copy_within(self.start..self.end, 0);
+			self.end -= self.start;
+			self.start = 0;
+			while self.end < len && self.fill()? {}
+		}
+		let end = self.end.min(self.start + len);
+		Ok(&self.buffer[self.start..end])
+	}
+
+	pub fn byte(&mut self) -> Result<u8, ImageError> {
+		if self.start == self.end && !self.fill()? {
+			return Err(ImageError::Truncated);
+		}
+		self.start += 1;
+		Ok(self.buffer[self.start - 1])
+	}
+
+	pub fn read_exact(&mut self, mut out: &mut [u8]) -> Result<(), ImageError> {
+		while !out.is_empty() {
+			if self.start == self.end && !self.fill()? {
+				return Err(ImageError::Truncated);
+			}
+			let len = out.len().min(self.end - self.start);
+			out[..len].copy_from_slice(&self.buffer[self.start..][..len]);
+			self.start += len;
+			out = &mut out[len..];
+		}
+		Ok(())
+	}
+
+	pub fn skip(&mut self, mut len: usize) -> Result<(), ImageError> {
+		while len > 0 {
+			if self.start == self.end && !self.fill()? {
+				return Err(ImageError::Truncated);
+			}
+			let skipped = len.min(self.end - self.start);
+			self.start += skipped;
+			len -= skipped;
+		}
+		Ok(())
+	}
+
+	pub fn u16_le(&mut self) -> Result<u16, ImageError> {
+		let mut bytes = [0; 2];
+		self.read_exact(&mut bytes)?;
+		Ok(u16::from_le_bytes(bytes))
+	}
+
+	pub fn u32_le(&mut self) -> Result<u32, ImageError> {
+		let mut bytes = [0; 4];
+		self.read_exact(&mut bytes)?;
+		Ok(u32::from_le_bytes(bytes))
+	}
+
+	pub fn u32_be(&mut self) -> Result<u32, ImageError> {
+		let mut bytes = [0; 4];
+		self.read_exact(&mut bytes)?;
+		Ok(u32::from_be_bytes(bytes))
+	}
+}
@@ -0,0 +1,497 @@
+//! PNG decoding.
+//!
+//! The CRC of every chunk up to the end of the image data is checked, but the
+//! zlib stream's own checksum isn't, as the image has already been produced
+//! by the time it's read.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::inflate::{Inflater, Source};
+use super::input::Input;
+use super::{check_size, ImageError, Rgba};
+use crate::io::Read;
+use crate::lcd::png::update_crc32;
+
+pub(super) const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The position and spacing of the pixels in each pass of an interlaced
+/// image: `(x, y, x step, y step)`.
+const PASSES: [(u32, u32, u32, u32); 7] = [
+	(0, 0, 8, 8),
+	(4, 0, 8, 8),
+	(0, 4, 4, 8),
+	(2, 0, 4, 4),
+	(0, 2, 2, 4),
+	(1, 0, 2, 2),
+	(0, 1, 1, 2),
+];
+
+mod color {
+	pub const GRAY: u8 = 0;
+	pub const RGB: u8 = 2;
+	pub const PALETTE: u8 = 3;
+	pub const GRAY_ALPHA: u8 = 4;
+	pub const RGBA: u8 = 6;
+}
+
+fn malformed(reason: &'static str) -> ImageError {
+	ImageError::Malformed(reason)
+}
+
+/// The image data, which is split across `IDAT` chunks.
+struct ImageData<'a, R> {
+	input: &'a mut Input<R>,
+	/// The bytes left in the current chunk.
+	remaining: u32,
+	/// The CRC of the current chunk so far.
+	crc: u32,
+}
+
+impl<R: Read> Source for ImageData<'_, R> {
+	fn byte(&mut self) -> Result<u8, ImageError> {
+		while self.remaining == 0 {
+			check_crc(self.input, self.crc)?;
+			let (len, kind) = chunk_header(self.input)?;
+			if kind != *b"IDAT" {
+				return Err(malformed("image data ends early"));
+			}
+			self.remaining = len;
+			self.crc = update_crc32(0, &kind);
+		}
+		let byte = self.input.byte()?;
+		self.remaining -= 1;
+		self.crc = update_crc32(self.crc, &[byte]);
+		Ok(byte)
+	}
+}
+
+fn chunk_header<R: Read>(input: &mut Input<R>) -> Result<(u32, [u8; 4]), ImageError> {
+	let len = input.u32_be()?;
+	if len > i32::MAX as u32 {
+		return Err(malformed("chunk is too long"));
+	}
+	let mut kind = [0; 4];
+	input.read_exact(&mut kind)?;
+	Ok((len, kind))
+}
+
+/// Skips `len` bytes of a chunk, returning its updated CRC.
+fn skip_data<R: Read>(input: &mut Input<R>, len: u32, mut crc: u32) -> Result<u32, ImageError> {
+	let mut left = len as usize;
+	let mut buffer = [0; 64];
+	while left > 0 {
+		let data = &mut buffer[..left.min(64)];
+		input.read_exact(data)?;
+		crc = update_crc32(crc, data);
+		left -= data.len();
+	}
+	Ok(crc)
+}
+
+fn check_crc<R: Read>(input: &mut Input<R>, crc: u32) -> Result<(), ImageError> {
+	if input.u32_be()? != crc {
+		return Err(malformed("chunk checksum doesn't match"));
+	}
+	Ok(())
+}
+
+pub(super) struct Png {
+	width: u32,
+	height: u32,
+	depth: u8,
+	color: u8,
+	interlaced: bool,
+	palette: Vec<Rgba>,
+	/// The color that's transparent in gray and RGB images, from the
+	/// `tRNS` chunk.
+	transparent: Option<[u16; 3]>,
+	inflater: Inflater,
+	/// What's left of the `IDAT` chunk being read.
+	remaining: u32,
+	crc: u32,
+	/// The current and previous row, each starting with the filter type.
+	row: Vec<u8>,
+	previous: Vec<u8>,
+	/// The current row, or the whole image once it's deinterlaced.
+	pixels: Vec<Rgba>,
+	y: u32,
+}
+
+impl Png {
+	pub fn new<R: Read>(input: &mut Input<R>) -> Result<Self, ImageError> {
+		input.skip(SIGNATURE.len())?;
+		let (len, kind) = chunk_header(input)?;
+		if kind != *b"IHDR" || len != 13 {
+			return Err(malformed("PNG doesn't start with a header"));
+		}
+		let mut header = [0; 13];
+		input.read_exact(&mut header)?;
+		check_crc(input, update_crc32(update_crc32(0, &kind), &header))?;
+		let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+		let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+		let (depth, color) = (header[8], header[9]);
+		check_size(width, height)?;
+		let depth_allowed = match color {
+			color::GRAY => [1, 2, 4, 8, 16].contains(&depth),
+			color::PALETTE => [1, 2, 4, 8].contains(&depth),
+			color::RGB | color::GRAY_ALPHA | color::RGBA => depth == 8 || depth == 16,
+			_ => return Err(malformed("unknown color type")),
+		};
+		if !depth_allowed {
+			return Err(malformed("invalid bit depth"));
+		}
+		if header[10] != 0 || header[11] != 0 {
+			return Err(ImageError::Unsupported(
+				"unknown compression or filter method",
+			));
+		}
+		let interlaced = match header[12] {
+			0 => false,
+			1 => true,
+			_ => return Err(ImageError::Unsupported("unknown interlace method")),
+		};
+
+		let mut png = Png {
+			width,
+			height,
+			depth,
+			color,
+			interlaced,
+			palette: Vec::new(),
+			transparent: None,
+			inflater: Inflater::new(),
+			remaining: 0,
+			crc: 0,
+			row: Vec::new(),
+			previous: Vec::new(),
+			pixels: Vec::new(),
+			y: 0,
+		};
+		png.read_chunks(input)?;
+		png.read_zlib_header(input)?;
+		Ok(png)
+	}
+
+	/// Reads the chunks before the image data.
+	fn read_chunks<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		loop {
+			let (len, kind) = chunk_header(input)?;
+			let mut crc = update_crc32(0, &kind);
+			match &kind {
+				b"IDAT" => {
+					if self.color == color::PALETTE && self.palette.is_empty() {
+						return Err(malformed("palette is missing"));
+					}
+					self.remaining = len;
+					self.crc = crc;
+					return Ok(());
+				}
+				b"PLTE" => {
+					if len % 3 != 0 || len > 256 * 3 || len == 0 {
+						return Err(malformed("invalid palette"));
+					}
+					let mut data = [0; 256 * 3];
+					let data = &mut data[..len as usize];
+					input.read_exact(data)?;
+					crc = update_crc32(crc, data);
+					self.palette = data
+						.chunks(3)
+						.map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+						.collect();
+				}
+				b"tRNS" => {
+					let mut data = [0; 256];
+					let data = data
+						.get_mut(..len as usize)
+						.ok_or_else(|| malformed("invalid transparency"))?;
+					input.read_exact(data)?;
+					crc = update_crc32(crc, data);
+					self.read_transparency(data)?;
+				}
+				b"IEND" => return Err(malformed("image data is missing")),
+				_ => {
+					// Ancillary chunks start with a lowercase letter, and can
+					// be skipped
+					if kind[0] & 0x20 == 0 {
+						return Err(ImageError::Unsupported("unknown critical chunk"));
+					}
+					crc = skip_data(input, len, crc)?;
+				}
+			}
+			check_crc(input, crc)?;
+		}
+	}
+
+	fn read_transparency(&mut self, data: &[u8]) -> Result<(), ImageError> {
+		let sample = |i: usize| u16::from_be_bytes([data[i * 2], data[i * 2 + 1]]);
+		match self.color {
+			color::PALETTE => {
+				// Transparency comes after the palette, but there may be
+				// fewer entries than colors
+				if data.len() > self.palette.len() {
+					return Err(malformed("invalid transparency"));
+				}
+				for (color, &alpha) in self.palette.iter_mut().zip(data) {
+					color[3] = alpha;
+				}
+			}
+			color::GRAY if data.len() == 2 => self.transparent = Some([sample(0); 3]),
+			color::RGB if data.len() == 6 => {
+				self.transparent = Some([sample(0), sample(1), sample(2)])
+			}
+			_ => return Err(malformed("invalid transparency")),
+		}
+		Ok(())
+	}
+
+	fn read_zlib_header<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		let mut data = ImageData {
+			input,
+			remaining: self.remaining,
+			crc: self.crc,
+		};
+		let (method, flags) = (data.byte()?, data.byte()?);
+		self.remaining = data.remaining;
+		self.crc = data.crc;
+		if !(method as u16 * 256 + flags as u16).is_multiple_of(31) {
+			return Err(malformed("invalid zlib header"));
+		}
+		if method & 0xF != 8 || method >> 4 > 7 || flags & 0x20 != 0 {
+			return Err(ImageError::Unsupported("unknown zlib compression"));
+		}
+		Ok(())
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn has_alpha(&self) -> bool {
+		match self.color {
+			color::GRAY_ALPHA | color::RGBA => true,
+			color::PALETTE => self.palette.iter().any(|color| color[3] != 255),
+			_ => self.transparent.is_some(),
+		}
+	}
+
+	fn channels(&self) -> usize {
+		match self.color {
+			color::RGB => 3,
+			color::GRAY_ALPHA => 2,
+			color::RGBA => 4,
+			_ => 1,
+		}
+	}
+
+	/// The number of bytes in a row `width` pixels wide, without the filter
+	/// type.
+	fn row_len(&self, width: u32) -> usize {
+		(width as usize * self.channels() * self.depth as usize).div_ceil(8)
+	}
+
+	pub fn next_row<R: Read>(
+		&mut self,
+		input: &mut Input<R>,
+	) -> Result<Option<(u32, &[Rgba])>, ImageError> {
+		if self.y >= self.height {
+			return Ok(None);
+		}
+		let y = self.y;
+		self.y += 1;
+		if self.interlaced {
+			if y == 0 {
+				self.deinterlace(input)?;
+				self.finish(input)?;
+			}
+			let width = self.width as usize;
+			return Ok(Some((y, &self.pixels[y as usize * width..][..width])));
+		}
+
+		if y == 0 {
+			self.start_pass(self.width);
+			self.pixels = vec![[0; 4]; self.width as usize];
+		}
+		self.read_row(input)?;
+		if self.y == self.height {
+			self.finish(input)?;
+		}
+		let mut pixels = core::mem::take(&mut self.pixels);
+		self.expand(&mut pixels);
+		self.pixels = pixels;
+		Ok(Some((y, &self.pixels)))
+	}
+
+	/// Checks the rest of the chunk that the image data ends in, which
+	/// includes the zlib checksum. Any chunks after it are ignored.
+	fn finish<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		let crc = skip_data(input, self.remaining, self.crc)?;
+		self.remaining = 0;
+		check_crc(input, crc)
+	}
+
+	fn start_pass(&mut self, width: u32) {
+		let len = self.row_len(width) + 1;
+		self.row.clear();
+		self.row.resize(len, 0);
+		self.previous.clear();
+		self.previous.resize(len, 0);
+	}
+
+	fn deinterlace<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		let mut image = vec![[0; 4]; self.width as usize * self.height as usize];
+		let mut pixels = Vec::new();
+		for &(x0, y0, dx, dy) in &PASSES {
+			// Passes with no pixels are left out completely
+			if self.width <= x0 || self.height <= y0 {
+				continue;
+			}
+			let width = (self.width - x0).div_ceil(dx);
+			let height = (self.height - y0).div_ceil(dy);
+			self.start_pass(width);
+			pixels.resize(width as usize, [0; 4]);
+			for row in 0..height {
+				self.read_row(input)?;
+				self.expand(&mut pixels);
+				let y = y0 + row * dy;
+				for (i, &pixel) in pixels.iter().enumerate() {
+					let x = x0 + i as u32 * dx;
+					image[(y * self.width + x) as usize] = pixel;
+				}
+			}
+		}
+		self.pixels = image;
+		Ok(())
+	}
+
+	/// Reads and unfilters the next row into `self.row`.
+	fn read_row<R: Read>(&mut self, input: &mut Input<R>) -> Result<(), ImageError> {
+		core::mem::swap(&mut self.row, &mut self.previous);
+		let mut row = core::mem::take(&mut self.row);
+		let mut data = ImageData {
+			input,
+			remaining: self.remaining,
+			crc: self.crc,
+		};
+		let result = self.inflater.read(&mut data, &mut row);
+		self.remaining = data.remaining;
+		self.crc = data.crc;
+		self.row = row;
+		result?;
+
+		// The filters work on whole pixels, or bytes if they're smaller
+		let step = (self.channels() * self.depth as usize / 8).max(1);
+		let (filter, row) = self.row.split_first_mut().unwrap();
+		let previous = &self.previous[1..];
+		match *filter {
+			0 => {}
+			1 => {
+				for i in step..row.len() {
+					row[i] = row[i].wrapping_add(row[i - step]);
+				}
+			}
+			2 => {
+				for (byte, &above) in row.iter_mut().zip(previous) {
+					*byte = byte.wrapping_add(above);
+				}
+			}
+			3 => {
+				for i in 0..row.len() {
+					let left = if i >= step { row[i - step] } else { 0 };
+					let average = (left as u16 + previous[i] as u16) / 2;
+					row[i] = row[i].wrapping_add(average as u8);
+				}
+			}
+			4 => {
+				for i in 0..row.len() {
+					let (left, above_left) = if i >= step {
+						(row[i - step], previous[i - step])
+					} else {
+						(0, 0)
+					};
+					row[i] = row[i].wrapping_add(paeth(left, previous[i], above_left));
+				}
+			}
+			_ => return Err(malformed("unknown filter type")),
+		}
+		Ok(())
+	}
+
+	/// Converts `self.row` to RGBA.
+	fn expand(&self, pixels: &mut [Rgba]) {
+		let row = &self.row[1..];
+		let depth = self.depth as usize;
+		// The value of a sample, and the same scaled to 8 bits
+		let sample = |i: usize| -> (u16, u8) {
+			match depth {
+				16 => {
+					let value = u16::from_be_bytes([row[i * 2], row[i * 2 + 1]]);
+					(value, (value >> 8) as u8)
+				}
+				8 => (row[i] as u16, row[i]),
+				_ => {
+					let bit = i * depth;
+					let mask = (1 << depth) - 1;
+					let value = (row[bit / 8] >> (8 - depth - bit % 8)) & mask;
+					(value as u16, value * (255 / mask))
+				}
+			}
+		};
+		let channels = self.channels();
+		for (x, pixel) in pixels.iter_mut().enumerate() {
+			let i = x * channels;
+			*pixel = match self.color {
+				color::GRAY => {
+					let (value, gray) = sample(i);
+					let alpha = if self.transparent == Some([value; 3]) {
+						0
+					} else {
+						255
+					};
+					[gray, gray, gray, alpha]
+				}
+				color::RGB => {
+					let (r, g, b) = (sample(i), sample(i + 1), sample(i + 2));
+					let alpha = if self.transparent == Some([r.0, g.0, b.0]) {
+						0
+					} else {
+						255
+					};
+					[r.1, g.1, b.1, alpha]
+				}
+				color::PALETTE => {
+					let index = sample(i).0 as usize;
+					// Out of range colors are an error, but most decoders
+					// show them as black
+					self.palette.get(index).copied().unwrap_or([0, 0, 0, 255])
+				}
+				color::GRAY_ALPHA => {
+					let (gray, alpha) = (sample(i).1, sample(i + 1).1);
+					[gray, gray, gray, alpha]
+				}
+				_ => [
+					sample(i).1,
+					sample(i + 1).1,
+					sample(i + 2).1,
+					sample(i + 3).1,
+				],
+			};
+		}
+	}
+}
+
+fn paeth(left: u8, above: u8, above_left: u8) -> u8 {
+	let estimate = left as i16 + above as i16 - above_left as i16;
+	let distance = |value: u8| (estimate - value as i16).abs();
+	if distance(left) <= distance(above) && distance(left) <= distance(above_left) {
+		left
+	} else if distance(above) <= distance(above_left) {
+		above
+	} else {
+		above_left
+	}
+}
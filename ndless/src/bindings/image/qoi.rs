@@ -0,0 +1,137 @@
+//! [QOI](https://qoiformat.org) decoding.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::input::Input;
+use super::{check_size, ImageError, Rgba};
+use crate::io::Read;
+
+pub(super) const SIGNATURE: &[u8] = b"qoif";
+
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_MASK: u8 = 0xC0;
+
+fn hash([r, g, b, a]: Rgba) -> usize {
+	(r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
+
+pub(super) struct Qoi {
+	width: u32,
+	height: u32,
+	channels: u8,
+	/// Recently seen pixels, by their hash.
+	seen: [Rgba; 64],
+	previous: Rgba,
+	/// The number of times left to repeat `previous`.
+	run: u8,
+	pixels: Vec<Rgba>,
+	y: u32,
+}
+
+impl Qoi {
+	pub fn new<R: Read>(input: &mut Input<R>) -> Result<Self, ImageError> {
+		input.skip(SIGNATURE.len())?;
+		let width = input.u32_be()?;
+		let height = input.u32_be()?;
+		let channels = input.byte()?;
+		let colorspace = input.byte()?;
+		if (channels != 3 && channels != 4) || colorspace > 1 {
+			return Err(ImageError::Malformed("invalid QOI header"));
+		}
+		check_size(width, height)?;
+		Ok(Qoi {
+			width,
+			height,
+			channels,
+			seen: [[0; 4]; 64],
+			previous: [0, 0, 0, 255],
+			run: 0,
+			pixels: vec![[0; 4]; width as usize],
+			y: 0,
+		})
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn has_alpha(&self) -> bool {
+		self.channels == 4
+	}
+
+	pub fn next_row<R: Read>(
+		&mut self,
+		input: &mut Input<R>,
+	) -> Result<Option<(u32, &[Rgba])>, ImageError> {
+		if self.y >= self.height {
+			return Ok(None);
+		}
+		for x in 0..self.pixels.len() {
+			self.pixels[x] = self.next_pixel(input)?;
+		}
+		self.y += 1;
+		Ok(Some((self.y - 1, &self.pixels)))
+	}
+
+	fn next_pixel<R: Read>(&mut self, input: &mut Input<R>) -> Result<Rgba, ImageError> {
+		if self.run > 0 {
+			self.run -= 1;
+			return Ok(self.previous);
+		}
+		let [r, g, b, a] = self.previous;
+		let op = input.byte()?;
+		let pixel = match op {
+			OP_RGB => {
+				let mut rgb = [0; 3];
+				input.read_exact(&mut rgb)?;
+				[rgb[0], rgb[1], rgb[2], a]
+			}
+			OP_RGBA => {
+				let mut rgba = [0; 4];
+				input.read_exact(&mut rgba)?;
+				rgba
+			}
+			_ => match op & OP_MASK {
+				OP_INDEX => self.seen[op as usize],
+				OP_DIFF => {
+					let diff = |shift: u8| ((op >> shift) & 3).wrapping_sub(2);
+					[
+						r.wrapping_add(diff(4)),
+						g.wrapping_add(diff(2)),
+						b.wrapping_add(diff(0)),
+						a,
+					]
+				}
+				OP_LUMA => {
+					let green = (op & 0x3F).wrapping_sub(32);
+					let next = input.byte()?;
+					let red = green.wrapping_add(next >> 4).wrapping_sub(8);
+					let blue = green.wrapping_add(next & 0xF).wrapping_sub(8);
+					[
+						r.wrapping_add(red),
+						g.wrapping_add(green),
+						b.wrapping_add(blue),
+						a,
+					]
+				}
+				_ => {
+					// A run, which is stored minus one
+					self.run = op & 0x3F;
+					self.previous
+				}
+			},
+		};
+		self.seen[hash(pixel)] = pixel;
+		self.previous = pixel;
+		Ok(pixel)
+	}
+}
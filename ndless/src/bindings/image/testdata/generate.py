@@ -0,0 +1,695 @@
+#!/usr/bin/env python3
+"""Writes the images that the decoders are tested against.
+
+Each picture is saved as `name.rgba`: its width and height as little-endian
+32-bit numbers, then 8-bit RGBA pixels from the top left. It's then encoded in
+as many ways as possible, as `name-variant.png`, `.bmp` or `.qoi`, and every
+one of those must decode to exactly the same pixels. Files starting with `x-`
+are damaged or invalid, and must fail to decode.
+
+Only the standard library is used, with zlib for compression, so that the
+files can be regenerated anywhere. Run this from any directory.
+"""
+
+import os
+import struct
+import zlib
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+W, H = 13, 11
+
+
+def save(name, data):
+    with open(os.path.join(OUT, name), "wb") as f:
+        f.write(data)
+
+
+def reference(name, width, height, pixels):
+    assert len(pixels) == width * height
+    data = struct.pack("<II", width, height)
+    data += b"".join(bytes(pixel) for pixel in pixels)
+    save(name + ".rgba", data)
+
+
+def grid(width, height, f):
+    return [f(x, y) for y in range(height) for x in range(width)]
+
+
+# PNG
+
+
+def chunk(kind, data):
+    crc = zlib.crc32(kind + data) & 0xFFFFFFFF
+    return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", crc)
+
+
+def paeth(a, b, c):
+    p = a + b - c
+    pa, pb, pc = abs(p - a), abs(p - b), abs(p - c)
+    if pa <= pb and pa <= pc:
+        return a
+    return b if pb <= pc else c
+
+
+def filter_row(kind, row, previous, step):
+    """Filters a row. Unknown filter types are written as type 0 would be."""
+    out = bytearray([kind])
+    for i, byte in enumerate(row):
+        left = row[i - step] if i >= step else 0
+        above = previous[i]
+        above_left = previous[i - step] if i >= step else 0
+        predicted = [
+            0,
+            left,
+            above,
+            (left + above) // 2,
+            paeth(left, above, above_left),
+        ][kind % 5]
+        out.append((byte - predicted) & 0xFF)
+    return out
+
+
+def pack(samples, depth):
+    """Packs samples of `depth` bits into bytes, most significant first."""
+    if depth == 16:
+        return b"".join(struct.pack(">H", sample) for sample in samples)
+    if depth == 8:
+        return bytes(samples)
+    out = bytearray()
+    bits = 0
+    count = 0
+    for sample in samples:
+        bits = (bits << depth) | sample
+        count += depth
+        if count == 8:
+            out.append(bits)
+            bits = count = 0
+    if count:
+        out.append(bits << (8 - count))
+    return bytes(out)
+
+
+PASSES = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+]
+
+
+def png(
+    width,
+    height,
+    color,
+    depth,
+    samples,
+    interlaced=False,
+    filters=None,
+    level=9,
+    strategy=zlib.Z_DEFAULT_STRATEGY,
+    idat_size=None,
+    before=(),
+    after=(),
+):
+    """Encodes an image, where `samples(x, y)` is a list of the channels of
+    each pixel. `filters` is the filter type of each row, in turn."""
+    channels = {0: 1, 2: 3, 3: 1, 4: 2, 6: 4}[color]
+    step = max(1, channels * depth // 8)
+    filters = filters or [0, 1, 2, 3, 4]
+    raw = bytearray()
+    row_number = 0
+    passes = PASSES if interlaced else [(0, 0, 1, 1)]
+    for x0, y0, dx, dy in passes:
+        xs = range(x0, width, dx)
+        ys = range(y0, height, dy)
+        if not xs or not ys:
+            continue
+        previous = None
+        for y in ys:
+            row = pack([s for x in xs for s in samples(x, y)], depth)
+            previous = previous or bytes(len(row))
+            raw += filter_row(filters[row_number % len(filters)], row, previous, step)
+            previous = row
+            row_number += 1
+    compressor = zlib.compressobj(level, zlib.DEFLATED, 15, 9, strategy)
+    data = compressor.compress(bytes(raw)) + compressor.flush()
+    header = struct.pack(">IIBBBBB", width, height, depth, color, 0, 0, int(interlaced))
+    out = b"\x89PNG\r\n\x1a\n" + chunk(b"IHDR", header)
+    for extra in before:
+        out += extra
+    idat_size = idat_size or len(data)
+    for i in range(0, len(data), idat_size):
+        out += chunk(b"IDAT", data[i : i + idat_size])
+    for extra in after:
+        out += extra
+    return out + chunk(b"IEND", b"")
+
+
+def png_variants(name, *args, **kwargs):
+    """Saves `name.png`, and an interlaced copy."""
+    save(name + ".png", png(*args, **kwargs))
+    save(name + "interlaced.png", png(*args, interlaced=True, **kwargs))
+
+
+def gradient(x, y):
+    return [x * 255 // (W - 1), y * 255 // (H - 1), (x * y * 7) % 256, 255]
+
+
+def translucent(x, y):
+    return [(x * 40) % 256, 255 - y * 20, (x + y) * 9, (x * 19 + y * 23) % 256]
+
+
+reference("gradient", W, H, grid(W, H, gradient))
+png_variants("gradient-rgb8", W, H, 2, 8, lambda x, y: gradient(x, y)[:3])
+for kind in range(5):
+    save(
+        "gradient-filter%d.png" % kind,
+        png(W, H, 2, 8, lambda x, y: gradient(x, y)[:3], filters=[kind]),
+    )
+save(
+    "gradient-stored.png",
+    png(W, H, 2, 8, lambda x, y: gradient(x, y)[:3], level=0),
+)
+save(
+    "gradient-fixed.png",
+    png(W, H, 2, 8, lambda x, y: gradient(x, y)[:3], strategy=zlib.Z_FIXED),
+)
+save(
+    "gradient-split.png",
+    png(
+        W,
+        H,
+        2,
+        8,
+        lambda x, y: gradient(x, y)[:3],
+        idat_size=7,
+        before=[chunk(b"gAMA", struct.pack(">I", 45455)), chunk(b"tEXt", b"Title\0Test")],
+        after=[chunk(b"tEXt", b"Comment\0After the image")],
+    ),
+)
+# 16-bit samples, where only the high byte is kept
+png_variants(
+    "gradient-rgb16",
+    W,
+    H,
+    2,
+    16,
+    lambda x, y: [v << 8 | (x * 31 + y) % 256 for v in gradient(x, y)[:3]],
+)
+
+reference("translucent", W, H, grid(W, H, translucent))
+png_variants("translucent-rgba8", W, H, 6, 8, translucent)
+save(
+    "translucent-rgba16.png",
+    png(W, H, 6, 16, lambda x, y: [v * 257 for v in translucent(x, y)]),
+)
+
+for depth in [1, 2, 4, 8, 16]:
+
+    def sample(x, y, depth=depth):
+        if depth == 16:
+            return (x * 4099 + y * 771 + x * y * 13) % 65536
+        return (x * 3 + y * 5 + x * y) % (1 << depth)
+
+    def scale(value, depth=depth):
+        if depth == 16:
+            return value >> 8
+        return value * 255 // ((1 << depth) - 1)
+
+    name = "gray%d" % depth
+    reference(name, W, H, grid(W, H, lambda x, y: [scale(sample(x, y))] * 3 + [255]))
+    png_variants(name + "-png", W, H, 0, depth, lambda x, y: [sample(x, y)])
+
+# A transparent gray, and RGB colors that are transparent
+reference(
+    "graykey",
+    W,
+    H,
+    grid(W, H, lambda x, y: [(x * y) % 7 * 40] * 3 + [0 if (x * y) % 7 == 2 else 255]),
+)
+png_variants(
+    "graykey-png",
+    W,
+    H,
+    0,
+    8,
+    lambda x, y: [(x * y) % 7 * 40],
+    before=[chunk(b"tRNS", struct.pack(">H", 80))],
+)
+
+
+def rgb16_key(x, y):
+    # The diagonal is transparent. Only the low byte of blue separates it
+    # from the top right pixel, which isn't.
+    if x == y or (x, y) == (W - 1, 0):
+        return [200 << 8 | 0x12, 100 << 8 | 0x12, 50 << 8 | (0x34 if x == y else 0x35)]
+    return [(x * 10) << 8 | 0x12, (y * 10) << 8 | 0x12, 50 << 8]
+
+
+reference(
+    "rgbkey",
+    W,
+    H,
+    grid(W, H, lambda x, y: [v >> 8 for v in rgb16_key(x, y)] + [0 if x == y else 255]),
+)
+
+
+png_variants(
+    "rgbkey-rgb16",
+    W,
+    H,
+    2,
+    16,
+    rgb16_key,
+    before=[chunk(b"tRNS", struct.pack(">HHH", 200 << 8 | 0x12, 100 << 8 | 0x12, 50 << 8 | 0x34))],
+)
+# Only the top left pixel matches the key in the 8-bit version
+reference(
+    "rgbkey8",
+    W,
+    H,
+    grid(W, H, lambda x, y: [x * 10, y * 10, 50 + (x != y), 0 if x == y == 0 else 255]),
+)
+save(
+    "rgbkey8-png.png",
+    png(
+        W,
+        H,
+        2,
+        8,
+        lambda x, y: [x * 10, y * 10, 50 + (x != y)],
+        before=[chunk(b"tRNS", struct.pack(">HHH", 0, 0, 50))],
+    ),
+)
+
+reference(
+    "grayalpha",
+    W,
+    H,
+    grid(W, H, lambda x, y: [x * 20] * 3 + [y * 25]),
+)
+png_variants("grayalpha-8", W, H, 4, 8, lambda x, y: [x * 20, y * 25])
+save("grayalpha-16.png", png(W, H, 4, 16, lambda x, y: [x * 20 * 257, y * 25 * 257]))
+
+
+def palette_colors(count):
+    return [[(i * 67) % 256, (i * 131 + 40) % 256, (i * 29 + 200) % 256] for i in range(count)]
+
+
+def palette_index(count):
+    return lambda x, y: (x + y * 2 + x * y) % count
+
+
+for depth in [1, 2, 4, 8]:
+    count = min(1 << depth, 200)
+    colors = palette_colors(count)
+    index = palette_index(count)
+    name = "palette%d" % depth
+    reference(name, W, H, grid(W, H, lambda x, y: colors[index(x, y)] + [255]))
+    plte = chunk(b"PLTE", bytes(c for color in colors for c in color))
+    png_variants(name + "-png", W, H, 3, depth, lambda x, y: [index(x, y)], before=[plte])
+
+# Transparency for only the first few palette entries
+colors = palette_colors(16)
+alphas = [0, 60, 120, 180]
+index = palette_index(16)
+reference(
+    "palettealpha",
+    W,
+    H,
+    grid(
+        W,
+        H,
+        lambda x, y: colors[index(x, y)]
+        + [alphas[index(x, y)] if index(x, y) < len(alphas) else 255],
+    ),
+)
+png_variants(
+    "palettealpha-png",
+    W,
+    H,
+    3,
+    4,
+    lambda x, y: [index(x, y)],
+    before=[
+        chunk(b"PLTE", bytes(c for color in colors for c in color)),
+        chunk(b"tRNS", bytes(alphas)),
+    ],
+)
+
+# Sizes that leave some interlacing passes empty
+for width, height in [(1, 1), (1, 9), (9, 1), (3, 2)]:
+    name = "size%dx%d" % (width, height)
+    reference(name, width, height, grid(width, height, lambda x, y: [x * 30, y * 25, 99, 255]))
+    png_variants(name + "-png", width, height, 2, 8, lambda x, y: [x * 30, y * 25, 99])
+
+# Invalid PNGs
+good = png(W, H, 2, 8, lambda x, y: gradient(x, y)[:3])
+idat = good.index(b"IDAT")
+idat_len = struct.unpack(">I", good[idat - 4 : idat])[0]
+crc = idat + 4 + idat_len
+save("x-crc.png", good[:crc] + bytes([good[crc] ^ 1]) + good[crc + 1 :])
+save("x-truncated.png", good[: len(good) // 2])
+save("x-header.png", good[:20])
+save(
+    "x-filter.png",
+    png(W, H, 2, 8, lambda x, y: gradient(x, y)[:3], filters=[5]),
+)
+save(
+    "x-depth.png",
+    b"\x89PNG\r\n\x1a\n"
+    + chunk(b"IHDR", struct.pack(">IIBBBBB", W, H, 4, 2, 0, 0, 0))
+    + good[good.index(b"IDAT") - 4 :],
+)
+save(
+    "x-noimage.png",
+    b"\x89PNG\r\n\x1a\n"
+    + chunk(b"IHDR", struct.pack(">IIBBBBB", W, H, 8, 2, 0, 0, 0))
+    + chunk(b"IEND", b""),
+)
+save(
+    "x-critical.png",
+    good[:33] + chunk(b"CRIT", b"unknown") + good[33:],
+)
+save(
+    "x-empty.png",
+    b"\x89PNG\r\n\x1a\n"
+    + chunk(b"IHDR", struct.pack(">IIBBBBB", 0, H, 8, 2, 0, 0, 0))
+    + good[33:],
+)
+save(
+    "x-nopalette.png",
+    png(W, H, 3, 8, lambda x, y: [0]),
+)
+zlib_start = idat + 4
+bad_zlib = bytearray(good)
+bad_zlib[zlib_start + 1] ^= 0x01
+bad_zlib[crc : crc + 4] = struct.pack(
+    ">I", zlib.crc32(bytes(bad_zlib[idat:crc])) & 0xFFFFFFFF
+)
+save("x-zlib.png", bytes(bad_zlib))
+
+
+# BMP
+
+
+def bmp(width, height, bits, rows, palette=(), compression=0, masks=None, header=40,
+        top_down=False, image=None):
+    """Encodes a BMP, where `rows` is the data of each row from the top,
+    without padding. `image` replaces the rows, for run-length encoding."""
+    if image is None:
+        stride = (width * bits + 31) // 32 * 4
+        order = rows if top_down else rows[::-1]
+        image = b"".join(row + bytes(stride - len(row)) for row in order)
+    if header == 12:
+        info = struct.pack("<IHHHH", 12, width, height, 1, bits)
+        colors = b"".join(bytes([b, g, r]) for r, g, b in palette)
+    else:
+        info = struct.pack(
+            "<IiiHHIIiiII",
+            header,
+            width,
+            -height if top_down else height,
+            1,
+            bits,
+            compression,
+            len(image),
+            2835,
+            2835,
+            len(palette),
+            0,
+        )
+        colors = b"".join(bytes([b, g, r, 0]) for r, g, b in palette)
+        if header > 40:
+            fields = struct.pack("<4I", *(masks or [0, 0, 0, 0]))
+            info += fields[: header - 40]
+            info += bytes(header - len(info))
+        elif masks:
+            info += struct.pack("<%dI" % len(masks), *masks)
+    offset = 14 + len(info) + len(colors)
+    return b"BM" + struct.pack("<IHHI", offset + len(image), 0, 0, offset) + info + colors + image
+
+
+def rows_of(width, height, f):
+    return [b"".join(f(x, y) for x in range(width)) for y in range(height)]
+
+
+def bgr(x, y):
+    r, g, b, _ = gradient(x, y)
+    return bytes([b, g, r])
+
+
+save("gradient-bmp24.bmp", bmp(W, H, 24, rows_of(W, H, bgr)))
+save("gradient-topdown.bmp", bmp(W, H, 24, rows_of(W, H, bgr), top_down=True))
+save("gradient-bmp32.bmp", bmp(W, H, 32, rows_of(W, H, lambda x, y: bgr(x, y) + b"\0")))
+save("gradient-v5.bmp", bmp(W, H, 24, rows_of(W, H, bgr), header=124))
+save("gradient-os2.bmp", bmp(W, H, 24, rows_of(W, H, bgr), header=12))
+
+
+def bgra(x, y):
+    r, g, b, a = translucent(x, y)
+    return bytes([b, g, r, a])
+
+
+alpha_masks = [0xFF0000, 0xFF00, 0xFF, 0xFF000000]
+save(
+    "translucent-v5.bmp",
+    bmp(W, H, 32, rows_of(W, H, bgra), compression=3, masks=alpha_masks, header=124),
+)
+save(
+    "translucent-v4.bmp",
+    bmp(W, H, 32, rows_of(W, H, bgra), compression=3, masks=alpha_masks, header=108),
+)
+save(
+    "translucent-alphabitfields.bmp",
+    bmp(W, H, 32, rows_of(W, H, bgra), compression=6, masks=alpha_masks),
+)
+# Red in the low byte instead of blue, in the smallest header with room
+# for all four masks
+save(
+    "translucent-rgba.bmp",
+    bmp(
+        W,
+        H,
+        32,
+        rows_of(W, H, lambda x, y: bytes(translucent(x, y))),
+        compression=3,
+        masks=[0xFF, 0xFF00, 0xFF0000, 0xFF000000],
+        header=56,
+    ),
+)
+
+
+def five_bits(x, y):
+    return [x * 2 % 32, y * 3 % 32, (x + y) % 32]
+
+
+reference(
+    "rgb555",
+    W,
+    H,
+    grid(W, H, lambda x, y: [v * 255 // 31 for v in five_bits(x, y)] + [255]),
+)
+save(
+    "rgb555-bmp16.bmp",
+    bmp(
+        W,
+        H,
+        16,
+        rows_of(W, H, lambda x, y: struct.pack("<H", five_bits(x, y)[0] << 10 | five_bits(x, y)[1] << 5 | five_bits(x, y)[2])),
+    ),
+)
+
+
+def rgb565(x, y):
+    return [x * 2 % 32, y * 6 % 64, (x + y) % 32]
+
+
+reference(
+    "rgb565",
+    W,
+    H,
+    grid(
+        W,
+        H,
+        lambda x, y: [
+            rgb565(x, y)[0] * 255 // 31,
+            rgb565(x, y)[1] * 255 // 63,
+            rgb565(x, y)[2] * 255 // 31,
+            255,
+        ],
+    ),
+)
+save(
+    "rgb565-bitfields.bmp",
+    bmp(
+        W,
+        H,
+        16,
+        rows_of(W, H, lambda x, y: struct.pack("<H", rgb565(x, y)[0] << 11 | rgb565(x, y)[1] << 5 | rgb565(x, y)[2])),
+        compression=3,
+        masks=[0xF800, 0x07E0, 0x001F],
+    ),
+)
+
+for depth in [1, 4, 8]:
+    count = min(1 << depth, 200)
+    colors = palette_colors(count)
+    index = palette_index(count)
+    rows = [pack([index(x, y) for x in range(W)], depth) for y in range(H)]
+    save("palette%d-bmp.bmp" % depth, bmp(W, H, depth, rows, palette=colors))
+    save("palette%d-os2.bmp" % depth, bmp(W, H, depth, rows, palette=colors, header=12))
+    save(
+        "palette%d-topdown.bmp" % depth,
+        bmp(W, H, depth, rows, palette=colors, top_down=True),
+    )
+
+
+def rle(width, height, index, four_bit):
+    """Run-length encodes an image, using every kind of instruction. Pixels
+    with index 0 are skipped over where possible, as that's what skipped
+    pixels decode to."""
+    out = bytearray()
+    for y in reversed(range(height)):
+        row = [index(x, y) for x in range(width)]
+        x = 0
+        while x < width:
+            if all(value == 0 for value in row[x:]):
+                break
+            zeros = 0
+            while x + zeros < width and row[x + zeros] == 0:
+                zeros += 1
+            if zeros >= 2:
+                out += bytes([0, 2, zeros, 0])
+                x += zeros
+                continue
+            run = 1
+            while x + run < width and row[x + run] == row[x] and run < 255:
+                run += 1
+            if run >= 2 or x + 2 >= width:
+                value = row[x] * 17 if four_bit else row[x]
+                out += bytes([run, value])
+                x += run
+                continue
+            # Literal pixels, until there's a run
+            end = x + 1
+            while end < width and end - x < 255 and (end + 1 >= width or row[end] != row[end + 1]):
+                end += 1
+            literal = row[x:end]
+            if len(literal) < 3:
+                out += bytes([1, literal[0] * 17 if four_bit else literal[0]])
+                x += 1
+                continue
+            out += bytes([0, len(literal)])
+            data = pack(literal, 4) if four_bit else bytes(literal)
+            out += data + bytes(len(data) % 2)
+            x = end
+        if y == 0:
+            out += bytes([0, 1])
+        else:
+            out += bytes([0, 0])
+    return bytes(out)
+
+
+for depth in [4, 8]:
+    count = 1 << depth if depth == 4 else 200
+    colors = palette_colors(count)
+
+    def index(x, y, count=count):
+        if 3 <= x < 8 and 2 <= y < 5:
+            return 0
+        if y == 6:
+            return 5
+        return (x // 3 + y * 2) % count
+
+    name = "runs%d" % depth
+    reference(name, W, H, grid(W, H, lambda x, y: colors[index(x, y)] + [255]))
+    save(
+        name + "-rle.bmp",
+        bmp(W, H, depth, None, palette=colors, compression=2 if depth == 4 else 1,
+            image=rle(W, H, index, depth == 4)),
+    )
+
+good = bmp(W, H, 24, rows_of(W, H, bgr))
+save("x-bmptruncated.bmp", good[: len(good) - 20])
+save("x-bmpjpeg.bmp", good[:30] + struct.pack("<I", 4) + good[34:])
+save("x-bmpbits.bmp", good[:28] + struct.pack("<H", 7) + good[30:])
+save("x-bmpheader.bmp", good[:14] + struct.pack("<I", 20) + good[18:])
+
+
+# QOI
+
+
+def qoi(width, height, pixels, channels=4):
+    """Encodes an image as described at https://qoiformat.org."""
+    out = bytearray(b"qoif" + struct.pack(">IIBB", width, height, channels, 0))
+    seen = [(0, 0, 0, 0)] * 64
+    previous = (0, 0, 0, 255)
+    run = 0
+    for i, pixel in enumerate(pixels):
+        pixel = tuple(pixel)
+        if pixel == previous:
+            run += 1
+            if run == 62 or i == len(pixels) - 1:
+                out.append(0xC0 | (run - 1))
+                run = 0
+            continue
+        if run:
+            out.append(0xC0 | (run - 1))
+            run = 0
+        r, g, b, a = pixel
+        position = (r * 3 + g * 5 + b * 7 + a * 11) % 64
+        if seen[position] == pixel:
+            out.append(position)
+        else:
+            seen[position] = pixel
+            if a == previous[3]:
+                dr = (r - previous[0] + 128) % 256 - 128
+                dg = (g - previous[1] + 128) % 256 - 128
+                db = (b - previous[2] + 128) % 256 - 128
+                if -2 <= dr <= 1 and -2 <= dg <= 1 and -2 <= db <= 1:
+                    out.append(0x40 | (dr + 2) << 4 | (dg + 2) << 2 | (db + 2))
+                elif -32 <= dg <= 31 and -8 <= dr - dg <= 7 and -8 <= db - dg <= 7:
+                    out.append(0x80 | (dg + 32))
+                    out.append((dr - dg + 8) << 4 | (db - dg + 8))
+                else:
+                    out += bytes([0xFE, r, g, b])
+            else:
+                out += bytes([0xFF, r, g, b, a])
+        previous = pixel
+    return bytes(out + b"\0" * 7 + b"\1")
+
+
+save("gradient-qoi.qoi", qoi(W, H, grid(W, H, gradient), 3))
+save("translucent-qoi.qoi", qoi(W, H, grid(W, H, translucent)))
+
+
+def smooth(x, y):
+    # Small steps between pixels, for the difference instructions
+    return [100 + x, 100 + y + x // 2, 100 + (x * 5) // 2, 255]
+
+
+reference("smooth", W, H, grid(W, H, smooth))
+save("smooth-qoi.qoi", qoi(W, H, grid(W, H, smooth), 3))
+colors = palette_colors(4)
+reference(
+    "blocks",
+    W,
+    H,
+    grid(W, H, lambda x, y: colors[(x // 4 + y // 3) % 4] + [255]),
+)
+save(
+    "blocks-qoi.qoi",
+    qoi(W, H, grid(W, H, lambda x, y: colors[(x // 4 + y // 3) % 4] + [255]), 3),
+)
+# A run that's longer than one row
+reference("flat", W, H, [[10, 20, 30, 255]] * (W * H))
+save("flat-qoi.qoi", qoi(W, H, [[10, 20, 30, 255]] * (W * H), 3))
+save("flat-bmp.bmp", bmp(W, H, 24, [bytes([30, 20, 10]) * W] * H))
+
+good = qoi(W, H, grid(W, H, gradient), 3)
+save("x-qoitruncated.qoi", good[: len(good) // 2])
+save("x-qoichannels.qoi", good[:12] + b"\x05" + good[13:])
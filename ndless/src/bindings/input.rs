@@ -422,10 +422,22 @@ const KEY_MAPPING: &[(ndless_sys::t_key, Key)] = &[
 pub fn iter_keys() -> impl Iterator<Item = Key> + 'static {
 	KEY_MAPPING
 		.iter()
-		.filter(|(raw_key, _key)| unsafe { ndless_sys::isKeyPressed(raw_key) } == 1)
+		.filter(|mapping| is_pressed(mapping))
 		.map(|(_, key)| *key)
 }
 
+/// Checks one entry of [`KEY_MAPPING`].
+fn is_pressed(mapping: &(ndless_sys::t_key, Key)) -> bool {
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::isKeyPressed(&mapping.0) == 1 }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::is_pressed(mapping.1)
+	}
+}
+
 /// Returns a [`Vec`] of pressed keys.
 ///
 /// # Example
@@ -445,31 +457,52 @@ pub fn is_key_pressed(key: impl Borrow<Key>) -> bool {
 	KEY_MAPPING
 		.iter()
 		.find(|(_, other)| other == key.borrow())
-		.map_or(
-			false,
-			|(raw_key, _key)| unsafe { ndless_sys::isKeyPressed(raw_key) } == 1,
-		)
+		.map_or(false, is_pressed)
 }
 
 /// Returns true if any buttons are currently pressed, including pushing the
 /// touchpad.
 pub fn any_key_pressed() -> bool {
-	unsafe { ndless_sys::any_key_pressed() > 0 }
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::any_key_pressed() > 0 }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::any_pressed()
+	}
 }
 
 /// Returns true if the "On" key is currently pressed.
 pub fn key_on_pressed() -> bool {
-	unsafe { ndless_sys::on_key_pressed() > 0 }
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::on_key_pressed() > 0 }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::is_pressed(Key::On)
+	}
 }
 
 /// Suspends the program until [`any_key_pressed`] returns true.
 pub fn wait_key_pressed() {
-	unsafe { ndless_sys::wait_key_pressed() }
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::wait_key_pressed()
+	}
+	#[cfg(feature = "host")]
+	crate::sim::wait_until(any_key_pressed, "a key to be pressed")
 }
 
 /// Suspends the program until [`any_key_pressed`]  returns false.
 pub fn wait_no_key_pressed() {
-	unsafe { ndless_sys::wait_no_key_pressed() }
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::wait_no_key_pressed()
+	}
+	#[cfg(feature = "host")]
+	crate::sim::wait_until(|| !any_key_pressed(), "all keys to be released")
 }
 
 pub mod touchpad {
@@ -518,7 +551,10 @@ pub mod touchpad {
 			pressed: 0,
 			arrow: 0,
 		};
+		#[cfg(not(feature = "host"))]
 		let status = unsafe { ndless_sys::touchpad_scan(&mut report) };
+		#[cfg(feature = "host")]
+		let status = crate::sim::touchpad_scan(&mut report);
 		match status {
 			0 => Ok(report.into()),
 			x => Err(x),
@@ -526,9 +562,16 @@ pub mod touchpad {
 	}
 
 	pub fn get_touchpad_info() -> Result<touchpad_info, ()> {
-		match unsafe { ndless_sys::touchpad_getinfo().as_ref() } {
-			Some(ret) => Ok(*ret),
-			None => Err(()),
+		#[cfg(not(feature = "host"))]
+		{
+			match unsafe { ndless_sys::touchpad_getinfo().as_ref() } {
+				Some(ret) => Ok(*ret),
+				None => Err(()),
+			}
+		}
+		#[cfg(feature = "host")]
+		{
+			Err(())
 		}
 	}
 }
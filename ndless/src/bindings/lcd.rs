@@ -0,0 +1,216 @@
+//! # Drawing to the LCD
+//! Switches the LCD to a [`Screen`] mode and copies whole frames to it.
+//!
+//! ```no_run
+//! use ndless::hw::screen::{Screen, HEIGHT, WIDTH};
+//! use ndless::lcd;
+//!
+//! let mut frame = vec![0u16; WIDTH * HEIGHT];
+//! lcd::init(Screen::Screen320x240x565);
+//! frame[WIDTH * 10 + 10] = 0xF800; // A red pixel
+//! lcd::blit(&frame, Screen::Screen320x240x565);
+//! // Give the screen back to the OS
+//! lcd::deinit();
+//! ```
+//!
+//! With the `host` feature, frames go to the
+//! [simulated framebuffer](crate::sim::frame) instead.
+//!
+//! A [`Framebuffer`] draws into a frame in any mode, converting colors to
+//! the mode's format, which is how [`font`](crate::font) draws text.
+//!
+//! [`screenshot`] saves what's on the screen to the Documents folder, for
+//! documentation or bug reports:
+//!
+//! ```no_run
+//! // Saved as /documents/bug.png.tns
+//! ndless::lcd::screenshot("bug").unwrap();
+//! ```
+
+use alloc::vec::Vec;
+use core::mem::size_of_val;
+
+use crate::hw::screen::{Screen, HEIGHT, WIDTH};
+
+pub use self::framebuffer::Framebuffer;
+pub use self::screenshot::{
+	capture, mode_from_control, screenshot, screenshot_numbered, screenshot_path, Capture,
+	ImageFormat, SCREENSHOT_FOLDER,
+};
+
+pub mod bmp;
+mod framebuffer;
+pub mod png;
+mod screenshot;
+
+program_state! {
+	/// Whether an exit hook has been registered to give the LCD back to the OS.
+	fn restore_registered() -> bool = false;
+}
+
+/// Switches the LCD to `screen`, returning `false` if the calculator can't
+/// show it. The LCD is given back to the OS when the program exits, even if
+/// [`deinit`] isn't called.
+pub fn init(screen: Screen) -> bool {
+	if screen != Screen::Unknown && unsafe { !*restore_registered() } {
+		unsafe { *restore_registered() = true };
+		crate::process::at_exit(|| {
+			unsafe { *restore_registered() = false };
+			deinit();
+		});
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		unsafe { ndless_sys::lcd_init(screen.raw()) }
+	}
+	#[cfg(feature = "host")]
+	{
+		crate::sim::lcd_init(screen)
+	}
+}
+
+/// Gives the LCD back to the OS.
+pub fn deinit() {
+	init(Screen::Unknown);
+}
+
+/// Shows `buffer`, which holds a whole frame in the format of `screen`. This
+/// should be the mode that the LCD was [initialized](init) with.
+///
+/// # Panics
+/// If `buffer` is smaller than [`screen.byte_len()`](Screen::byte_len).
+pub fn blit<T: Copy>(buffer: &[T], screen: Screen) {
+	assert!(
+		size_of_val(buffer) >= screen.byte_len(),
+		"a {:?} frame needs {} bytes",
+		screen,
+		screen.byte_len()
+	);
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::lcd_blit(buffer.as_ptr() as *mut _, screen.raw())
+	}
+	#[cfg(feature = "host")]
+	{
+		let bytes =
+			unsafe { core::slice::from_raw_parts(buffer.as_ptr() as *const u8, screen.byte_len()) };
+		crate::sim::lcd_blit(bytes, screen)
+	}
+}
+
+/// Converts a frame in the format of `screen` to 8-bit RGB, three bytes per
+/// pixel, as it would look on the screen. The result is always [`WIDTH`] by
+/// [`HEIGHT`], so frames for sideways screens are turned the right way round.
+/// The 8-bit mode is treated as grayscale, since its palette isn't known.
+///
+/// # Panics
+/// If `frame` is smaller than [`screen.byte_len()`](Screen::byte_len).
+pub fn to_rgb888(screen: Screen, frame: &[u8]) -> Vec<u8> {
+	assert!(frame.len() >= screen.byte_len(), "the frame is too small");
+	let mut rgb = Vec::with_capacity(WIDTH * HEIGHT * 3);
+	for y in 0..HEIGHT {
+		for x in 0..WIDTH {
+			rgb.extend_from_slice(&pixel_rgb888(screen, frame, x, y));
+		}
+	}
+	rgb
+}
+
+/// The color of the pixel `x` from the left and `y` from the top of the
+/// screen, in a frame in the format of `screen`. See [`to_rgb888`].
+pub fn pixel_rgb888(screen: Screen, frame: &[u8], x: usize, y: usize) -> [u8; 3] {
+	let index = if screen.is_rotated() {
+		x * HEIGHT + y
+	} else {
+		y * WIDTH + x
+	};
+	let word = || u16::from_le_bytes([frame[index * 2], frame[index * 2 + 1]]);
+	let channel = |value: u16, bits: u32| {
+		let max = (1 << bits) - 1;
+		((value as u32 & max) * 255 / max) as u8
+	};
+	match screen {
+		Screen::Screen320x240x4 => {
+			// The high nibble is the left pixel
+			let byte = frame[index / 2];
+			let gray = if index % 2 == 0 {
+				byte >> 4
+			} else {
+				byte & 0xF
+			};
+			[gray * 17; 3]
+		}
+		Screen::Screen320x240x8 => [frame[index]; 3],
+		Screen::Screen320x240x16 => {
+			let word = word();
+			[
+				channel(word >> 8, 4),
+				channel(word >> 4, 4),
+				channel(word, 4),
+			]
+		}
+		Screen::Screen320x240x565 | Screen::Screen240x320x565 => {
+			let word = word();
+			[
+				channel(word >> 11, 5),
+				channel(word >> 5, 6),
+				channel(word, 5),
+			]
+		}
+		Screen::Screen320x240x555 | Screen::Screen240x320x555 => {
+			let word = word();
+			[
+				channel(word >> 10, 5),
+				channel(word >> 5, 5),
+				channel(word, 5),
+			]
+		}
+		Screen::Unknown => [0; 3],
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+	#[test]
+	fn rgb888() {
+		let mut frame = vec![0u8; Screen::Screen320x240x565.byte_len()];
+		// Red at (1, 0), white at (0, 1)
+		frame[2..4].copy_from_slice(&0xF800u16.to_le_bytes());
+		frame[WIDTH * 2..WIDTH * 2 + 2].copy_from_slice(&0xFFFFu16.to_le_bytes());
+		let rgb = to_rgb888(Screen::Screen320x240x565, &frame);
+		assert_eq!(rgb.len(), WIDTH * HEIGHT * 3);
+		assert_eq!(rgb[..6], [0, 0, 0, 255, 0, 0]);
+		assert_eq!(rgb[WIDTH * 3..WIDTH * 3 + 3], [255, 255, 255]);
+
+		let mut gray = vec![0u8; Screen::Screen320x240x4.byte_len()];
+		gray[0] = 0xF8;
+		let rgb = to_rgb888(Screen::Screen320x240x4, &gray);
+		assert_eq!(rgb[..6], [255, 255, 255, 136, 136, 136]);
+
+		// A sideways frame is stored in columns
+		let mut rotated = vec![0u8; Screen::Screen240x320x565.byte_len()];
+		rotated[2..4].copy_from_slice(&0x001Fu16.to_le_bytes());
+		let rgb = to_rgb888(Screen::Screen240x320x565, &rotated);
+		assert_eq!(rgb[WIDTH * 3..WIDTH * 3 + 3], [0, 0, 255]);
+	}
+
+	#[cfg(feature = "host")]
+	#[test]
+	fn restored_at_exit() {
+		use crate::sim::{lcd_mode, reset};
+
+		reset();
+		init(Screen::Screen320x240x565);
+		init(Screen::Screen320x240x4);
+		crate::process::__run_at_exit();
+		assert_eq!(lcd_mode(), None);
+		// The hook is registered again for the next program
+		init(Screen::Screen320x240x565);
+		crate::process::__run_at_exit();
+		assert_eq!(lcd_mode(), None);
+	}
+}
@@ -0,0 +1,79 @@
+//! A BMP encoder, for saving what's on the screen.
+//!
+//! Images are saved as uncompressed 24-bit bitmaps, which almost any program
+//! can open. A full screen comes out at about 230 KB.
+
+use alloc::vec::Vec;
+
+/// The size of the file header and the `BITMAPINFOHEADER` together.
+const HEADER_LEN: usize = 14 + 40;
+/// 72 DPI, in pixels per meter.
+const PIXELS_PER_METER: u32 = 2835;
+
+/// Encodes an image of 8-bit RGB pixels, three bytes each, as a BMP file.
+///
+/// # Panics
+/// If `rgb` isn't `width * height * 3` bytes.
+pub fn encode(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+	let stride = width as usize * 3;
+	assert_eq!(rgb.len(), stride * height as usize, "wrong image size");
+	// Rows are padded to a multiple of 4 bytes
+	let padded = (stride + 3) & !3;
+	let image_len = padded * height as usize;
+
+	let mut bmp = Vec::with_capacity(HEADER_LEN + image_len);
+	bmp.extend_from_slice(b"BM");
+	bmp.extend_from_slice(&((HEADER_LEN + image_len) as u32).to_le_bytes());
+	bmp.extend_from_slice(&[0; 4]);
+	bmp.extend_from_slice(&(HEADER_LEN as u32).to_le_bytes());
+
+	bmp.extend_from_slice(&40u32.to_le_bytes());
+	bmp.extend_from_slice(&width.to_le_bytes());
+	// A positive height means that the rows are stored from the bottom up
+	bmp.extend_from_slice(&height.to_le_bytes());
+	// One plane, 24 bits per pixel, not compressed
+	bmp.extend_from_slice(&1u16.to_le_bytes());
+	bmp.extend_from_slice(&24u16.to_le_bytes());
+	bmp.extend_from_slice(&0u32.to_le_bytes());
+	bmp.extend_from_slice(&(image_len as u32).to_le_bytes());
+	bmp.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+	bmp.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+	// No palette
+	bmp.extend_from_slice(&[0; 8]);
+
+	for row in rgb.chunks(stride.max(1)).take(height as usize).rev() {
+		for pixel in row.chunks(3) {
+			bmp.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+		}
+		bmp.resize(bmp.len() + padded - stride, 0);
+	}
+	bmp
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encoding() {
+		// Red and green on top, blue and white below
+		let rgb = [
+			255, 0, 0, 0, 255, 0, //
+			0, 0, 255, 255, 255, 255,
+		];
+		let bmp = encode(2, 2, &rgb);
+		assert_eq!(bmp.len(), 54 + 8 * 2);
+		assert_eq!(bmp[..2], *b"BM");
+		assert_eq!(bmp[2..6], 70u32.to_le_bytes());
+		assert_eq!(bmp[10..14], 54u32.to_le_bytes());
+		assert_eq!(bmp[18..26], [2, 0, 0, 0, 2, 0, 0, 0]);
+		assert_eq!(bmp[28..30], 24u16.to_le_bytes());
+		assert_eq!(bmp[34..38], 16u32.to_le_bytes());
+		// The bottom row comes first, in BGR order and padded
+		assert_eq!(bmp[54..62], [255, 0, 0, 255, 255, 255, 0, 0]);
+		assert_eq!(bmp[62..70], [0, 0, 255, 0, 255, 0, 0, 0]);
+
+		// Rows that are already a multiple of 4 bytes aren't padded
+		assert_eq!(encode(4, 1, &[0; 12]).len(), 54 + 12);
+	}
+}
@@ -0,0 +1,209 @@
+use crate::hw::screen::{Screen, HEIGHT, WIDTH};
+
+/// A whole frame in the format of a [`Screen`], which can be drawn on one
+/// pixel or span at a time and then shown with [`blit`](super::blit).
+///
+/// Coordinates are always for the screen as it's held, [`WIDTH`] by
+/// [`HEIGHT`], so the same code draws to sideways frames too. Colors are the
+/// raw values for the mode, as made by [`color`](Framebuffer::color): a gray
+/// level from 0 to 15 in 4-bit mode, from 0 to 255 in 8-bit mode, or the 16
+/// bits of a pixel otherwise.
+///
+/// ```no_run
+/// use ndless::hw::screen::{lcd_type, Screen};
+/// use ndless::lcd::{self, Framebuffer};
+///
+/// let screen = lcd_type();
+/// let mut data = vec![0u8; screen.byte_len()];
+/// let mut frame = Framebuffer::new(screen, &mut data);
+/// let red = frame.color([255, 0, 0]);
+/// frame.fill_span(10, 10, 100, red);
+/// lcd::init(screen);
+/// lcd::blit(frame.data(), screen);
+/// ```
+pub struct Framebuffer<'a> {
+	screen: Screen,
+	data: &'a mut [u8],
+}
+
+impl<'a> Framebuffer<'a> {
+	/// # Panics
+	/// If `data` is smaller than [`screen.byte_len()`](Screen::byte_len), or
+	/// `screen` is [`Unknown`](Screen::Unknown).
+	pub fn new(screen: Screen, data: &'a mut [u8]) -> Framebuffer<'a> {
+		assert!(screen != Screen::Unknown, "the screen mode isn't known");
+		assert!(
+			data.len() >= screen.byte_len(),
+			"a {:?} frame needs {} bytes",
+			screen,
+			screen.byte_len()
+		);
+		Framebuffer { screen, data }
+	}
+
+	/// A framebuffer over 16-bit pixels, as used with
+	/// [`graphics::Canvas`](crate::graphics::Canvas).
+	///
+	/// # Panics
+	/// The same as [`new`](Framebuffer::new).
+	pub fn from_u16(screen: Screen, pixels: &'a mut [u16]) -> Framebuffer<'a> {
+		let data = unsafe {
+			core::slice::from_raw_parts_mut(pixels.as_mut_ptr() as *mut u8, pixels.len() * 2)
+		};
+		Framebuffer::new(screen, data)
+	}
+
+	pub fn screen(&self) -> Screen {
+		self.screen
+	}
+
+	pub fn data(&self) -> &[u8] {
+		self.data
+	}
+
+	/// The raw value of an 8-bit RGB color in this mode. The 8-bit mode is
+	/// treated as grayscale, like [`to_rgb888`](super::to_rgb888) does.
+	pub fn color(&self, rgb: [u8; 3]) -> u16 {
+		let [r, g, b] = rgb.map(u16::from);
+		let gray = (77 * r + 150 * g + 29 * b) >> 8;
+		match self.screen {
+			Screen::Screen320x240x4 => gray >> 4,
+			Screen::Screen320x240x8 => gray,
+			Screen::Screen320x240x16 => (r >> 4) << 8 | (g >> 4) << 4 | b >> 4,
+			Screen::Screen320x240x565 | Screen::Screen240x320x565 => {
+				crate::graphics::rgb565(rgb[0], rgb[1], rgb[2])
+			}
+			Screen::Screen320x240x555 | Screen::Screen240x320x555 => {
+				(r >> 3) << 10 | (g >> 3) << 5 | b >> 3
+			}
+			Screen::Unknown => 0,
+		}
+	}
+
+	/// The color of a pixel as 8-bit RGB, or `None` if it's off the screen.
+	pub fn pixel(&self, x: i32, y: i32) -> Option<[u8; 3]> {
+		if on_screen(x, y) {
+			Some(super::pixel_rgb888(
+				self.screen,
+				self.data,
+				x as usize,
+				y as usize,
+			))
+		} else {
+			None
+		}
+	}
+
+	/// Sets a pixel to a raw `color`, if it's on the screen.
+	pub fn set_pixel(&mut self, x: i32, y: i32, color: u16) {
+		if on_screen(x, y) {
+			self.set(self.index(x as usize, y as usize), color);
+		}
+	}
+
+	/// Sets `width` pixels to the right of and including `x` in row `y`, or
+	/// the part of them that's on the screen.
+	pub fn fill_span(&mut self, x: i32, y: i32, width: u32, color: u16) {
+		if !(0..HEIGHT as i32).contains(&y) {
+			return;
+		}
+		let start = x.max(0) as usize;
+		let end = (x as i64 + width as i64).min(WIDTH as i64);
+		for x in start..end.max(0) as usize {
+			self.set(self.index(x, y as usize), color);
+		}
+	}
+
+	/// Sets every pixel to `color`.
+	pub fn fill(&mut self, color: u16) {
+		for y in 0..HEIGHT as i32 {
+			self.fill_span(0, y, WIDTH as u32, color);
+		}
+	}
+
+	/// The index of a pixel in the data, counted in pixels.
+	fn index(&self, x: usize, y: usize) -> usize {
+		if self.screen.is_rotated() {
+			x * HEIGHT + y
+		} else {
+			y * WIDTH + x
+		}
+	}
+
+	fn set(&mut self, index: usize, color: u16) {
+		match self.screen.bits_per_pixel() {
+			4 => {
+				// The high nibble is the left pixel
+				let byte = &mut self.data[index / 2];
+				let nibble = color as u8 & 0xF;
+				*byte = if index.is_multiple_of(2) {
+					*byte & 0x0F | nibble << 4
+				} else {
+					*byte & 0xF0 | nibble
+				};
+			}
+			8 => self.data[index] = color as u8,
+			_ => self.data[index * 2..index * 2 + 2].copy_from_slice(&color.to_le_bytes()),
+		}
+	}
+}
+
+fn on_screen(x: i32, y: i32) -> bool {
+	(0..WIDTH as i32).contains(&x) && (0..HEIGHT as i32).contains(&y)
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+	#[test]
+	fn formats() {
+		let white = [255, 255, 255];
+		for &screen in &[
+			Screen::Screen320x240x4,
+			Screen::Screen320x240x8,
+			Screen::Screen320x240x16,
+			Screen::Screen320x240x565,
+			Screen::Screen240x320x565,
+			Screen::Screen320x240x555,
+			Screen::Screen240x320x555,
+		] {
+			let mut data = vec![0; screen.byte_len()];
+			let mut frame = Framebuffer::new(screen, &mut data);
+			let color = frame.color(white);
+			frame.fill_span(-5, 1, 8, color);
+			frame.fill_span(WIDTH as i32 - 1, 2, 10, color);
+			frame.set_pixel(3, 239, color);
+			frame.set_pixel(3, 240, color);
+			assert_eq!(frame.pixel(0, 1), Some(white), "{:?}", screen);
+			assert_eq!(frame.pixel(2, 1), Some(white), "{:?}", screen);
+			assert_eq!(frame.pixel(3, 1), Some([0; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(WIDTH as i32 - 1, 2), Some(white));
+			assert_eq!(frame.pixel(3, 239), Some(white), "{:?}", screen);
+			assert_eq!(frame.pixel(2, 239), Some([0; 3]), "{:?}", screen);
+			assert_eq!(frame.pixel(3, 240), None);
+		}
+	}
+
+	#[test]
+	fn colors() {
+		let mut data = vec![0; Screen::Screen320x240x4.byte_len()];
+		let mut frame = Framebuffer::new(Screen::Screen320x240x4, &mut data);
+		let gray = frame.color([128, 128, 128]);
+		assert_eq!(gray, 8);
+		frame.set_pixel(1, 0, 0xF);
+		frame.set_pixel(0, 0, gray);
+		assert_eq!(data[0], 0x8F);
+
+		let mut pixels = vec![0u16; WIDTH * HEIGHT];
+		let mut frame = Framebuffer::from_u16(Screen::Screen240x320x555, &mut pixels);
+		let blue = frame.color([0, 0, 255]);
+		assert_eq!(blue, 0x001F);
+		assert_eq!(frame.color([255, 0, 0]), 0x7C00);
+		frame.set_pixel(1, 2, blue);
+		// Sideways frames are stored in columns
+		assert_eq!(pixels[HEIGHT + 2], 0x001F);
+	}
+}
@@ -0,0 +1,138 @@
+//! A small PNG encoder, for saving what's on the screen.
+//!
+//! The image data isn't compressed, which keeps this fast enough for the
+//! calculator. A full screen comes out at about 230 KB.
+
+use alloc::vec::Vec;
+
+/// The most that a stored deflate block can hold.
+const MAX_BLOCK: usize = 0xFFFF;
+
+/// Encodes an image of 8-bit RGB pixels, three bytes each, as a PNG file.
+///
+/// # Panics
+/// If `rgb` isn't `width * height * 3` bytes.
+pub fn encode(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+	let stride = width as usize * 3;
+	assert_eq!(rgb.len(), stride * height as usize, "wrong image size");
+
+	// Each row starts with filter type 0, which leaves it unchanged
+	let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+	for row in rgb.chunks(stride.max(1)).take(height as usize) {
+		raw.push(0);
+		raw.extend_from_slice(row);
+	}
+
+	let mut png = Vec::with_capacity(raw.len() + raw.len() / MAX_BLOCK * 5 + 64);
+	png.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+
+	let mut header = Vec::with_capacity(13);
+	header.extend_from_slice(&width.to_be_bytes());
+	header.extend_from_slice(&height.to_be_bytes());
+	// 8 bits per channel, RGB, deflate, standard filters, not interlaced
+	header.extend_from_slice(&[8, 2, 0, 0, 0]);
+	chunk(&mut png, b"IHDR", &header);
+	chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+	chunk(&mut png, b"IEND", &[]);
+	png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+	png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+	let start = png.len();
+	png.extend_from_slice(kind);
+	png.extend_from_slice(data);
+	let crc = crc32(&png[start..]);
+	png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream without compressing it.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+	// Deflate with a 32K window, and the check bits for that
+	out.extend_from_slice(&[0x78, 0x01]);
+	let mut blocks = data.chunks(MAX_BLOCK).peekable();
+	if blocks.peek().is_none() {
+		out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+	}
+	while let Some(block) = blocks.next() {
+		let last = blocks.peek().is_none();
+		let len = block.len() as u16;
+		out.push(last as u8);
+		out.extend_from_slice(&len.to_le_bytes());
+		out.extend_from_slice(&(!len).to_le_bytes());
+		out.extend_from_slice(block);
+	}
+	out.extend_from_slice(&adler32(data).to_be_bytes());
+	out
+}
+
+/// The CRC-32 that PNG chunks end with.
+pub fn crc32(data: &[u8]) -> u32 {
+	update_crc32(0, data)
+}
+
+/// Continues a CRC-32 with more data, so that
+/// `update_crc32(crc32(a), b)` is the CRC-32 of `a` followed by `b`.
+pub fn update_crc32(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			let mask = (crc & 1).wrapping_neg();
+			crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+		}
+	}
+	!crc
+}
+
+/// The checksum that zlib streams end with.
+pub fn adler32(data: &[u8]) -> u32 {
+	const MOD: u32 = 65521;
+	let (mut a, mut b) = (1u32, 0u32);
+	// 5552 bytes is the most that can be summed before `b` could overflow
+	for block in data.chunks(5552) {
+		for &byte in block {
+			a += byte as u32;
+			b += a;
+		}
+		a %= MOD;
+		b %= MOD;
+	}
+	(b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn checksums() {
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+		assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+		assert_eq!(update_crc32(crc32(b"1234"), b"56789"), 0xCBF4_3926);
+		assert_eq!(adler32(b""), 1);
+		assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+	}
+
+	#[test]
+	fn encoding() {
+		let png = encode(2, 1, &[255, 0, 0, 0, 0, 255]);
+		assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+		assert_eq!(png[12..16], *b"IHDR");
+		assert_eq!(png[16..24], [0, 0, 0, 2, 0, 0, 0, 1]);
+		assert_eq!(png[png.len() - 12..], *b"\0\0\0\0IEND\xAE\x42\x60\x82");
+		// The image data is one row of seven bytes in a stored block
+		let idat = &png[33..];
+		assert_eq!(idat[..8], [0, 0, 0, 18, b'I', b'D', b'A', b'T']);
+		assert_eq!(idat[8..15], [0x78, 0x01, 1, 7, 0, 0xF8, 0xFF]);
+		assert_eq!(idat[15..22], [0, 255, 0, 0, 0, 0, 255]);
+
+		// Large images are split into several blocks
+		let big = zlib_stored(&[7; MAX_BLOCK + 1]);
+		assert_eq!(big.len(), 2 + 5 * 2 + MAX_BLOCK + 1 + 4);
+		assert_eq!(big[2], 0);
+		assert_eq!(big[2 + 5 + MAX_BLOCK], 1);
+	}
+}
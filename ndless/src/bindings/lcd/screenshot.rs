@@ -0,0 +1,283 @@
+//! Saving what's on the screen.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::{bmp, png, to_rgb888};
+use crate::hw::mmio::lcd_control;
+use crate::hw::screen::{Screen, HEIGHT, WIDTH};
+use crate::io;
+use crate::path::{DocumentsPath, Path, PathBuf};
+
+/// The file formats that screenshots can be saved in.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum ImageFormat {
+	/// An uncompressed 24-bit bitmap.
+	Bmp,
+	/// A PNG, which isn't compressed either, but is more widely accepted.
+	Png,
+}
+
+impl ImageFormat {
+	pub fn extension(self) -> &'static str {
+		match self {
+			ImageFormat::Bmp => "bmp",
+			ImageFormat::Png => "png",
+		}
+	}
+
+	/// Works out the format from the extension of `path`, ignoring a `.tns`
+	/// at the end. Returns `None` if it isn't `.bmp` or `.png`.
+	pub fn from_path(path: &Path) -> Option<ImageFormat> {
+		let path = path.strip_tns();
+		let extension = path.extension()?.to_str()?;
+		[ImageFormat::Bmp, ImageFormat::Png]
+			.iter()
+			.copied()
+			.find(|format| extension.eq_ignore_ascii_case(format.extension()))
+	}
+
+	/// Encodes an image of 8-bit RGB pixels, three bytes each.
+	pub fn encode(self, width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+		match self {
+			ImageFormat::Bmp => bmp::encode(width, height, rgb),
+			ImageFormat::Png => png::encode(width, height, rgb),
+		}
+	}
+}
+
+/// The contents of the LCD, as returned by [`capture`].
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Capture {
+	/// The mode that the LCD is in.
+	pub screen: Screen,
+	/// Whether the LCD controller swaps red and blue.
+	pub bgr: bool,
+	/// The frame buffer, in the format of `screen`.
+	pub data: Vec<u8>,
+}
+
+impl Capture {
+	/// The picture as 8-bit RGB, three bytes per pixel, [`WIDTH`] by
+	/// [`HEIGHT`].
+	pub fn to_rgb888(&self) -> Vec<u8> {
+		let mut rgb = to_rgb888(self.screen, &self.data);
+		if self.bgr {
+			rgb.chunks_mut(3).for_each(|pixel| pixel.swap(0, 2));
+		}
+		rgb
+	}
+
+	pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+		format.encode(WIDTH as u32, HEIGHT as u32, &self.to_rgb888())
+	}
+}
+
+/// Works out the mode of the LCD from its controller's
+/// [control register](crate::hw::mmio::Lcd::control), returning the mode
+/// and whether red and blue are swapped. `rotated` is whether the panel is
+/// mounted sideways, as on the CX HW-W. Returns `None` for modes that
+/// [`Screen`] doesn't cover.
+pub fn mode_from_control(control: u32, rotated: bool) -> Option<(Screen, bool)> {
+	let screen = match (control_bits(control), rotated) {
+		(2, false) => Screen::Screen320x240x4,
+		(3, false) => Screen::Screen320x240x8,
+		(7, false) => Screen::Screen320x240x16,
+		(4, false) => Screen::Screen320x240x555,
+		(4, true) => Screen::Screen240x320x555,
+		(6, false) => Screen::Screen320x240x565,
+		(6, true) => Screen::Screen240x320x565,
+		_ => return None,
+	};
+	Some((screen, lcd_control::BGR.get(control) != 0))
+}
+
+fn control_bits(control: u32) -> u32 {
+	lcd_control::BITS_PER_PIXEL.get(control)
+}
+
+/// Copies what's on the screen right now, in whatever mode the LCD is in.
+/// With the `host` feature, this is the last frame that was
+/// [sent](super::blit) to the simulated LCD.
+pub fn capture() -> io::Result<Capture> {
+	#[cfg(not(feature = "host"))]
+	{
+		let lcd = crate::hw::mmio::lcd();
+		let control = lcd.control().read();
+		let rotated = crate::hw::screen::lcd_type().is_rotated();
+		let (screen, bgr) = mode_from_control(control, rotated).ok_or_else(|| {
+			io::Error::new(
+				io::ErrorKind::Other,
+				format!("the LCD is in an unknown mode ({:#x})", control),
+			)
+		})?;
+		let base = lcd.upper_base().read() as usize;
+		let data =
+			unsafe { core::slice::from_raw_parts(base as *const u8, screen.byte_len()) }.to_vec();
+		Ok(Capture { screen, bgr, data })
+	}
+	#[cfg(feature = "host")]
+	{
+		let frame = crate::sim::frame().ok_or_else(|| {
+			io::Error::new(io::ErrorKind::Other, "nothing has been drawn to the LCD")
+		})?;
+		Ok(Capture {
+			screen: frame.screen,
+			bgr: false,
+			data: frame.data,
+		})
+	}
+}
+
+/// The path that a screenshot of `path` is saved to, relative to the
+/// Documents folder, and its format. If the name doesn't end in `.bmp` or
+/// `.png`, with or without `.tns`, a PNG is saved. `.tns` is added so that
+/// the OS can see the file.
+pub fn screenshot_path(path: &Path) -> io::Result<(DocumentsPath, ImageFormat)> {
+	let stripped = path.strip_tns();
+	let (path, format) = match ImageFormat::from_path(&stripped) {
+		Some(format) => (stripped, format),
+		None => {
+			let name = stripped.file_name().unwrap_or_default().to_string_lossy();
+			let name = format!("{}.{}", name, ImageFormat::Png.extension());
+			(stripped.with_file_name(name), ImageFormat::Png)
+		}
+	};
+	let path = DocumentsPath::new(path.with_tns_extension())
+		.map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+	Ok((path, format))
+}
+
+/// Saves what's on the screen to `path` in the Documents folder, returning
+/// the full path that was written. The format is worked out from the name,
+/// so `bug.bmp` is saved as a bitmap in `bug.bmp.tns`, and `bug` as a PNG in
+/// `bug.png.tns`. Folders are created if they don't exist.
+///
+/// ```no_run
+/// use ndless::lcd;
+///
+/// // Saved to /documents/screenshots/title.png.tns
+/// lcd::screenshot("screenshots/title.png").unwrap();
+/// ```
+pub fn screenshot(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+	let (path, format) = screenshot_path(path.as_ref())?;
+	save(&path.resolve()?, format)
+}
+
+/// The folder in Documents that [`screenshot_numbered`] saves to.
+pub const SCREENSHOT_FOLDER: &str = "screenshots";
+
+/// Saves what's on the screen as `screenshots/screenshot-N` in the Documents
+/// folder, with the first number that isn't already used.
+pub fn screenshot_numbered(format: ImageFormat) -> io::Result<PathBuf> {
+	let folder = crate::env::get_documents_dir()?.join(SCREENSHOT_FOLDER);
+	let path = (1..)
+		.map(|number| folder.join(format!("screenshot-{}.{}.tns", number, format.extension())))
+		.find(|path| crate::fs::metadata(path).is_err())
+		.unwrap();
+	save(&path, format)
+}
+
+fn save(path: &Path, format: ImageFormat) -> io::Result<PathBuf> {
+	let image = capture()?.encode(format);
+	if let Some(parent) = path.parent() {
+		crate::fs::create_dir_all(parent)?;
+	}
+	crate::fs::write(path, image)?;
+	Ok(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn modes() {
+		let control = |bits: u32, bgr: bool| {
+			lcd_control::BGR.set(lcd_control::BITS_PER_PIXEL.set(0x801, bits), bgr as u32)
+		};
+		assert_eq!(
+			mode_from_control(control(6, true), false),
+			Some((Screen::Screen320x240x565, true))
+		);
+		assert_eq!(
+			mode_from_control(control(6, false), true),
+			Some((Screen::Screen240x320x565, false))
+		);
+		assert_eq!(
+			mode_from_control(control(2, false), false),
+			Some((Screen::Screen320x240x4, false))
+		);
+		assert_eq!(
+			mode_from_control(control(7, false), false),
+			Some((Screen::Screen320x240x16, false))
+		);
+		assert_eq!(mode_from_control(control(5, false), false), None);
+		assert_eq!(mode_from_control(control(2, false), true), None);
+	}
+
+	#[test]
+	fn paths() {
+		let path = |name: &str| {
+			let (path, format) = screenshot_path(Path::new(name)).unwrap();
+			(path.as_path().to_str().unwrap().into(), format)
+		};
+		let expected = |name: &str, format| (alloc::string::String::from(name), format);
+		assert_eq!(path("bug"), expected("bug.png.tns", ImageFormat::Png));
+		assert_eq!(path("bug.BMP"), expected("bug.BMP.tns", ImageFormat::Bmp));
+		assert_eq!(
+			path("/shots/bug.png.tns"),
+			expected("shots/bug.png.tns", ImageFormat::Png)
+		);
+		assert_eq!(path("bug.v2"), expected("bug.v2.png.tns", ImageFormat::Png));
+		assert!(screenshot_path(Path::new("../bug")).is_err());
+		assert_eq!(
+			ImageFormat::from_path(Path::new("a.bmp.tns")),
+			Some(ImageFormat::Bmp)
+		);
+		assert_eq!(ImageFormat::from_path(Path::new("a.tns")), None);
+	}
+
+	#[test]
+	fn bgr() {
+		let mut data = alloc::vec![0; Screen::Screen320x240x565.byte_len()];
+		data[..2].copy_from_slice(&0xF800u16.to_le_bytes());
+		let mut capture = Capture {
+			screen: Screen::Screen320x240x565,
+			bgr: false,
+			data,
+		};
+		assert_eq!(capture.to_rgb888()[..3], [255, 0, 0]);
+		capture.bgr = true;
+		assert_eq!(capture.to_rgb888()[..3], [0, 0, 255]);
+		assert_eq!(capture.encode(ImageFormat::Bmp)[..2], *b"BM");
+		assert_eq!(capture.encode(ImageFormat::Png)[1..4], *b"PNG");
+	}
+
+	#[cfg(feature = "host")]
+	#[test]
+	fn saving() {
+		crate::sim::reset();
+		let dir = std::env::temp_dir().join(std::format!(
+			"ndless-screenshot-test-{}-{:?}",
+			std::process::id(),
+			std::thread::current().id()
+		));
+		crate::sim::set_documents_dir(&dir);
+		assert!(screenshot("nothing").is_err());
+
+		super::super::init(Screen::Screen320x240x565);
+		super::super::blit(&[0xFFFFu16; WIDTH * HEIGHT], Screen::Screen320x240x565);
+		let png = screenshot("shots/title").unwrap();
+		assert_eq!(png.to_str(), dir.join("shots/title.png.tns").to_str());
+		assert_eq!(crate::fs::read(&png).unwrap()[1..4], *b"PNG");
+		let first = screenshot_numbered(ImageFormat::Bmp).unwrap();
+		let second = screenshot_numbered(ImageFormat::Bmp).unwrap();
+		assert!(first.ends_with("screenshots/screenshot-1.bmp.tns"));
+		assert!(second.ends_with("screenshots/screenshot-2.bmp.tns"));
+		let bmp = crate::fs::read(&second).unwrap();
+		assert_eq!(bmp.len(), 54 + WIDTH * HEIGHT * 3);
+		assert_eq!(bmp[54..57], [255, 255, 255]);
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,817 @@
+//! # Logging
+//! An implementation of the [`log`] crate's [`Log`] trait, available with the
+//! `log` feature. Records are timestamped with [`timer`](crate::timer) ticks
+//! and sent to any number of [`Sink`]s: the serial port, a rotating log file,
+//! or an [`Overlay`] that keeps the last few lines and
+//! [draws](Overlay::draw) them over the frame.
+//!
+//! Levels can be set per module using the same syntax as `env_logger`, such as
+//! `info,game::ai=debug,ndless=off`. [`Builder::filter_from_config`] reads this
+//! from the `log.<program>` key of `ndless.cfg.tns`, then from a `--log=...`
+//! program argument.
+//!
+//! ```no_run
+//! use ndless::logger::{Builder, FileSink};
+//!
+//! Builder::new()
+//!     .filter("info")
+//!     .filter_from_config()
+//!     .serial()
+//!     .sink(FileSink::in_documents("game.log.tns"))
+//!     .init()
+//!     .unwrap();
+//! log::info!("started");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use core::str::FromStr;
+
+pub use log::{Level, LevelFilter, SetLoggerError};
+use log::{Log, Metadata, Record};
+
+use crate::font::{Style, Target, TINY_4X6};
+use crate::fs::{self, File, OpenOptions};
+use crate::graphics::Rect;
+use crate::io::Write;
+use crate::path::{Path, PathBuf};
+
+/// The path of Ndless's configuration file.
+pub const NDLESS_CFG: &str = "/documents/ndless/ndless.cfg.tns";
+
+/// Formats a log line as `[seconds.millis] LEVEL target: message`.
+pub fn format_record(ticks: u32, level: Level, target: &str, args: &fmt::Arguments) -> String {
+	format!(
+		"[{:5}.{:03}] {:<5} {}: {}",
+		ticks / crate::timer::TICKS_PER_SECOND,
+		ticks % crate::timer::TICKS_PER_SECOND * 1000 / crate::timer::TICKS_PER_SECOND,
+		level,
+		target,
+		args
+	)
+}
+
+/// Per-module log levels.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Filter {
+	default: LevelFilter,
+	modules: Vec<(String, LevelFilter)>,
+}
+
+impl Default for Filter {
+	fn default() -> Self {
+		Filter::new(LevelFilter::Error)
+	}
+}
+
+impl Filter {
+	/// Creates a filter that applies `default` to every module.
+	pub fn new(default: LevelFilter) -> Filter {
+		Filter {
+			default,
+			modules: Vec::new(),
+		}
+	}
+
+	/// Parses a comma-separated list of directives. Each directive is either a
+	/// level, which sets the default, `module=level`, or just `module`, which
+	/// enables everything from that module. Invalid directives are ignored.
+	pub fn parse(spec: &str) -> Filter {
+		let mut filter = Filter::default();
+		filter.merge(spec);
+		filter
+	}
+
+	/// Applies the directives in `spec` on top of this filter. See
+	/// [`parse`](Filter::parse).
+	pub fn merge(&mut self, spec: &str) {
+		for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+			let mut parts = directive.splitn(2, '=');
+			let name = parts.next().unwrap_or_default().trim();
+			match parts.next().map(str::trim) {
+				Some(level) => {
+					if let Ok(level) = LevelFilter::from_str(level) {
+						self.insert(name, level);
+					}
+				}
+				None => match LevelFilter::from_str(name) {
+					Ok(level) => self.default = level,
+					Err(_) => self.insert(name, LevelFilter::Trace),
+				},
+			}
+		}
+	}
+
+	/// Sets the level of a module and all of its submodules.
+	pub fn insert(&mut self, module: &str, level: LevelFilter) {
+		match self.modules.iter_mut().find(|(name, _)| name == module) {
+			Some(entry) => entry.1 = level,
+			None => self.modules.push((module.to_string(), level)),
+		}
+	}
+
+	/// Returns the level for a log target, using the most specific module that
+	/// matches.
+	pub fn level_for(&self, target: &str) -> LevelFilter {
+		self.modules
+			.iter()
+			.filter(|(name, _)| {
+				target.starts_with(name.as_str())
+					&& (target.len() == name.len() || target[name.len()..].starts_with("::"))
+			})
+			.max_by_key(|(name, _)| name.len())
+			.map_or(self.default, |(_, level)| *level)
+	}
+
+	pub fn enabled(&self, target: &str, level: Level) -> bool {
+		level <= self.level_for(target)
+	}
+
+	/// The most verbose level that any module may log at.
+	pub fn max_level(&self) -> LevelFilter {
+		self.modules
+			.iter()
+			.map(|(_, level)| *level)
+			.fold(self.default, core::cmp::max)
+	}
+}
+
+/// Finds `key=value` in the contents of a configuration file like
+/// `ndless.cfg.tns`. The last occurrence wins.
+pub fn find_cfg_value<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+	contents
+		.lines()
+		.filter_map(|line| {
+			let mut parts = line.splitn(2, '=');
+			let name = parts.next()?.trim();
+			let value = parts.next()?.trim();
+			if name == key {
+				Some(value)
+			} else {
+				None
+			}
+		})
+		.next_back()
+}
+
+/// Finds a filter passed as `--log=spec` or `--log spec` in program arguments.
+pub fn find_log_arg<I: IntoIterator<Item = String>>(args: I) -> Option<String> {
+	let mut args = args.into_iter();
+	let mut found = None;
+	while let Some(arg) = args.next() {
+		if arg == "--log" {
+			found = args.next();
+		} else if let Some(spec) = arg.strip_prefix("--log=") {
+			found = Some(spec.to_string());
+		}
+	}
+	found
+}
+
+/// A destination for log lines.
+pub trait Sink {
+	/// Writes a single formatted line, without a trailing newline.
+	fn write_line(&mut self, level: Level, line: &str);
+	fn flush(&mut self) {}
+}
+
+/// Writes to the serial port, like [`println!`](crate::println).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SerialSink;
+
+impl Sink for SerialSink {
+	fn write_line(&mut self, _level: Level, line: &str) {
+		let _ = crate::out::print_fmt(format_args!("{}\n", line));
+	}
+}
+
+/// How large a log file may grow, and how many old files are kept.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rotation {
+	/// The size at which the file is rotated. 0 disables rotation.
+	pub max_bytes: u64,
+	/// The number of old files to keep, named `name.1.tns`, `name.2.tns`, and
+	/// so on, with 1 being the most recent.
+	pub max_files: usize,
+}
+
+impl Default for Rotation {
+	fn default() -> Self {
+		Rotation {
+			max_bytes: 16 * 1024,
+			max_files: 2,
+		}
+	}
+}
+
+impl Rotation {
+	/// Returns `true` if writing `incoming` bytes to a file of `current` bytes
+	/// should rotate it first. A line is never split, so an empty file is never
+	/// rotated.
+	pub fn needs_rotation(&self, current: u64, incoming: usize) -> bool {
+		self.max_bytes > 0 && current > 0 && current + incoming as u64 > self.max_bytes
+	}
+
+	/// The path of the `index`th old file. `documents/game.log.tns` becomes
+	/// `documents/game.log.1.tns`.
+	pub fn rotated_path(base: &Path, index: usize) -> PathBuf {
+		let name = base
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_default();
+		let name = match name.strip_suffix(".tns") {
+			Some(stem) => format!("{}.{}.tns", stem, index),
+			None => format!("{}.{}", name, index),
+		};
+		base.with_file_name(name)
+	}
+
+	/// The renames that rotate `base`, in the order that they must be done.
+	/// The oldest file is overwritten by the first rename, or is the target of
+	/// the only rename if `max_files` is 1. If `max_files` is 0, the plan is
+	/// empty and the file should be truncated instead.
+	pub fn plan(&self, base: &Path) -> Vec<(PathBuf, PathBuf)> {
+		let mut renames = Vec::new();
+		if self.max_files == 0 {
+			return renames;
+		}
+		for index in (1..self.max_files).rev() {
+			renames.push((
+				Rotation::rotated_path(base, index),
+				Rotation::rotated_path(base, index + 1),
+			));
+		}
+		renames.push((base.to_path_buf(), Rotation::rotated_path(base, 1)));
+		renames
+	}
+}
+
+/// Appends to a log file, rotating it when it gets too big.
+pub struct FileSink {
+	path: PathBuf,
+	rotation: Rotation,
+	file: Option<File>,
+	size: u64,
+}
+
+impl FileSink {
+	/// Logs to `path`. The file is opened when the first line is written.
+	pub fn new<P: Into<PathBuf>>(path: P) -> FileSink {
+		FileSink {
+			path: path.into(),
+			rotation: Rotation::default(),
+			file: None,
+			size: 0,
+		}
+	}
+
+	/// Logs to a file in the Documents folder.
+	pub fn in_documents(name: &str) -> FileSink {
+		let dir = crate::env::get_documents_dir().unwrap_or_else(|_| PathBuf::from("/documents"));
+		FileSink::new(dir.join(name))
+	}
+
+	pub fn rotation(mut self, rotation: Rotation) -> FileSink {
+		self.rotation = rotation;
+		self
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	fn open(&mut self) -> Option<&mut File> {
+		if self.file.is_none() {
+			let existed = self.path.exists();
+			let file = OpenOptions::new()
+				.append(true)
+				.create(true)
+				.open(&self.path)
+				.ok()?;
+			self.size = fs::metadata(&self.path).map(|meta| meta.len()).unwrap_or(0);
+			self.file = Some(file);
+			if !existed {
+				crate::ndless::refresh_documents();
+			}
+		}
+		self.file.as_mut()
+	}
+
+	fn rotate(&mut self) {
+		self.file = None;
+		let plan = self.rotation.plan(&self.path);
+		if plan.is_empty() {
+			let _ = fs::remove_file(&self.path);
+		}
+		for (from, to) in plan {
+			if from.exists() {
+				let _ = fs::remove_file(&to);
+				let _ = fs::rename(&from, &to);
+			}
+		}
+	}
+}
+
+impl Sink for FileSink {
+	fn write_line(&mut self, _level: Level, line: &str) {
+		if self.rotation.needs_rotation(self.size, line.len() + 1) {
+			self.rotate();
+		}
+		if let Some(file) = self.open() {
+			if writeln!(file, "{}", line).is_ok() {
+				self.size += line.len() as u64 + 1;
+			}
+		}
+	}
+
+	fn flush(&mut self) {
+		if let Some(file) = &mut self.file {
+			let _ = file.flush();
+		}
+	}
+}
+
+/// Keeps the most recent lines so that they can be drawn on screen. Clones
+/// share the same lines, so one can be given to the [`Builder`] while another
+/// is kept for drawing.
+///
+/// ```no_run
+/// use ndless::hw::screen::lcd_type;
+/// use ndless::lcd::{self, Framebuffer};
+/// use ndless::logger::{Builder, Overlay};
+///
+/// let overlay = Overlay::new(8);
+/// Builder::new().sink(overlay.clone()).init().unwrap();
+///
+/// let screen = lcd_type();
+/// let mut data = vec![0u8; screen.byte_len()];
+/// let mut frame = Framebuffer::new(screen, &mut data);
+/// // Draw the game, then the log on top
+/// overlay.draw(&mut frame, 0, 0);
+/// lcd::blit(frame.data(), screen);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Overlay {
+	lines: Rc<RefCell<VecDeque<(Level, String)>>>,
+	capacity: usize,
+}
+
+impl Overlay {
+	pub fn new(capacity: usize) -> Overlay {
+		Overlay {
+			lines: Rc::new(RefCell::new(VecDeque::with_capacity(capacity))),
+			capacity,
+		}
+	}
+
+	/// Calls `f` with each line, oldest first.
+	pub fn for_each_line<F: FnMut(Level, &str)>(&self, mut f: F) {
+		for (level, line) in self.lines.borrow().iter() {
+			f(*level, line)
+		}
+	}
+
+	/// Returns a copy of the current lines, oldest first.
+	pub fn lines(&self) -> Vec<String> {
+		self.lines
+			.borrow()
+			.iter()
+			.map(|(_, line)| line.clone())
+			.collect()
+	}
+
+	pub fn clear(&self) {
+		self.lines.borrow_mut().clear();
+	}
+
+	/// Draws the lines over whatever is on `target`, oldest first, with the
+	/// top left of the first line at `x` and `y`. Lines are drawn in
+	/// [`TINY_4X6`] on a black background, in the [color](level_color) of
+	/// their level. Returns the area that was drawn on.
+	pub fn draw(&self, target: &mut impl Target, x: i32, y: i32) -> Rect {
+		let mut width = 0;
+		let mut height = 0;
+		for (level, line) in self.lines.borrow().iter() {
+			let style = Style::new(level_color(*level)).background([0; 3]);
+			let area = TINY_4X6.draw(target, line, x, y + height as i32, &style);
+			width = width.max(area.width);
+			height += area.height;
+		}
+		Rect::new(x, y, width, height)
+	}
+}
+
+/// The color that [`Overlay::draw`] uses for each level, as 8-bit RGB.
+pub fn level_color(level: Level) -> [u8; 3] {
+	match level {
+		Level::Error => [255, 85, 85],
+		Level::Warn => [255, 255, 85],
+		Level::Info => [255, 255, 255],
+		Level::Debug => [85, 255, 255],
+		Level::Trace => [170, 170, 170],
+	}
+}
+
+impl Sink for Overlay {
+	fn write_line(&mut self, level: Level, line: &str) {
+		if self.capacity == 0 {
+			return;
+		}
+		let mut lines = self.lines.borrow_mut();
+		if lines.len() == self.capacity {
+			lines.pop_front();
+		}
+		lines.push_back((level, line.to_string()));
+	}
+}
+
+/// The logger installed by [`Builder::init`].
+pub struct Logger {
+	filter: Filter,
+	sinks: RefCell<Vec<Box<dyn Sink>>>,
+}
+
+// Threads are not supported in ndless, so the logger can never be accessed
+// from two places at once.
+unsafe impl Sync for Logger {}
+unsafe impl Send for Logger {}
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		self.filter.enabled(metadata.target(), metadata.level())
+	}
+
+	fn log(&self, record: &Record) {
+		if !self.enabled(record.metadata()) {
+			return;
+		}
+		// A sink that logs would otherwise panic here, so drop its records
+		if let Ok(mut sinks) = self.sinks.try_borrow_mut() {
+			let line = format_record(
+				crate::timer::get_ticks(),
+				record.level(),
+				record.target(),
+				record.args(),
+			);
+			for sink in sinks.iter_mut() {
+				sink.write_line(record.level(), &line);
+			}
+		}
+	}
+
+	fn flush(&self) {
+		if let Ok(mut sinks) = self.sinks.try_borrow_mut() {
+			for sink in sinks.iter_mut() {
+				sink.flush();
+			}
+		}
+	}
+}
+
+/// The logger that [`Builder::init`] installed. Like the logger itself, this
+/// is shared by every thread.
+static mut INSTALLED: Option<Logger> = None;
+
+/// What is actually handed to [`log::set_logger`], so that the logger can be
+/// freed by [`release`] instead of leaked. Records are dropped after that.
+struct Installed;
+
+impl Installed {
+	fn logger(&self) -> Option<&Logger> {
+		unsafe { (*core::ptr::addr_of!(INSTALLED)).as_ref() }
+	}
+}
+
+impl Log for Installed {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		self.logger().is_some_and(|logger| logger.enabled(metadata))
+	}
+
+	fn log(&self, record: &Record) {
+		if let Some(logger) = self.logger() {
+			logger.log(record);
+		}
+	}
+
+	fn flush(&self) {
+		if let Some(logger) = self.logger() {
+			logger.flush();
+		}
+	}
+}
+
+/// Flushes and frees the installed logger. See
+/// [`process::__release_runtime_state`](crate::process::__release_runtime_state).
+pub(crate) unsafe fn release() {
+	if let Some(logger) = (*core::ptr::addr_of_mut!(INSTALLED)).take() {
+		logger.flush();
+	}
+}
+
+/// Configures and installs the logger.
+#[derive(Default)]
+pub struct Builder {
+	filter: Filter,
+	sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Builder {
+	/// Creates a builder that only logs errors and has no sinks.
+	pub fn new() -> Builder {
+		Builder::default()
+	}
+
+	/// Applies filter directives. See [`Filter::parse`].
+	pub fn filter(&mut self, spec: &str) -> &mut Builder {
+		self.filter.merge(spec);
+		self
+	}
+
+	pub fn filter_level(&mut self, level: LevelFilter) -> &mut Builder {
+		self.filter.default = level;
+		self
+	}
+
+	pub fn filter_module(&mut self, module: &str, level: LevelFilter) -> &mut Builder {
+		self.filter.insert(module, level);
+		self
+	}
+
+	/// Applies directives from the `log.<program>` key of [`NDLESS_CFG`],
+	/// then from a `--log` program argument, where `<program>` is the name of
+	/// the program without `.tns`.
+	pub fn filter_from_config(&mut self) -> &mut Builder {
+		let program = crate::env::args()
+			.next()
+			.and_then(|path| {
+				PathBuf::from(path)
+					.file_stem()
+					.map(|stem| stem.to_string_lossy().into_owned())
+			})
+			.unwrap_or_default();
+		if let Ok(contents) = fs::read_to_string(NDLESS_CFG) {
+			if let Some(spec) = find_cfg_value(&contents, &format!("log.{}", program)) {
+				self.filter.merge(spec);
+			}
+		}
+		if let Some(spec) = find_log_arg(crate::env::args().skip(1)) {
+			self.filter.merge(&spec);
+		}
+		self
+	}
+
+	/// Logs to the serial port.
+	pub fn serial(&mut self) -> &mut Builder {
+		self.sink(SerialSink)
+	}
+
+	pub fn sink<S: Sink + 'static>(&mut self, sink: S) -> &mut Builder {
+		self.sinks.push(Box::new(sink));
+		self
+	}
+
+	/// Installs the logger. This may only be done once.
+	pub fn init(&mut self) -> Result<(), SetLoggerError> {
+		let logger = Logger {
+			filter: core::mem::take(&mut self.filter),
+			sinks: RefCell::new(core::mem::take(&mut self.sinks)),
+		};
+		let max_level = logger.filter.max_level();
+		log::set_logger(&Installed)?;
+		unsafe { INSTALLED = Some(logger) };
+		log::set_max_level(max_level);
+		Ok(())
+	}
+}
+
+/// Logs to the serial port using the filter from
+/// [`Builder::filter_from_config`].
+///
+/// # Panics
+///
+/// Panics if a logger has already been installed.
+pub fn init() {
+	Builder::new()
+		.filter_from_config()
+		.serial()
+		.init()
+		.expect("a logger was already installed")
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+	use alloc::vec;
+
+	use super::*;
+	use crate::hw::screen::Screen;
+	use crate::lcd::Framebuffer;
+
+	#[test]
+	fn format() {
+		assert_eq!(
+			format_record(
+				12 * 32768 + 32768 / 4,
+				Level::Info,
+				"game::ai",
+				&format_args!("thinking for {} ms", 5)
+			),
+			"[   12.250] INFO  game::ai: thinking for 5 ms"
+		);
+		assert_eq!(
+			format_record(0, Level::Error, "game", &format_args!("oops")),
+			"[    0.000] ERROR game: oops"
+		);
+	}
+
+	#[test]
+	fn filter_parse() {
+		let filter = Filter::parse("info, game::ai=debug,ndless=off,game::ai::search, bad=nope");
+		assert_eq!(filter.level_for("game"), LevelFilter::Info);
+		assert_eq!(filter.level_for("game::ai"), LevelFilter::Debug);
+		assert_eq!(filter.level_for("game::ai::eval"), LevelFilter::Debug);
+		assert_eq!(filter.level_for("game::ai::search"), LevelFilter::Trace);
+		assert_eq!(filter.level_for("game::aim"), LevelFilter::Info);
+		assert_eq!(filter.level_for("ndless::fs"), LevelFilter::Off);
+		assert_eq!(filter.level_for("bad"), LevelFilter::Info);
+		assert_eq!(filter.max_level(), LevelFilter::Trace);
+		assert!(filter.enabled("game", Level::Warn));
+		assert!(!filter.enabled("game", Level::Debug));
+		assert!(!filter.enabled("ndless", Level::Error));
+
+		assert_eq!(Filter::parse("").level_for("game"), LevelFilter::Error);
+		assert_eq!(Filter::parse("WARN").level_for("game"), LevelFilter::Warn);
+	}
+
+	#[test]
+	fn filter_merge() {
+		let mut filter = Filter::parse("warn,game=info");
+		filter.merge("game=trace,error");
+		assert_eq!(filter.level_for("game"), LevelFilter::Trace);
+		assert_eq!(filter.level_for("other"), LevelFilter::Error);
+		assert_eq!(filter.modules.len(), 1);
+	}
+
+	#[test]
+	fn cfg_and_args() {
+		let cfg = "ext.txt=editor\nlog.game = debug,game::ai=trace\nlog.other=off\nlog.game=info\n";
+		assert_eq!(find_cfg_value(cfg, "log.game"), Some("info"));
+		assert_eq!(find_cfg_value(cfg, "log.other"), Some("off"));
+		assert_eq!(find_cfg_value(cfg, "log.missing"), None);
+
+		let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+		assert_eq!(
+			find_log_arg(args(&["--log=debug"])),
+			Some("debug".to_string())
+		);
+		assert_eq!(
+			find_log_arg(args(&["file.txt", "--log", "game=trace"])),
+			Some("game=trace".to_string())
+		);
+		assert_eq!(find_log_arg(args(&["--log"])), None);
+		assert_eq!(find_log_arg(args(&["--logger=x"])), None);
+	}
+
+	#[test]
+	fn rotation() {
+		let rotation = Rotation {
+			max_bytes: 100,
+			max_files: 3,
+		};
+		assert!(!rotation.needs_rotation(0, 500));
+		assert!(!rotation.needs_rotation(50, 50));
+		assert!(rotation.needs_rotation(50, 51));
+		assert!(!Rotation {
+			max_bytes: 0,
+			max_files: 3
+		}
+		.needs_rotation(1000, 1));
+
+		let base = Path::new("/documents/game.log.tns");
+		assert_eq!(
+			Rotation::rotated_path(base, 2),
+			PathBuf::from("/documents/game.log.2.tns")
+		);
+		assert_eq!(
+			Rotation::rotated_path(Path::new("game.log"), 1),
+			PathBuf::from("game.log.1")
+		);
+		assert_eq!(
+			rotation.plan(base),
+			vec![
+				(
+					PathBuf::from("/documents/game.log.2.tns"),
+					PathBuf::from("/documents/game.log.3.tns")
+				),
+				(
+					PathBuf::from("/documents/game.log.1.tns"),
+					PathBuf::from("/documents/game.log.2.tns")
+				),
+				(
+					PathBuf::from("/documents/game.log.tns"),
+					PathBuf::from("/documents/game.log.1.tns")
+				),
+			]
+		);
+		assert!(Rotation {
+			max_bytes: 100,
+			max_files: 0
+		}
+		.plan(base)
+		.is_empty());
+	}
+
+	#[test]
+	fn overlay() {
+		let overlay = Overlay::new(2);
+		let mut sink = overlay.clone();
+		sink.write_line(Level::Info, "one");
+		sink.write_line(Level::Warn, "two");
+		sink.write_line(Level::Error, "three");
+		assert_eq!(
+			overlay.lines(),
+			vec!["two".to_string(), "three".to_string()]
+		);
+		let mut levels = Vec::new();
+		overlay.for_each_line(|level, _| levels.push(level));
+		assert_eq!(levels, vec![Level::Warn, Level::Error]);
+		overlay.clear();
+		assert!(overlay.lines().is_empty());
+	}
+
+	#[test]
+	fn overlay_drawing() {
+		let screen = Screen::Screen320x240x565;
+		// How each color looks once it's been through the frame
+		let mut scratch = vec![0; screen.byte_len()];
+		let mut scratch = Framebuffer::new(screen, &mut scratch);
+		let mut shown = |rgb: [u8; 3]| {
+			scratch.set_pixel(0, 0, scratch.color(rgb));
+			scratch.pixel(0, 0).unwrap()
+		};
+		let (black, blue) = (shown([0; 3]), shown([0, 0, 255]));
+		let info = shown(level_color(Level::Info));
+		let error = shown(level_color(Level::Error));
+
+		let mut data = vec![0; screen.byte_len()];
+		let mut frame = Framebuffer::new(screen, &mut data);
+		frame.fill(frame.color([0, 0, 255]));
+		let overlay = Overlay::new(4);
+		let mut sink = overlay.clone();
+		sink.write_line(Level::Info, "started");
+		sink.write_line(Level::Error, "out of lives");
+		let area = overlay.draw(&mut frame, 2, 10);
+		assert_eq!(area, Rect::new(2, 10, 12 * 4, 12));
+
+		// The colors in the area covered by one line
+		let colors = |top: i32| {
+			let mut colors = Vec::new();
+			for y in top..top + 6 {
+				for x in 2..2 + 12 * 4 {
+					let color = frame.pixel(x, y).unwrap();
+					if !colors.contains(&color) {
+						colors.push(color);
+					}
+				}
+			}
+			colors.sort_unstable();
+			colors
+		};
+		let mut expected = vec![black, info, blue];
+		expected.sort_unstable();
+		// "started" is shorter, so the frame shows after it
+		assert_eq!(colors(10), expected);
+		let mut expected = vec![black, error];
+		expected.sort_unstable();
+		assert_eq!(colors(16), expected);
+		assert_eq!(frame.pixel(1, 10), Some(blue));
+		assert_eq!(frame.pixel(2, 22), Some(blue));
+
+		assert_eq!(
+			Overlay::new(4).draw(&mut frame, 0, 0),
+			Rect::new(0, 0, 0, 0)
+		);
+	}
+
+	#[test]
+	fn released() {
+		let overlay = Overlay::new(4);
+		Builder::new()
+			.filter("info")
+			.sink(overlay.clone())
+			.init()
+			.unwrap();
+		log::info!("kept");
+		unsafe { release() };
+		log::info!("dropped");
+		let lines = overlay.lines();
+		assert_eq!(lines.len(), 1);
+		assert!(lines[0].ends_with(": kept"), "{:?}", lines);
+	}
+}
@@ -1,10 +1,23 @@
+pub mod assets;
+pub mod console;
 pub mod env;
+pub mod font;
+pub mod graphics;
 pub mod hw;
+pub mod image;
 pub mod input;
+pub mod lcd;
+#[cfg(feature = "log")]
+pub mod logger;
 pub mod math;
 pub mod msg;
 pub mod ndless;
 pub mod out;
 pub mod process;
+pub mod resident;
+#[cfg(feature = "host")]
+pub mod sim;
+#[cfg(feature = "storage")]
+pub mod storage;
 pub mod thread;
 pub mod timer;
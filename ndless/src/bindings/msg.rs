@@ -1,13 +1,23 @@
 //! # Message boxes
 //! This module contains functions to display message boxes to the user.
+//!
+//! With the `host` feature, nothing is shown. Instead, each message box is
+//! recorded in [`sim::messages`](crate::sim::messages), and the ones that
+//! need an answer take the next one given to
+//! [`sim::respond`](crate::sim::respond).
 
 use alloc::string::String;
+#[cfg(not(feature = "host"))]
 use core::mem::transmute;
+#[cfg(not(feature = "host"))]
 use core::slice;
 
+#[cfg(not(feature = "host"))]
 use cstr_core::CStr;
+#[cfg(not(feature = "host"))]
 use ndless_sys::_show_msgbox;
 
+#[cfg(not(feature = "host"))]
 use crate::cstr;
 use crate::prelude::*;
 
@@ -21,67 +31,95 @@ pub enum Button {
 
 /// Creates a dialog box with a single button, labeled "OK"
 pub fn msg(title: &str, msg: &str) {
-	let title = cstr!(title);
-	let msg = cstr!(msg);
-	unsafe {
-		_show_msgbox(title.as_ptr(), msg.as_ptr(), 0);
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_message(title, msg, &[]);
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let msg = cstr!(msg);
+		unsafe {
+			_show_msgbox(title.as_ptr(), msg.as_ptr(), 0);
+		}
 	}
 }
 
 /// Creates a dialog box with two buttons
 pub fn msg_2b(title: &str, msg: &str, btn1: &str, btn2: &str) -> Button {
-	let title = cstr!(title);
-	let msg = cstr!(msg);
-	let btn1 = cstr!(btn1);
-	let btn2 = cstr!(btn2);
-	unsafe {
-		transmute(_show_msgbox(
-			title.as_ptr(),
-			msg.as_ptr(),
-			2,
-			btn1.as_ptr(),
-			btn2.as_ptr(),
-		))
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_message(title, msg, &[btn1, btn2]).button(2)
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let msg = cstr!(msg);
+		let btn1 = cstr!(btn1);
+		let btn2 = cstr!(btn2);
+		unsafe {
+			transmute(_show_msgbox(
+				title.as_ptr(),
+				msg.as_ptr(),
+				2,
+				btn1.as_ptr(),
+				btn2.as_ptr(),
+			))
+		}
 	}
 }
 
 /// Creates a dialog box with three buttons
 pub fn msg_3b(title: &str, msg: &str, btn1: &str, btn2: &str, btn3: &str) -> Button {
-	let title = cstr!(title);
-	let msg = cstr!(msg);
-	let btn1 = cstr!(btn1);
-	let btn2 = cstr!(btn2);
-	let btn3 = cstr!(btn3);
-	unsafe {
-		transmute(_show_msgbox(
-			title.as_ptr(),
-			msg.as_ptr(),
-			3,
-			btn1.as_ptr(),
-			btn2.as_ptr(),
-			btn3.as_ptr(),
-		))
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_message(title, msg, &[btn1, btn2, btn3]).button(3)
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let msg = cstr!(msg);
+		let btn1 = cstr!(btn1);
+		let btn2 = cstr!(btn2);
+		let btn3 = cstr!(btn3);
+		unsafe {
+			transmute(_show_msgbox(
+				title.as_ptr(),
+				msg.as_ptr(),
+				3,
+				btn1.as_ptr(),
+				btn2.as_ptr(),
+				btn3.as_ptr(),
+			))
+		}
 	}
 }
 
 /// Creates a dialog box with a numerical input
 pub fn msg_numeric(title: &str, subtitle: &str, msg: &str, range: (i32, i32)) -> Option<i32> {
-	let title = cstr!(title);
-	let subtitle = cstr!(subtitle);
-	let msg = cstr!(msg);
-	let mut num = 0i32;
-	match unsafe {
-		ndless_sys::show_1numeric_input(
-			title.as_ptr(),
-			subtitle.as_ptr(),
-			msg.as_ptr(),
-			&mut num,
-			range.0,
-			range.1,
-		)
-	} {
-		1 => Some(num),
-		_ => None,
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_message(title, &format!("{}\n{}", subtitle, msg), &[]).number(range)
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let subtitle = cstr!(subtitle);
+		let msg = cstr!(msg);
+		let mut num = 0i32;
+		match unsafe {
+			ndless_sys::show_1numeric_input(
+				title.as_ptr(),
+				subtitle.as_ptr(),
+				msg.as_ptr(),
+				&mut num,
+				range.0,
+				range.1,
+			)
+		} {
+			1 => Some(num),
+			_ => None,
+		}
 	}
 }
 
@@ -94,52 +132,72 @@ pub fn msg_2numeric(
 	msg2: &str,
 	range2: (i32, i32),
 ) -> Option<(i32, i32)> {
-	let title = cstr!(title);
-	let subtitle = cstr!(subtitle);
-	let msg1 = cstr!(msg1);
-	let msg2 = cstr!(msg2);
-	let mut num1 = 0i32;
-	let mut num2 = 0i32;
-	match unsafe {
-		ndless_sys::show_2numeric_input(
-			title.as_ptr(),
-			subtitle.as_ptr(),
-			msg1.as_ptr(),
-			&mut num1,
-			range1.0,
-			range1.1,
-			msg2.as_ptr(),
-			&mut num2,
-			range2.0,
-			range2.1,
-		)
-	} {
-		1 => Some((num1, num2)),
-		_ => None,
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_message(title, &format!("{}\n{}\n{}", subtitle, msg1, msg2), &[])
+			.numbers(range1, range2)
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let subtitle = cstr!(subtitle);
+		let msg1 = cstr!(msg1);
+		let msg2 = cstr!(msg2);
+		let mut num1 = 0i32;
+		let mut num2 = 0i32;
+		match unsafe {
+			ndless_sys::show_2numeric_input(
+				title.as_ptr(),
+				subtitle.as_ptr(),
+				msg1.as_ptr(),
+				&mut num1,
+				range1.0,
+				range1.1,
+				msg2.as_ptr(),
+				&mut num2,
+				range2.0,
+				range2.1,
+			)
+		} {
+			1 => Some((num1, num2)),
+			_ => None,
+		}
 	}
 }
 
 /// Creates a dialog box with a text input
 pub fn msg_input(title: &str, msg: &str, default: &str) -> Option<String> {
-	let title = cstr!(title);
-	let msg = cstr!(msg);
-	let default = cstr!(default);
-	let mut ptr: *mut cty::c_char = core::ptr::null_mut();
-	let ret = match unsafe {
-		ndless_sys::show_msg_user_input(title.as_ptr(), msg.as_ptr(), default.as_ptr(), &mut ptr)
-	} {
-		-1 => None,
-		len => unsafe {
-			Some(
-				CStr::from_bytes_with_nul_unchecked(slice::from_raw_parts(
-					ptr as *const u8,
-					len as usize + 1,
-				))
-				.to_string_lossy()
-				.into_owned(),
+	#[cfg(feature = "host")]
+	{
+		crate::sim::show_input(title, msg, default).text()
+	}
+	#[cfg(not(feature = "host"))]
+	{
+		let title = cstr!(title);
+		let msg = cstr!(msg);
+		let default = cstr!(default);
+		let mut ptr: *mut cty::c_char = core::ptr::null_mut();
+		let ret = match unsafe {
+			ndless_sys::show_msg_user_input(
+				title.as_ptr(),
+				msg.as_ptr(),
+				default.as_ptr(),
+				&mut ptr,
 			)
-		},
-	};
-	unsafe { ndless_sys::free(ptr as *mut cty::c_void) };
-	ret
+		} {
+			-1 => None,
+			len => unsafe {
+				Some(
+					CStr::from_bytes_with_nul_unchecked(slice::from_raw_parts(
+						ptr as *const u8,
+						len as usize + 1,
+					))
+					.to_string_lossy()
+					.into_owned(),
+				)
+			},
+		};
+		unsafe { ndless_sys::free(ptr as *mut cty::c_void) };
+		ret
+	}
 }
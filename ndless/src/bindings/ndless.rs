@@ -8,6 +8,33 @@ pub fn assert_ndless_rev(required_version: u32) {
 	unsafe { ndless_sys::assert_ndless_rev(required_version) }
 }
 
+/// Returns the revision of Ndless that is installed on the calculator.
+pub fn ndless_rev() -> u32 {
+	unsafe { ndless_sys::nl_ndless_rev() }
+}
+
+/// How many OS versions [`os_index`] can tell apart.
+const OS_VERSIONS: usize = 128;
+
+/// `1` to `OS_VERSIONS`, so that `nl_osvalue` gives back the index plus one,
+/// or `0` for an OS that isn't in the list.
+static OS_INDICES: [u32; OS_VERSIONS] = {
+	let mut indices = [0; OS_VERSIONS];
+	let mut i = 0;
+	while i < OS_VERSIONS {
+		indices[i] = i as u32 + 1;
+		i += 1;
+	}
+	indices
+};
+
+/// Returns the index that Ndless gives the OS version that is running. This
+/// is the same index that [`OsTable`](crate::resident::OsTable) uses.
+pub fn os_index() -> Option<usize> {
+	let value = unsafe { ndless_sys::nl_osvalue(OS_INDICES.as_ptr(), OS_VERSIONS as u32) };
+	(value as usize).checked_sub(1)
+}
+
 pub fn is_startup() -> bool {
 	unsafe { ndless_sys::nl_isstartup() > 0 }
 }
@@ -22,11 +49,16 @@ pub fn bkpt() {
 	}
 }
 
-/// See
-/// [Hackspire](https://hackspire.org/index.php/Ndless_features_and_limitations#Resident_programs)
+/// Keeps the program in memory after `main` returns. See
+/// [`resident`](crate::resident) for how to make use of this, and
+/// [Hackspire](https://hackspire.org/index.php/Ndless_features_and_limitations#Resident_programs).
+///
+/// The program's arguments are freed when `main` returns, so they are copied
+/// for [`env::args`](crate::env::args) to keep working.
 pub fn set_resident() {
 	unsafe {
 		if ndless_static_vars::PROGRAM_STATE == ndless_static_vars::ProgramState::Normal {
+			crate::resident::save_args();
 			ndless_sys::nl_set_resident();
 			ndless_static_vars::ARGUMENTS = None;
 			ndless_static_vars::PROGRAM_STATE = ndless_static_vars::ProgramState::Resident;
@@ -34,10 +66,25 @@ pub fn set_resident() {
 	}
 }
 
+/// Returns whether [`set_resident`] has been called.
+pub fn is_resident() -> bool {
+	unsafe { ndless_static_vars::PROGRAM_STATE == ndless_static_vars::ProgramState::Resident }
+}
+
+/// since Ndless v3.1 r756. Stops the OS from redrawing the screen when the
+/// program exits, so that its output stays visible.
+pub fn no_screen_redraw() {
+	unsafe { ndless_sys::nl_no_scr_redraw() }
+}
+
 /// Must be called at the end of a program that creates or deletes files,
 /// to update the OS document browser.
 pub fn refresh_documents() {
-	unsafe { ndless_sys::refresh_osscr() }
+	#[cfg(not(feature = "host"))]
+	unsafe {
+		ndless_sys::refresh_osscr()
+	}
+	// The computer's file browser doesn't need to be told
 }
 
 /// return true if a third-party Launcher was used to boot the OS, such as
@@ -1,12 +1,30 @@
 //! # Serial output
-//! This module contains tools to output to the serial port. Also, take a look
-//! at [io::stdout](crate::io::stdout).
+//! This module contains tools to output to the serial port, or to the
+//! [on-screen console](crate::console) if it is enabled. Also, take a look
+//! at [io::stdout](crate::io::stdout), and [io::stdin](crate::io::stdin) for
+//! reading input from the serial port.
 
 use core::fmt::Arguments;
 
+use crate::console::{self, Output};
 use crate::io;
 use crate::io::Write;
 
 pub fn print_fmt(fmt: Arguments) -> core::fmt::Result {
-	io::stdout().write_fmt(fmt).map_err(|_| core::fmt::Error)
+	let output = console::output();
+	if output != Output::Console {
+		io::stdout().write_fmt(fmt).map_err(|_| core::fmt::Error)?;
+	}
+	if output != Output::Serial {
+		console::with_console(|console| {
+			console.write_fmt(fmt)?;
+			console.flush()
+		})
+		.map_err(|_| core::fmt::Error)?;
+	}
+	Ok(())
+}
+
+pub fn eprint_fmt(fmt: Arguments) -> core::fmt::Result {
+	io::stderr().write_fmt(fmt).map_err(|_| core::fmt::Error)
 }
@@ -1,10 +1,82 @@
-use core::fmt;
+//! # Processes
+//! Exiting the program and running cleanup code when it does.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt::{self, Write};
+
+use crate::error::Error;
+use crate::fs::OpenOptions;
+use crate::io;
+use crate::path::PathBuf;
+use crate::time::DateTime;
+
+type ExitHook = Box<dyn FnOnce()>;
+
+program_state! {
+	fn exit_hooks() -> Vec<ExitHook> = Vec::new();
+}
+
+/// Registers a function to be called when the program exits, whether by
+/// returning from `main`, calling [`exit`], or panicking. Hooks are called in
+/// the reverse order that they were added, so that later resources are cleaned
+/// up first.
+///
+/// This is used to put the hardware back the way the OS expects it, such as
+/// the LCD mode and timers, even if destructors don't get to run. The hooks
+/// for this crate's own resources are registered automatically.
+///
+/// # Examples
+///
+/// ```rust
+/// ndless::process::at_exit(|| ndless::println!("goodbye"));
+/// ```
+pub fn at_exit<F: FnOnce() + 'static>(hook: F) {
+	unsafe { (*exit_hooks()).push(Box::new(hook)) }
+}
+
+#[doc(hidden)]
+pub fn __run_at_exit() {
+	// Each hook is removed before it is called, so if it panics, the panic
+	// handler can carry on with the rest
+	while let Some(hook) = unsafe { (*exit_hooks()).pop() } {
+		hook();
+	}
+	unsafe {
+		*exit_hooks() = Vec::new();
+		if let Some(exit) = crate::RUNTIME_EXIT {
+			crate::RUNTIME_EXIT = None;
+			exit();
+		}
+	}
+}
+
+/// Frees the memory that the runtime keeps for the rest of the program, such
+/// as the logger, the [assets](crate::assets) index and buffered
+/// [stdin](crate::io::stdin) input, so that it isn't mistaken for a leak.
+///
+/// # Safety
+///
+/// Nothing may use that state afterwards, including `'static` references that
+/// it handed out, such as [`assets::paths`](crate::assets::paths). This is
+/// only meant to be called once the program is done, after
+/// [`__run_at_exit`].
+#[doc(hidden)]
+pub unsafe fn __release_runtime_state() {
+	crate::assets::release();
+	crate::io::release_stdin();
+	#[cfg(feature = "log")]
+	crate::logger::release();
+}
 
 /// ## WARNING
 ///
 /// This **will** leak memory without careful planning, as it does not run any
-/// destructors! You need to make sure that all scopes end before calling this!
-/// You can use
+/// destructors or [`at_exit`] hooks! You need to make sure that all scopes end
+/// before calling this! You can use
 ///
 /// ```rust
 /// fn main() {
@@ -20,11 +92,13 @@ pub fn abort() -> ! {
 	unsafe { ndless_sys::abort() }
 }
 
+/// Runs the [`at_exit`] hooks, then exits with the given status code.
+///
 /// ## WARNING
 ///
 /// This **will** leak memory without careful planning, as it does not run any
-/// destructors! You need to make sure that all scopes end before calling this!
-/// You can either use
+/// destructors! You need to make sure that all scopes end before calling this,
+/// or use [`exit_gracefully`] instead. You can either use
 ///
 /// ```rust
 /// fn main() {
@@ -47,9 +121,235 @@ pub fn abort() -> ! {
 /// ```
 /// to ensure that no memory leaks.
 pub fn exit(code: i32) -> ! {
+	__run_at_exit();
 	unsafe { ndless_sys::exit(code) }
 }
 
+/// A request to exit with a status code, created by [`exit_gracefully`].
+///
+/// Rather than ending the program immediately like [`exit`], this is passed up
+/// to `main` with `?` or `return`, so that everything along the way is dropped
+/// normally. `main` can return it directly, or as a `Result<(), Exit>`, in
+/// which case it isn't shown as an error.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Exit {
+	code: i32,
+}
+
+impl Exit {
+	pub fn code(self) -> i32 {
+		self.code
+	}
+}
+
+/// Returns an [`Exit`] error, which ends the program with `code` once it has
+/// been returned from `main`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use ndless::process::{self, Exit};
+///
+/// fn run() -> Result<(), Exit> {
+///     let buffer = vec![0u8; 1024];
+///     if buffer.is_empty() {
+///         // `buffer` is freed on the way out
+///         process::exit_gracefully(2)?;
+///     }
+///     Ok(())
+/// }
+///
+/// #[ndless::prelude::entry]
+/// fn main() -> Result<(), Exit> {
+///     run()
+/// }
+/// ```
+pub fn exit_gracefully<T>(code: i32) -> Result<T, Exit> {
+	Err(Exit { code })
+}
+
+/// A status code for `main` to return, like `std::process::ExitCode`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ExitCode(i32);
+
+impl ExitCode {
+	pub const SUCCESS: ExitCode = ExitCode(0);
+	pub const FAILURE: ExitCode = ExitCode(1);
+
+	pub fn code(self) -> i32 {
+		self.0
+	}
+}
+
+impl From<u8> for ExitCode {
+	fn from(code: u8) -> Self {
+		ExitCode(i32::from(code))
+	}
+}
+
+impl From<i32> for ExitCode {
+	fn from(code: i32) -> Self {
+		ExitCode(code)
+	}
+}
+
+/// The name of the file that [`ErrorPresenter::LogFile`] appends to.
+pub const ERROR_LOG_NAME: &str = "error.log.tns";
+
+/// How an error returned from `main` is shown. This is set with
+/// [`set_error_presenter`], or with `#[entry(error = "...")]` using the names
+/// `"message_box"`, `"console"`, `"log_file"` and `"silent"`.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ErrorPresenter {
+	/// A message box titled "Error". This is the default.
+	#[default]
+	MessageBox,
+	/// The [on-screen console](crate::console), waiting for a key to be pressed
+	/// before exiting.
+	Console,
+	/// A line at the end of [`ERROR_LOG_NAME`], in the program's directory.
+	LogFile,
+	/// Nothing. The error is only reflected in the exit code.
+	Silent,
+	/// A function that is passed the error message.
+	Custom(fn(&str)),
+}
+
+impl ErrorPresenter {
+	/// Shows an error message.
+	pub fn present(self, message: &str) {
+		match self {
+			ErrorPresenter::MessageBox => crate::msg::msg("Error", message),
+			ErrorPresenter::Console => {
+				crate::console::with_console(|console| {
+					let _ = write!(
+						console,
+						"\x1b[31mError:\x1b[0m {}\n\nPress any key to exit",
+						message
+					);
+					console.refresh();
+				});
+				crate::input::wait_no_key_pressed();
+				crate::input::wait_key_pressed();
+			}
+			ErrorPresenter::LogFile => {
+				// There's nowhere else to report a failure to
+				let _ = append_error_log(message);
+			}
+			ErrorPresenter::Silent => {}
+			ErrorPresenter::Custom(present) => present(message),
+		}
+	}
+}
+
+fn append_error_log(message: &str) -> io::Result<()> {
+	let path = crate::env::args()
+		.next()
+		.map(PathBuf::from)
+		.and_then(|path| path.parent().map(|dir| dir.join(ERROR_LOG_NAME)))
+		.unwrap_or_else(|| PathBuf::from(ERROR_LOG_NAME));
+	let existed = path.exists();
+	let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
+	io::Write::write_fmt(
+		&mut file,
+		format_args!("[{}] {}\n", DateTime::now_utc(), message),
+	)?;
+	drop(file);
+	if !existed {
+		crate::ndless::refresh_documents();
+	}
+	Ok(())
+}
+
+program_state! {
+	fn error_presenter_state() -> ErrorPresenter = ErrorPresenter::MessageBox;
+}
+
+/// Returns the current [`ErrorPresenter`].
+pub fn error_presenter() -> ErrorPresenter {
+	unsafe { *error_presenter_state() }
+}
+
+/// Sets how errors returned from `main` are shown.
+pub fn set_error_presenter(presenter: ErrorPresenter) {
+	unsafe { *error_presenter_state() = presenter }
+}
+
+/// Formats an error returned from `main`. [`Report`]s, `Box<dyn Error>` and
+/// `Box<dyn Error + Send + Sync>` are formatted with
+/// [`Display`](fmt::Display), followed by their [sources](Error::source).
+/// Anything else is formatted with [`Debug`](fmt::Debug).
+pub fn error_message<E: fmt::Debug + 'static>(err: &E) -> String {
+	let any = err as &dyn Any;
+	if let Some(err) = any.downcast_ref::<Box<dyn Error>>() {
+		with_sources(err.to_string(), err.source())
+	} else if let Some(err) = any.downcast_ref::<Box<dyn Error + Send + Sync>>() {
+		with_sources(err.to_string(), err.source())
+	} else {
+		format!("{:?}", err)
+	}
+}
+
+fn with_sources(mut message: String, mut source: Option<&(dyn Error + 'static)>) -> String {
+	if source.is_some() {
+		message.push_str("\n\nCaused by:");
+	}
+	while let Some(err) = source {
+		message.push_str(&format!("\n  {}", err));
+		source = err.source();
+	}
+	message
+}
+
+/// An error for `main` to return that is shown with
+/// [`Display`](fmt::Display) and its [sources](Error::source), rather than
+/// with [`Debug`](fmt::Debug). Anything that implements [`Error`] can be
+/// converted into a `Report` with `?`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use ndless::fs::File;
+/// use ndless::process::Report;
+///
+/// #[ndless::prelude::entry]
+/// fn main() -> Result<(), Report> {
+///     // Shows "No such file or directory" rather than `Os { code: 2, .. }`
+///     let _save = File::open("/documents/save.tns")?;
+///     Ok(())
+/// }
+/// ```
+pub struct Report(Box<dyn Error>);
+
+impl Report {
+	pub fn new<E: Error + 'static>(err: E) -> Report {
+		Report(Box::new(err))
+	}
+
+	/// Returns the error that is being reported.
+	pub fn into_inner(self) -> Box<dyn Error> {
+		self.0
+	}
+}
+
+impl<E: Error + 'static> From<E> for Report {
+	fn from(err: E) -> Report {
+		Report::new(err)
+	}
+}
+
+impl fmt::Debug for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&with_sources(self.0.to_string(), self.0.source()))
+	}
+}
+
 /// A trait for implementing arbitrary return types in the `main` function.
 ///
 /// The c-main function only supports to return integers as return type.
@@ -57,7 +357,13 @@ pub fn exit(code: i32) -> ! {
 /// to an integer.
 ///
 /// The default implementations are returning 0 to indicate
-/// a successful execution. In case of a failure, 1 is returned.
+/// a successful execution. In case of a failure, 1 is returned, after the
+/// error has been shown by the current [`ErrorPresenter`].
+///
+/// Unlike in `std`, the error type of a `Result` must be `'static`, so that
+/// [`Report`]s, boxed errors and [`exit_gracefully`] can be recognized. Errors
+/// that borrow data can be converted first, such as with
+/// `map_err(|err| format!("{:?}", err))`.
 pub trait Termination {
 	/// Is called to get the representation of the value as status code.
 	/// This status code is returned to the operating system.
@@ -78,28 +384,106 @@ impl Termination for i32 {
 	}
 }
 
-impl<E: fmt::Debug> Termination for Result<(), E> {
+impl Termination for ! {
 	fn report(self) -> i32 {
-		match self {
-			Ok(()) => ().report(),
-			Err(err) => Err::<!, _>(err).report(),
-		}
+		self
 	}
 }
 
-impl Termination for ! {
+impl Termination for ExitCode {
+	#[inline]
 	fn report(self) -> i32 {
-		self
+		self.0
+	}
+}
+
+impl Termination for Exit {
+	fn report(self) -> i32 {
+		self.code
 	}
 }
 
-impl<E: fmt::Debug> Termination for Result<!, E> {
+impl<T: Termination, E: fmt::Debug + 'static> Termination for Result<T, E> {
 	fn report(self) -> i32 {
-		let err = match self {
-			Err(err) => err,
-			_ => unreachable!(),
-		};
-		crate::msg::msg("Error", &alloc::format!("Error: {:?}", err));
-		1
+		match self {
+			Ok(val) => val.report(),
+			Err(err) => match (&err as &dyn Any).downcast_ref::<Exit>() {
+				// Exiting with exit_gracefully isn't a failure
+				Some(exit) => exit.report(),
+				None => {
+					error_presenter().present(&error_message(&err));
+					1
+				}
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+
+	use super::*;
+
+	#[derive(Debug)]
+	struct Corrupt(io::Error);
+
+	impl fmt::Display for Corrupt {
+		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			f.write_str("the save file is corrupt")
+		}
+	}
+
+	impl Error for Corrupt {
+		fn source(&self) -> Option<&(dyn Error + 'static)> {
+			Some(&self.0)
+		}
+	}
+
+	fn corrupt() -> Corrupt {
+		Corrupt(io::Error::new(io::ErrorKind::InvalidData, "bad checksum"))
+	}
+
+	#[test]
+	fn report_shows_sources() {
+		let report = Report::from(corrupt());
+		let expected = "the save file is corrupt\n\nCaused by:\n  bad checksum";
+		assert_eq!(report.to_string(), expected);
+		assert_eq!(error_message(&report), expected);
+		assert_eq!(error_message(&Box::<dyn Error>::from(corrupt())), expected);
+	}
+
+	#[test]
+	fn other_errors_use_debug() {
+		assert_eq!(error_message(&"missing"), "\"missing\"");
+		assert_eq!(error_message(&Exit { code: 3 }), "Exit { code: 3 }");
+	}
+
+	#[test]
+	fn graceful_exit_code() {
+		assert_eq!(Ok::<_, Exit>(()).report(), 0);
+		assert_eq!(exit_gracefully::<()>(3).report(), 3);
+		assert_eq!(Ok::<_, Exit>(ExitCode::FAILURE).report(), 1);
+	}
+
+	#[cfg(feature = "host")]
+	#[test]
+	fn runtime_state_is_released() {
+		use crate::io::{stdin, BufRead};
+
+		crate::sim::reset();
+		at_exit(|| {});
+		let mut stdin = stdin();
+		stdin.set_echo(false);
+		crate::sim::type_serial("never read\r");
+		assert_eq!(stdin.fill_buf().unwrap(), b"never read\n");
+		__run_at_exit();
+		assert_eq!(unsafe { (*exit_hooks()).capacity() }, 0);
+		unsafe { __release_runtime_state() };
+		let mut buf = [0; 16];
+		assert_eq!(
+			stdin.try_read(&mut buf).unwrap_err().kind(),
+			io::ErrorKind::WouldBlock
+		);
 	}
 }
@@ -0,0 +1,305 @@
+//! # Resident programs
+//! A resident program stays in memory after `main` returns, and usually
+//! installs hooks into the OS so that its code runs later, such as when a key
+//! is pressed or a document is opened. See
+//! [Hackspire](https://hackspire.org/index.php/Ndless_features_and_limitations#Resident_programs)
+//! for more about how they work.
+//!
+//! A hook replaces the instructions at an address in the OS with a call to a
+//! handler. Since each OS version has its code at different addresses, each
+//! hook is given an [`OsTable`] of addresses, and the right one is selected
+//! when the hooks are installed.
+//!
+//! ```no_run
+//! use ndless::resident::{HookSet, OsTable, Persistent, Registers};
+//!
+//! // Addresses for each OS version, in the order Ndless uses
+//! static KEY_HOOK: [u32; 4] = [0x1002_3A10, 0x1002_3A44, 0, 0x1002_4F00];
+//! static PRESSES: Persistent<u32> = Persistent::new();
+//!
+//! fn on_key(_registers: &mut Registers) {
+//!     PRESSES.with(|presses| *presses += 1);
+//! }
+//!
+//! #[ndless::prelude::entry(resident)]
+//! fn main() {
+//!     // Nothing can be using it before the hook is installed
+//!     PRESSES.set(0).unwrap();
+//!     let mut hooks = HookSet::new();
+//!     hooks.hook(OsTable::new(&KEY_HOOK).unwrap(), on_key);
+//!     match hooks.install() {
+//!         // Keep the hooks after main returns
+//!         Ok(()) => hooks.leak(),
+//!         Err(err) => ndless::msg::msg("Error", &format!("{}", err)),
+//!     }
+//! }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::mem;
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::error::Error;
+
+pub use self::table::{OsTable, TableError, OS_MEMORY};
+
+mod table;
+mod trampoline;
+
+/// The registers when a hook was called. Changes are written back when the
+/// handler returns, before the original instructions run.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Registers {
+	/// `r0` to `r12`.
+	pub r: [u32; 13],
+	pub lr: u32,
+}
+
+/// A function that is called when the OS reaches a hooked address.
+pub type Handler = fn(&mut Registers);
+
+static mut IN_HANDLER: bool = false;
+
+/// Called by trampolines, with the address of a [`Handler`] in `handler`.
+extern "C" fn dispatch(registers: &mut Registers, handler: usize) {
+	unsafe {
+		let handler: Handler = mem::transmute(handler);
+		// If a handler causes the OS to reach another hook, let the OS carry on
+		// without running it
+		if IN_HANDLER {
+			return;
+		}
+		IN_HANDLER = true;
+		handler(registers);
+		IN_HANDLER = false;
+	}
+}
+
+struct Hook {
+	table: OsTable,
+	handler: Handler,
+}
+
+struct Installed {
+	address: *mut u32,
+	original: [u32; 2],
+	// Referenced by the patched OS code, so it must not move
+	_trampoline: Box<[u32; trampoline::LEN]>,
+}
+
+/// A group of hooks that are installed and uninstalled together.
+///
+/// The hooks are uninstalled when the `HookSet` is dropped. To keep them after
+/// `main` returns, make the program resident and call [`leak`](HookSet::leak).
+#[derive(Default)]
+pub struct HookSet {
+	hooks: Vec<Hook>,
+	installed: Vec<Installed>,
+}
+
+impl HookSet {
+	pub fn new() -> HookSet {
+		Default::default()
+	}
+
+	/// Adds a hook, which is installed by [`install`](HookSet::install).
+	pub fn hook(&mut self, table: OsTable, handler: Handler) -> &mut Self {
+		self.hooks.push(Hook { table, handler });
+		self
+	}
+
+	pub fn is_installed(&self) -> bool {
+		!self.installed.is_empty()
+	}
+
+	/// Installs every hook. If any of them can't be installed on this OS,
+	/// none are.
+	pub fn install(&mut self) -> Result<(), InstallError> {
+		if self.is_installed() {
+			return Err(InstallError::AlreadyInstalled);
+		}
+		let mut addresses = Vec::with_capacity(self.hooks.len());
+		for (index, hook) in self.hooks.iter().enumerate() {
+			let address = hook
+				.table
+				.current()
+				.ok_or(InstallError::UnsupportedOs { hook: index })?;
+			let address = address as *mut u32;
+			for offset in 0..2 {
+				let instruction = unsafe { read_volatile(address.add(offset)) };
+				if !trampoline::is_relocatable(instruction) {
+					return Err(InstallError::NotRelocatable {
+						hook: index,
+						instruction,
+					});
+				}
+			}
+			if addresses.contains(&address) {
+				return Err(InstallError::Duplicate { hook: index });
+			}
+			addresses.push(address);
+		}
+		for (hook, address) in self.hooks.iter().zip(addresses) {
+			unsafe {
+				let original = [read_volatile(address), read_volatile(address.add(1))];
+				let trampoline = Box::new(trampoline::build(
+					original,
+					address.add(2) as u32,
+					dispatch as extern "C" fn(&mut Registers, usize) as usize as u32,
+					hook.handler as usize as u32,
+				));
+				let patch = trampoline::patch(trampoline.as_ptr() as u32);
+				write_volatile(address, patch[0]);
+				write_volatile(address.add(1), patch[1]);
+				self.installed.push(Installed {
+					address,
+					original,
+					_trampoline: trampoline,
+				});
+			}
+		}
+		crate::hw::clear_cache();
+		Ok(())
+	}
+
+	/// Restores the original OS code.
+	pub fn uninstall(&mut self) {
+		if !self.is_installed() {
+			return;
+		}
+		for installed in self.installed.drain(..) {
+			unsafe {
+				write_volatile(installed.address, installed.original[0]);
+				write_volatile(installed.address.add(1), installed.original[1]);
+			}
+		}
+		crate::hw::clear_cache();
+	}
+
+	/// Keeps the hooks installed forever, for resident programs.
+	pub fn leak(self) {
+		mem::forget(self)
+	}
+}
+
+impl Drop for HookSet {
+	fn drop(&mut self) {
+		self.uninstall();
+	}
+}
+
+/// Returned by [`HookSet::install`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InstallError {
+	AlreadyInstalled,
+	/// The hook at this index doesn't have an address for the running OS.
+	UnsupportedOs {
+		hook: usize,
+	},
+	/// The hook at this index would replace an instruction that can't be
+	/// moved, such as a branch. The address is probably wrong.
+	NotRelocatable {
+		hook: usize,
+		instruction: u32,
+	},
+	/// The hook at this index has the same address as an earlier hook.
+	Duplicate {
+		hook: usize,
+	},
+}
+
+impl fmt::Display for InstallError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			InstallError::AlreadyInstalled => f.write_str("hooks are already installed"),
+			InstallError::UnsupportedOs { hook } => {
+				write!(f, "hook {} doesn't support this OS version", hook)
+			}
+			InstallError::NotRelocatable { hook, instruction } => write!(
+				f,
+				"hook {} would replace an instruction that can't be moved ({:#010x})",
+				hook, instruction
+			),
+			InstallError::Duplicate { hook } => {
+				write!(f, "hook {} has the same address as another hook", hook)
+			}
+		}
+	}
+}
+
+impl Error for InstallError {}
+
+/// A value that lives after `main` returns, for use by hooks in resident
+/// programs. It starts out empty.
+///
+/// Access is through [`with`](Persistent::with), which refuses to give out a
+/// second reference while the first is in use, such as when a hook is called
+/// from inside another.
+pub struct Persistent<T> {
+	value: UnsafeCell<Option<T>>,
+	borrowed: Cell<bool>,
+}
+
+// The calculator only has one thread
+unsafe impl<T> Sync for Persistent<T> {}
+
+impl<T> Default for Persistent<T> {
+	fn default() -> Persistent<T> {
+		Persistent::new()
+	}
+}
+
+impl<T> Persistent<T> {
+	pub const fn new() -> Persistent<T> {
+		Persistent {
+			value: UnsafeCell::new(None),
+			borrowed: Cell::new(false),
+		}
+	}
+
+	/// Stores a value, returning the old one. Returns `Err` with the new value
+	/// if the old one is in use.
+	pub fn set(&self, value: T) -> Result<Option<T>, T> {
+		if self.borrowed.get() {
+			return Err(value);
+		}
+		Ok(unsafe { (*self.value.get()).replace(value) })
+	}
+
+	/// Removes the value, unless it is in use.
+	pub fn take(&self) -> Option<T> {
+		if self.borrowed.get() {
+			return None;
+		}
+		unsafe { (*self.value.get()).take() }
+	}
+
+	/// Runs a function with the value. Returns `None` if there is no value, or
+	/// it is already in use.
+	pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+		if self.borrowed.get() {
+			return None;
+		}
+		self.borrowed.set(true);
+		let result = unsafe { (*self.value.get()).as_mut().map(f) };
+		self.borrowed.set(false);
+		result
+	}
+}
+
+static SAVED_ARGS: Persistent<Vec<String>> = Persistent::new();
+
+/// Copies the program's arguments, since they are freed when `main` returns
+/// in a resident program.
+pub(crate) fn save_args() {
+	let _ = SAVED_ARGS.set(crate::env::args().collect());
+}
+
+pub(crate) fn saved_args() -> Vec<String> {
+	SAVED_ARGS.with(|args| args.clone()).unwrap_or_default()
+}
@@ -0,0 +1,151 @@
+use core::fmt;
+use core::ops::Range;
+
+use crate::error::Error;
+
+/// The part of memory that the OS is loaded into. Hooks must point inside it.
+pub const OS_MEMORY: Range<u32> = 0x1000_0000..0x1400_0000;
+
+/// A value for each OS version, in the order that Ndless's `nl_osvalue` uses.
+/// `0` means that the OS version isn't supported.
+///
+/// Tables are checked when they are created, so mistakes in an address list
+/// can be caught by a test on the host rather than by crashing the calculator.
+///
+/// ```
+/// use ndless::resident::OsTable;
+///
+/// static ADDRESSES: [u32; 2] = [0x1001_2340, 0];
+///
+/// let table = OsTable::new(&ADDRESSES).unwrap();
+/// assert_eq!(table.get(0), Some(0x1001_2340));
+/// assert_eq!(table.get(1), None);
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct OsTable {
+	addresses: &'static [u32],
+}
+
+impl OsTable {
+	/// Checks that every address is either `0`, or a word-aligned address in
+	/// [`OS_MEMORY`].
+	pub fn new(addresses: &'static [u32]) -> Result<OsTable, TableError> {
+		if addresses.iter().all(|&address| address == 0) {
+			return Err(TableError::Empty);
+		}
+		for (index, &address) in addresses.iter().enumerate() {
+			if address == 0 {
+				continue;
+			}
+			if address % 4 != 0 {
+				return Err(TableError::Misaligned { index, address });
+			}
+			if !OS_MEMORY.contains(&address) {
+				return Err(TableError::OutOfRange { index, address });
+			}
+		}
+		Ok(OsTable { addresses })
+	}
+
+	pub fn addresses(&self) -> &'static [u32] {
+		self.addresses
+	}
+
+	/// The address for the OS with Ndless's index `os`, if it is supported.
+	pub fn get(&self, os: usize) -> Option<u32> {
+		self.addresses
+			.get(os)
+			.copied()
+			.filter(|&address| address != 0)
+	}
+
+	/// The address for the OS that is running, if it is supported.
+	pub fn current(&self) -> Option<u32> {
+		let address =
+			unsafe { ndless_sys::nl_osvalue(self.addresses.as_ptr(), self.addresses.len() as u32) };
+		if address == 0 {
+			None
+		} else {
+			Some(address)
+		}
+	}
+}
+
+/// A problem with the addresses given to [`OsTable::new`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TableError {
+	/// No OS version is supported.
+	Empty,
+	/// An address isn't a multiple of 4, so it can't be the start of an ARM
+	/// instruction.
+	Misaligned { index: usize, address: u32 },
+	/// An address isn't in [`OS_MEMORY`].
+	OutOfRange { index: usize, address: u32 },
+}
+
+impl fmt::Display for TableError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			TableError::Empty => f.write_str("no OS version has an address"),
+			TableError::Misaligned { index, address } => write!(
+				f,
+				"address {:#010x} for OS {} is not word-aligned",
+				address, index
+			),
+			TableError::OutOfRange { index, address } => write!(
+				f,
+				"address {:#010x} for OS {} is outside of the OS",
+				address, index
+			),
+		}
+	}
+}
+
+impl Error for TableError {}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+
+	use super::{OsTable, TableError};
+
+	#[test]
+	fn valid() {
+		static ADDRESSES: [u32; 4] = [0x1000_0000, 0, 0x1234_5678, 0x13FF_FFFC];
+		let table = OsTable::new(&ADDRESSES).unwrap();
+		assert_eq!(table.get(0), Some(0x1000_0000));
+		assert_eq!(table.get(1), None);
+		assert_eq!(table.get(2), Some(0x1234_5678));
+		assert_eq!(table.get(3), Some(0x13FF_FFFC));
+		assert_eq!(table.get(4), None);
+	}
+
+	#[test]
+	fn invalid() {
+		static EMPTY: [u32; 0] = [];
+		static ZEROS: [u32; 2] = [0, 0];
+		static MISALIGNED: [u32; 3] = [0x1000_0000, 0, 0x1000_0002];
+		static LOW: [u32; 1] = [0x0FFF_FFFC];
+		static HIGH: [u32; 2] = [0, 0x1400_0000];
+		assert_eq!(OsTable::new(&EMPTY), Err(TableError::Empty));
+		assert_eq!(OsTable::new(&ZEROS), Err(TableError::Empty));
+		assert_eq!(
+			OsTable::new(&MISALIGNED),
+			Err(TableError::Misaligned {
+				index: 2,
+				address: 0x1000_0002
+			})
+		);
+		assert_eq!(
+			OsTable::new(&LOW),
+			Err(TableError::OutOfRange {
+				index: 0,
+				address: 0x0FFF_FFFC
+			})
+		);
+		assert_eq!(
+			OsTable::new(&HIGH).unwrap_err().to_string(),
+			"address 0x14000000 for OS 1 is outside of the OS"
+		);
+	}
+}
@@ -0,0 +1,148 @@
+//! ARM code that is written into the OS to call hooks.
+//!
+//! The first two instructions at a hooked address are replaced with a jump to
+//! a trampoline:
+//!
+//! ```text
+//! ldr pc, [pc, #-4]
+//! .word trampoline
+//! ```
+//!
+//! The trampoline saves the registers and the flags, calls the handler with a
+//! pointer to the registers, restores them, runs the two instructions that
+//! were replaced and jumps back to the instruction after them. The flags are
+//! restored so that conditional instructions among the replaced ones see the
+//! same flags as they would have in the OS.
+
+/// `ldr pc, [pc, #-4]`, which jumps to the address in the next word.
+const JUMP_TO_NEXT_WORD: u32 = 0xE51F_F004;
+
+/// The number of words in a trampoline.
+pub const LEN: usize = 14;
+
+/// The instructions that replace the first two at a hooked address.
+pub fn patch(trampoline: u32) -> [u32; 2] {
+	[JUMP_TO_NEXT_WORD, trampoline]
+}
+
+/// Builds a trampoline. `dispatch` is called with the address of the saved
+/// registers in `r0` and `handler` in `r1`.
+pub fn build(original: [u32; 2], return_address: u32, dispatch: u32, handler: u32) -> [u32; LEN] {
+	[
+		0xE92D_5FFF, // stmfd sp!, {r0-r12, lr}
+		0xE10F_4000, // mrs r4, cpsr (r4 is kept by the handler)
+		0xE1A0_000D, // mov r0, sp
+		0xE59F_1020, // ldr r1, [pc, #0x20] (handler)
+		0xE59F_C018, // ldr r12, [pc, #0x18] (dispatch)
+		0xE12F_FF3C, // blx r12
+		0xE128_F004, // msr cpsr_f, r4
+		0xE8BD_5FFF, // ldmfd sp!, {r0-r12, lr}
+		original[0],
+		original[1],
+		JUMP_TO_NEXT_WORD,
+		return_address,
+		dispatch,
+		handler,
+	]
+}
+
+/// Whether an instruction still does the same thing when it is moved into a
+/// trampoline. Branches and instructions that read `pc` depend on where they
+/// are.
+pub fn is_relocatable(instruction: u32) -> bool {
+	let condition = instruction >> 28;
+	let class = (instruction >> 25) & 0b111;
+	let rn = (instruction >> 16) & 0xF;
+	let rm = instruction & 0xF;
+	if condition == 0xF {
+		// Unconditional instructions, such as `blx label`
+		return false;
+	}
+	if instruction & 0x0FFF_FFD0 == 0x012F_FF10 {
+		// bx and blx with a register
+		return rm != 15;
+	}
+	match class {
+		// b and bl
+		0b101 => false,
+		// Instructions that can have a register operand
+		0b000 | 0b011 => rn != 15 && rm != 15,
+		// Immediate operands and block transfers
+		0b001 | 0b010 | 0b100 => rn != 15,
+		_ => true,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Where an `ldr rd, [pc, #imm]` at `index` loads from.
+	fn pc_relative_target(code: &[u32], index: usize) -> usize {
+		let instruction = code[index];
+		assert_eq!(
+			instruction & 0xFF7F_0000,
+			0xE51F_0000,
+			"not a pc-relative load"
+		);
+		let offset = (instruction & 0xFFF) as usize;
+		// pc is two instructions ahead
+		let pc = (index + 2) * 4;
+		if instruction & (1 << 23) != 0 {
+			(pc + offset) / 4
+		} else {
+			(pc - offset) / 4
+		}
+	}
+
+	#[test]
+	fn literals() {
+		let code = build(
+			[0xAAAA_AAAA, 0xBBBB_BBBB],
+			0x1000_0008,
+			0xCCCC_CCCC,
+			0xDDDD_DDDD,
+		);
+		assert_eq!(code[pc_relative_target(&code, 3)], 0xDDDD_DDDD);
+		assert_eq!(code[pc_relative_target(&code, 4)], 0xCCCC_CCCC);
+		assert_eq!(&code[8..10], &[0xAAAA_AAAA, 0xBBBB_BBBB]);
+		assert_eq!(code[pc_relative_target(&code, 10)], 0x1000_0008);
+
+		// The flags are saved to r4 before the handler and put back after it,
+		// before the original instructions
+		let blx = code.iter().position(|&word| word == 0xE12F_FF3C).unwrap();
+		let mrs = code.iter().position(|&word| word == 0xE10F_4000).unwrap();
+		let msr = code.iter().position(|&word| word == 0xE128_F004).unwrap();
+		assert!(mrs < blx && blx < msr && msr < 8);
+
+		let patch = patch(0x1234_5678);
+		assert_eq!(patch[pc_relative_target(&patch, 0)], 0x1234_5678);
+	}
+
+	#[test]
+	fn relocatable() {
+		// push {r4, lr}
+		assert!(is_relocatable(0xE92D_4010));
+		// mov r4, r0
+		assert!(is_relocatable(0xE1A0_4000));
+		// ldr r0, [r1, #4]
+		assert!(is_relocatable(0xE591_0004));
+		// bx lr
+		assert!(is_relocatable(0xE12F_FF1E));
+		// swi 0
+		assert!(is_relocatable(0xEF00_0000));
+
+		// ldr r0, [pc, #8]
+		assert!(!is_relocatable(0xE59F_0008));
+		// add r0, pc, #4
+		assert!(!is_relocatable(0xE28F_0004));
+		// mov r0, pc
+		assert!(!is_relocatable(0xE1A0_000F));
+		// bl somewhere
+		assert!(!is_relocatable(0xEB00_0010));
+		// beq somewhere
+		assert!(!is_relocatable(0x0A00_0010));
+		// blx label
+		assert!(!is_relocatable(0xFA00_0010));
+	}
+}
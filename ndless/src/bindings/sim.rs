@@ -0,0 +1,881 @@
+//! # Simulated calculator
+//! With the `host` feature, programs can be built for the computer and unit
+//! tested with `cargo test`. Instead of talking to Ndless, the rest of the
+//! crate is backed by a simulated calculator:
+//!
+//! - The [clock](ticks) only moves when a test [advances](advance) it, or when
+//!   the program [sleeps](crate::thread::sleep) or [idles](crate::hw::idle),
+//!   which skip straight to the time they'd wake up at.
+//! - Keys are [pressed](press) by the test, or [scripted](KeyScript) to be
+//!   pressed and released as the clock moves.
+//! - Message boxes are [recorded](messages), and take their answers from
+//!   [`respond`].
+//! - [Standard input](crate::io::stdin) reads what was [typed](type_serial)
+//!   into the serial port.
+//! - Frames sent to the [LCD](crate::lcd) are kept, and can be [saved as a
+//!   PNG](Frame::save_png).
+//! - Files are read and written with the computer's file system, with the
+//!   [documents folder](set_documents_dir) in a temporary directory. Like the
+//!   calculator's OS, [`rename`](crate::fs::rename) won't replace a file.
+//!
+//! ```rust
+//! use core::time::Duration;
+//! use ndless::input::{get_keys, Key};
+//! use ndless::msg::{msg_2b, Button};
+//! use ndless::sim::{self, KeyScript, Response};
+//!
+//! sim::play(KeyScript::new().wait_ms(200).tap(Key::Enter));
+//! ndless::thread::sleep(Duration::from_millis(250));
+//! assert_eq!(get_keys(), [Key::Enter]);
+//!
+//! sim::respond(Response::Button(Button::Two));
+//! assert_eq!(msg_2b("Quit", "Save first?", "Yes", "No"), Button::Two);
+//! assert_eq!(sim::messages()[0].buttons, ["Yes", "No"]);
+//! ```
+//!
+//! Each test thread gets its own calculator, documents folder, and program
+//! state, such as [exit hooks](crate::process::at_exit) and the
+//! [console](crate::console), so tests can run in parallel. The exceptions are
+//! the [logger](crate::logger), which the `log` crate keeps for the whole
+//! process, and the program's [arguments](crate::env::args).
+//! Anything that would wait forever, like waiting for a key that isn't
+//! scripted, panics instead.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use cstr_core::CString;
+
+use crate::hw::model::HardwareIds;
+use crate::hw::power::{self, BatteryStatus, Calibration, ChargeState};
+use crate::hw::screen::{self, Screen, HEIGHT, WIDTH};
+use crate::input::Key;
+use crate::msg::Button;
+use crate::timer::Ticks;
+
+pub(crate) mod libc;
+
+/// How long [`KeyScript::tap`] holds a key for.
+pub const TAP: Duration = Duration::from_millis(100);
+
+/// The brightness that the screen starts at, in percent.
+const BRIGHTNESS: u8 = 80;
+
+/// A TI-Nspire CX.
+pub const CX: HardwareIds = HardwareIds {
+	hwtype: 1,
+	subtype: 0,
+	screen: Screen::Screen320x240x565,
+	keypad: 3,
+};
+
+/// A grayscale TI-Nspire with a Touchpad keypad.
+pub const TOUCHPAD: HardwareIds = HardwareIds {
+	hwtype: 0,
+	subtype: 0,
+	screen: Screen::Screen320x240x4,
+	keypad: 3,
+};
+
+/// A TI-Nspire CX II.
+pub const CX_II: HardwareIds = HardwareIds {
+	hwtype: 2,
+	subtype: 0,
+	screen: Screen::Screen320x240x565,
+	keypad: 4,
+};
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+enum Event {
+	Press(Key),
+	Release(Key),
+}
+
+/// A sequence of key presses and releases, to be [played](play) as the clock
+/// moves.
+///
+/// ```rust
+/// use ndless::input::Key;
+/// use ndless::sim::KeyScript;
+///
+/// // Type "42", then hold the right arrow for a second
+/// let script = KeyScript::new()
+/// 	.tap(Key::Key4)
+/// 	.tap(Key::Key2)
+/// 	.hold_ms(Key::Right, 1000);
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct KeyScript {
+	events: Vec<(u64, Event)>,
+	end: u64,
+}
+
+impl KeyScript {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn press(mut self, key: Key) -> Self {
+		self.events.push((self.end, Event::Press(key)));
+		self
+	}
+
+	pub fn release(mut self, key: Key) -> Self {
+		self.events.push((self.end, Event::Release(key)));
+		self
+	}
+
+	/// Waits before the next step.
+	pub fn wait(mut self, dur: Duration) -> Self {
+		self.end += dur.as_ticks() as u64;
+		self
+	}
+
+	pub fn wait_ms(self, ms: u32) -> Self {
+		self.wait(Duration::from_millis(ms as u64))
+	}
+
+	/// Presses `key`, waits, and releases it.
+	pub fn hold(self, key: Key, dur: Duration) -> Self {
+		self.press(key).wait(dur).release(key)
+	}
+
+	pub fn hold_ms(self, key: Key, ms: u32) -> Self {
+		self.hold(key, Duration::from_millis(ms as u64))
+	}
+
+	/// Holds `key` for [`TAP`], then waits as long again, so that tapping
+	/// the same key twice counts as two presses.
+	pub fn tap(self, key: Key) -> Self {
+		self.hold(key, TAP).wait(TAP)
+	}
+
+	/// How long the script takes to play.
+	pub fn duration(&self) -> Duration {
+		Duration::from_ticks(self.end as u32)
+	}
+}
+
+/// What the user does with the next message box that needs an answer.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum Response {
+	/// Answers [`msg_2b`](crate::msg::msg_2b) or
+	/// [`msg_3b`](crate::msg::msg_3b).
+	Button(Button),
+	/// Answers [`msg_numeric`](crate::msg::msg_numeric).
+	Number(i32),
+	/// Answers [`msg_2numeric`](crate::msg::msg_2numeric).
+	Numbers(i32, i32),
+	/// Answers [`msg_input`](crate::msg::msg_input).
+	Text(String),
+	/// Closes an input box without entering anything.
+	Cancel,
+}
+
+/// A message box that the program showed.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Message {
+	pub title: String,
+	/// The message, with the subtitle and the label of each field on separate
+	/// lines for numeric input boxes.
+	pub text: String,
+	/// Empty if the only button is "OK".
+	pub buttons: Vec<String>,
+	/// The text that an input box started with.
+	pub default: Option<String>,
+}
+
+/// A message box that's waiting for its [`Response`].
+pub(crate) struct Dialog {
+	title: String,
+}
+
+impl Dialog {
+	fn response(&self) -> Response {
+		with(|calc| calc.responses.pop_front()).unwrap_or_else(|| {
+			panic!(
+				"the message box {:?} needs an answer from `sim::respond`",
+				self.title
+			)
+		})
+	}
+
+	fn wrong(&self, response: Response) -> ! {
+		panic!(
+			"{:?} isn't an answer to the message box {:?}",
+			response, self.title
+		)
+	}
+
+	pub(crate) fn button(self, buttons: u32) -> Button {
+		match self.response() {
+			Response::Button(button) if button as u32 <= buttons => button,
+			other => self.wrong(other),
+		}
+	}
+
+	pub(crate) fn number(self, range: (i32, i32)) -> Option<i32> {
+		match self.response() {
+			Response::Number(num) if in_range(num, range) => Some(num),
+			Response::Cancel => None,
+			other => self.wrong(other),
+		}
+	}
+
+	pub(crate) fn numbers(self, range1: (i32, i32), range2: (i32, i32)) -> Option<(i32, i32)> {
+		match self.response() {
+			Response::Numbers(num1, num2) if in_range(num1, range1) && in_range(num2, range2) => {
+				Some((num1, num2))
+			}
+			Response::Cancel => None,
+			other => self.wrong(other),
+		}
+	}
+
+	pub(crate) fn text(self) -> Option<String> {
+		match self.response() {
+			Response::Text(text) => Some(text),
+			Response::Cancel => None,
+			other => self.wrong(other),
+		}
+	}
+}
+
+fn in_range(num: i32, (min, max): (i32, i32)) -> bool {
+	min <= num && num <= max
+}
+
+/// A frame that was sent to the LCD.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Frame {
+	pub screen: Screen,
+	pub data: Vec<u8>,
+}
+
+impl Frame {
+	/// The color of a pixel, as 8-bit RGB. See
+	/// [`lcd::to_rgb888`](crate::lcd::to_rgb888).
+	pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+		crate::lcd::pixel_rgb888(self.screen, &self.data, x, y)
+	}
+
+	/// The whole frame as 8-bit RGB, three bytes per pixel.
+	pub fn to_rgb888(&self) -> Vec<u8> {
+		crate::lcd::to_rgb888(self.screen, &self.data)
+	}
+
+	pub fn to_png(&self) -> Vec<u8> {
+		crate::lcd::png::encode(WIDTH as u32, HEIGHT as u32, &self.to_rgb888())
+	}
+
+	/// Writes the frame to a PNG file on the computer, to look at when a test
+	/// fails.
+	pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+		std::fs::write(path, self.to_png())
+	}
+}
+
+struct Calculator {
+	hardware: HardwareIds,
+	/// Ticks since the calculator was reset. These don't wrap around, unlike
+	/// [`get_ticks`](crate::timer::get_ticks).
+	now: u64,
+	alarm: Option<u64>,
+	/// Scripted events, in the order that they happen.
+	events: VecDeque<(u64, Event)>,
+	pressed: Vec<Key>,
+	registers: BTreeMap<usize, u32>,
+	lcd: Option<Screen>,
+	frame: Option<Frame>,
+	responses: VecDeque<Response>,
+	messages: Vec<Message>,
+	serial: VecDeque<u8>,
+	battery: BatteryStatus,
+	cpu_speed: u32,
+	documents: CString,
+}
+
+impl Calculator {
+	fn new() -> Self {
+		let mut calc = Calculator {
+			hardware: CX,
+			now: 0,
+			alarm: None,
+			events: VecDeque::new(),
+			pressed: Vec::new(),
+			registers: BTreeMap::new(),
+			lcd: None,
+			frame: None,
+			responses: VecDeque::new(),
+			messages: Vec::new(),
+			serial: VecDeque::new(),
+			battery: battery_at(4000, &power::CX),
+			// 132 MHz, which the OS starts with
+			cpu_speed: 0x0004_1002,
+			documents: CString::new(default_documents()).unwrap(),
+		};
+		calc.reset_brightness();
+		calc
+	}
+
+	fn reset_brightness(&mut self) {
+		let range = if self.hardware.hwtype < 1 {
+			screen::CLASSIC_CONTRAST
+		} else {
+			screen::CX_BACKLIGHT
+		};
+		let register = crate::hw::mmio::DISPLAY.cx + 0x20;
+		self.registers.insert(register, range.to_raw(BRIGHTNESS));
+	}
+
+	/// Moves the clock to `target`, playing the scripted events on the way.
+	fn advance_to(&mut self, target: u64) {
+		while let Some(&(at, event)) = self.events.front() {
+			if at > target {
+				break;
+			}
+			self.events.pop_front();
+			self.now = self.now.max(at);
+			match event {
+				Event::Press(key) => {
+					if !self.pressed.contains(&key) {
+						self.pressed.push(key)
+					}
+				}
+				Event::Release(key) => self.pressed.retain(|&other| other != key),
+			}
+		}
+		self.now = self.now.max(target);
+	}
+
+	fn next_event(&self) -> Option<u64> {
+		self.events.front().map(|&(at, _)| at)
+	}
+}
+
+/// A documents folder for this thread, so that tests running at the same time
+/// don't see each other's files.
+fn default_documents() -> String {
+	static THREADS: AtomicUsize = AtomicUsize::new(0);
+	std::thread_local! {
+		static THREAD: usize = THREADS.fetch_add(1, Ordering::Relaxed);
+	}
+	let name = std::format!("ndless-sim-{}-{}", std::process::id(), THREAD.with(|&thread| thread));
+	let dir = std::env::temp_dir().join(name);
+	dir.join("documents").to_string_lossy().into_owned()
+}
+
+fn battery_at(millivolts: u32, calibration: &Calibration) -> BatteryStatus {
+	BatteryStatus {
+		millivolts,
+		percent: calibration.percent(millivolts),
+		charge: ChargeState::Discharging,
+	}
+}
+
+std::thread_local! {
+	static CALCULATOR: RefCell<Calculator> = RefCell::new(Calculator::new());
+}
+
+fn with<R>(f: impl FnOnce(&mut Calculator) -> R) -> R {
+	CALCULATOR.with(|calc| f(&mut calc.borrow_mut()))
+}
+
+/// Turns the calculator off and on again, forgetting everything that happened
+/// on this thread.
+pub fn reset() {
+	with(|calc| *calc = Calculator::new())
+}
+
+/// Changes the calculator that's simulated, which is a [`CX`] by default.
+/// This also resets the brightness.
+pub fn set_hardware(ids: HardwareIds) {
+	with(|calc| {
+		calc.hardware = ids;
+		calc.reset_brightness();
+	})
+}
+
+pub fn hardware() -> HardwareIds {
+	with(|calc| calc.hardware)
+}
+
+/// The clock, as returned by [`get_ticks`](crate::timer::get_ticks).
+pub fn ticks() -> u32 {
+	with(|calc| calc.now as u32)
+}
+
+/// Moves the clock forward, pressing and releasing scripted keys on the way.
+pub fn advance(dur: Duration) {
+	advance_ticks(dur.as_ticks())
+}
+
+pub fn advance_ms(ms: u32) {
+	advance(Duration::from_millis(ms as u64))
+}
+
+pub fn advance_ticks(ticks: u32) {
+	with(|calc| {
+		let target = calc.now + ticks as u64;
+		calc.advance_to(target)
+	})
+}
+
+pub fn press(key: Key) {
+	with(|calc| {
+		if !calc.pressed.contains(&key) {
+			calc.pressed.push(key)
+		}
+	})
+}
+
+pub fn release(key: Key) {
+	with(|calc| calc.pressed.retain(|&other| other != key))
+}
+
+/// Releases every key.
+pub fn release_all() {
+	with(|calc| calc.pressed.clear())
+}
+
+pub fn is_pressed(key: Key) -> bool {
+	with(|calc| calc.pressed.contains(&key))
+}
+
+pub fn any_pressed() -> bool {
+	with(|calc| !calc.pressed.is_empty())
+}
+
+/// Starts playing `script` from now. Scripts that are already playing carry
+/// on at the same time.
+pub fn play(script: KeyScript) {
+	with(|calc| {
+		let now = calc.now;
+		let mut events: Vec<_> = calc.events.drain(..).collect();
+		events.extend(
+			script
+				.events
+				.into_iter()
+				.map(|(at, event)| (now + at, event)),
+		);
+		// Stable, so that events at the same time keep their order
+		events.sort_by_key(|&(at, _)| at);
+		calc.events = events.into();
+	})
+}
+
+/// Whether any scripted events haven't happened yet.
+pub fn is_playing() -> bool {
+	with(|calc| !calc.events.is_empty())
+}
+
+/// Queues up the answer to a message box. Answers are used in the order that
+/// they're given.
+pub fn respond(response: Response) {
+	with(|calc| calc.responses.push_back(response))
+}
+
+/// Every message box that's been shown, oldest first.
+pub fn messages() -> Vec<Message> {
+	with(|calc| calc.messages.clone())
+}
+
+/// Sends bytes to the calculator's serial port, which the program reads with
+/// [`stdin`](crate::io::stdin). Lines end with `\n` or `\r`, and `\x04` (`^D`)
+/// on an empty line ends the input.
+pub fn type_serial(bytes: impl AsRef<[u8]>) {
+	with(|calc| calc.serial.extend(bytes.as_ref()))
+}
+
+/// The mode that the LCD was [initialized](crate::lcd::init) with, or `None`
+/// if the OS has it.
+pub fn lcd_mode() -> Option<Screen> {
+	with(|calc| calc.lcd)
+}
+
+/// The last frame that was [sent](crate::lcd::blit) to the LCD.
+pub fn frame() -> Option<Frame> {
+	with(|calc| calc.frame.clone())
+}
+
+/// Sets what [`battery_status`](crate::hw::power::battery_status) returns.
+pub fn set_battery(status: BatteryStatus) {
+	with(|calc| calc.battery = status)
+}
+
+pub fn battery() -> BatteryStatus {
+	with(|calc| calc.battery)
+}
+
+/// Moves the documents folder, which is in a temporary directory by default.
+/// It's created if it doesn't exist.
+pub fn set_documents_dir(path: impl AsRef<std::path::Path>) {
+	let path = path.as_ref().to_string_lossy().into_owned();
+	with(|calc| calc.documents = CString::new(path).expect("the path contains a null byte"))
+}
+
+/// Reads a memory-mapped register, which is `0` unless it's been written to.
+pub fn read_register(address: usize) -> u32 {
+	with(|calc| *calc.registers.get(&address).unwrap_or(&0))
+}
+
+pub fn write_register(address: usize, value: u32) {
+	with(|calc| {
+		calc.registers.insert(address, value);
+	})
+}
+
+pub(crate) fn set_alarm(ticks: u32) {
+	with(|calc| calc.alarm = Some(calc.now + ticks as u64))
+}
+
+pub(crate) fn clear_alarm() {
+	with(|calc| calc.alarm = None)
+}
+
+/// Skips to the alarm or the next scripted event, whichever is first.
+pub(crate) fn idle() {
+	with(|calc| {
+		let wake = match (calc.alarm, calc.next_event()) {
+			(Some(alarm), Some(event)) => alarm.min(event),
+			(Some(alarm), None) => alarm,
+			(None, Some(event)) => event,
+			(None, None) => panic!(
+				"the program went to sleep without a timer set or any keys scripted, so it would \
+				 never wake up"
+			),
+		};
+		calc.advance_to(wake);
+	})
+}
+
+/// Plays scripted events until `done` returns `true`.
+pub(crate) fn wait_until(done: impl Fn() -> bool, what: &str) {
+	while !done() {
+		match with(|calc| calc.next_event()) {
+			Some(at) => with(|calc| calc.advance_to(at)),
+			None => panic!("the program waited for {} with no more keys scripted", what),
+		}
+	}
+}
+
+pub(crate) fn read_serial() -> Option<u8> {
+	with(|calc| calc.serial.pop_front())
+}
+
+/// Called before waiting for more input on the serial port.
+pub(crate) fn wait_serial() {
+	if with(|calc| calc.serial.is_empty()) {
+		panic!(
+			"the program waited for input on the serial port with nothing left from \
+			 `sim::type_serial`"
+		);
+	}
+}
+
+/// The simulated touchpad is never touched.
+pub(crate) fn touchpad_scan(report: &mut ndless_sys::touchpad_report_t) -> i32 {
+	report.contact = 0;
+	report.pressed = 0;
+	0
+}
+
+pub(crate) fn set_cpu_speed(raw: u32) -> u32 {
+	with(|calc| core::mem::replace(&mut calc.cpu_speed, raw))
+}
+
+pub(crate) fn lcd_init(screen: Screen) -> bool {
+	with(|calc| {
+		calc.lcd = match screen {
+			Screen::Unknown => None,
+			screen => Some(screen),
+		}
+	});
+	true
+}
+
+pub(crate) fn lcd_blit(data: &[u8], screen: Screen) {
+	with(|calc| {
+		calc.frame = Some(Frame {
+			screen,
+			data: data.to_vec(),
+		})
+	})
+}
+
+pub(crate) fn show_message(title: &str, text: &str, buttons: &[&str]) -> Dialog {
+	log_message(Message {
+		title: title.into(),
+		text: text.into(),
+		buttons: buttons.iter().map(|&button| button.into()).collect(),
+		default: None,
+	})
+}
+
+pub(crate) fn show_input(title: &str, text: &str, default: &str) -> Dialog {
+	log_message(Message {
+		title: title.into(),
+		text: text.into(),
+		buttons: Vec::new(),
+		default: Some(default.into()),
+	})
+}
+
+fn log_message(message: Message) -> Dialog {
+	let title = message.title.clone();
+	with(|calc| calc.messages.push(message));
+	Dialog { title }
+}
+
+pub(crate) fn documents_dir() -> *const cty::c_char {
+	with(|calc| {
+		let _ = std::fs::create_dir_all(calc.documents.to_string_lossy().as_ref());
+		calc.documents.as_ptr()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+	use core::fmt::Write;
+
+	use super::*;
+	use crate::input::{get_keys, is_key_pressed, wait_key_pressed, wait_no_key_pressed};
+	use crate::msg::{msg, msg_2numeric, msg_3b, msg_input, msg_numeric};
+	use crate::timer::{get_ticks, TICKS_PER_SECOND};
+
+	#[test]
+	fn clock() {
+		reset();
+		assert_eq!(get_ticks(), 0);
+		advance(Duration::from_secs(2));
+		assert_eq!(get_ticks(), 2 * TICKS_PER_SECOND);
+		crate::thread::sleep(Duration::from_millis(500));
+		assert_eq!(
+			get_ticks(),
+			2 * TICKS_PER_SECOND + Duration::from_millis(500).as_ticks()
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "never wake up")]
+	fn idle_forever() {
+		reset();
+		crate::hw::idle();
+	}
+
+	#[test]
+	fn keys() {
+		reset();
+		press(Key::Esc);
+		assert!(is_key_pressed(Key::Esc));
+		release(Key::Esc);
+		assert!(get_keys().is_empty());
+
+		let script = KeyScript::new()
+			.wait_ms(1000)
+			.tap(Key::A)
+			.hold_ms(Key::Up, 500)
+			.press(Key::Enter);
+		assert_eq!(script.duration().as_millis(), 1700);
+		play(script);
+		advance_ms(900);
+		assert!(get_keys().is_empty());
+		wait_key_pressed();
+		assert_eq!(get_keys(), [Key::A]);
+		assert_eq!(get_ticks(), Duration::from_millis(1000).as_ticks());
+		wait_no_key_pressed();
+		advance_ms(150);
+		assert_eq!(get_keys(), [Key::Up]);
+		crate::thread::sleep(Duration::from_secs(1));
+		assert_eq!(get_keys(), [Key::Enter]);
+		assert!(!is_playing());
+	}
+
+	#[test]
+	fn message_boxes() {
+		reset();
+		msg("Hello", "World");
+		respond(Response::Button(Button::Three));
+		respond(Response::Number(7));
+		respond(Response::Cancel);
+		respond(Response::Text("Ada".into()));
+		assert_eq!(
+			msg_3b("Save", "Save first?", "Yes", "No", "Cancel"),
+			Button::Three
+		);
+		assert_eq!(msg_numeric("Level", "Pick one", "Level:", (1, 10)), Some(7));
+		assert_eq!(
+			msg_2numeric("Size", "Grid", "Width:", (1, 9), "Height:", (1, 9)),
+			None
+		);
+		assert_eq!(
+			msg_input("Name", "Your name:", "Player"),
+			Some("Ada".into())
+		);
+		let messages = messages();
+		assert_eq!(messages.len(), 5);
+		assert_eq!(
+			messages[0],
+			Message {
+				title: "Hello".into(),
+				text: "World".into(),
+				buttons: vec![],
+				default: None,
+			}
+		);
+		assert_eq!(messages[1].buttons, ["Yes", "No", "Cancel"]);
+		assert_eq!(messages[3].text, "Grid\nWidth:\nHeight:");
+		assert_eq!(messages[4].default.as_deref(), Some("Player"));
+	}
+
+	#[test]
+	#[should_panic(expected = "isn't an answer")]
+	fn out_of_range() {
+		reset();
+		respond(Response::Number(11));
+		msg_numeric("Level", "Pick one", "Level:", (1, 10));
+	}
+
+	#[test]
+	fn hardware() {
+		reset();
+		assert_eq!(crate::hw::model(), crate::hw::Model::Cx);
+		assert_eq!(crate::hw::screen::backlight(), Some(BRIGHTNESS));
+		assert_eq!(crate::hw::screen::set_brightness(30), BRIGHTNESS);
+		assert_eq!(crate::hw::screen::brightness(), 30);
+		set_hardware(TOUCHPAD);
+		assert!(crate::hw::is_classic());
+		assert!(crate::hw::has_touchpad());
+		assert_eq!(crate::hw::screen::contrast(), Some(BRIGHTNESS));
+		assert_eq!(crate::hw::screen::lcd_type(), Screen::Screen320x240x4);
+
+		let status = battery_at(3500, &power::CX);
+		set_battery(status);
+		assert_eq!(crate::hw::power::battery_status(), status);
+	}
+
+	#[test]
+	fn console() {
+		reset();
+		let mut console = crate::console::Console::new();
+		assert_eq!(lcd_mode(), Some(Screen::Screen320x240x565));
+		console.write_str("#").unwrap();
+		console.refresh();
+		let frame = frame().unwrap();
+		assert_eq!(frame.screen, Screen::Screen320x240x565);
+		let lit = (0..8)
+			.flat_map(|y| (0..8).map(move |x| (x, y)))
+			.filter(|&(x, y)| frame.pixel(x, y) != frame.pixel(WIDTH - 1, HEIGHT - 1))
+			.count();
+		assert!(lit > 0, "the character wasn't drawn");
+		assert_eq!(frame.to_png()[1..4], *b"PNG");
+		drop(console);
+		assert_eq!(lcd_mode(), None);
+	}
+
+	#[test]
+	fn serial_input() {
+		use crate::io::{stdin, BufRead, ErrorKind};
+
+		reset();
+		let mut stdin = stdin();
+		stdin.set_echo(false);
+		type_serial("hello\r\nwrold\x08\x08\x08\x08orld");
+		let mut line = String::new();
+		stdin.read_line(&mut line).unwrap();
+		assert_eq!(line, "hello\n");
+		let mut buf = [0; 16];
+		assert_eq!(
+			stdin.try_read(&mut buf).unwrap_err().kind(),
+			ErrorKind::WouldBlock
+		);
+		type_serial("\n\x04");
+		line.clear();
+		stdin.read_line(&mut line).unwrap();
+		assert_eq!(line, "world\n");
+		line.clear();
+		assert_eq!(stdin.read_line(&mut line).unwrap(), 0);
+	}
+
+	#[test]
+	fn serial_line_then_end_of_input() {
+		use crate::io::{stdin, ErrorKind};
+
+		reset();
+		let mut stdin = stdin();
+		stdin.set_echo(false);
+		type_serial("last\r\x04");
+		let mut buf = [0; 16];
+		assert_eq!(stdin.try_read(&mut buf).unwrap(), 5);
+		assert_eq!(&buf[..5], b"last\n");
+		assert_eq!(stdin.try_read(&mut buf).unwrap(), 0);
+		assert_eq!(
+			stdin.try_read(&mut buf).unwrap_err().kind(),
+			ErrorKind::WouldBlock
+		);
+	}
+
+	#[test]
+	fn threads() {
+		use crate::console::{output, set_output, Output};
+
+		reset();
+		let documents = crate::env::get_documents_dir().unwrap();
+		let other = std::thread::spawn(|| {
+			set_output(Output::Both);
+			crate::process::at_exit(|| panic!("ran on the wrong thread"));
+			crate::env::get_documents_dir().unwrap()
+		})
+		.join()
+		.unwrap();
+		assert_ne!(other, documents);
+		assert_eq!(output(), Output::Serial);
+		crate::process::__run_at_exit();
+	}
+
+	#[test]
+	fn files() {
+		reset();
+		let dir = std::env::temp_dir().join(std::format!(
+			"ndless-sim-test-{}-{:?}",
+			std::process::id(),
+			std::thread::current().id()
+		));
+		set_documents_dir(&dir);
+		let documents = crate::env::get_documents_dir().unwrap();
+		assert_eq!(documents.to_str(), dir.to_str());
+
+		let path = documents.join("save.tns");
+		crate::fs::write(&path, b"level 3").unwrap();
+		assert_eq!(crate::fs::read(&path).unwrap(), b"level 3");
+		let metadata = crate::fs::metadata(&path).unwrap();
+		assert!(metadata.is_file());
+		assert_eq!(metadata.len(), 7);
+		assert!(crate::fs::metadata(&documents).unwrap().is_dir());
+		let names: Vec<_> = crate::fs::read_dir(&documents)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name())
+			.collect();
+		assert_eq!(names.len(), 1);
+		assert_eq!(names[0].to_str(), Some("save.tns"));
+		assert!(crate::fs::metadata(documents.join("missing.tns")).is_err());
+
+		// Open files keep their metadata when they're renamed
+		let file = crate::fs::File::open(&path).unwrap();
+		let renamed = documents.join("renamed.tns");
+		crate::fs::rename(&path, &renamed).unwrap();
+		assert_eq!(file.metadata().unwrap().len(), 7);
+		let err = crate::fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.open(&renamed)
+			.unwrap_err();
+		assert_eq!(err.kind(), crate::io::ErrorKind::AlreadyExists);
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,125 @@
+//! Stand-ins for the Ndless functions that the file system uses, built on
+//! `std`. Everything else comes from the computer's C library.
+#![allow(clippy::missing_safety_doc)]
+
+use alloc::boxed::Box;
+use std::ffi::{CStr, CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::IntoRawFd;
+
+use cty::{c_char, c_int};
+use ndless_sys::{nuc_dirent, nuc_stat as Stat, NUC_DIR};
+
+use crate::file_io::sys::os::set_errno;
+use crate::libc::{PO_APPEND, PO_CREAT, PO_EXCL, PO_RDONLY, PO_RDWR, PO_TRUNC, PO_WRONLY};
+
+const EIO: c_int = 5;
+const EEXIST: c_int = 17;
+
+struct Dir {
+	entries: std::fs::ReadDir,
+	/// The name of the last entry, which `readdir` returns a pointer to.
+	name: CString,
+}
+
+fn fail(err: std::io::Error) -> c_int {
+	set_errno(err.raw_os_error().unwrap_or(EIO));
+	-1
+}
+
+unsafe fn path<'a>(path: *const c_char) -> &'a OsStr {
+	OsStr::from_bytes(CStr::from_ptr(path).to_bytes())
+}
+
+pub unsafe fn opendir(name: *const c_char) -> *mut NUC_DIR {
+	match std::fs::read_dir(path(name)) {
+		Ok(entries) => Box::into_raw(Box::new(Dir {
+			entries,
+			name: CString::default(),
+		})) as *mut NUC_DIR,
+		Err(err) => {
+			fail(err);
+			core::ptr::null_mut()
+		}
+	}
+}
+
+/// Unlike Nucleus, this doesn't return `.` and `..`.
+pub unsafe fn readdir(dir: *mut NUC_DIR) -> *mut nuc_dirent {
+	let dir = &mut *(dir as *mut Dir);
+	match dir.entries.next() {
+		Some(Ok(entry)) => {
+			dir.name = CString::new(entry.file_name().as_bytes()).unwrap_or_default();
+			dir.name.as_ptr() as *mut nuc_dirent
+		}
+		Some(Err(err)) => {
+			fail(err);
+			core::ptr::null_mut()
+		}
+		None => core::ptr::null_mut(),
+	}
+}
+
+pub unsafe fn closedir(dir: *mut NUC_DIR) -> c_int {
+	drop(Box::from_raw(dir as *mut Dir));
+	0
+}
+
+pub unsafe fn nuc_stat(name: *const c_char, stat: *mut Stat) -> c_int {
+	match std::fs::metadata(path(name)) {
+		Ok(metadata) => {
+			// The file types and permissions have the same bits as on Linux
+			*stat = Stat {
+				st_dev: metadata.dev() as _,
+				st_ino: metadata.ino() as _,
+				st_mode: metadata.mode(),
+				st_nlink: metadata.nlink() as _,
+				st_uid: metadata.uid() as _,
+				st_gid: metadata.gid() as _,
+				st_rdev: metadata.rdev() as _,
+				st_size: metadata.size() as _,
+				st_atime: metadata.atime() as _,
+				st_mtime: metadata.mtime() as _,
+				st_ctime: metadata.ctime() as _,
+			};
+			0
+		}
+		Err(err) => fail(err),
+	}
+}
+
+/// Opens a file with the flags of `NU_Open`. Like on the calculator, `mode`
+/// doesn't change anything.
+pub unsafe fn open(name: *mut c_char, flags: u32, _mode: u32) -> c_int {
+	let access = flags & (PO_WRONLY | PO_RDWR);
+	let result = std::fs::OpenOptions::new()
+		.read(access != PO_WRONLY)
+		.write(access != PO_RDONLY)
+		.append(flags & PO_APPEND != 0)
+		.truncate(flags & PO_TRUNC != 0)
+		.create(flags & PO_CREAT != 0)
+		.create_new(flags & (PO_CREAT | PO_EXCL) == PO_CREAT | PO_EXCL)
+		.open(path(name));
+	match result {
+		Ok(file) => file.into_raw_fd(),
+		Err(err) => fail(err),
+	}
+}
+
+/// Like Nucleus, this won't replace a file that already exists.
+pub unsafe fn rename(old: *const c_char, new: *const c_char) -> c_int {
+	let new = path(new);
+	if std::fs::symlink_metadata(new).is_ok() {
+		set_errno(EEXIST);
+		return -1;
+	}
+	match std::fs::rename(path(old), new) {
+		Ok(()) => 0,
+		Err(err) => fail(err),
+	}
+}
+
+pub unsafe fn get_documents_dir() -> *const c_char {
+	super::documents_dir()
+}
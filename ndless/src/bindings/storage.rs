@@ -0,0 +1,506 @@
+//! # Storage
+//! A small key-value store for saving settings and game progress, available
+//! with the `storage` feature. Values are anything that implements
+//! [`serde`]'s `Serialize` and `Deserialize`, and are stored in a compact
+//! binary format with [`postcard`].
+//!
+//! Saving is crash-safe: the new contents are written to a temporary file,
+//! which is then renamed over the old one. If the calculator is reset or runs
+//! out of battery part way through, the next [`Settings::open`] finds
+//! whichever copy is complete. Each file also has a checksum, so damaged files
+//! are detected instead of being read as garbage.
+//!
+//! ```no_run
+//! use ndless::storage::Settings;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Default)]
+//! struct Progress {
+//!     level: u32,
+//!     lives: u8,
+//! }
+//!
+//! let mut settings = Settings::in_documents("game.sav.tns").unwrap();
+//! // Schema 2 renamed "score" to "high_score"
+//! settings.migrate(2, |from, settings| {
+//!     if from < 2 {
+//!         settings.rename("score", "high_score");
+//!     }
+//! });
+//! let progress: Progress = settings.get("progress").unwrap().unwrap_or_default();
+//! settings.set("high_score", &1200u32).unwrap();
+//! settings.save().unwrap();
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::fs;
+use crate::io;
+use crate::path::{Path, PathBuf};
+
+pub use self::format::{FormatError, Source};
+
+pub mod format;
+
+/// A key-value store saved in a file.
+///
+/// Changes are only written to the file by [`save`](Settings::save). They are
+/// not saved automatically when the `Settings` is dropped, since there would be
+/// no way to report an error.
+#[derive(Debug, Clone)]
+pub struct Settings {
+	path: PathBuf,
+	schema: u32,
+	entries: BTreeMap<String, Vec<u8>>,
+	dirty: bool,
+}
+
+impl Settings {
+	/// Creates an empty store that will be saved to `path`, ignoring anything
+	/// already there. This can be used to start over if
+	/// [`open`](Settings::open) reports that the file is damaged.
+	pub fn new<P: Into<PathBuf>>(path: P) -> Settings {
+		Settings {
+			path: path.into(),
+			schema: 0,
+			entries: BTreeMap::new(),
+			dirty: false,
+		}
+	}
+
+	/// Loads the store saved at `path`. If it doesn't exist, the store is
+	/// empty and has schema version 0.
+	///
+	/// If the last save was interrupted, this loads the newest complete copy
+	/// and finishes the save.
+	pub fn open<P: Into<PathBuf>>(path: P) -> Result<Settings, StorageError> {
+		let mut settings = Settings::new(path);
+		let temp = temp_path(&settings.path);
+		let primary = read_if_exists(&settings.path)?;
+		let temp_data = read_if_exists(&temp)?;
+		let (source, contents) = format::recover(primary.as_deref(), temp_data.as_deref())?;
+		match source {
+			Source::Temp => commit(&temp, &settings.path)?,
+			Source::Primary | Source::Empty if temp_data.is_some() => {
+				// The leftovers of an interrupted save, which the user can't
+				// delete since they don't show up in the OS
+				let _ = fs::remove_file(&temp);
+			}
+			_ => {}
+		}
+		settings.schema = contents.schema;
+		settings.entries = contents.entries;
+		Ok(settings)
+	}
+
+	/// Loads a store from the Documents folder, with
+	/// [`open`](Settings::open). The name should end in `.tns` for it to show
+	/// up in the OS.
+	pub fn in_documents(name: &str) -> Result<Settings, StorageError> {
+		let dir = crate::env::get_documents_dir().unwrap_or_else(|_| PathBuf::from("/documents"));
+		Settings::open(dir.join(name))
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// The schema version of the saved data, which the program can use to
+	/// tell how old it is. See [`migrate`](Settings::migrate).
+	pub fn schema(&self) -> u32 {
+		self.schema
+	}
+
+	pub fn set_schema(&mut self, schema: u32) {
+		if self.schema != schema {
+			self.schema = schema;
+			self.dirty = true;
+		}
+	}
+
+	/// Upgrades data saved by older versions of the program. If the schema
+	/// version is older than `schema`, `f` is called with the old version and
+	/// the schema version is updated. New, empty stores are upgraded too, with
+	/// an old version of 0.
+	pub fn migrate(&mut self, schema: u32, f: impl FnOnce(u32, &mut Settings)) {
+		if self.schema < schema {
+			let from = self.schema;
+			f(from, self);
+			self.set_schema(schema);
+		}
+	}
+
+	/// Returns `true` if there are changes that haven't been saved.
+	pub fn is_dirty(&self) -> bool {
+		self.dirty
+	}
+
+	/// Gets a value, or `None` if there isn't one for `key`.
+	pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, StorageError> {
+		match self.entries.get(key) {
+			Some(bytes) => Ok(Some(postcard::from_bytes(bytes)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Sets a value, replacing any that was already there.
+	pub fn set<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), StorageError> {
+		let bytes = postcard::to_allocvec(value)?;
+		self.set_bytes(key, bytes)
+	}
+
+	/// Gets a value without deserializing it.
+	pub fn get_bytes(&self, key: &str) -> Option<&[u8]> {
+		self.entries.get(key).map(Vec::as_slice)
+	}
+
+	/// Sets a value without serializing it.
+	pub fn set_bytes<V: Into<Vec<u8>>>(&mut self, key: &str, value: V) -> Result<(), StorageError> {
+		if key.len() > u16::MAX as usize {
+			return Err(StorageError::KeyTooLong);
+		}
+		self.entries.insert(key.into(), value.into());
+		self.dirty = true;
+		Ok(())
+	}
+
+	pub fn contains_key(&self, key: &str) -> bool {
+		self.entries.contains_key(key)
+	}
+
+	/// Removes a value, returning `true` if there was one.
+	pub fn remove(&mut self, key: &str) -> bool {
+		let removed = self.entries.remove(key).is_some();
+		self.dirty |= removed;
+		removed
+	}
+
+	/// Moves a value to a new key, replacing any value there. Returns `true`
+	/// if there was a value to move.
+	pub fn rename(&mut self, from: &str, to: &str) -> bool {
+		match self.entries.remove(from) {
+			Some(value) => {
+				self.entries.insert(to.into(), value);
+				self.dirty = true;
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &str> {
+		self.entries.keys().map(String::as_str)
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Removes every value. The schema version is kept.
+	pub fn clear(&mut self) {
+		self.dirty |= !self.entries.is_empty();
+		self.entries.clear();
+	}
+
+	/// Writes the store to its file.
+	pub fn save(&mut self) -> Result<(), StorageError> {
+		let data = format::encode(self.schema, &self.entries);
+		let temp = temp_path(&self.path);
+		let existed = self.path.exists();
+		fs::write(&temp, &data)?;
+		commit(&temp, &self.path)?;
+		if !existed {
+			crate::ndless::refresh_documents();
+		}
+		self.dirty = false;
+		Ok(())
+	}
+}
+
+/// The file that a save is written to before it replaces the real one. It
+/// doesn't end in `.tns`, so the OS doesn't show it.
+fn temp_path(path: &Path) -> PathBuf {
+	let name = path
+		.file_name()
+		.map(|name| name.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	path.with_file_name(format!("{}.tmp", name))
+}
+
+fn read_if_exists(path: &Path) -> io::Result<Option<Vec<u8>>> {
+	match fs::read(path) {
+		Ok(data) => Ok(Some(data)),
+		Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Replaces `path` with `temp`.
+fn commit(temp: &Path, path: &Path) -> io::Result<()> {
+	// The OS can't rename over an existing file. If the old file has been
+	// removed when the save is interrupted, `open` finds the new one in `temp`.
+	if fs::rename(temp, path).is_err() {
+		fs::remove_file(path)?;
+		fs::rename(temp, path)?;
+	}
+	Ok(())
+}
+
+/// An error reading or writing [`Settings`].
+#[derive(Debug)]
+pub enum StorageError {
+	Io(io::Error),
+	/// The file couldn't be decoded, and there was no complete copy to recover.
+	Corrupt(FormatError),
+	/// A value couldn't be serialized or deserialized, usually because it was
+	/// saved with a different type.
+	Serde(postcard::Error),
+	/// Keys can be at most 65535 bytes long.
+	KeyTooLong,
+}
+
+impl fmt::Display for StorageError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			StorageError::Io(err) => write!(f, "couldn't access settings file: {}", err),
+			StorageError::Corrupt(err) => fmt::Display::fmt(err, f),
+			StorageError::Serde(err) => write!(f, "couldn't convert setting: {}", err),
+			StorageError::KeyTooLong => f.write_str("settings key is too long"),
+		}
+	}
+}
+
+impl Error for StorageError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			StorageError::Io(err) => Some(err),
+			StorageError::Corrupt(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for StorageError {
+	fn from(err: io::Error) -> Self {
+		StorageError::Io(err)
+	}
+}
+
+impl From<FormatError> for StorageError {
+	fn from(err: FormatError) -> Self {
+		StorageError::Corrupt(err)
+	}
+}
+
+impl From<postcard::Error> for StorageError {
+	fn from(err: postcard::Error) -> Self {
+		StorageError::Serde(err)
+	}
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+	use alloc::string::ToString;
+	use alloc::vec;
+
+	use serde::{Deserialize, Serialize};
+
+	use super::*;
+
+	#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+	struct Progress {
+		level: u32,
+		lives: u8,
+		name: String,
+		unlocked: Vec<bool>,
+	}
+
+	/// A directory of its own for each test, which is removed when dropped.
+	struct TempDir(PathBuf);
+
+	impl TempDir {
+		fn new(test: &str) -> TempDir {
+			let dir = std::env::temp_dir().join(std::format!(
+				"ndless-storage-test-{}-{}",
+				std::process::id(),
+				test
+			));
+			let _ = std::fs::remove_dir_all(&dir);
+			std::fs::create_dir_all(&dir).unwrap();
+			TempDir(PathBuf::from(dir.to_str().unwrap()))
+		}
+
+		fn join(&self, name: &str) -> PathBuf {
+			self.0.join(name)
+		}
+	}
+
+	impl Drop for TempDir {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.0);
+		}
+	}
+
+	fn encode_one(schema: u32, key: &str, value: &[u8]) -> Vec<u8> {
+		let mut entries = BTreeMap::new();
+		entries.insert(key.to_string(), value.to_vec());
+		format::encode(schema, &entries)
+	}
+
+	#[test]
+	fn typed_round_trip() {
+		let dir = TempDir::new("typed_round_trip");
+		let path = dir.join("game.sav.tns");
+		let progress = Progress {
+			level: 7,
+			lives: 3,
+			name: "Alice".into(),
+			unlocked: vec![true, false, true],
+		};
+
+		let mut settings = Settings::open(&path).unwrap();
+		assert!(settings.is_empty());
+		settings.set("progress", &progress).unwrap();
+		settings.set("high_score", &1200u32).unwrap();
+		settings.set("volume", &Some(-3i8)).unwrap();
+		settings.set("name", "Bob").unwrap();
+		assert!(settings.is_dirty());
+		settings.save().unwrap();
+		assert!(!settings.is_dirty());
+
+		let settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.get("progress").unwrap(), Some(progress));
+		assert_eq!(settings.get("high_score").unwrap(), Some(1200u32));
+		assert_eq!(settings.get("volume").unwrap(), Some(Some(-3i8)));
+		assert_eq!(settings.get("name").unwrap(), Some(String::from("Bob")));
+		assert_eq!(settings.get::<u32>("missing").unwrap(), None);
+		assert!(matches!(
+			settings.get::<Progress>("high_score"),
+			Err(StorageError::Serde(_))
+		));
+	}
+
+	#[test]
+	fn save_replaces_existing_file() {
+		let dir = TempDir::new("save_replaces_existing_file");
+		let path = dir.join("game.sav.tns");
+		let mut settings = Settings::open(&path).unwrap();
+		settings.set("level", &1u32).unwrap();
+		settings.save().unwrap();
+
+		// The simulator, like the OS, can't rename over the file from the first
+		// save, so saving again removes it first
+		let temp = temp_path(&path);
+		fs::write(&temp, b"").unwrap();
+		assert!(fs::rename(&temp, &path).is_err());
+		fs::remove_file(&temp).unwrap();
+
+		settings.set("level", &2u32).unwrap();
+		settings.save().unwrap();
+		assert!(!temp.exists());
+		let settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.get("level").unwrap(), Some(2u32));
+	}
+
+	#[test]
+	fn open_finishes_interrupted_save() {
+		let dir = TempDir::new("open_finishes_interrupted_save");
+		let path = dir.join("game.sav.tns");
+		let temp = temp_path(&path);
+
+		// Interrupted after the old file was removed
+		fs::write(&temp, encode_one(1, "level", &[5])).unwrap();
+		let settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.get_bytes("level"), Some(&[5][..]));
+		assert!(!temp.exists());
+		assert_eq!(fs::read(&path).unwrap(), encode_one(1, "level", &[5]));
+
+		// Interrupted before the old file was removed
+		fs::write(&temp, encode_one(1, "level", &[6])).unwrap();
+		let settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.get_bytes("level"), Some(&[6][..]));
+		assert!(!temp.exists());
+		assert_eq!(fs::read(&path).unwrap(), encode_one(1, "level", &[6]));
+	}
+
+	#[test]
+	fn open_removes_partial_save() {
+		let dir = TempDir::new("open_removes_partial_save");
+		let path = dir.join("game.sav.tns");
+		let temp = temp_path(&path);
+		let complete = encode_one(2, "level", &[5]);
+		let partial = encode_one(2, "level", &[6]);
+		let partial = &partial[..partial.len() - 3];
+
+		fs::write(&path, &complete).unwrap();
+		fs::write(&temp, partial).unwrap();
+		let settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.schema(), 2);
+		assert_eq!(settings.get_bytes("level"), Some(&[5][..]));
+		assert!(!temp.exists());
+		assert_eq!(fs::read(&path).unwrap(), complete);
+
+		// An interrupted first save leaves nothing to load
+		fs::remove_file(&path).unwrap();
+		fs::write(&temp, partial).unwrap();
+		let settings = Settings::open(&path).unwrap();
+		assert!(settings.is_empty());
+		assert_eq!(settings.schema(), 0);
+		assert!(!temp.exists());
+		assert!(!path.exists());
+	}
+
+	#[test]
+	fn open_reports_damage() {
+		let dir = TempDir::new("open_reports_damage");
+		let path = dir.join("game.sav.tns");
+		fs::write(&path, b"not a settings file").unwrap();
+		assert!(matches!(
+			Settings::open(&path),
+			Err(StorageError::Corrupt(FormatError::BadMagic))
+		));
+	}
+
+	#[test]
+	fn migrate_renames_keys() {
+		let dir = TempDir::new("migrate_renames_keys");
+		let path = dir.join("game.sav.tns");
+		let mut settings = Settings::open(&path).unwrap();
+		settings.set_schema(1);
+		settings.set("score", &900u32).unwrap();
+		settings.save().unwrap();
+
+		let mut settings = Settings::open(&path).unwrap();
+		let mut migrated_from = None;
+		settings.migrate(2, |from, settings| {
+			migrated_from = Some(from);
+			if from < 2 {
+				assert!(settings.rename("score", "high_score"));
+			}
+		});
+		assert_eq!(migrated_from, Some(1));
+		assert_eq!(settings.schema(), 2);
+		assert!(settings.is_dirty());
+		settings.save().unwrap();
+
+		let mut settings = Settings::open(&path).unwrap();
+		assert_eq!(settings.schema(), 2);
+		assert!(!settings.contains_key("score"));
+		assert_eq!(settings.get("high_score").unwrap(), Some(900u32));
+		// Already up to date
+		settings.migrate(2, |_, _| panic!("migrated twice"));
+		assert!(!settings.is_dirty());
+	}
+}
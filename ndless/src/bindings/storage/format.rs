@@ -0,0 +1,377 @@
+//! The file format used by [`Settings`](super::Settings).
+//!
+//! All numbers are little-endian:
+//!
+//! | Size     | Contents                                   |
+//! |----------|--------------------------------------------|
+//! | 4        | [`MAGIC`]                                  |
+//! | 1        | [`VERSION`]                                |
+//! | 4        | The schema version, set by the program     |
+//! | 4        | The number of entries                      |
+//! | ...      | Each entry: a `u16` key length, the UTF-8 key, a `u32` value length and the value |
+//! | 4        | The [`crc32`] of everything before it      |
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt;
+
+use crate::error::Error;
+
+pub const MAGIC: [u8; 4] = *b"NDKV";
+/// The version of the file format itself, which is different from the
+/// program's schema version.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 13;
+const CHECKSUM_LEN: usize = 4;
+
+/// The decoded contents of a file.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Contents {
+	pub schema: u32,
+	pub entries: BTreeMap<String, Vec<u8>>,
+}
+
+/// The reason a file couldn't be decoded.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FormatError {
+	/// The file isn't a settings file.
+	BadMagic,
+	/// The file was written by a newer version of this crate.
+	UnsupportedVersion(u8),
+	/// The file ends early, usually because it was only partly written.
+	Truncated,
+	/// The file was changed or damaged after it was written.
+	Checksum { stored: u32, computed: u32 },
+	/// The checksum matches, but the entries don't make sense.
+	Malformed,
+}
+
+impl fmt::Display for FormatError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			FormatError::BadMagic => f.write_str("not a settings file"),
+			FormatError::UnsupportedVersion(version) => {
+				write!(f, "unsupported settings file version {}", version)
+			}
+			FormatError::Truncated => f.write_str("settings file is incomplete"),
+			FormatError::Checksum { stored, computed } => write!(
+				f,
+				"settings file is damaged (checksum {:#010x}, expected {:#010x})",
+				computed, stored
+			),
+			FormatError::Malformed => f.write_str("settings file is malformed"),
+		}
+	}
+}
+
+impl Error for FormatError {}
+
+/// The CRC-32 used by zip and PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 != 0 {
+				(crc >> 1) ^ 0xEDB8_8320
+			} else {
+				crc >> 1
+			};
+		}
+	}
+	!crc
+}
+
+/// Encodes a file. Keys must be at most `u16::MAX` bytes long.
+pub fn encode(schema: u32, entries: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+	let len = entries
+		.iter()
+		.map(|(key, value)| 6 + key.len() + value.len())
+		.sum::<usize>();
+	let mut data = Vec::with_capacity(HEADER_LEN + len + CHECKSUM_LEN);
+	data.extend_from_slice(&MAGIC);
+	data.push(VERSION);
+	data.extend_from_slice(&schema.to_le_bytes());
+	data.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+	for (key, value) in entries {
+		debug_assert!(key.len() <= u16::MAX as usize);
+		data.extend_from_slice(&(key.len() as u16).to_le_bytes());
+		data.extend_from_slice(key.as_bytes());
+		data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+		data.extend_from_slice(value);
+	}
+	let checksum = crc32(&data);
+	data.extend_from_slice(&checksum.to_le_bytes());
+	data
+}
+
+/// Decodes a file, checking that it is complete and undamaged.
+pub fn decode(data: &[u8]) -> Result<Contents, FormatError> {
+	if data.len() < MAGIC.len() || data[..MAGIC.len()] != MAGIC {
+		// A file cut off inside the magic number is still recognizable
+		return if MAGIC.starts_with(data) {
+			Err(FormatError::Truncated)
+		} else {
+			Err(FormatError::BadMagic)
+		};
+	}
+	if data.len() > MAGIC.len() && data[MAGIC.len()] != VERSION {
+		return Err(FormatError::UnsupportedVersion(data[MAGIC.len()]));
+	}
+	if data.len() < HEADER_LEN + CHECKSUM_LEN {
+		return Err(FormatError::Truncated);
+	}
+	let (body, checksum) = data.split_at(data.len() - CHECKSUM_LEN);
+	let stored = u32::from_le_bytes(checksum.try_into().unwrap());
+	let computed = crc32(body);
+	if stored != computed {
+		// The length isn't stored, so a file that was cut off looks the same
+		// as one that was damaged. Check whether the entries run past the end
+		// to tell them apart.
+		return Err(if entries_len(body).is_none() {
+			FormatError::Truncated
+		} else {
+			FormatError::Checksum { stored, computed }
+		});
+	}
+
+	let mut reader = Reader {
+		data: &body[MAGIC.len() + 1..],
+	};
+	let schema = reader.u32().ok_or(FormatError::Malformed)?;
+	let count = reader.u32().ok_or(FormatError::Malformed)?;
+	let mut entries = BTreeMap::new();
+	for _ in 0..count {
+		let key_len = reader.u16().ok_or(FormatError::Malformed)?;
+		let key = reader
+			.bytes(key_len as usize)
+			.ok_or(FormatError::Malformed)?;
+		let key = core::str::from_utf8(key).map_err(|_| FormatError::Malformed)?;
+		let value_len = reader.u32().ok_or(FormatError::Malformed)?;
+		let value = reader
+			.bytes(value_len as usize)
+			.ok_or(FormatError::Malformed)?;
+		if entries.insert(key.into(), value.to_vec()).is_some() {
+			return Err(FormatError::Malformed);
+		}
+	}
+	if !reader.data.is_empty() {
+		return Err(FormatError::Malformed);
+	}
+	Ok(Contents { schema, entries })
+}
+
+/// The length of the entries in `body`, or `None` if they don't fit.
+fn entries_len(body: &[u8]) -> Option<usize> {
+	let mut reader = Reader {
+		data: &body[MAGIC.len() + 5..],
+	};
+	let count = reader.u32()?;
+	let start = reader.data.len();
+	for _ in 0..count {
+		let key_len = reader.u16()?;
+		reader.bytes(key_len as usize)?;
+		let value_len = reader.u32()?;
+		reader.bytes(value_len as usize)?;
+	}
+	Some(start - reader.data.len())
+}
+
+struct Reader<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+	fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+		if self.data.len() < len {
+			return None;
+		}
+		let (bytes, rest) = self.data.split_at(len);
+		self.data = rest;
+		Some(bytes)
+	}
+
+	fn u16(&mut self) -> Option<u16> {
+		self.bytes(2)
+			.map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+	}
+
+	fn u32(&mut self) -> Option<u32> {
+		self.bytes(4)
+			.map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+	}
+}
+
+/// Where [`recover`] found the newest complete copy of a file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Source {
+	/// The file itself.
+	Primary,
+	/// The temporary file, which was completely written but not yet renamed.
+	Temp,
+	/// Neither file exists, or there's only a temporary file from a first
+	/// save that was interrupted.
+	Empty,
+}
+
+/// Chooses between a file and its temporary copy, given the contents of each
+/// if they exist.
+///
+/// A save writes the temporary file and then renames it over the file, so a
+/// complete temporary file is always newer than the file. If the temporary
+/// file was only partly written, the file is still complete. If the first
+/// save was interrupted, there's no file and nothing was lost, so the result
+/// is [`Empty`](Source::Empty). If neither is usable, the file's error is
+/// returned.
+pub fn recover(
+	primary: Option<&[u8]>,
+	temp: Option<&[u8]>,
+) -> Result<(Source, Contents), FormatError> {
+	if let Some(Ok(contents)) = temp.map(decode) {
+		return Ok((Source::Temp, contents));
+	}
+	match primary {
+		Some(data) => decode(data).map(|contents| (Source::Primary, contents)),
+		None => Ok((Source::Empty, Contents::default())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+	fn sample() -> Contents {
+		let mut entries = BTreeMap::new();
+		entries.insert("high_score".into(), vec![0x39, 0x05]);
+		entries.insert("name".into(), b"\x05Alice".to_vec());
+		entries.insert("empty".into(), vec![]);
+		Contents { schema: 3, entries }
+	}
+
+	fn encoded() -> Vec<u8> {
+		let contents = sample();
+		encode(contents.schema, &contents.entries)
+	}
+
+	#[test]
+	fn checksum() {
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+	}
+
+	#[test]
+	fn round_trip() {
+		assert_eq!(decode(&encoded()), Ok(sample()));
+		let empty = encode(0, &BTreeMap::new());
+		assert_eq!(empty.len(), HEADER_LEN + CHECKSUM_LEN);
+		assert_eq!(decode(&empty), Ok(Contents::default()));
+	}
+
+	#[test]
+	fn layout() {
+		let mut entries = BTreeMap::new();
+		entries.insert("a".into(), vec![0xAA]);
+		let data = encode(0x0102_0304, &entries);
+		assert_eq!(
+			&data[..data.len() - 4],
+			&[b'N', b'D', b'K', b'V', 1, 4, 3, 2, 1, 1, 0, 0, 0, 1, 0, b'a', 1, 0, 0, 0, 0xAA][..]
+		);
+		let checksum = crc32(&data[..data.len() - 4]);
+		assert_eq!(&data[data.len() - 4..], &checksum.to_le_bytes());
+	}
+
+	#[test]
+	fn torn_writes() {
+		// A write can stop after any number of bytes
+		let data = encoded();
+		for len in 0..data.len() {
+			assert_eq!(
+				decode(&data[..len]),
+				Err(FormatError::Truncated),
+				"{} bytes",
+				len
+			);
+		}
+	}
+
+	#[test]
+	fn damage() {
+		let data = encoded();
+		for index in HEADER_LEN..data.len() {
+			let mut damaged = data.clone();
+			damaged[index] ^= 0x10;
+			let err = decode(&damaged).unwrap_err();
+			assert!(
+				matches!(err, FormatError::Checksum { .. } | FormatError::Truncated),
+				"byte {}: {:?}",
+				index,
+				err
+			);
+		}
+		let mut damaged = data.clone();
+		damaged[0] = b'X';
+		assert_eq!(decode(&damaged), Err(FormatError::BadMagic));
+		let mut damaged = data;
+		damaged[4] = 2;
+		assert_eq!(decode(&damaged), Err(FormatError::UnsupportedVersion(2)));
+		assert_eq!(decode(b"PK\x03\x04"), Err(FormatError::BadMagic));
+	}
+
+	#[test]
+	fn malformed() {
+		// A valid checksum around entries that don't add up
+		let mut data = encoded();
+		data.truncate(data.len() - CHECKSUM_LEN);
+		data.push(0);
+		let checksum = crc32(&data);
+		data.extend_from_slice(&checksum.to_le_bytes());
+		assert_eq!(decode(&data), Err(FormatError::Malformed));
+
+		let mut data = encode(0, &BTreeMap::new());
+		data.truncate(HEADER_LEN);
+		data[9] = 1;
+		data.extend_from_slice(&[1, 0, 0xFF, 0, 0, 0, 0]);
+		let checksum = crc32(&data);
+		data.extend_from_slice(&checksum.to_le_bytes());
+		assert_eq!(decode(&data), Err(FormatError::Malformed));
+	}
+
+	#[test]
+	fn recovery() {
+		let old = encode(1, &BTreeMap::new());
+		let new = encoded();
+		let torn = &new[..new.len() / 2];
+
+		// Nothing saved yet
+		assert_eq!(
+			recover(None, None),
+			Ok((Source::Empty, Contents::default()))
+		);
+		// A normal file
+		assert_eq!(recover(Some(&old), None).unwrap().0, Source::Primary);
+		// Interrupted while writing the temporary file
+		assert_eq!(recover(Some(&old), Some(torn)).unwrap().0, Source::Primary);
+		// Interrupted before the temporary file was renamed
+		assert_eq!(
+			recover(Some(&old), Some(&new)),
+			Ok((Source::Temp, sample()))
+		);
+		// Interrupted after the old file was removed
+		assert_eq!(recover(None, Some(&new)), Ok((Source::Temp, sample())));
+		// Interrupted during the first save
+		assert_eq!(
+			recover(None, Some(torn)),
+			Ok((Source::Empty, Contents::default()))
+		);
+		// Nothing usable
+		assert_eq!(
+			recover(Some(b"garbage"), Some(torn)),
+			Err(FormatError::BadMagic)
+		);
+	}
+}
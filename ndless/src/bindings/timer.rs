@@ -1,117 +1,167 @@
 //! Timer
 //!
 //! Tools for interacting with low-level timers of the nspire.
-#![allow(clippy::unreadable_literal)]
-
-use core::ptr::{read_volatile, write_volatile};
 
+#[cfg(not(feature = "host"))]
 use ndless_static_vars::*;
 
-use crate::hw::has_colors;
+#[cfg(not(feature = "host"))]
+use crate::hw::mmio::{self, sp804_control, Timer, SP804_CLOCK_32K};
 use crate::time::Duration;
 
 pub const TICKS_PER_SECOND: u32 = 32768;
 pub const TICKS_PER_MILLISECOND: u32 = 33;
 pub const MICROSECONDS_PER_TICK: u32 = 1000 / TICKS_PER_MILLISECOND;
 
+/// Counts up with 32 bits, without interrupts.
+#[cfg(not(feature = "host"))]
+const TICK_CONTROL: u32 = sp804_control::ENABLE.mask() | sp804_control::SIZE_32.mask();
+/// Counts down once with 32 bits, then interrupts to wake up the CPU.
+#[cfg(not(feature = "host"))]
+const SLEEP_CONTROL: u32 = sp804_control::PERIODIC.mask()
+	| sp804_control::INTERRUPT_ENABLE.mask()
+	| sp804_control::SIZE_32.mask()
+	| sp804_control::ONE_SHOT.mask();
+
+#[doc(hidden)]
+#[cfg(feature = "host")]
+pub fn __init() {}
+
 #[doc(hidden)]
+#[cfg(not(feature = "host"))]
 pub fn __init() {
 	unsafe {
-		if has_colors() {
-			let value = 0x900C0004 as *mut u32;
-			let control = 0x900C0008 as *mut u32;
-			let clock_source = 0x900C0080 as *mut u32;
-			write_volatile(clock_source, 0xA);
-			write_volatile(control, 0b10000010);
-			START_VALUE = read_volatile(value);
-		} else {
-			let value = 0x900C0000 as *mut u32;
-			let control = 0x900C0008 as *mut u32;
-			let divider = 0x900C0004 as *mut u32;
-			write_volatile(divider, 1);
-			write_volatile(control, 0b00001111);
-			write_volatile(value, 0);
+		match mmio::fast_timer() {
+			Timer::Cx(timer) => {
+				ORIG_TICK_CONTROL = timer.control().read();
+				ORIG_TICK_SETTING = timer.clock_select().read();
+				timer.clock_select().write(SP804_CLOCK_32K);
+				timer.control().write(TICK_CONTROL);
+				START_VALUE = timer.value().read();
+			}
+			Timer::Classic(timer) => {
+				ORIG_TICK_CONTROL = timer.control().read();
+				ORIG_TICK_SETTING = timer.divider().read();
+				timer.divider().write(1);
+				timer.control().write(0b00001111);
+				timer.value().write(0);
+			}
 		}
 		init_sleep();
 	}
+	crate::process::at_exit(restore);
+}
+
+/// Puts the tick timer back the way the OS had it.
+#[cfg(not(feature = "host"))]
+fn restore() {
+	unsafe {
+		match mmio::fast_timer() {
+			Timer::Cx(timer) => {
+				timer.control().write(ORIG_TICK_CONTROL);
+				timer.clock_select().write(ORIG_TICK_SETTING);
+			}
+			Timer::Classic(timer) => {
+				timer.control().write(ORIG_TICK_CONTROL);
+				timer.divider().write(ORIG_TICK_SETTING);
+			}
+		}
+	}
+}
+
+/// Returns the number of ticks since the program started, based on
+/// a 32768Hz timer (i.e. 32768 ticks per second). With the `host` feature,
+/// this is the [simulated clock](crate::sim::ticks).
+#[cfg(feature = "host")]
+pub fn get_ticks() -> u32 {
+	crate::sim::ticks()
 }
 
 /// Returns the number of ticks since the program started, based on
 /// a 32768Hz timer (i.e. 32768 ticks per second).
+#[cfg(not(feature = "host"))]
 pub fn get_ticks() -> u32 {
 	unsafe {
-		if has_colors() {
-			let value = 0x900C0004 as *mut u32;
-			START_VALUE.wrapping_sub(read_volatile(value))
-		} else {
-			let value = 0x900C0000 as *mut u32;
-			TICK_SUM += read_volatile(value);
-			write_volatile(value, 0);
-			TICK_SUM
+		match mmio::fast_timer() {
+			Timer::Cx(timer) => START_VALUE.wrapping_sub(timer.value().read()),
+			Timer::Classic(timer) => {
+				TICK_SUM += timer.value().read();
+				timer.value().write(0);
+				TICK_SUM
+			}
 		}
 	}
 }
 
+#[cfg(not(feature = "host"))]
 fn init_sleep() {
 	unsafe {
-		if has_colors() {
-			let control = 0x900D0008 as *mut u32;
-			let load = 0x900D0000 as *mut u32;
-			let _value = 0x900D0004 as *mut u32;
-			ORIG_CONTROL = read_volatile(control);
-			ORIG_LOAD = read_volatile(load);
-		} else {
-			let _timer = 0x900D0000 as *mut u32;
-			let control = 0x900D0008 as *mut u32;
-			let divider = 0x900D0004 as *mut u32;
-			ORIG_DIVIDER = read_volatile(divider);
-			ORIG_CONTROL = read_volatile(control);
+		match mmio::sleep_timer() {
+			Timer::Cx(timer) => {
+				ORIG_CONTROL = timer.control().read();
+				ORIG_LOAD = timer.load().read();
+			}
+			Timer::Classic(timer) => {
+				ORIG_DIVIDER = timer.divider().read();
+				ORIG_CONTROL = timer.control().read();
+			}
 		}
 	}
 }
 
 /// Prepares the system for sleep. [`idle`][crate::hw::idle] must be
 /// called to actually sleep.
+#[cfg(feature = "host")]
 pub fn configure_sleep(ticks: u32) {
-	unsafe {
-		init_sleep();
-		if has_colors() {
-			let control = 0x900D0008 as *mut u32;
-			let load = 0x900D0000 as *mut u32;
-			let _value = 0x900D0004 as *mut u32;
-			write_volatile(control, 0);
-			write_volatile(control, 0b01100011);
-			write_volatile(control, 0b11100011);
-			write_volatile(load, ticks);
-		} else {
-			let timer = 0x900D0000 as *mut u32;
-			let control = 0x900D0008 as *mut u32;
-			let divider = 0x900D0004 as *mut u32;
-			write_volatile(control, 0);
-			write_volatile(divider, 1);
-			write_volatile(timer, ticks.max(2u32.pow(16) - 1));
+	crate::sim::set_alarm(ticks)
+}
+
+/// Prepares the system for sleep. [`idle`][crate::hw::idle] must be
+/// called to actually sleep.
+#[cfg(not(feature = "host"))]
+pub fn configure_sleep(ticks: u32) {
+	init_sleep();
+	match mmio::sleep_timer() {
+		Timer::Cx(timer) => {
+			timer.control().write(0);
+			timer.control().write(SLEEP_CONTROL);
+			timer
+				.control()
+				.write(SLEEP_CONTROL | sp804_control::ENABLE.mask());
+			timer.load().write(ticks);
+		}
+		Timer::Classic(timer) => {
+			timer.control().write(0);
+			timer.divider().write(1);
+			timer.value().write(ticks.max(2u32.pow(16) - 1));
 		}
 	}
 }
 
 /// Resets the sleep timer so it may be used normally.
+#[cfg(feature = "host")]
+pub fn disable_sleep() {
+	crate::sim::clear_alarm()
+}
+
+/// Resets the sleep timer so it may be used normally.
+#[cfg(not(feature = "host"))]
 pub fn disable_sleep() {
 	unsafe {
-		if has_colors() {
-			let control = 0x900D0008 as *mut u32;
-			let load = 0x900D0000 as *mut u32;
-			let _value = 0x900D0004 as *mut u32;
-			write_volatile(control, 0);
-			write_volatile(control, ORIG_CONTROL & 0b01111111);
-			write_volatile(load, ORIG_LOAD);
-			write_volatile(control, ORIG_CONTROL);
-		} else {
-			let timer = 0x900D0000 as *mut u32;
-			let control = 0x900D0008 as *mut u32;
-			let divider = 0x900D0004 as *mut u32;
-			write_volatile(control, ORIG_CONTROL);
-			write_volatile(divider, ORIG_DIVIDER);
-			write_volatile(timer, 32);
+		match mmio::sleep_timer() {
+			Timer::Cx(timer) => {
+				timer.control().write(0);
+				timer
+					.control()
+					.write(sp804_control::ENABLE.set(ORIG_CONTROL, 0));
+				timer.load().write(ORIG_LOAD);
+				timer.control().write(ORIG_CONTROL);
+			}
+			Timer::Classic(timer) => {
+				timer.control().write(ORIG_CONTROL);
+				timer.divider().write(ORIG_DIVIDER);
+				timer.value().write(32);
+			}
 		}
 	}
 }
@@ -462,8 +462,7 @@ impl File {
 		self.inner.truncate(size)
 	}
 
-	// TODO: implement fstat
-	/*/// Queries metadata about the underlying file.
+	/// Queries metadata about the underlying file.
 	///
 	/// # Examples
 	///
@@ -479,7 +478,7 @@ impl File {
 
 	pub fn metadata(&self) -> io::Result<Metadata> {
 		self.inner.file_attr().map(Metadata)
-	}*/
+	}
 
 	/// Creates a new `File` instance that shares the same underlying file
 	/// handle as the existing `File` instance. Reads, writes, and seeks will
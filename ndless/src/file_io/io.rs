@@ -275,12 +275,14 @@ pub use self::buffered::IntoInnerError;
 pub use self::buffered::{BufReader, BufWriter, LineWriter};
 pub use self::cursor::Cursor;
 pub use self::error::{Error, ErrorKind, Result};
+pub use self::line_discipline::LineDiscipline;
 pub use self::util::{copy, empty, repeat, sink, Empty, Repeat, Sink};
 
 mod buffered;
 mod cursor;
 mod error;
 mod impls;
+mod line_discipline;
 pub mod prelude;
 mod util;
 
@@ -434,6 +436,137 @@ impl Write for Stdout {
 	}
 }
 
+/// A handle to the serial port for error output. Since there is only one serial
+/// port, this is the same as [`Stdout`].
+pub struct Stderr {
+	inner: super::sys::stdio::Stderr,
+}
+
+pub fn stderr() -> Stderr {
+	Stderr {
+		inner: super::sys::stdio::Stderr::new(),
+	}
+}
+
+impl Write for Stderr {
+	fn write(&mut self, buf: &[u8]) -> Result<usize> {
+		self.inner.write(buf)
+	}
+
+	fn flush(&mut self) -> Result<()> {
+		self.inner.flush()
+	}
+}
+
+program_state! {
+	fn stdin_state() -> LineDiscipline = LineDiscipline::new();
+}
+program_state! {
+	fn stdin_echo() -> bool = true;
+}
+
+/// The line discipline of [`Stdin`]. There's only one thread, and it's only
+/// borrowed for as long as the `&mut Stdin` it's reached through.
+fn stdin_line() -> &'static mut LineDiscipline {
+	unsafe { &mut *stdin_state() }
+}
+
+/// Frees input that was received but never read. See
+/// [`process::__release_runtime_state`](crate::process::__release_runtime_state).
+pub(crate) unsafe fn release_stdin() {
+	*stdin_state() = LineDiscipline::new();
+}
+
+/// A handle to input from the serial port, such as a terminal connected with a
+/// cable or Firebird's serial console.
+///
+/// Input is processed a line at a time by a [`LineDiscipline`], so nothing can
+/// be read until the user presses enter. All handles share the same buffer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ndless::io::{self, BufRead};
+///
+/// for line in io::stdin().lines() {
+///     let line = line.unwrap();
+///     ndless::println!("got {}", line);
+/// }
+/// ```
+pub struct Stdin {
+	_priv: (),
+}
+
+/// Returns a handle to input from the serial port.
+pub fn stdin() -> Stdin {
+	Stdin { _priv: () }
+}
+
+impl Stdin {
+	/// Sets whether typed characters are sent back to the terminal. This is on
+	/// by default, since most serial terminals don't show what is typed.
+	pub fn set_echo(&mut self, echo: bool) {
+		unsafe { *stdin_echo() = echo }
+	}
+
+	/// Processes everything in the UART's receive FIFO without waiting.
+	fn poll(&mut self) {
+		let mut echo = Vec::new();
+		while let Some(byte) = super::sys::stdio::read_byte() {
+			stdin_line().push(byte, &mut echo);
+		}
+		if unsafe { *stdin_echo() } && !echo.is_empty() {
+			let _ = stdout().write_all(&echo);
+		}
+	}
+
+	/// Reads a completed line without blocking. Returns
+	/// [`ErrorKind::WouldBlock`] if the user hasn't finished a line yet, or
+	/// `Ok(0)` at the end of input.
+	pub fn try_read(&mut self, buf: &mut [u8]) -> Result<usize> {
+		self.poll();
+		// Lines completed before `^D` come first, like in `fill_buf`
+		let line = stdin_line();
+		if line.buffer().is_empty() {
+			if line.take_eof() {
+				return Ok(0);
+			}
+			return Err(ErrorKind::WouldBlock.into());
+		}
+		self.read(buf)
+	}
+}
+
+impl Read for Stdin {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+		let available = self.fill_buf()?;
+		let amt = cmp::min(available.len(), buf.len());
+		buf[..amt].copy_from_slice(&available[..amt]);
+		self.consume(amt);
+		Ok(amt)
+	}
+}
+
+impl BufRead for Stdin {
+	/// Blocks until a line has been completed, or returns an empty slice at the
+	/// end of input.
+	fn fill_buf(&mut self) -> Result<&[u8]> {
+		while stdin_line().buffer().is_empty() {
+			if stdin_line().take_eof() {
+				return Ok(&[]);
+			}
+			#[cfg(feature = "host")]
+			crate::sim::wait_serial();
+			self.poll();
+		}
+		Ok(stdin_line().buffer())
+	}
+
+	fn consume(&mut self, amt: usize) {
+		stdin_line().consume(amt)
+	}
+}
+
 /// The `Read` trait allows for reading bytes from a source.
 ///
 /// Implementors of the `Read` trait are called 'readers'.
@@ -0,0 +1,218 @@
+use alloc::vec::Vec;
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7F;
+const END_OF_TRANSMISSION: u8 = 0x04;
+const KILL_LINE: u8 = 0x15;
+
+/// Turns raw bytes from a serial terminal into lines of input, like a Unix
+/// terminal in canonical mode.
+///
+/// - `\r`, `\n` and `\r\n` all end a line, which is then available as a line
+///   ending in `\n`.
+/// - Backspace (`^H`) and delete (`^?`) erase the last character, including all
+///   of its bytes if it is not ASCII.
+/// - `^U` erases the whole line.
+/// - `^D` on an empty line signals the end of input. On a line with text, it
+///   makes the text available without a newline.
+/// - Other control characters, except for tab, are ignored.
+///
+/// Every call to [`push`](LineDiscipline::push) appends the bytes that should
+/// be echoed back to the terminal so that the user can see what they type.
+#[derive(Debug, Default, Clone)]
+pub struct LineDiscipline {
+	line: Vec<u8>,
+	ready: Vec<u8>,
+	pos: usize,
+	last_cr: bool,
+	eof: bool,
+}
+
+impl LineDiscipline {
+	pub const fn new() -> LineDiscipline {
+		LineDiscipline {
+			line: Vec::new(),
+			ready: Vec::new(),
+			pos: 0,
+			last_cr: false,
+			eof: false,
+		}
+	}
+
+	/// Processes one received byte.
+	pub fn push(&mut self, byte: u8, echo: &mut Vec<u8>) {
+		let last_cr = self.last_cr;
+		self.last_cr = false;
+		match byte {
+			b'\n' if last_cr => {}
+			b'\r' | b'\n' => {
+				self.last_cr = byte == b'\r';
+				self.line.push(b'\n');
+				self.finish_line();
+				echo.extend_from_slice(b"\r\n");
+			}
+			BACKSPACE | DELETE => {
+				if self.erase_char() {
+					echo.extend_from_slice(b"\x08 \x08");
+				}
+			}
+			KILL_LINE => {
+				while self.erase_char() {
+					echo.extend_from_slice(b"\x08 \x08");
+				}
+			}
+			END_OF_TRANSMISSION => {
+				if self.line.is_empty() {
+					self.eof = true;
+				} else {
+					self.finish_line();
+				}
+			}
+			b'\t' => {
+				self.line.push(byte);
+				echo.push(byte);
+			}
+			byte if byte < 0x20 => {}
+			byte => {
+				self.line.push(byte);
+				echo.push(byte);
+			}
+		}
+	}
+
+	/// Removes the last character of the current line. Returns `false` if the
+	/// line was empty.
+	fn erase_char(&mut self) -> bool {
+		// Remove UTF-8 continuation bytes, then the first byte of the character
+		while let Some(&byte) = self.line.last() {
+			self.line.pop();
+			if byte & 0xC0 != 0x80 {
+				return true;
+			}
+		}
+		false
+	}
+
+	fn finish_line(&mut self) {
+		if self.pos == self.ready.len() {
+			self.ready.clear();
+			self.pos = 0;
+		}
+		self.ready.append(&mut self.line);
+	}
+
+	/// The line that is currently being typed, which is not yet readable.
+	pub fn pending(&self) -> &[u8] {
+		&self.line
+	}
+
+	/// Input that has been completed and not yet consumed.
+	pub fn buffer(&self) -> &[u8] {
+		&self.ready[self.pos..]
+	}
+
+	/// Marks `amt` bytes of [`buffer`](LineDiscipline::buffer) as read.
+	pub fn consume(&mut self, amt: usize) {
+		self.pos = (self.pos + amt).min(self.ready.len());
+		if self.pos == self.ready.len() {
+			self.ready.clear();
+			self.pos = 0;
+		}
+	}
+
+	/// Returns `true` once if `^D` was received on an empty line. Input that
+	/// was completed before it should be read first.
+	pub fn take_eof(&mut self) -> bool {
+		if self.buffer().is_empty() && self.eof {
+			self.eof = false;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use super::LineDiscipline;
+
+	fn feed(input: &[u8]) -> (LineDiscipline, Vec<u8>) {
+		let mut discipline = LineDiscipline::new();
+		let mut echo = Vec::new();
+		for &byte in input {
+			discipline.push(byte, &mut echo);
+		}
+		(discipline, echo)
+	}
+
+	#[test]
+	fn line_endings() {
+		let (discipline, echo) = feed(b"a\rb\nc\r\nd\n\re");
+		assert_eq!(discipline.buffer(), b"a\nb\nc\nd\n\n");
+		assert_eq!(discipline.pending(), b"e");
+		assert_eq!(echo, b"a\r\nb\r\nc\r\nd\r\n\r\ne");
+	}
+
+	#[test]
+	fn backspace() {
+		let (discipline, echo) = feed(b"helo\x08lo\x7f\x7fp\r");
+		assert_eq!(discipline.buffer(), b"help\n");
+		assert_eq!(echo, b"helo\x08 \x08lo\x08 \x08\x08 \x08p\r\n");
+
+		let (discipline, echo) = feed(b"\x08\x08x");
+		assert_eq!(discipline.pending(), b"x");
+		assert_eq!(echo, b"x");
+	}
+
+	#[test]
+	fn backspace_utf8() {
+		let (discipline, echo) = feed("aé€\x08\x08b\n".as_bytes());
+		assert_eq!(discipline.buffer(), b"ab\n");
+		assert_eq!(echo.iter().filter(|&&b| b == 0x08).count(), 4);
+	}
+
+	#[test]
+	fn kill_line() {
+		let (discipline, echo) = feed(b"abc\x15d\n");
+		assert_eq!(discipline.buffer(), b"d\n");
+		assert_eq!(&echo[..3], b"abc");
+		assert_eq!(&echo[3..12], b"\x08 \x08\x08 \x08\x08 \x08");
+	}
+
+	#[test]
+	fn control_characters() {
+		let (discipline, echo) = feed(b"a\x1b\x01\tb\n");
+		assert_eq!(discipline.buffer(), b"a\tb\n");
+		assert_eq!(echo, b"a\tb\r\n");
+	}
+
+	#[test]
+	fn end_of_input() {
+		let (mut discipline, _) = feed(b"partial\x04");
+		assert_eq!(discipline.buffer(), b"partial");
+		assert!(!discipline.take_eof());
+
+		let (mut discipline, _) = feed(b"line\n\x04");
+		assert!(!discipline.take_eof());
+		discipline.consume(5);
+		assert!(discipline.take_eof());
+		assert!(!discipline.take_eof());
+	}
+
+	#[test]
+	fn consume() {
+		let (mut discipline, mut echo) = feed(b"one\ntwo\n");
+		discipline.consume(2);
+		assert_eq!(discipline.buffer(), b"e\ntwo\n");
+		discipline.push(b'3', &mut echo);
+		discipline.push(b'\n', &mut echo);
+		assert_eq!(discipline.buffer(), b"e\ntwo\n3\n");
+		discipline.consume(100);
+		assert_eq!(discipline.buffer(), b"");
+		discipline.push(b'x', &mut echo);
+		discipline.push(b'\r', &mut echo);
+		assert_eq!(discipline.buffer(), b"x\n");
+	}
+}
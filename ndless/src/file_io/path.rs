@@ -88,6 +88,12 @@ use crate::file_io::sys::path::{is_sep_byte, is_verbatim_sep, parse_prefix, MAIN
 use crate::fs;
 use crate::io;
 
+mod tns;
+
+pub use self::tns::{
+	validate_name, DocumentsPath, NameError, FORBIDDEN_CHARS, MAX_NAME_LEN, TNS_EXTENSION,
+};
+
 ////////////////////////////////////////////////////////////////////////////////
 // GENERAL NOTES
 ////////////////////////////////////////////////////////////////////////////////
@@ -2478,6 +2484,67 @@ impl AsRef<OsStr> for Path {
 	}
 }
 
+/// # TI-Nspire paths
+///
+/// The OS only shows files ending in `.tns` in its document browser, and hides
+/// the extension when it does.
+impl Path {
+	/// Returns `true` if the file name ends in `.tns`, in any case.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use ndless::path::Path;
+	///
+	/// assert!(Path::new("/documents/game.tns").has_tns_extension());
+	/// assert!(!Path::new("/documents/game.txt").has_tns_extension());
+	/// ```
+	pub fn has_tns_extension(&self) -> bool {
+		tns::has_tns_extension(self)
+	}
+
+	/// Adds `.tns` to the file name, unless it already ends in `.tns`. Unlike
+	/// [`with_extension`](Path::with_extension), an existing extension is kept,
+	/// so `save.dat` becomes `save.dat.tns`.
+	pub fn with_tns_extension(&self) -> PathBuf {
+		tns::with_tns_extension(self)
+	}
+
+	/// Removes `.tns` from the end of the file name, if it's there.
+	pub fn strip_tns(&self) -> PathBuf {
+		tns::strip_tns(self)
+	}
+
+	/// The file name as shown in the OS document browser, or `None` if the
+	/// file doesn't end in `.tns` and so isn't shown.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use ndless::path::Path;
+	///
+	/// let path = Path::new("/documents/ndless/My Game.tns");
+	/// assert_eq!(path.display_name().as_deref(), Some("My Game"));
+	/// ```
+	pub fn display_name(&self) -> Option<String> {
+		tns::display_name(self)
+	}
+
+	/// Checks that every file and folder name in the path is allowed by the
+	/// OS. See [`validate_name`].
+	pub fn validate_names(&self) -> Result<(), NameError> {
+		tns::validate(self)
+	}
+}
+
+impl DocumentsPath {
+	/// The absolute path, inside the folder returned by
+	/// [`get_documents_dir`](crate::env::get_documents_dir).
+	pub fn resolve(&self) -> io::Result<PathBuf> {
+		Ok(self.resolve_in(&crate::env::get_documents_dir()?))
+	}
+}
+
 impl fmt::Debug for Path {
 	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
 		fmt::Debug::fmt(&self.inner, formatter)
@@ -0,0 +1,343 @@
+//! Paths as the TI-Nspire OS sees them.
+//!
+//! The OS only shows files ending in `.tns` in its document browser, and shows
+//! them without the extension. Documents are kept in a Documents folder, which
+//! is usually `/documents`, but should be found with
+//! [`get_documents_dir`](crate::env::get_documents_dir).
+
+use alloc::string::String;
+use core::fmt;
+
+use super::{Component, Path, PathBuf};
+use crate::error::Error;
+
+/// The extension that the OS needs to show a file.
+pub const TNS_EXTENSION: &str = "tns";
+/// The longest file or folder name that the OS accepts, in bytes.
+pub const MAX_NAME_LEN: usize = 255;
+/// Characters that can't be used in file or folder names, as well as control
+/// characters.
+pub const FORBIDDEN_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Why a file or folder name isn't allowed by the OS.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum NameError {
+	Empty,
+	/// `.` or `..`.
+	Reserved,
+	/// The name is longer than [`MAX_NAME_LEN`] bytes.
+	TooLong(usize),
+	/// The name contains one of [`FORBIDDEN_CHARS`] or a control character.
+	Forbidden(char),
+	/// The name isn't valid UTF-8.
+	NotUnicode,
+	/// The path uses `..` to leave the Documents folder.
+	OutsideDocuments,
+}
+
+impl fmt::Display for NameError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			NameError::Empty => f.write_str("name is empty"),
+			NameError::Reserved => f.write_str("name is reserved"),
+			NameError::TooLong(len) => write!(
+				f,
+				"name is {} bytes long, but can be at most {}",
+				len, MAX_NAME_LEN
+			),
+			NameError::Forbidden(c) => write!(f, "name contains forbidden character {:?}", c),
+			NameError::NotUnicode => f.write_str("name isn't valid unicode"),
+			NameError::OutsideDocuments => f.write_str("path leaves the Documents folder"),
+		}
+	}
+}
+
+impl Error for NameError {}
+
+/// Checks that the OS allows a file or folder name.
+pub fn validate_name(name: &str) -> Result<(), NameError> {
+	match name {
+		"" => return Err(NameError::Empty),
+		"." | ".." => return Err(NameError::Reserved),
+		_ => {}
+	}
+	if name.len() > MAX_NAME_LEN {
+		return Err(NameError::TooLong(name.len()));
+	}
+	match name
+		.chars()
+		.find(|c| FORBIDDEN_CHARS.contains(c) || c.is_control())
+	{
+		Some(c) => Err(NameError::Forbidden(c)),
+		None => Ok(()),
+	}
+}
+
+/// Removes a `.tns` extension from a file name, in any case.
+fn strip_tns_str(name: &str) -> Option<&str> {
+	let split = name.len().checked_sub(TNS_EXTENSION.len() + 1)?;
+	if !name.is_char_boundary(split) {
+		return None;
+	}
+	let (stem, extension) = name.split_at(split);
+	if !stem.is_empty()
+		&& extension[1..].eq_ignore_ascii_case(TNS_EXTENSION)
+		&& &extension[..1] == "."
+	{
+		Some(stem)
+	} else {
+		None
+	}
+}
+
+pub(super) fn has_tns_extension(path: &Path) -> bool {
+	path.file_name()
+		.and_then(|name| name.to_str())
+		.and_then(strip_tns_str)
+		.is_some()
+}
+
+pub(super) fn with_tns_extension(path: &Path) -> PathBuf {
+	match path.file_name() {
+		Some(name) if !has_tns_extension(path) => {
+			let mut name = name.to_os_string();
+			name.push(".");
+			name.push(TNS_EXTENSION);
+			path.with_file_name(name)
+		}
+		_ => path.to_path_buf(),
+	}
+}
+
+pub(super) fn strip_tns(path: &Path) -> PathBuf {
+	match path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.and_then(strip_tns_str)
+	{
+		Some(stem) => path.with_file_name(stem),
+		None => path.to_path_buf(),
+	}
+}
+
+pub(super) fn display_name(path: &Path) -> Option<String> {
+	path.file_name()
+		.and_then(|name| name.to_str())
+		.and_then(strip_tns_str)
+		.map(String::from)
+}
+
+pub(super) fn validate(path: &Path) -> Result<(), NameError> {
+	for component in path.components() {
+		if let Component::Normal(name) = component {
+			validate_name(name.to_str().ok_or(NameError::NotUnicode)?)?;
+		}
+	}
+	Ok(())
+}
+
+/// A path inside the Documents folder, such as `ndless/game.sav.tns`.
+///
+/// Each name is checked with [`validate_name`] when the path is made, so a
+/// `DocumentsPath` can always be created by the OS.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct DocumentsPath {
+	relative: PathBuf,
+}
+
+impl DocumentsPath {
+	/// Makes a path relative to the Documents folder. A leading `/` is
+	/// ignored, so `/ndless/game.tns` is the same as `ndless/game.tns`.
+	pub fn new<P: AsRef<Path>>(path: P) -> Result<DocumentsPath, NameError> {
+		let mut relative = PathBuf::new();
+		for component in path.as_ref().components() {
+			match component {
+				Component::Normal(name) => {
+					validate_name(name.to_str().ok_or(NameError::NotUnicode)?)?;
+					relative.push(name);
+				}
+				Component::ParentDir => return Err(NameError::OutsideDocuments),
+				Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+			}
+		}
+		if relative.as_os_str().is_empty() {
+			return Err(NameError::Empty);
+		}
+		Ok(DocumentsPath { relative })
+	}
+
+	/// Finds the part of an absolute path that is inside `documents`.
+	pub fn from_absolute(path: &Path, documents: &Path) -> Option<DocumentsPath> {
+		DocumentsPath::new(path.strip_prefix(documents).ok()?).ok()
+	}
+
+	/// The path relative to the Documents folder.
+	pub fn as_path(&self) -> &Path {
+		&self.relative
+	}
+
+	pub fn join<P: AsRef<Path>>(&self, path: P) -> Result<DocumentsPath, NameError> {
+		let joined = DocumentsPath::new(path)?;
+		Ok(DocumentsPath {
+			relative: self.relative.join(joined.relative),
+		})
+	}
+
+	/// Adds `.tns` to the file name if it doesn't already have it. This fails
+	/// with [`NameError::TooLong`] if the name no longer fits.
+	pub fn with_tns_extension(&self) -> Result<DocumentsPath, NameError> {
+		let relative = with_tns_extension(&self.relative);
+		if let Some(name) = relative.file_name().and_then(|name| name.to_str()) {
+			validate_name(name)?;
+		}
+		Ok(DocumentsPath { relative })
+	}
+
+	/// The absolute path, given the Documents folder. Use
+	/// [`resolve`](DocumentsPath::resolve) to find the folder automatically.
+	pub fn resolve_in(&self, documents: &Path) -> PathBuf {
+		documents.join(&self.relative)
+	}
+
+	/// The path as shown in the OS, like `ndless/game.sav`, or `None` if the
+	/// file doesn't end in `.tns` and so isn't shown.
+	pub fn display_path(&self) -> Option<String> {
+		let name = display_name(&self.relative)?;
+		let mut display = String::new();
+		if let Some(parent) = self.relative.parent() {
+			for component in parent.components() {
+				display.push_str(&component.as_os_str().to_string_lossy());
+				display.push('/');
+			}
+		}
+		display.push_str(&name);
+		Some(display)
+	}
+}
+
+impl AsRef<Path> for DocumentsPath {
+	fn as_ref(&self) -> &Path {
+		&self.relative
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn has(path: &str) -> bool {
+		has_tns_extension(Path::new(path))
+	}
+
+	#[test]
+	fn extension() {
+		assert!(has("game.tns"));
+		assert!(has("/documents/ndless/game.TNS"));
+		assert!(has("save.dat.tns"));
+		assert!(!has(".tns"));
+		assert!(!has("game.tnss"));
+		assert!(!has("game"));
+		assert!(has("/documents/game.tns/"));
+		assert!(!has("/"));
+
+		let with = |path| with_tns_extension(Path::new(path));
+		assert_eq!(with("game"), Path::new("game.tns"));
+		assert_eq!(
+			with("/documents/save.dat"),
+			Path::new("/documents/save.dat.tns")
+		);
+		assert_eq!(with("game.tns"), Path::new("game.tns"));
+		assert_eq!(with("game.Tns"), Path::new("game.Tns"));
+		assert_eq!(with("/"), Path::new("/"));
+
+		let strip = |path| strip_tns(Path::new(path));
+		assert_eq!(strip("/documents/game.tns"), Path::new("/documents/game"));
+		assert_eq!(strip("save.dat.TNS"), Path::new("save.dat"));
+		assert_eq!(strip("game"), Path::new("game"));
+		assert_eq!(strip(".tns"), Path::new(".tns"));
+	}
+
+	#[test]
+	fn names() {
+		let name = |path| display_name(Path::new(path));
+		assert_eq!(name("/documents/My Game.tns").as_deref(), Some("My Game"));
+		assert_eq!(name("save.dat.tns").as_deref(), Some("save.dat"));
+		assert_eq!(name("/documents/notes.txt"), None);
+		assert_eq!(name("/"), None);
+
+		assert_eq!(validate_name("My Game (2).tns"), Ok(()));
+		assert_eq!(validate_name("héllo"), Ok(()));
+		assert_eq!(validate_name(""), Err(NameError::Empty));
+		assert_eq!(validate_name(".."), Err(NameError::Reserved));
+		assert_eq!(validate_name("a:b"), Err(NameError::Forbidden(':')));
+		assert_eq!(validate_name("tab\there"), Err(NameError::Forbidden('\t')));
+		assert_eq!(validate_name(&"a".repeat(MAX_NAME_LEN)), Ok(()));
+		assert_eq!(
+			validate_name(&"a".repeat(MAX_NAME_LEN + 1)),
+			Err(NameError::TooLong(MAX_NAME_LEN + 1))
+		);
+
+		assert_eq!(validate(Path::new("/documents/ndless/game.tns")), Ok(()));
+		assert_eq!(
+			validate(Path::new("../a?.tns")),
+			Err(NameError::Forbidden('?'))
+		);
+	}
+
+	#[test]
+	fn documents() {
+		let path = DocumentsPath::new("/ndless/./game.sav").unwrap();
+		assert_eq!(path.as_path(), Path::new("ndless/game.sav"));
+		assert_eq!(path.display_path(), None);
+		let path = path.with_tns_extension().unwrap();
+		assert_eq!(path.as_path(), Path::new("ndless/game.sav.tns"));
+		assert_eq!(path.display_path().as_deref(), Some("ndless/game.sav"));
+		assert_eq!(
+			path.resolve_in(Path::new("/documents")),
+			Path::new("/documents/ndless/game.sav.tns")
+		);
+		assert_eq!(
+			DocumentsPath::new("game.tns")
+				.unwrap()
+				.display_path()
+				.as_deref(),
+			Some("game")
+		);
+
+		let dir = DocumentsPath::new("games").unwrap();
+		assert_eq!(
+			dir.join("tetris/scores.tns").unwrap().as_path(),
+			Path::new("games/tetris/scores.tns")
+		);
+		assert_eq!(dir.join("../x"), Err(NameError::OutsideDocuments));
+
+		assert_eq!(
+			DocumentsPath::from_absolute(
+				Path::new("/documents/ndless/game.tns"),
+				Path::new("/documents")
+			),
+			Some(DocumentsPath::new("ndless/game.tns").unwrap())
+		);
+		assert_eq!(
+			DocumentsPath::from_absolute(Path::new("/phoenix/syst"), Path::new("/documents")),
+			None
+		);
+
+		assert_eq!(DocumentsPath::new("/"), Err(NameError::Empty));
+		assert_eq!(
+			DocumentsPath::new("a/../../b"),
+			Err(NameError::OutsideDocuments)
+		);
+		assert_eq!(DocumentsPath::new("a/b|c"), Err(NameError::Forbidden('|')));
+
+		// The extension has to fit in the name too
+		let long = "a".repeat(MAX_NAME_LEN - 4);
+		let path = DocumentsPath::new(&long).unwrap().with_tns_extension();
+		assert_eq!(path.unwrap().as_path().as_os_str().len(), MAX_NAME_LEN);
+		let path = DocumentsPath::new(&(long + "a")).unwrap();
+		assert_eq!(
+			path.with_tns_extension(),
+			Err(NameError::TooLong(MAX_NAME_LEN + 1))
+		);
+	}
+}
@@ -3,6 +3,7 @@
 use crate::file_io::sys::platform::fs::MetadataExt as UnixMetadataExt;
 use crate::file_io::sys_common::{AsInner, AsInnerMut, FromInner};
 use crate::fs::{self, OpenOptions, Permissions};
+#[cfg(not(feature = "host"))]
 use crate::libc;
 /// Unix-specific extensions to [`fs::Permissions`].
 ///
@@ -133,6 +134,7 @@ pub trait OpenOptionsExt {
 	/// # }
 	/// ```
 
+	#[cfg(not(feature = "host"))]
 	fn custom_flags(&mut self, flags: i32) -> &mut Self;
 }
 
@@ -142,6 +144,7 @@ impl OpenOptionsExt for OpenOptions {
 		self
 	}
 
+	#[cfg(not(feature = "host"))]
 	fn custom_flags(&mut self, flags: i32) -> &mut OpenOptions {
 		self.as_inner_mut().custom_flags(flags);
 		self
@@ -460,6 +463,7 @@ impl MetadataExt for fs::Metadata {
 ///
 /// [`FileType`]: ../../../../std/fs/struct.FileType.html
 
+#[cfg(not(feature = "host"))]
 pub trait FileTypeExt {
 	/// Returns `true` if this file type is a block device.
 	///
@@ -535,6 +539,7 @@ pub trait FileTypeExt {
 	fn is_socket(&self) -> bool;
 }
 
+#[cfg(not(feature = "host"))]
 impl FileTypeExt for fs::FileType {
 	fn is_block_device(&self) -> bool {
 		self.as_inner().is(libc::S_IFBLK)
@@ -554,6 +559,7 @@ impl FileTypeExt for fs::FileType {
 ///
 /// [`fs::DirBuilder`]: ../../../../std/fs/struct.DirBuilder.html
 
+#[cfg(not(feature = "host"))]
 pub trait DirBuilderExt {
 	/// Sets the mode to create new directories with. This option defaults to
 	/// 0o777.
@@ -571,6 +577,7 @@ pub trait DirBuilderExt {
 	fn mode(&mut self, mode: u32) -> &mut Self;
 }
 
+#[cfg(not(feature = "host"))]
 impl DirBuilderExt for fs::DirBuilder {
 	fn mode(&mut self, mode: u32) -> &mut fs::DirBuilder {
 		self.as_inner_mut().set_mode(mode);
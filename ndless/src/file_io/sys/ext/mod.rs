@@ -30,7 +30,9 @@ pub mod io;
 ///
 /// Includes all extension traits, and some important type definitions.
 pub mod prelude {
-	pub use super::fs::{FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+	#[cfg(not(feature = "host"))]
+	pub use super::fs::FileTypeExt;
+	pub use super::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
 	pub use super::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 	pub use embedded_ffi::{OsStrExt, OsStringExt};
 }
@@ -5,7 +5,6 @@ use core::mem;
 use core::ptr;
 
 use embedded_ffi::{CStr, CString, OsStr, OsString};
-use ndless_sys::fileno;
 
 use libc::{c_int, c_long, mode_t};
 use libc::{lseek as lseek64, nuc_stat, readdir as readdir64};
@@ -18,11 +17,19 @@ pub use crate::file_io::sys_common::fs::remove_dir_all;
 use crate::file_io::sys_common::{AsInner, FromInner};
 use crate::io::{self, Error, ErrorKind, SeekFrom};
 use crate::libc;
-use crate::libc::{fopen, ftruncate};
+use crate::libc::{ftruncate, open, PS_IREAD, PS_IWRITE};
 use crate::path::{Path, PathBuf};
 use alloc::borrow::ToOwned;
 
-pub struct File(FileDesc);
+use self::open_mode::Flags;
+
+mod open_mode;
+
+pub struct File {
+	fd: FileDesc,
+	read: bool,
+	write: bool,
+}
 
 #[derive(Clone)]
 pub struct FileAttr {
@@ -55,12 +62,7 @@ pub struct DirEntry {
 #[derive(Clone, Debug)]
 pub struct OpenOptions {
 	// generic
-	read: bool,
-	write: bool,
-	append: bool,
-	truncate: bool,
-	create: bool,
-	create_new: bool,
+	flags: Flags,
 	// system-specific
 	custom_flags: i32,
 	mode: mode_t,
@@ -230,22 +232,11 @@ impl DirEntry {
 		self.name.as_bytes()
 	}
 }
-macro_rules! c_str {
-	($lit:expr) => {
-		concat!($lit, "\0").as_ptr() as *const libc::c_char
-	};
-}
-
 impl OpenOptions {
 	pub fn new() -> OpenOptions {
 		OpenOptions {
 			// generic
-			read: false,
-			write: false,
-			append: false,
-			truncate: false,
-			create: false,
-			create_new: false,
+			flags: Flags::default(),
 			// system-specific
 			custom_flags: 0,
 			mode: 0,
@@ -253,41 +244,32 @@ impl OpenOptions {
 	}
 
 	pub fn read(&mut self, read: bool) {
-		self.read = read;
+		self.flags.read = read;
 	}
 	pub fn write(&mut self, write: bool) {
-		self.write = write;
+		self.flags.write = write;
 	}
 	pub fn append(&mut self, append: bool) {
-		self.append = append;
+		self.flags.append = append;
 	}
 	pub fn truncate(&mut self, truncate: bool) {
-		self.truncate = truncate;
+		self.flags.truncate = truncate;
 	}
 	pub fn create(&mut self, create: bool) {
-		self.create = create;
+		self.flags.create = create;
 	}
 	pub fn create_new(&mut self, create_new: bool) {
-		self.create_new = create_new;
+		self.flags.create_new = create_new;
 	}
 
+	#[cfg(not(feature = "host"))]
 	pub fn custom_flags(&mut self, flags: i32) {
 		self.custom_flags = flags;
 	}
+	/// The file system doesn't have permissions, so this is ignored.
 	pub fn mode(&mut self, mode: u32) {
 		self.mode = mode as mode_t;
 	}
-
-	fn c_mode(&self) -> *const libc::c_char {
-		match (self.append, self.write, self.read, self.create) {
-			(true, _, true, _) => c_str!("a+"),
-			(true, _, _, _) => c_str!("a"),
-			(_, true, true, true) => c_str!("w+"),
-			(_, true, true, _) => c_str!("r+"),
-			(_, true, _, _) => c_str!("w"),
-			_ => c_str!("r"),
-		}
-	}
 }
 
 impl File {
@@ -297,36 +279,55 @@ impl File {
 	}
 
 	pub fn open_c(path: &CStr, opts: &OpenOptions) -> io::Result<File> {
-		// TODO: fix fopen
-		let file_ptr = unsafe { fopen(path.as_ptr(), opts.c_mode()) };
-		if file_ptr.is_null() {
-			return Err(crate::io::Error::last_os_error());
+		if opts.custom_flags != 0 {
+			return Err(Error::new(
+				ErrorKind::InvalidInput,
+				"custom flags aren't supported",
+			));
+		}
+		let plan = opts.flags.plan().map_err(Error::from)?;
+		crate::file_io::sys::os::set_errno(0);
+		let fd = unsafe { open(path.as_ptr() as *mut _, plan.flags, PS_IREAD | PS_IWRITE) };
+		if fd < 0 {
+			return Err(open_error(path, opts));
 		}
-		let fd = cvt_r(|| unsafe { fileno(file_ptr as _) })?;
-		let fd = FileDesc::new(fd);
-
-		Ok(File(fd))
-	}
-
-	/*pub fn file_attr(&self) -> io::Result<FileAttr> {
-		let mut stat: stat64 = unsafe { mem::zeroed() };
-		cvt(unsafe {
-			fstat64(self.0.raw(), &mut stat)
-		})?;
-		Ok(FileAttr { stat: nuc_stat {
-			st_dev: stat.st_dev as u16,
-			st_ino: stat.st_ino as libc::c_uint,
-			st_mode: stat.st_mode,
-			st_nlink: stat.st_nlink as u16,
-			st_uid: stat.st_uid as u16,
-			st_gid: stat.st_gid as u16,
-			st_rdev: stat.st_rdev as u16,
-			st_size: stat.st_size as u32,
-			st_atime: stat.st_atim.tv_sec as u32,
-			st_mtime: stat.st_mtim.tv_sec as u32,
-			st_ctime: stat.st_ctim.tv_sec as libc::uint,
-		} })
-	}*/
+
+		Ok(File {
+			fd: FileDesc::new(fd),
+			read: plan.read,
+			write: plan.write,
+		})
+	}
+
+	pub fn file_attr(&self) -> io::Result<FileAttr> {
+		let mut stat: libc::stat = unsafe { mem::zeroed() };
+		cvt(unsafe { libc::fstat(self.fd.raw(), &mut stat) })?;
+		let mut attr = FileAttr {
+			stat: nuc_stat {
+				st_dev: stat.st_dev as _,
+				st_ino: stat.st_ino as _,
+				st_mode: stat.st_mode as _,
+				st_nlink: stat.st_nlink as _,
+				st_uid: stat.st_uid as _,
+				st_gid: stat.st_gid as _,
+				st_rdev: stat.st_rdev as _,
+				st_size: stat.st_size as _,
+				st_atime: stat.st_atim.tv_sec as _,
+				st_mtime: stat.st_mtim.tv_sec as _,
+				st_ctime: stat.st_ctim.tv_sec as _,
+			},
+		};
+		// The file system may not update the size until the file is closed
+		attr.stat.st_size = self.len()? as libc::c_uint;
+		Ok(attr)
+	}
+
+	fn len(&self) -> io::Result<u64> {
+		let pos = self.seek(SeekFrom::Current(0))?;
+		let len = self.seek(SeekFrom::End(0))?;
+		self.seek(SeekFrom::Start(pos))?;
+		Ok(len)
+	}
 
 	pub fn fsync(&self) -> io::Result<()> {
 		Ok(())
@@ -337,19 +338,45 @@ impl File {
 	}
 
 	pub fn truncate(&self, size: u64) -> io::Result<()> {
-		#[cfg(target_os = "android")]
-		return crate::sys::android::ftruncate64(self.0.raw(), size);
-
-		#[cfg(not(target_os = "android"))]
-		return cvt_r(|| unsafe { ftruncate(self.0.raw(), size as c_long) }).map(|_| ());
+		if !self.write {
+			return Err(Error::from_raw_os_error(libc::EINVAL));
+		}
+		let len = self.len()?;
+		if size <= len {
+			return cvt_r(|| unsafe { ftruncate(self.fd.raw(), size as c_long) }).map(|_| ());
+		}
+		// Extend the file with zeros, which the file system can't do by itself
+		let pos = self.seek(SeekFrom::End(0))?;
+		let zeros = [0; 512];
+		let mut remaining = size - len;
+		let result = loop {
+			if remaining == 0 {
+				break Ok(());
+			}
+			let chunk = remaining.min(zeros.len() as u64) as usize;
+			match self.fd.write(&zeros[..chunk]) {
+				Ok(0) => break Err(Error::from(ErrorKind::WriteZero)),
+				Ok(written) => remaining -= written as u64,
+				Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+				Err(err) => break Err(err),
+			}
+		};
+		self.seek(SeekFrom::Start(pos))?;
+		result
 	}
 
 	pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
-		self.0.read(buf)
+		if !self.read {
+			return Err(Error::from_raw_os_error(libc::EBADF));
+		}
+		self.fd.read(buf)
 	}
 
 	pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
-		self.0.write(buf)
+		if !self.write {
+			return Err(Error::from_raw_os_error(libc::EBADF));
+		}
+		self.fd.write(buf)
 	}
 
 	pub fn flush(&self) -> io::Result<()> {
@@ -366,20 +393,24 @@ impl File {
 		};
 		#[cfg(target_os = "emscripten")]
 		let pos = pos as i32;
-		let n = cvt(unsafe { lseek64(self.0.raw(), pos as libc::c_long, whence) })?;
+		let n = cvt(unsafe { lseek64(self.fd.raw(), pos as libc::c_long, whence) })?;
 		Ok(n as u64)
 	}
 
 	pub fn duplicate(&self) -> io::Result<File> {
-		self.0.duplicate().map(File)
+		Ok(File {
+			fd: self.fd.duplicate()?,
+			read: self.read,
+			write: self.write,
+		})
 	}
 
 	pub fn fd(&self) -> &FileDesc {
-		&self.0
+		&self.fd
 	}
 
 	pub fn into_fd(self) -> FileDesc {
-		self.0
+		self.fd
 	}
 }
 
@@ -394,42 +425,47 @@ impl DirBuilder {
 		Ok(())
 	}
 
+	#[cfg(not(feature = "host"))]
 	pub fn set_mode(&mut self, mode: u32) {
 		self.mode = mode as mode_t;
 	}
 }
 
+/// Works out why `open` failed. The simulator sets `errno`, but Nucleus
+/// returns an error code of its own instead.
+fn open_error(path: &CStr, opts: &OpenOptions) -> Error {
+	let errno = crate::file_io::sys::os::errno();
+	if errno != 0 {
+		return Error::from_raw_os_error(errno);
+	}
+	match stat_c(path) {
+		Ok(ref attr) if attr.file_type().is_dir() => Error::from_raw_os_error(libc::EISDIR),
+		Ok(_) if opts.flags.create_new => Error::from_raw_os_error(libc::EEXIST),
+		Ok(_) => Error::from_raw_os_error(libc::EACCES),
+		Err(err) => err,
+	}
+}
+
 fn cstr(path: &Path) -> io::Result<CString> {
 	Ok(CString::new(path.as_os_str().as_bytes())?)
 }
 
 impl FromInner<c_int> for File {
 	fn from_inner(fd: c_int) -> File {
-		File(FileDesc::new(fd))
+		File {
+			fd: FileDesc::new(fd),
+			read: true,
+			write: true,
+		}
 	}
 }
 
 impl fmt::Debug for File {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		fn get_path(_fd: c_int) -> Option<PathBuf> {
-			// FIXME(#24570): implement this for other Unix platforms
-			None
-		}
-
-		fn get_mode(_fd: c_int) -> Option<(bool, bool)> {
-			// FIXME(#24570): implement this for other Unix platforms
-			None
-		}
-
-		let fd = self.0.raw();
 		let mut b = f.debug_struct("File");
-		b.field("fd", &fd);
-		if let Some(path) = get_path(fd) {
-			b.field("path", &path);
-		}
-		if let Some((read, write)) = get_mode(fd) {
-			b.field("read", &read).field("write", &write);
-		}
+		b.field("fd", &self.fd.raw())
+			.field("read", &self.read)
+			.field("write", &self.write);
 		b.finish()
 	}
 }
@@ -481,7 +517,10 @@ pub fn link(src: &Path, dst: &Path) -> io::Result<()> {
 }
 
 pub fn stat(p: &Path) -> io::Result<FileAttr> {
-	let p = cstr(p)?;
+	stat_c(&cstr(p)?)
+}
+
+fn stat_c(p: &CStr) -> io::Result<FileAttr> {
 	let mut stat: nuc_stat = unsafe { mem::zeroed() };
 	cvt(unsafe { nuc_stat(p.as_ptr(), &mut stat) })?;
 	Ok(FileAttr { stat })
@@ -0,0 +1,321 @@
+//! Maps [`OpenOptions`](super::OpenOptions) onto the flags of `NU_Open`, so
+//! that files behave the same way as they do with `std`.
+//!
+//! Like `open` with `O_CREAT | O_EXCL`, `create_new` is checked by the file
+//! system while it opens the file, so nothing can create the file in between.
+
+use crate::io::ErrorKind;
+use crate::libc::{PO_APPEND, PO_BINARY, PO_CREAT, PO_EXCL, PO_RDONLY, PO_RDWR, PO_TRUNC, PO_WRONLY};
+
+/// The options of [`OpenOptions`](super::OpenOptions) that affect how a file
+/// is opened.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Flags {
+	pub read: bool,
+	pub write: bool,
+	pub append: bool,
+	pub truncate: bool,
+	pub create: bool,
+	pub create_new: bool,
+}
+
+/// How to open a file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Plan {
+	/// The flags for `NU_Open`.
+	pub flags: u32,
+	/// Whether reads are allowed.
+	pub read: bool,
+	/// Whether writes are allowed.
+	pub write: bool,
+}
+
+impl Flags {
+	/// Checks that the options make sense together, with the same rules as
+	/// `std` on Unix.
+	pub fn validate(&self) -> Result<(), ErrorKind> {
+		if !self.read && !self.write && !self.append {
+			return Err(ErrorKind::InvalidInput);
+		}
+		match (self.write, self.append) {
+			(true, false) => {}
+			(false, false) => {
+				if self.truncate || self.create || self.create_new {
+					return Err(ErrorKind::InvalidInput);
+				}
+			}
+			(_, true) => {
+				if self.truncate && !self.create_new {
+					return Err(ErrorKind::InvalidInput);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Decides how to open a file.
+	pub fn plan(&self) -> Result<Plan, ErrorKind> {
+		self.validate()?;
+		let write = self.write || self.append;
+		let access = match (self.read, write) {
+			(true, false) => PO_RDONLY,
+			(false, true) => PO_WRONLY,
+			_ => PO_RDWR,
+		};
+		let creation = if self.create_new {
+			// A new file is empty anyway
+			PO_CREAT | PO_EXCL
+		} else {
+			let create = if self.create { PO_CREAT } else { 0 };
+			let truncate = if self.truncate { PO_TRUNC } else { 0 };
+			create | truncate
+		};
+		let append = if self.append { PO_APPEND } else { 0 };
+		Ok(Plan {
+			flags: access | creation | append | PO_BINARY,
+			read: self.read,
+			write,
+		})
+	}
+}
+
+/// A conformance suite for opening files. The expected results are checked
+/// against `std` on the host, then against [`Flags::plan`] with the
+/// simulator's `NU_Open`, so that both agree.
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use std::fs;
+	use std::io::{Read, Seek, SeekFrom, Write};
+	use std::path::{Path, PathBuf};
+	use std::string::String;
+	use std::vec::Vec;
+
+	use super::*;
+
+	const R: u8 = 1;
+	const W: u8 = 2;
+	const A: u8 = 4;
+	const T: u8 = 8;
+	const C: u8 = 16;
+	const N: u8 = 32;
+
+	fn flags(bits: u8) -> Flags {
+		Flags {
+			read: bits & R != 0,
+			write: bits & W != 0,
+			append: bits & A != 0,
+			truncate: bits & T != 0,
+			create: bits & C != 0,
+			create_new: bits & N != 0,
+		}
+	}
+
+	/// What happened after opening a file that contained `hello`, or didn't
+	/// exist.
+	#[derive(Debug, Eq, PartialEq)]
+	enum Outcome {
+		Failed(ErrorKind),
+		Opened {
+			/// Whether reading succeeded.
+			read: bool,
+			/// The contents after seeking to the start and writing `X`, or
+			/// `None` if writing failed.
+			written: Option<String>,
+		},
+	}
+
+	use Outcome::*;
+
+	fn ok(read: bool, written: Option<&str>) -> Outcome {
+		Opened {
+			read,
+			written: written.map(String::from),
+		}
+	}
+
+	/// Every combination of options, whether the file exists, and the
+	/// outcome.
+	fn cases() -> Vec<(u8, bool, Outcome)> {
+		std::vec![
+			(0, true, Failed(ErrorKind::InvalidInput)),
+			(T | C | N, true, Failed(ErrorKind::InvalidInput)),
+			// Reading
+			(R, true, ok(true, None)),
+			(R, false, Failed(ErrorKind::NotFound)),
+			(R | T, true, Failed(ErrorKind::InvalidInput)),
+			(R | C, false, Failed(ErrorKind::InvalidInput)),
+			(R | N, false, Failed(ErrorKind::InvalidInput)),
+			// Writing
+			(W, true, ok(false, Some("Xello"))),
+			(W, false, Failed(ErrorKind::NotFound)),
+			(W | T, true, ok(false, Some("X"))),
+			(W | T, false, Failed(ErrorKind::NotFound)),
+			(W | C, true, ok(false, Some("Xello"))),
+			(W | C, false, ok(false, Some("X"))),
+			(W | C | T, true, ok(false, Some("X"))),
+			(W | C | T, false, ok(false, Some("X"))),
+			(W | N, true, Failed(ErrorKind::AlreadyExists)),
+			(W | N, false, ok(false, Some("X"))),
+			(W | C | N | T, true, Failed(ErrorKind::AlreadyExists)),
+			// Reading and writing
+			(R | W, true, ok(true, Some("Xello"))),
+			(R | W, false, Failed(ErrorKind::NotFound)),
+			(R | W | T, true, ok(true, Some("X"))),
+			(R | W | C, true, ok(true, Some("Xello"))),
+			(R | W | C, false, ok(true, Some("X"))),
+			(R | W | C | T, true, ok(true, Some("X"))),
+			(R | W | N, true, Failed(ErrorKind::AlreadyExists)),
+			(R | W | N, false, ok(true, Some("X"))),
+			// Appending
+			(A, true, ok(false, Some("helloX"))),
+			(A, false, Failed(ErrorKind::NotFound)),
+			(A | C, true, ok(false, Some("helloX"))),
+			(A | C, false, ok(false, Some("X"))),
+			(A | T, true, Failed(ErrorKind::InvalidInput)),
+			(A | C | T, true, Failed(ErrorKind::InvalidInput)),
+			(A | N, true, Failed(ErrorKind::AlreadyExists)),
+			(A | N, false, ok(false, Some("X"))),
+			(A | N | T, false, ok(false, Some("X"))),
+			(W | A, true, ok(false, Some("helloX"))),
+			(R | A, true, ok(true, Some("helloX"))),
+			(R | A | C, false, ok(true, Some("X"))),
+			(R | W | A | C, true, ok(true, Some("helloX"))),
+		]
+	}
+
+	/// A file that only allows what its plan does.
+	struct Gated {
+		file: fs::File,
+		read: bool,
+		write: bool,
+	}
+
+	fn observe(
+		path: &Path,
+		exists: bool,
+		open: impl FnOnce(&Path) -> Result<Gated, ErrorKind>,
+	) -> Outcome {
+		if exists {
+			fs::write(path, "hello").unwrap();
+		} else {
+			let _ = fs::remove_file(path);
+		}
+		let mut file = match open(path) {
+			Ok(file) => file,
+			Err(kind) => {
+				// Failing must leave the file alone
+				match fs::read_to_string(path) {
+					Ok(contents) => assert!(exists && contents == "hello"),
+					Err(_) => assert!(!exists),
+				}
+				return Failed(kind);
+			}
+		};
+		let read = file.read && file.file.read(&mut [0; 1]).is_ok();
+		let written = file.write && {
+			file.file.seek(SeekFrom::Start(0)).unwrap();
+			file.file.write_all(b"X").is_ok()
+		};
+		drop(file);
+		let contents = fs::read_to_string(path).unwrap();
+		let written = if written {
+			Some(contents)
+		} else {
+			assert_eq!(contents, if exists { "hello" } else { "" });
+			None
+		};
+		Opened { read, written }
+	}
+
+	fn temp_path(name: &str) -> PathBuf {
+		std::env::temp_dir().join(std::format!("ndless-open-{}-{}", name, std::process::id()))
+	}
+
+	#[test]
+	fn std_conformance() {
+		let path = temp_path("std");
+		for (bits, exists, expected) in cases() {
+			let flags = flags(bits);
+			let outcome = observe(&path, exists, |path| {
+				fs::OpenOptions::new()
+					.read(flags.read)
+					.write(flags.write)
+					.append(flags.append)
+					.truncate(flags.truncate)
+					.create(flags.create)
+					.create_new(flags.create_new)
+					.open(path)
+					.map(|file| Gated {
+						file,
+						read: true,
+						write: true,
+					})
+					.map_err(|err| match err.kind() {
+						std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+						std::io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+						std::io::ErrorKind::InvalidInput => ErrorKind::InvalidInput,
+						kind => panic!("unexpected error {:?}", kind),
+					})
+			});
+			assert_eq!(outcome, expected, "{:?}, exists: {}", flags, exists);
+		}
+		let _ = fs::remove_file(&path);
+	}
+
+	#[cfg(feature = "host")]
+	#[test]
+	fn open_conformance() {
+		use std::ffi::CString;
+		use std::os::unix::ffi::OsStrExt;
+		use std::os::unix::io::FromRawFd;
+
+		let path = temp_path("open");
+		for (bits, exists, expected) in cases() {
+			let flags = flags(bits);
+			let outcome = observe(&path, exists, |path| {
+				let plan = flags.plan()?;
+				let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+				let fd = unsafe { crate::libc::open(c_path.as_ptr() as *mut _, plan.flags, 0) };
+				if fd < 0 {
+					return Err(crate::io::Error::last_os_error().kind());
+				}
+				Ok(Gated {
+					file: unsafe { fs::File::from_raw_fd(fd) },
+					read: plan.read,
+					write: plan.write,
+				})
+			});
+			assert_eq!(outcome, expected, "{:?}, exists: {}", flags, exists);
+		}
+		let _ = fs::remove_file(&path);
+	}
+
+	#[test]
+	fn modes() {
+		assert_eq!(flags(R).plan().unwrap().flags, PO_RDONLY | PO_BINARY);
+		assert_eq!(flags(W).plan().unwrap().flags, PO_WRONLY | PO_BINARY);
+		assert_eq!(
+			flags(W | C).plan().unwrap().flags,
+			PO_WRONLY | PO_CREAT | PO_BINARY
+		);
+		assert_eq!(
+			flags(R | W | T).plan().unwrap().flags,
+			PO_RDWR | PO_TRUNC | PO_BINARY
+		);
+		assert_eq!(
+			flags(A | C).plan().unwrap().flags,
+			PO_WRONLY | PO_CREAT | PO_APPEND | PO_BINARY
+		);
+		assert_eq!(
+			flags(R | A).plan().unwrap().flags,
+			PO_RDWR | PO_APPEND | PO_BINARY
+		);
+		// Truncating doesn't matter for a file that's new
+		assert_eq!(
+			flags(A | N | T).plan().unwrap().flags,
+			PO_WRONLY | PO_CREAT | PO_EXCL | PO_APPEND | PO_BINARY
+		);
+	}
+}
@@ -30,7 +30,8 @@ use crate::path::{self, PathBuf};
 const TMPBUF_SZ: usize = 128;
 
 extern "C" {
-	#[link_name = "__errno"]
+	#[cfg_attr(not(feature = "host"), link_name = "__errno")]
+	#[cfg_attr(feature = "host", link_name = "__errno_location")]
 	fn errno_location() -> *mut c_int;
 }
 
@@ -46,6 +47,8 @@ pub fn set_errno(e: i32) {
 /// Gets a detailed string description for the given error number.
 pub fn error_string(errno: i32) -> String {
 	extern "C" {
+		// glibc's own `strerror_r` returns a pointer instead
+		#[cfg_attr(feature = "host", link_name = "__xpg_strerror_r")]
 		fn strerror_r(errnum: c_int, buf: *mut c_char, buflen: libc::size_t) -> c_int;
 	}
 
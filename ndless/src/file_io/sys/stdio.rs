@@ -1,5 +1,11 @@
+#![allow(clippy::unreadable_literal)]
+
 use core::mem::ManuallyDrop;
+#[cfg(not(feature = "host"))]
+use core::ptr::read_volatile;
 
+#[cfg(not(feature = "host"))]
+use crate::hw::has_colors;
 use crate::libc;
 
 use super::super::io;
@@ -22,3 +28,55 @@ impl io::Write for Stdout {
 		Ok(())
 	}
 }
+
+/// There is only one serial port, so this writes to the same place as
+/// [`Stdout`].
+pub struct Stderr(());
+
+impl Stderr {
+	pub fn new() -> Stderr {
+		Stderr(())
+	}
+}
+
+impl io::Write for Stderr {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		ManuallyDrop::new(FileDesc::new(libc::STDOUT_FILENO)).write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Reads a byte from the UART's receive FIFO, if one is waiting.
+#[cfg(feature = "host")]
+pub fn read_byte() -> Option<u8> {
+	crate::sim::read_serial()
+}
+
+/// Reads a byte from the UART's receive FIFO, if one is waiting.
+#[cfg(not(feature = "host"))]
+pub fn read_byte() -> Option<u8> {
+	unsafe {
+		if has_colors() {
+			// PL011: bit 4 of the flag register is set when the FIFO is empty
+			let data = 0x90020000 as *const u32;
+			let flags = 0x90020018 as *const u32;
+			if read_volatile(flags) & (1 << 4) == 0 {
+				Some(read_volatile(data) as u8)
+			} else {
+				None
+			}
+		} else {
+			// 16550: bit 0 of the line status register is set when data is ready
+			let data = 0x90020000 as *const u32;
+			let line_status = 0x90020014 as *const u32;
+			if read_volatile(line_status) & 1 != 0 {
+				Some(read_volatile(data) as u8)
+			} else {
+				None
+			}
+		}
+	}
+}
@@ -18,6 +18,14 @@ use crate::error::Error;
 use crate::file_io::sys::time;
 use crate::file_io::sys_common::FromInner;
 
+pub use self::datetime::{
+	days_in_month, is_leap_year, DateTime, ParseDateTimeError, UtcOffset, Weekday, MAX_YEAR,
+	MIN_YEAR,
+};
+
+mod datetime;
+pub mod rtc;
+
 /// A measurement of a monotonically nondecreasing clock.
 /// Opaque and useful only with `Duration`.
 ///
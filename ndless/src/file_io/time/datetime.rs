@@ -0,0 +1,993 @@
+//! Calendar dates and times of day.
+//!
+//! All of the calendar math in this module is done in pure Rust using the
+//! proleptic Gregorian calendar, so it does not depend on the calculator's
+//! C library. Leap seconds are not represented.
+
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+
+use crate::error::Error;
+use crate::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// The earliest year that may be stored in a [`DateTime`].
+pub const MIN_YEAR: i32 = -9999;
+/// The latest year that may be stored in a [`DateTime`].
+pub const MAX_YEAR: i32 = 9999;
+
+/// Returns `true` if `year` is a leap year in the proleptic Gregorian
+/// calendar.
+pub const fn is_leap_year(year: i32) -> bool {
+	year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days in `month` (1-12) of `year`, or 0 if `month` is
+/// out of range.
+pub const fn days_in_month(year: i32, month: u8) -> u8 {
+	match month {
+		1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+		4 | 6 | 9 | 11 => 30,
+		2 if is_leap_year(year) => 29,
+		2 => 28,
+		_ => 0,
+	}
+}
+
+/// Number of days between 1970-01-01 and the given date.
+///
+/// Based on Howard Hinnant's `days_from_civil`:
+/// <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+	let year = i64::from(year) - if month <= 2 { 1 } else { 0 };
+	let month = i64::from(month);
+	let era = year.div_euclid(400);
+	let year_of_era = year.rem_euclid(400);
+	let day_of_year =
+		(153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+	let days = days + 719_468;
+	let era = days.div_euclid(146_097);
+	let day_of_era = days.rem_euclid(146_097);
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let mp = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	(year, month, day)
+}
+
+/// A day of the week.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Weekday {
+	Monday,
+	Tuesday,
+	Wednesday,
+	Thursday,
+	Friday,
+	Saturday,
+	Sunday,
+}
+
+impl Weekday {
+	/// Days since Monday, from 0 to 6.
+	pub fn number_days_from_monday(self) -> u8 {
+		self as u8
+	}
+
+	/// Days since Sunday, from 0 to 6.
+	pub fn number_days_from_sunday(self) -> u8 {
+		(self as u8 + 1) % 7
+	}
+
+	fn from_days_since_epoch(days: i64) -> Weekday {
+		// 1970-01-01 was a Thursday
+		match (days + 3).rem_euclid(7) {
+			0 => Weekday::Monday,
+			1 => Weekday::Tuesday,
+			2 => Weekday::Wednesday,
+			3 => Weekday::Thursday,
+			4 => Weekday::Friday,
+			5 => Weekday::Saturday,
+			_ => Weekday::Sunday,
+		}
+	}
+}
+
+/// An offset from UTC, between -23:59:59 and +23:59:59.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct UtcOffset {
+	seconds: i32,
+}
+
+impl UtcOffset {
+	/// Coordinated Universal Time.
+	pub const UTC: UtcOffset = UtcOffset { seconds: 0 };
+
+	/// Creates an offset from a number of seconds east of UTC. Returns `None`
+	/// if the offset is a day or more.
+	pub const fn from_seconds(seconds: i32) -> Option<UtcOffset> {
+		if seconds > -(SECONDS_PER_DAY as i32) && seconds < SECONDS_PER_DAY as i32 {
+			Some(UtcOffset { seconds })
+		} else {
+			None
+		}
+	}
+
+	/// Creates an offset from hours and minutes east of UTC. The sign of
+	/// `hours` applies to the minutes as well, so `from_hm(-3, 30)` is
+	/// `-03:30`.
+	pub fn from_hm(hours: i8, minutes: u8) -> Option<UtcOffset> {
+		if minutes >= 60 {
+			return None;
+		}
+		let minutes = i32::from(minutes) * SECONDS_PER_MINUTE as i32;
+		let hours = i32::from(hours) * SECONDS_PER_HOUR as i32;
+		UtcOffset::from_seconds(if hours < 0 {
+			hours - minutes
+		} else {
+			hours + minutes
+		})
+	}
+
+	/// The number of seconds east of UTC.
+	pub const fn whole_seconds(self) -> i32 {
+		self.seconds
+	}
+
+	/// Returns `true` if this is [`UtcOffset::UTC`].
+	pub const fn is_utc(self) -> bool {
+		self.seconds == 0
+	}
+}
+
+impl fmt::Display for UtcOffset {
+	/// Formats as `Z` for UTC, or `+HH:MM` (with `:SS` if needed) otherwise.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_utc() {
+			return f.write_str("Z");
+		}
+		let sign = if self.seconds < 0 { '-' } else { '+' };
+		let seconds = self.seconds.abs();
+		write!(
+			f,
+			"{}{:02}:{:02}",
+			sign,
+			seconds / SECONDS_PER_HOUR as i32,
+			seconds / SECONDS_PER_MINUTE as i32 % 60
+		)?;
+		if seconds % 60 != 0 {
+			write!(f, ":{:02}", seconds % 60)?;
+		}
+		Ok(())
+	}
+}
+
+/// A calendar date and time of day at a fixed offset from UTC.
+///
+/// Two `DateTime`s compare equal if they refer to the same instant, even if
+/// their offsets differ.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ndless::time::{DateTime, UtcOffset};
+///
+/// let now = DateTime::now_utc();
+/// let local = now.to_offset(UtcOffset::from_hm(2, 0).unwrap());
+/// println!("{}", local); // 2020-05-17T15:45:09+02:00
+///
+/// let parsed: DateTime = "2020-05-17T13:45:09Z".parse().unwrap();
+/// assert_eq!(parsed.weekday(), ndless::time::Weekday::Sunday);
+/// ```
+#[derive(Clone, Copy)]
+pub struct DateTime {
+	year: i32,
+	month: u8,
+	day: u8,
+	hour: u8,
+	minute: u8,
+	second: u8,
+	nanosecond: u32,
+	offset: UtcOffset,
+}
+
+impl DateTime {
+	/// Midnight, 1970-01-01 UTC.
+	pub const UNIX_EPOCH: DateTime = DateTime {
+		year: 1970,
+		month: 1,
+		day: 1,
+		hour: 0,
+		minute: 0,
+		second: 0,
+		nanosecond: 0,
+		offset: UtcOffset::UTC,
+	};
+
+	/// Creates a new UTC date and time, returning `None` if any component is
+	/// out of range.
+	pub fn new(
+		year: i32,
+		month: u8,
+		day: u8,
+		hour: u8,
+		minute: u8,
+		second: u8,
+	) -> Option<DateTime> {
+		if !(MIN_YEAR..=MAX_YEAR).contains(&year)
+			|| day == 0
+			|| day > days_in_month(year, month)
+			|| hour >= 24
+			|| minute >= 60
+			|| second >= 60
+		{
+			return None;
+		}
+		Some(DateTime {
+			year,
+			month,
+			day,
+			hour,
+			minute,
+			second,
+			nanosecond: 0,
+			offset: UtcOffset::UTC,
+		})
+	}
+
+	/// Replaces the fractional second, returning `None` if `nanosecond` is a
+	/// full second or more.
+	pub fn with_nanosecond(self, nanosecond: u32) -> Option<DateTime> {
+		if nanosecond >= NANOS_PER_SECOND {
+			return None;
+		}
+		Some(DateTime { nanosecond, ..self })
+	}
+
+	/// Keeps the same date and time of day, but interprets them at a
+	/// different offset. This changes the instant that is represented.
+	pub fn assume_offset(self, offset: UtcOffset) -> DateTime {
+		DateTime { offset, ..self }
+	}
+
+	/// Converts to the same instant as seen at a different offset. Returns
+	/// `None` if the result would fall outside of [`MIN_YEAR`] and
+	/// [`MAX_YEAR`].
+	pub fn checked_to_offset(self, offset: UtcOffset) -> Option<DateTime> {
+		DateTime::from_local_seconds(
+			self.unix_timestamp() + i64::from(offset.seconds),
+			self.nanosecond,
+			offset,
+		)
+	}
+
+	/// Converts to the same instant as seen at a different offset.
+	///
+	/// # Panics
+	///
+	/// Panics if the result would fall outside of [`MIN_YEAR`] and
+	/// [`MAX_YEAR`]. See [`checked_to_offset`](Self::checked_to_offset) for a
+	/// version without panic.
+	pub fn to_offset(self, offset: UtcOffset) -> DateTime {
+		self.checked_to_offset(offset)
+			.expect("date out of range when changing offset")
+	}
+
+	/// Creates a UTC date and time from the number of seconds since
+	/// 1970-01-01 00:00:00 UTC, returning `None` if it is out of range.
+	pub fn from_unix_timestamp(seconds: i64) -> Option<DateTime> {
+		DateTime::from_local_seconds(seconds, 0, UtcOffset::UTC)
+	}
+
+	/// The number of whole seconds since 1970-01-01 00:00:00 UTC. Negative for
+	/// dates before then.
+	pub fn unix_timestamp(&self) -> i64 {
+		self.local_seconds() - i64::from(self.offset.seconds)
+	}
+
+	fn local_seconds(&self) -> i64 {
+		days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY
+			+ i64::from(self.hour) * SECONDS_PER_HOUR
+			+ i64::from(self.minute) * SECONDS_PER_MINUTE
+			+ i64::from(self.second)
+	}
+
+	fn from_local_seconds(seconds: i64, nanosecond: u32, offset: UtcOffset) -> Option<DateTime> {
+		let days = seconds.div_euclid(SECONDS_PER_DAY);
+		let time = seconds.rem_euclid(SECONDS_PER_DAY);
+		let (year, month, day) = civil_from_days(days);
+		let year = i32::try_from(year).ok()?;
+		if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+			return None;
+		}
+		Some(DateTime {
+			year,
+			month,
+			day,
+			hour: (time / SECONDS_PER_HOUR) as u8,
+			minute: (time / SECONDS_PER_MINUTE % 60) as u8,
+			second: (time % 60) as u8,
+			nanosecond,
+			offset,
+		})
+	}
+
+	/// Returns `Some(t)` where `t` is `self + duration`, or `None` if the
+	/// result is out of range. The offset is kept.
+	pub fn checked_add(&self, duration: Duration) -> Option<DateTime> {
+		let mut seconds = i64::try_from(duration.as_secs()).ok()?;
+		let mut nanosecond = self.nanosecond + duration.subsec_nanos();
+		if nanosecond >= NANOS_PER_SECOND {
+			nanosecond -= NANOS_PER_SECOND;
+			seconds = seconds.checked_add(1)?;
+		}
+		DateTime::from_local_seconds(
+			self.local_seconds().checked_add(seconds)?,
+			nanosecond,
+			self.offset,
+		)
+	}
+
+	/// Returns `Some(t)` where `t` is `self - duration`, or `None` if the
+	/// result is out of range. The offset is kept.
+	pub fn checked_sub(&self, duration: Duration) -> Option<DateTime> {
+		let mut seconds = i64::try_from(duration.as_secs()).ok()?;
+		let mut nanosecond = self.nanosecond;
+		if nanosecond < duration.subsec_nanos() {
+			nanosecond += NANOS_PER_SECOND;
+			seconds = seconds.checked_add(1)?;
+		}
+		DateTime::from_local_seconds(
+			self.local_seconds().checked_sub(seconds)?,
+			nanosecond - duration.subsec_nanos(),
+			self.offset,
+		)
+	}
+
+	/// Returns the amount of time between `earlier` and `self`. If `earlier`
+	/// is actually later, the distance between them is returned as an
+	/// [`Err`].
+	pub fn duration_since(&self, earlier: DateTime) -> Result<Duration, Duration> {
+		let (later, earlier, ok) = if *self >= earlier {
+			(*self, earlier, true)
+		} else {
+			(earlier, *self, false)
+		};
+		let mut seconds = (later.unix_timestamp() - earlier.unix_timestamp()) as u64;
+		let nanos = if later.nanosecond >= earlier.nanosecond {
+			later.nanosecond - earlier.nanosecond
+		} else {
+			seconds -= 1;
+			later.nanosecond + NANOS_PER_SECOND - earlier.nanosecond
+		};
+		let duration = Duration::new(seconds, nanos);
+		if ok {
+			Ok(duration)
+		} else {
+			Err(duration)
+		}
+	}
+
+	pub fn year(&self) -> i32 {
+		self.year
+	}
+
+	/// The month, from 1 to 12.
+	pub fn month(&self) -> u8 {
+		self.month
+	}
+
+	/// The day of the month, from 1 to 31.
+	pub fn day(&self) -> u8 {
+		self.day
+	}
+
+	pub fn hour(&self) -> u8 {
+		self.hour
+	}
+
+	pub fn minute(&self) -> u8 {
+		self.minute
+	}
+
+	pub fn second(&self) -> u8 {
+		self.second
+	}
+
+	pub fn nanosecond(&self) -> u32 {
+		self.nanosecond
+	}
+
+	pub fn offset(&self) -> UtcOffset {
+		self.offset
+	}
+
+	/// The day of the year, from 1 to 366.
+	pub fn ordinal(&self) -> u16 {
+		(days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1)
+			as u16
+	}
+
+	pub fn weekday(&self) -> Weekday {
+		Weekday::from_days_since_epoch(days_from_civil(self.year, self.month, self.day))
+	}
+}
+
+impl PartialEq for DateTime {
+	fn eq(&self, other: &DateTime) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for DateTime {}
+
+impl PartialOrd for DateTime {
+	fn partial_cmp(&self, other: &DateTime) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for DateTime {
+	fn cmp(&self, other: &DateTime) -> Ordering {
+		(self.unix_timestamp(), self.nanosecond).cmp(&(other.unix_timestamp(), other.nanosecond))
+	}
+}
+
+impl Hash for DateTime {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.unix_timestamp().hash(state);
+		self.nanosecond.hash(state);
+	}
+}
+
+impl From<SystemTime> for DateTime {
+	/// # Panics
+	///
+	/// Panics if the time is outside of [`MIN_YEAR`] and [`MAX_YEAR`].
+	fn from(time: SystemTime) -> DateTime {
+		match time.duration_since(UNIX_EPOCH) {
+			Ok(after) => DateTime::UNIX_EPOCH.checked_add(after),
+			Err(before) => DateTime::UNIX_EPOCH.checked_sub(before.duration()),
+		}
+		.expect("SystemTime out of range for DateTime")
+	}
+}
+
+impl From<DateTime> for SystemTime {
+	fn from(time: DateTime) -> SystemTime {
+		match time.duration_since(DateTime::UNIX_EPOCH) {
+			Ok(after) => UNIX_EPOCH + after,
+			Err(before) => UNIX_EPOCH - before,
+		}
+	}
+}
+
+impl fmt::Debug for DateTime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl fmt::Display for DateTime {
+	/// Formats as ISO 8601, e.g. `2020-05-17T13:45:09Z`. Fractional seconds
+	/// are only shown if present, in groups of three digits.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.year >= 0 && self.year <= 9999 {
+			write!(f, "{:04}", self.year)?;
+		} else {
+			write!(f, "{:+05}", self.year)?;
+		}
+		write!(
+			f,
+			"-{:02}-{:02}T{:02}:{:02}:{:02}",
+			self.month, self.day, self.hour, self.minute, self.second
+		)?;
+		if self.nanosecond.is_multiple_of(1_000_000) && self.nanosecond != 0 {
+			write!(f, ".{:03}", self.nanosecond / 1_000_000)?;
+		} else if self.nanosecond.is_multiple_of(1_000) && self.nanosecond != 0 {
+			write!(f, ".{:06}", self.nanosecond / 1_000)?;
+		} else if self.nanosecond != 0 {
+			write!(f, ".{:09}", self.nanosecond)?;
+		}
+		write!(f, "{}", self.offset)
+	}
+}
+
+/// An error which can be returned when parsing a [`DateTime`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDateTimeError {
+	kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseErrorKind {
+	InvalidFormat,
+	OutOfRange,
+	TrailingCharacters,
+}
+
+impl ParseDateTimeError {
+	fn new(kind: ParseErrorKind) -> ParseDateTimeError {
+		ParseDateTimeError { kind }
+	}
+
+	fn message(&self) -> &'static str {
+		match self.kind {
+			ParseErrorKind::InvalidFormat => "invalid ISO 8601 date and time",
+			ParseErrorKind::OutOfRange => "date or time component out of range",
+			ParseErrorKind::TrailingCharacters => "trailing characters after date and time",
+		}
+	}
+}
+
+impl Error for ParseDateTimeError {
+	fn description(&self) -> &str {
+		self.message()
+	}
+}
+
+impl fmt::Display for ParseDateTimeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.message())
+	}
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> Parser<'a> {
+	fn peek(&self) -> Option<u8> {
+		self.bytes.first().copied()
+	}
+
+	fn eat(&mut self, byte: u8) -> bool {
+		if self.peek() == Some(byte) {
+			self.bytes = &self.bytes[1..];
+			true
+		} else {
+			false
+		}
+	}
+
+	fn expect(&mut self, byte: u8) -> Result<(), ParseDateTimeError> {
+		if self.eat(byte) {
+			Ok(())
+		} else {
+			Err(ParseDateTimeError::new(ParseErrorKind::InvalidFormat))
+		}
+	}
+
+	/// Reads exactly `count` decimal digits.
+	fn digits(&mut self, count: usize) -> Result<u32, ParseDateTimeError> {
+		if self.bytes.len() < count || !self.bytes[..count].iter().all(u8::is_ascii_digit) {
+			return Err(ParseDateTimeError::new(ParseErrorKind::InvalidFormat));
+		}
+		let value = self.bytes[..count]
+			.iter()
+			.fold(0, |acc, &b| acc * 10 + u32::from(b - b'0'));
+		self.bytes = &self.bytes[count..];
+		Ok(value)
+	}
+
+	/// Skips any number of decimal digits.
+	fn skip_digits(&mut self) {
+		let len = self.bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+		self.bytes = &self.bytes[len..];
+	}
+}
+
+impl FromStr for DateTime {
+	type Err = ParseDateTimeError;
+
+	/// Parses an ISO 8601 / RFC 3339 date and time.
+	///
+	/// Accepted forms are `YYYY-MM-DD`, optionally followed by `T` (or a
+	/// space) and `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fff…`, optionally followed
+	/// by `Z`, `±HH:MM:SS`, `±HH:MM`, `±HHMM` or `±HH`. Missing times are
+	/// midnight and missing offsets are UTC. Years outside of 0000-9999 must
+	/// carry a sign.
+	fn from_str(s: &str) -> Result<DateTime, ParseDateTimeError> {
+		use ParseErrorKind::*;
+		let mut p = Parser {
+			bytes: s.as_bytes(),
+		};
+
+		let year = match p.peek() {
+			Some(sign @ b'+') | Some(sign @ b'-') => {
+				p.bytes = &p.bytes[1..];
+				let len = p.bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+				if !(4..=6).contains(&len) {
+					return Err(ParseDateTimeError::new(InvalidFormat));
+				}
+				let year = p.digits(len)? as i64;
+				if sign == b'-' {
+					-year
+				} else {
+					year
+				}
+			}
+			_ => i64::from(p.digits(4)?),
+		};
+		let year = i32::try_from(year).map_err(|_| ParseDateTimeError::new(OutOfRange))?;
+		p.expect(b'-')?;
+		let month = p.digits(2)? as u8;
+		p.expect(b'-')?;
+		let day = p.digits(2)? as u8;
+
+		let (mut hour, mut minute, mut second, mut nanosecond) = (0, 0, 0, 0);
+		if p.eat(b'T') || p.eat(b't') || p.eat(b' ') {
+			hour = p.digits(2)? as u8;
+			p.expect(b':')?;
+			minute = p.digits(2)? as u8;
+			if p.eat(b':') {
+				second = p.digits(2)? as u8;
+				if p.eat(b'.') || p.eat(b',') {
+					let len = p.bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+					if len == 0 {
+						return Err(ParseDateTimeError::new(InvalidFormat));
+					}
+					// Anything past nanosecond precision is truncated
+					let significant = len.min(9);
+					nanosecond = p.digits(significant)? * 10u32.pow((9 - significant) as u32);
+					p.skip_digits();
+				}
+			}
+		}
+
+		let offset = match p.peek() {
+			Some(b'Z') | Some(b'z') => {
+				p.bytes = &p.bytes[1..];
+				UtcOffset::UTC
+			}
+			Some(sign @ b'+') | Some(sign @ b'-') => {
+				p.bytes = &p.bytes[1..];
+				let hours = p.digits(2)?;
+				let (minutes, seconds) = if p.eat(b':') {
+					let minutes = p.digits(2)?;
+					(minutes, if p.eat(b':') { p.digits(2)? } else { 0 })
+				} else if matches!(p.peek(), Some(b'0'..=b'9')) {
+					(p.digits(2)?, 0)
+				} else {
+					(0, 0)
+				};
+				if minutes >= 60 || seconds >= 60 {
+					return Err(ParseDateTimeError::new(OutOfRange));
+				}
+				let total = (i64::from(hours) * SECONDS_PER_HOUR
+					+ i64::from(minutes) * SECONDS_PER_MINUTE
+					+ i64::from(seconds)) as i32;
+				UtcOffset::from_seconds(if sign == b'-' { -total } else { total })
+					.ok_or_else(|| ParseDateTimeError::new(OutOfRange))?
+			}
+			_ => UtcOffset::UTC,
+		};
+
+		if !p.bytes.is_empty() {
+			return Err(ParseDateTimeError::new(TrailingCharacters));
+		}
+
+		DateTime::new(year, month, day, hour, minute, second)
+			.and_then(|date| date.with_nanosecond(nanosecond))
+			.map(|date| date.assume_offset(offset))
+			.ok_or_else(|| ParseDateTimeError::new(OutOfRange))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+
+	use super::*;
+
+	fn date(year: i32, month: u8, day: u8) -> DateTime {
+		DateTime::new(year, month, day, 0, 0, 0).unwrap()
+	}
+
+	#[test]
+	fn leap_years() {
+		assert!(is_leap_year(2000));
+		assert!(is_leap_year(2020));
+		assert!(is_leap_year(-4));
+		assert!(is_leap_year(0));
+		assert!(!is_leap_year(1900));
+		assert!(!is_leap_year(2100));
+		assert!(!is_leap_year(2019));
+		assert_eq!(days_in_month(2020, 2), 29);
+		assert_eq!(days_in_month(2100, 2), 28);
+		assert_eq!(days_in_month(2020, 13), 0);
+	}
+
+	#[test]
+	fn civil_round_trip() {
+		// Every day from 1600 to 2400, in both directions
+		let start = days_from_civil(1600, 1, 1);
+		let end = days_from_civil(2400, 12, 31);
+		let (mut year, mut month, mut day) = (1600i64, 1u8, 1u8);
+		for days in start..=end {
+			assert_eq!(civil_from_days(days), (year, month, day));
+			assert_eq!(days_from_civil(year as i32, month, day), days);
+			day += 1;
+			if day > days_in_month(year as i32, month) {
+				day = 1;
+				month += 1;
+				if month > 12 {
+					month = 1;
+					year += 1;
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn known_timestamps() {
+		assert_eq!(date(1970, 1, 1).unix_timestamp(), 0);
+		assert_eq!(date(1997, 1, 1).unix_timestamp(), 852_076_800);
+		assert_eq!(date(2000, 3, 1).unix_timestamp(), 951_868_800);
+		assert_eq!(date(1969, 12, 31).unix_timestamp(), -86_400);
+		assert_eq!(date(1, 1, 1).unix_timestamp(), -62_135_596_800);
+		assert_eq!(
+			DateTime::new(2038, 1, 19, 3, 14, 7)
+				.unwrap()
+				.unix_timestamp(),
+			i64::from(i32::MAX)
+		);
+		assert_eq!(
+			DateTime::from_unix_timestamp(1_589_723_109).unwrap(),
+			DateTime::new(2020, 5, 17, 13, 45, 9).unwrap()
+		);
+		assert_eq!(
+			DateTime::from_unix_timestamp(-1).unwrap(),
+			DateTime::new(1969, 12, 31, 23, 59, 59).unwrap()
+		);
+		assert!(DateTime::from_unix_timestamp(i64::MAX).is_none());
+		assert!(DateTime::from_unix_timestamp(i64::MIN).is_none());
+	}
+
+	#[test]
+	fn invalid_components() {
+		assert!(DateTime::new(2019, 2, 29, 0, 0, 0).is_none());
+		assert!(DateTime::new(2020, 2, 29, 0, 0, 0).is_some());
+		assert!(DateTime::new(2020, 0, 1, 0, 0, 0).is_none());
+		assert!(DateTime::new(2020, 4, 31, 0, 0, 0).is_none());
+		assert!(DateTime::new(2020, 1, 0, 0, 0, 0).is_none());
+		assert!(DateTime::new(2020, 1, 1, 24, 0, 0).is_none());
+		assert!(DateTime::new(2020, 1, 1, 0, 60, 0).is_none());
+		assert!(DateTime::new(2020, 1, 1, 0, 0, 60).is_none());
+		assert!(DateTime::new(10000, 1, 1, 0, 0, 0).is_none());
+		assert!(date(2020, 1, 1).with_nanosecond(1_000_000_000).is_none());
+		assert!(UtcOffset::from_hm(24, 0).is_none());
+		assert!(UtcOffset::from_hm(1, 60).is_none());
+	}
+
+	#[test]
+	fn weekday_and_ordinal() {
+		assert_eq!(date(1970, 1, 1).weekday(), Weekday::Thursday);
+		assert_eq!(date(2000, 1, 1).weekday(), Weekday::Saturday);
+		assert_eq!(date(2020, 5, 17).weekday(), Weekday::Sunday);
+		assert_eq!(date(1969, 12, 29).weekday(), Weekday::Monday);
+		assert_eq!(Weekday::Sunday.number_days_from_sunday(), 0);
+		assert_eq!(Weekday::Sunday.number_days_from_monday(), 6);
+		assert_eq!(date(2020, 1, 1).ordinal(), 1);
+		assert_eq!(date(2020, 12, 31).ordinal(), 366);
+		assert_eq!(date(2019, 12, 31).ordinal(), 365);
+		assert_eq!(date(2019, 3, 1).ordinal(), 60);
+	}
+
+	#[test]
+	fn offsets() {
+		let utc = DateTime::new(2020, 12, 31, 23, 30, 0).unwrap();
+		let plus_two = utc.to_offset(UtcOffset::from_hm(2, 0).unwrap());
+		assert_eq!(
+			(
+				plus_two.year(),
+				plus_two.month(),
+				plus_two.day(),
+				plus_two.hour()
+			),
+			(2021, 1, 1, 1)
+		);
+		assert_eq!(plus_two, utc);
+		assert_eq!(plus_two.unix_timestamp(), utc.unix_timestamp());
+
+		let minus = utc.to_offset(UtcOffset::from_hm(-3, 30).unwrap());
+		assert_eq!((minus.hour(), minus.minute()), (20, 0));
+		assert_eq!(minus.offset().whole_seconds(), -12_600);
+
+		let shifted = utc.assume_offset(UtcOffset::from_hm(1, 0).unwrap());
+		assert_eq!(shifted.unix_timestamp(), utc.unix_timestamp() - 3600);
+		assert!(shifted < utc);
+	}
+
+	#[test]
+	fn arithmetic() {
+		let start = DateTime::new(2020, 2, 28, 23, 59, 59)
+			.unwrap()
+			.with_nanosecond(500_000_000)
+			.unwrap();
+		let later = start.checked_add(Duration::from_millis(600)).unwrap();
+		assert_eq!(
+			(later.month(), later.day(), later.hour(), later.second()),
+			(2, 29, 0, 0)
+		);
+		assert_eq!(later.nanosecond(), 100_000_000);
+		assert_eq!(later.checked_sub(Duration::from_millis(600)), Some(start));
+		assert_eq!(later.duration_since(start), Ok(Duration::from_millis(600)));
+		assert_eq!(start.duration_since(later), Err(Duration::from_millis(600)));
+
+		let year = date(2019, 3, 1)
+			.checked_add(Duration::from_secs(365 * 86_400))
+			.unwrap();
+		assert_eq!(year, date(2020, 2, 29));
+		assert!(date(9999, 12, 31)
+			.checked_add(Duration::from_secs(86_400))
+			.is_none());
+	}
+
+	#[test]
+	fn system_time() {
+		let time = DateTime::new(2020, 5, 17, 13, 45, 9)
+			.unwrap()
+			.with_nanosecond(250)
+			.unwrap();
+		let system = SystemTime::from(time);
+		assert_eq!(
+			system.duration_since(UNIX_EPOCH).unwrap(),
+			Duration::new(1_589_723_109, 250)
+		);
+		assert_eq!(DateTime::from(system), time);
+		assert_eq!(DateTime::from(UNIX_EPOCH), DateTime::UNIX_EPOCH);
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(
+			DateTime::new(2020, 5, 7, 3, 4, 5).unwrap().to_string(),
+			"2020-05-07T03:04:05Z"
+		);
+		let time = DateTime::new(2020, 5, 7, 3, 4, 5)
+			.unwrap()
+			.assume_offset(UtcOffset::from_hm(-9, 30).unwrap());
+		assert_eq!(time.to_string(), "2020-05-07T03:04:05-09:30");
+		assert_eq!(
+			time.with_nanosecond(120_000_000).unwrap().to_string(),
+			"2020-05-07T03:04:05.120-09:30"
+		);
+		assert_eq!(
+			time.with_nanosecond(1_000).unwrap().to_string(),
+			"2020-05-07T03:04:05.000001-09:30"
+		);
+		assert_eq!(
+			time.with_nanosecond(7).unwrap().to_string(),
+			"2020-05-07T03:04:05.000000007-09:30"
+		);
+		assert_eq!(date(-44, 3, 15).to_string(), "-0044-03-15T00:00:00Z");
+		assert_eq!(
+			UtcOffset::from_seconds(3_725).unwrap().to_string(),
+			"+01:02:05"
+		);
+	}
+
+	#[test]
+	fn offset_seconds_round_trip() {
+		let time = date(2020, 5, 7).to_offset(UtcOffset::from_seconds(-3_725).unwrap());
+		assert_eq!(time.to_string(), "2020-05-06T22:57:55-01:02:05");
+		let parsed: DateTime = time.to_string().parse().unwrap();
+		assert_eq!(parsed, time);
+		assert_eq!(parsed.offset(), time.offset());
+		assert_eq!(
+			"2020-05-07T00:00:00+01:00:60"
+				.parse::<DateTime>()
+				.unwrap_err()
+				.kind,
+			ParseErrorKind::OutOfRange
+		);
+	}
+
+	#[test]
+	fn parse() {
+		let expected = DateTime::new(2020, 5, 17, 13, 45, 9).unwrap();
+		assert_eq!("2020-05-17T13:45:09Z".parse(), Ok(expected));
+		assert_eq!("2020-05-17 13:45:09".parse(), Ok(expected));
+		assert_eq!("2020-05-17T15:45:09+02:00".parse(), Ok(expected));
+		assert_eq!("2020-05-17T15:45:09+0200".parse(), Ok(expected));
+		assert_eq!("2020-05-17T15:45:09+02".parse(), Ok(expected));
+		assert_eq!("2020-05-17T13:15:09-00:30".parse(), Ok(expected));
+		assert_eq!("2020-05-17".parse(), Ok(date(2020, 5, 17)));
+		assert_eq!(
+			"2020-05-17T13:45Z".parse(),
+			Ok(DateTime::new(2020, 5, 17, 13, 45, 0).unwrap())
+		);
+		assert_eq!(
+			"2020-05-17T13:45:09.5Z"
+				.parse::<DateTime>()
+				.unwrap()
+				.nanosecond(),
+			500_000_000
+		);
+		assert_eq!(
+			"2020-05-17T13:45:09.1234567891Z"
+				.parse::<DateTime>()
+				.unwrap()
+				.nanosecond(),
+			123_456_789
+		);
+		assert_eq!(
+			"2020-01-01T00:00:00.0000000000000001Z".parse(),
+			Ok(date(2020, 1, 1))
+		);
+		assert_eq!("-0044-03-15".parse(), Ok(date(-44, 3, 15)));
+
+		for bad in &[
+			"",
+			"2020",
+			"2020-5-17",
+			"2020-05-17T",
+			"2020-05-17T13",
+			"2020-05-17T13:45:09.Z",
+			"2020-05-17T13:45:09+2",
+			"20200517",
+		] {
+			assert_eq!(
+				bad.parse::<DateTime>().unwrap_err().kind,
+				ParseErrorKind::InvalidFormat,
+				"{}",
+				bad
+			);
+		}
+		for bad in &[
+			"2019-02-29",
+			"2020-13-01",
+			"2020-05-17T24:00:00",
+			"2020-05-17T13:45:09+24:00",
+		] {
+			assert_eq!(
+				bad.parse::<DateTime>().unwrap_err().kind,
+				ParseErrorKind::OutOfRange,
+				"{}",
+				bad
+			);
+		}
+		assert_eq!(
+			"2020-05-17T13:45:09Zjunk"
+				.parse::<DateTime>()
+				.unwrap_err()
+				.kind,
+			ParseErrorKind::TrailingCharacters
+		);
+	}
+
+	#[test]
+	fn format_parse_round_trip() {
+		let offsets = [0, 3_600, -12_600, 49_500, -43_200];
+		let mut seconds = -62_135_596_800i64;
+		while seconds < 253_402_300_799 - 86_400 {
+			for &offset in &offsets {
+				let time = DateTime::from_unix_timestamp(seconds)
+					.unwrap()
+					.with_nanosecond((seconds.rem_euclid(1_000)) as u32 * 1_000_000)
+					.unwrap()
+					.to_offset(UtcOffset::from_seconds(offset).unwrap());
+				let parsed: DateTime = time.to_string().parse().unwrap();
+				assert_eq!(parsed, time);
+				assert_eq!(parsed.offset(), time.offset());
+			}
+			seconds += 7_777_777;
+		}
+	}
+}
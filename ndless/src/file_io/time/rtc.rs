@@ -0,0 +1,63 @@
+//! The real-time clock.
+//!
+//! The RTC is a 32-bit counter of seconds that keeps running while the
+//! calculator is turned off. The OS counts from 1997-01-01 00:00:00 and does
+//! not keep track of a time zone, so the values here are treated as UTC. Use
+//! [`DateTime::to_offset`] to display them in local time.
+#![allow(clippy::unreadable_literal)]
+
+use core::convert::TryFrom;
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::io;
+
+use super::datetime::DateTime;
+
+const VALUE: *const u32 = 0x90090000 as *const u32;
+const LOAD: *mut u32 = 0x90090008 as *mut u32;
+
+/// The Unix timestamp of 1997-01-01 00:00:00 UTC, when the RTC reads 0.
+pub const EPOCH_OFFSET: i64 = 852_076_800;
+
+/// Returns the raw value of the RTC, in seconds since 1997-01-01.
+pub fn seconds() -> u32 {
+	unsafe { read_volatile(VALUE) }
+}
+
+/// Sets the raw value of the RTC, in seconds since 1997-01-01. This changes
+/// the clock for the OS as well.
+pub fn set_seconds(seconds: u32) {
+	unsafe { write_volatile(LOAD, seconds) }
+}
+
+/// Reads the current date and time from the RTC.
+pub fn now() -> DateTime {
+	DateTime::from_unix_timestamp(i64::from(seconds()) + EPOCH_OFFSET)
+		.expect("every RTC value is a valid date")
+}
+
+/// Sets the RTC to the given date and time. The fractional second is
+/// discarded.
+///
+/// # Errors
+///
+/// Returns [`InvalidInput`](io::ErrorKind::InvalidInput) if the time is before
+/// 1997 or after 2133, which the RTC cannot represent.
+pub fn set(time: &DateTime) -> io::Result<()> {
+	let seconds = u32::try_from(time.unix_timestamp() - EPOCH_OFFSET).map_err(|_| {
+		io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"time cannot be represented by the RTC",
+		)
+	})?;
+	set_seconds(seconds);
+	Ok(())
+}
+
+impl DateTime {
+	/// Reads the current date and time from the real-time clock. See
+	/// [`rtc`](super::rtc) for details.
+	pub fn now_utc() -> DateTime {
+		now()
+	}
+}
@@ -9,6 +9,32 @@
 #![feature(core_intrinsics)]
 #![feature(never_type)]
 pub extern crate alloc;
+#[cfg(feature = "host")]
+extern crate std;
+
+/// Declares state that belongs to the running program, such as the exit
+/// hooks, as a function that returns a pointer to it. On the calculator, this
+/// is a `static mut`. With the `host` feature, each thread has its own, like
+/// the [simulated calculator](sim), so tests don't share it.
+macro_rules! program_state {
+	($(#[$attr:meta])* $vis:vis fn $name:ident() -> $ty:ty = $init:expr;) => {
+		$(#[$attr])*
+		#[cfg(not(feature = "host"))]
+		$vis fn $name() -> *mut $ty {
+			static mut STATE: $ty = $init;
+			unsafe { core::ptr::addr_of_mut!(STATE) }
+		}
+
+		$(#[$attr])*
+		#[cfg(feature = "host")]
+		$vis fn $name() -> *mut $ty {
+			std::thread_local! {
+				static STATE: core::cell::UnsafeCell<$ty> = const { core::cell::UnsafeCell::new($init) };
+			}
+			STATE.with(|state| state.get())
+		}
+	};
+}
 
 pub use bindings::*;
 
@@ -48,6 +74,29 @@ macro_rules! println {
 	)
 }
 
+#[macro_export]
+macro_rules! eprint {
+	($($arg:tt)*) => (
+		match $crate::out::eprint_fmt(format_args!($($arg)*)) {
+			_ => {}
+		}
+	)
+}
+
+#[macro_export]
+macro_rules! eprintln {
+	() => (
+		match $crate::out::eprint_fmt(format_args!("\n")) {
+			_ => {}
+		}
+	);
+	($($arg:tt)*) => (
+		match $crate::out::eprint_fmt(format_args!("{}\n", format_args!($($arg)*))) {
+			_ => {}
+		}
+	)
+}
+
 #[macro_export]
 macro_rules! dbg {
     () => {
@@ -84,6 +133,8 @@ pub mod prelude {
 	pub use ndless_macros::entry;
 
 	pub use dbg;
+	pub use eprint;
+	pub use eprintln;
 	pub use print;
 	pub use println;
 
@@ -100,6 +151,16 @@ macro_rules! cstr {
 
 #[doc(hidden)]
 pub use ndless_static_vars::ARGUMENTS;
+/// Setup that runs at the end of [`__init`], before `main`. This is how
+/// `ndless-handler` gets its allocator ready, so that `#[entry]` programs don't
+/// need to name it. `__init` always allocates before running it, so a global
+/// allocator can set it the first time that it's used.
+#[doc(hidden)]
+pub use ndless_static_vars::RUNTIME_INIT;
+/// Runs once at the end of [`process::__run_at_exit`], after every exit hook.
+/// `ndless-handler` checks for leaked memory here.
+#[doc(hidden)]
+pub use ndless_static_vars::RUNTIME_EXIT;
 
 #[doc(hidden)]
 pub unsafe fn __init(args: &'static [*const cty::c_char]) {
@@ -108,5 +169,11 @@ pub unsafe fn __init(args: &'static [*const cty::c_char]) {
 		.next()
 		.map(path::PathBuf::from)
 		.and_then(|path| path.parent().map(env::set_current_dir));
+	if let Some(init) = RUNTIME_INIT {
+		RUNTIME_INIT = None;
+		init();
+	}
+	// After `RUNTIME_INIT`, so that the exit hook it registers is allocated
+	// after `ndless-handler` takes its leak baseline, and freed before the check
 	timer::__init();
 }
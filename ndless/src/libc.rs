@@ -1,10 +1,18 @@
 pub use cty::*;
+#[cfg(not(feature = "host"))]
 pub use ndless_sys::nuc_readdir as readdir;
-pub use ndless_sys::NU_Open as open;
 pub use ndless_sys::*;
+#[cfg(not(feature = "host"))]
 pub use ndless_sys::{
-	nuc_closedir as closedir, nuc_opendir as opendir, stat as lstat, NU_Set_Current_Dir as chdir,
-	NU_Truncate as ftruncate,
+	nuc_closedir as closedir, nuc_opendir as opendir, stat as lstat, NU_Open as open,
+	NU_Set_Current_Dir as chdir, NU_Truncate as ftruncate,
+};
+
+// The simulator uses the computer's own `chdir` and `ftruncate`, but opens
+// and renames like Nucleus
+#[cfg(feature = "host")]
+pub use crate::sim::libc::{
+	closedir, get_documents_dir, nuc_stat, open, opendir, readdir, rename,
 };
 
 extern "C" {
@@ -22,21 +30,40 @@ pub const EWOULDBLOCK: c_int = EAGAIN;
 
 pub const O_NONBLOCK: c_int = 2048;
 
+// Only for `FileTypeExt`, which the simulator leaves out
+#[cfg(not(feature = "host"))]
 pub const S_IFIFO: mode_t = 4096;
+#[cfg(not(feature = "host"))]
 pub const S_IFCHR: mode_t = 8192;
+#[cfg(not(feature = "host"))]
 pub const S_IFBLK: mode_t = 24576;
 pub const S_IFDIR: mode_t = 16384;
 pub const S_IFREG: mode_t = 32768;
+#[cfg(not(feature = "host"))]
 pub const S_IFSOCK: mode_t = 49152;
 pub const S_IFMT: mode_t = 61440;
 
+// The flags and modes of `NU_Open`
+pub const PO_RDONLY: u32 = 0x0000;
+pub const PO_WRONLY: u32 = 0x0001;
+pub const PO_RDWR: u32 = 0x0002;
+pub const PO_APPEND: u32 = 0x0008;
+pub const PO_CREAT: u32 = 0x0100;
+pub const PO_TRUNC: u32 = 0x0200;
+pub const PO_EXCL: u32 = 0x0400;
+pub const PO_BINARY: u32 = 0x8000;
+pub const PS_IWRITE: u32 = 0o400;
+pub const PS_IREAD: u32 = 0o200;
+
 pub const _SC_PAGESIZE: c_int = 30;
 pub const EPERM: c_int = 1;
 pub const ENOENT: c_int = 2;
 pub const EINTR: c_int = 4;
+pub const EBADF: c_int = 9;
 pub const EAGAIN: c_int = 11;
 pub const EACCES: c_int = 13;
 pub const EEXIST: c_int = 17;
+pub const EISDIR: c_int = 21;
 pub const EINVAL: c_int = 22;
 pub const EPIPE: c_int = 32;
 pub const ERANGE: c_int = 34;